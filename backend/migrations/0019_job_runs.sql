@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS job_runs (
+  id UUID PRIMARY KEY,
+  job_name TEXT NOT NULL,
+  started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  finished_at TIMESTAMPTZ,
+  status TEXT NOT NULL,
+  error TEXT,
+  CONSTRAINT job_runs_status_check CHECK (status IN ('running', 'succeeded', 'failed'))
+);
+
+CREATE INDEX IF NOT EXISTS job_runs_job_name_started_at_idx ON job_runs (job_name, started_at DESC);
+
+CREATE TABLE IF NOT EXISTS job_schedules (
+  job_name TEXT PRIMARY KEY,
+  schedule TEXT NOT NULL,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS asset_price_fetch_status (
+  asset_id UUID PRIMARY KEY REFERENCES assets(id) ON DELETE CASCADE,
+  symbol TEXT NOT NULL,
+  last_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  last_success_at TIMESTAMPTZ,
+  last_error TEXT,
+  consecutive_failures INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS asset_price_fetch_status_failures_idx
+  ON asset_price_fetch_status (consecutive_failures)
+  WHERE consecutive_failures > 0;
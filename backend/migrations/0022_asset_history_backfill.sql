@@ -0,0 +1,5 @@
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS history_backfilled_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS assets_history_backfill_pending_idx
+  ON assets (created_at)
+  WHERE history_backfilled_at IS NULL;
@@ -0,0 +1,25 @@
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS cost_basis_method TEXT NOT NULL DEFAULT 'fifo';
+
+ALTER TABLE assets DROP CONSTRAINT IF EXISTS assets_cost_basis_method_check;
+ALTER TABLE assets
+  ADD CONSTRAINT assets_cost_basis_method_check
+  CHECK (cost_basis_method IN ('fifo', 'average', 'specific'));
+
+CREATE TABLE IF NOT EXISTS asset_trades (
+  id UUID PRIMARY KEY,
+  asset_id UUID NOT NULL REFERENCES assets(id) ON DELETE CASCADE,
+  side TEXT NOT NULL,
+  quantity DOUBLE PRECISION NOT NULL,
+  price DOUBLE PRECISION NOT NULL,
+  fees DOUBLE PRECISION NOT NULL DEFAULT 0,
+  currency_code TEXT NOT NULL,
+  traded_at TIMESTAMPTZ NOT NULL,
+  lot_id UUID REFERENCES asset_trades(id) ON DELETE SET NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  CONSTRAINT asset_trades_side_check CHECK (side IN ('buy', 'sell')),
+  CONSTRAINT asset_trades_quantity_check CHECK (quantity > 0),
+  CONSTRAINT asset_trades_price_check CHECK (price >= 0),
+  CONSTRAINT asset_trades_fees_check CHECK (fees >= 0)
+);
+
+CREATE INDEX IF NOT EXISTS asset_trades_asset_id_traded_at_idx ON asset_trades (asset_id, traded_at);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS benchmark_prices (
+  symbol TEXT NOT NULL,
+  price_date DATE NOT NULL,
+  close DOUBLE PRECISION NOT NULL,
+  recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (symbol, price_date)
+);
@@ -0,0 +1,29 @@
+CREATE TABLE IF NOT EXISTS asset_income_events (
+  id UUID PRIMARY KEY,
+  asset_id UUID NOT NULL REFERENCES assets(id) ON DELETE CASCADE,
+  kind TEXT NOT NULL,
+  amount DOUBLE PRECISION NOT NULL,
+  currency_code TEXT NOT NULL,
+  paid_at TIMESTAMPTZ NOT NULL,
+  description TEXT,
+  transaction_id UUID REFERENCES transactions(id) ON DELETE SET NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  CONSTRAINT asset_income_events_kind_check CHECK (kind IN ('dividend', 'interest')),
+  CONSTRAINT asset_income_events_amount_check CHECK (amount > 0)
+);
+
+CREATE INDEX IF NOT EXISTS asset_income_events_asset_id_paid_at_idx
+  ON asset_income_events (asset_id, paid_at);
+
+CREATE TABLE IF NOT EXISTS asset_splits (
+  id UUID PRIMARY KEY,
+  asset_id UUID NOT NULL REFERENCES assets(id) ON DELETE CASCADE,
+  numerator DOUBLE PRECISION NOT NULL,
+  denominator DOUBLE PRECISION NOT NULL,
+  effective_at TIMESTAMPTZ NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  CONSTRAINT asset_splits_ratio_check CHECK (numerator > 0 AND denominator > 0)
+);
+
+CREATE INDEX IF NOT EXISTS asset_splits_asset_id_effective_at_idx
+  ON asset_splits (asset_id, effective_at);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS allocation_targets (
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  dimension TEXT NOT NULL,
+  bucket TEXT NOT NULL,
+  target_weight DOUBLE PRECISION NOT NULL,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (user_id, dimension, bucket),
+  CONSTRAINT allocation_targets_dimension_check CHECK (dimension IN ('strategy', 'asset_type')),
+  CONSTRAINT allocation_targets_weight_check CHECK (target_weight >= 0 AND target_weight <= 1)
+);
@@ -0,0 +1,31 @@
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS valuation_mode TEXT NOT NULL DEFAULT 'market';
+
+ALTER TABLE assets DROP CONSTRAINT IF EXISTS assets_valuation_mode_check;
+ALTER TABLE assets
+  ADD CONSTRAINT assets_valuation_mode_check CHECK (valuation_mode IN ('market', 'manual'));
+
+CREATE TABLE IF NOT EXISTS asset_valuations (
+  id UUID PRIMARY KEY,
+  asset_id UUID NOT NULL REFERENCES assets(id) ON DELETE CASCADE,
+  price DOUBLE PRECISION NOT NULL,
+  currency_code TEXT NOT NULL,
+  valued_at TIMESTAMPTZ NOT NULL,
+  note TEXT,
+  price_history_id UUID REFERENCES price_history(id) ON DELETE SET NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  CONSTRAINT asset_valuations_price_check CHECK (price >= 0)
+);
+
+CREATE INDEX IF NOT EXISTS asset_valuations_asset_id_valued_at_idx
+  ON asset_valuations (asset_id, valued_at);
+
+CREATE TABLE IF NOT EXISTS asset_valuation_schedules (
+  asset_id UUID PRIMARY KEY REFERENCES assets(id) ON DELETE CASCADE,
+  method TEXT NOT NULL,
+  annual_rate DOUBLE PRECISION NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  CONSTRAINT asset_valuation_schedules_method_check
+    CHECK (method IN ('compound', 'straight_line')),
+  CONSTRAINT asset_valuation_schedules_rate_check CHECK (annual_rate > -1 AND annual_rate < 10)
+);
@@ -0,0 +1,32 @@
+CREATE TABLE IF NOT EXISTS liabilities (
+  id UUID PRIMARY KEY,
+  account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  principal DOUBLE PRECISION NOT NULL,
+  annual_rate DOUBLE PRECISION NOT NULL,
+  term_months INTEGER NOT NULL,
+  payment_frequency TEXT NOT NULL DEFAULT 'monthly',
+  currency_code TEXT NOT NULL,
+  start_date DATE NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  CONSTRAINT liabilities_principal_check CHECK (principal > 0),
+  CONSTRAINT liabilities_rate_check CHECK (annual_rate >= 0),
+  CONSTRAINT liabilities_term_check CHECK (term_months > 0),
+  CONSTRAINT liabilities_frequency_check
+    CHECK (payment_frequency IN ('weekly', 'biweekly', 'monthly', 'quarterly'))
+);
+
+CREATE INDEX IF NOT EXISTS liabilities_account_id_idx ON liabilities (account_id);
+
+CREATE TABLE IF NOT EXISTS liability_payments (
+  id UUID PRIMARY KEY,
+  liability_id UUID NOT NULL REFERENCES liabilities(id) ON DELETE CASCADE,
+  period INTEGER NOT NULL,
+  principal_amount DOUBLE PRECISION NOT NULL,
+  interest_amount DOUBLE PRECISION NOT NULL,
+  paid_at TIMESTAMPTZ NOT NULL,
+  principal_transaction_id UUID REFERENCES transactions(id) ON DELETE SET NULL,
+  interest_transaction_id UUID REFERENCES transactions(id) ON DELETE SET NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  CONSTRAINT liability_payments_period_unique UNIQUE (liability_id, period)
+);
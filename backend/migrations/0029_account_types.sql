@@ -0,0 +1,28 @@
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS account_type TEXT NOT NULL DEFAULT 'checking';
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS credit_limit DOUBLE PRECISION;
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS statement_closing_day INTEGER;
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS payment_due_day INTEGER;
+
+ALTER TABLE accounts DROP CONSTRAINT IF EXISTS accounts_account_type_check;
+ALTER TABLE accounts
+  ADD CONSTRAINT accounts_account_type_check
+  CHECK (account_type IN ('checking', 'savings', 'credit_card', 'brokerage', 'cash', 'loan'));
+
+ALTER TABLE accounts DROP CONSTRAINT IF EXISTS accounts_credit_card_days_check;
+ALTER TABLE accounts
+  ADD CONSTRAINT accounts_credit_card_days_check
+  CHECK (
+    (statement_closing_day IS NULL OR statement_closing_day BETWEEN 1 AND 31)
+    AND (payment_due_day IS NULL OR payment_due_day BETWEEN 1 AND 31)
+  );
+
+CREATE TABLE IF NOT EXISTS bill_reminders (
+  id UUID PRIMARY KEY,
+  account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+  due_date DATE NOT NULL,
+  statement_balance DOUBLE PRECISION NOT NULL,
+  currency_code TEXT NOT NULL,
+  dismissed_at TIMESTAMPTZ,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  CONSTRAINT bill_reminders_account_due_unique UNIQUE (account_id, due_date)
+);
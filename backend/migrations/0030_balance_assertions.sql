@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS balance_assertions (
+  id UUID PRIMARY KEY,
+  account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+  asserted_on DATE NOT NULL,
+  balance DOUBLE PRECISION NOT NULL,
+  currency_code TEXT NOT NULL,
+  note TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  CONSTRAINT balance_assertions_account_date_unique UNIQUE (account_id, asserted_on)
+);
@@ -0,0 +1,4 @@
+ALTER TABLE api_keys
+  ADD COLUMN IF NOT EXISTS scopes TEXT[],
+  ADD COLUMN IF NOT EXISTS account_ids UUID[] NOT NULL DEFAULT '{}',
+  ADD COLUMN IF NOT EXISTS account_group_ids UUID[] NOT NULL DEFAULT '{}';
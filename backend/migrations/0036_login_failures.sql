@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS login_failures (
+  scope TEXT NOT NULL,
+  key TEXT NOT NULL,
+  failure_count INTEGER NOT NULL DEFAULT 0,
+  last_failed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  locked_until TIMESTAMPTZ,
+  PRIMARY KEY (scope, key),
+  CONSTRAINT login_failures_scope_check CHECK (scope IN ('account', 'ip'))
+);
@@ -0,0 +1,10 @@
+-- Hash any tokens still stored in plaintext, then drop the plaintext column.
+UPDATE api_keys
+SET token_hash = encode(sha256(convert_to(token, 'UTF8')), 'hex'),
+    token_prefix = left(token, 10)
+WHERE token IS NOT NULL;
+
+ALTER TABLE api_keys DROP COLUMN IF EXISTS token;
+
+ALTER TABLE api_keys
+  ADD COLUMN IF NOT EXISTS replaced_by UUID REFERENCES api_keys(id) ON DELETE SET NULL;
@@ -0,0 +1,23 @@
+CREATE TABLE IF NOT EXISTS account_group_invitations (
+  id UUID PRIMARY KEY,
+  group_id UUID NOT NULL REFERENCES account_groups(id) ON DELETE CASCADE,
+  email TEXT NOT NULL,
+  role TEXT NOT NULL,
+  token_hash TEXT NOT NULL UNIQUE,
+  status TEXT NOT NULL DEFAULT 'pending',
+  invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+  responded_by UUID REFERENCES users(id) ON DELETE SET NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  expires_at TIMESTAMPTZ NOT NULL,
+  responded_at TIMESTAMPTZ,
+  CONSTRAINT account_group_invitations_role_check CHECK (role IN ('view', 'edit', 'admin')),
+  CONSTRAINT account_group_invitations_status_check
+    CHECK (status IN ('pending', 'accepted', 'declined', 'revoked'))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS account_group_invitations_pending_idx
+  ON account_group_invitations (group_id, email)
+  WHERE status = 'pending';
+
+CREATE INDEX IF NOT EXISTS account_group_invitations_email_idx
+  ON account_group_invitations (email);
@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS change_history (
+  id UUID PRIMARY KEY,
+  user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+  entity_type TEXT NOT NULL,
+  entity_id UUID NOT NULL,
+  account_ids UUID[] NOT NULL DEFAULT '{}',
+  action TEXT NOT NULL,
+  changes JSONB NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  CONSTRAINT change_history_entity_type_check
+    CHECK (entity_type IN ('account', 'transaction', 'asset')),
+  CONSTRAINT change_history_action_check CHECK (action IN ('created', 'updated', 'deleted'))
+);
+
+CREATE INDEX IF NOT EXISTS change_history_account_ids_idx
+  ON change_history USING GIN (account_ids);
+CREATE INDEX IF NOT EXISTS change_history_entity_idx
+  ON change_history (entity_type, entity_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS change_history_user_idx
+  ON change_history (user_id, created_at DESC);
@@ -0,0 +1,9 @@
+-- "broker" and "custom" were accepted without a provider behind them.
+UPDATE user_preferences
+SET value = to_jsonb('stooq'::text)
+WHERE key = 'asset_data_source'
+  AND value #>> '{}' IN ('broker', 'custom');
+
+UPDATE assets
+SET data_source = NULL
+WHERE data_source IN ('broker', 'custom');
@@ -0,0 +1,16 @@
+-- Backfilled daily closes are stored at 22:00 UTC; keep one per asset and day.
+ALTER TABLE price_history ADD COLUMN IF NOT EXISTS close_day DATE;
+
+DELETE FROM price_history ph
+USING price_history other
+WHERE ph.asset_id = other.asset_id
+  AND ph.recorded_at = other.recorded_at
+  AND ph.id > other.id
+  AND (ph.recorded_at AT TIME ZONE 'UTC')::time = TIME '22:00';
+
+UPDATE price_history
+SET close_day = (recorded_at AT TIME ZONE 'UTC')::date
+WHERE (recorded_at AT TIME ZONE 'UTC')::time = TIME '22:00';
+
+CREATE UNIQUE INDEX IF NOT EXISTS price_history_close_day_idx
+  ON price_history(asset_id, close_day);
@@ -0,0 +1,25 @@
+-- Splits are applied when trades and prices are read; rows keep the units they were recorded in.
+-- Each row holds the factor for times in [valid_from, valid_until): the product of every later split.
+CREATE OR REPLACE VIEW asset_split_factors AS
+WITH RECURSIVE ordered AS (
+  SELECT asset_id, effective_at, numerator / denominator AS ratio,
+         ROW_NUMBER() OVER (PARTITION BY asset_id ORDER BY effective_at DESC, id) AS position
+  FROM asset_splits
+),
+factors AS (
+  SELECT asset_id, effective_at, ratio AS factor, position
+  FROM ordered
+  WHERE position = 1
+  UNION ALL
+  SELECT o.asset_id, o.effective_at, f.factor * o.ratio, o.position
+  FROM factors f
+  INNER JOIN ordered o ON o.asset_id = f.asset_id AND o.position = f.position + 1
+)
+SELECT asset_id,
+       COALESCE(
+         LEAD(effective_at) OVER (PARTITION BY asset_id ORDER BY position),
+         '-infinity'::TIMESTAMPTZ
+       ) AS valid_from,
+       effective_at AS valid_until,
+       factor
+FROM factors;
@@ -0,0 +1,4 @@
+-- Each login state is tied to the browser that started it.
+DELETE FROM oidc_login_states;
+
+ALTER TABLE oidc_login_states ADD COLUMN IF NOT EXISTS binding_hash TEXT NOT NULL;
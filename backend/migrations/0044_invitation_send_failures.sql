@@ -0,0 +1,7 @@
+-- Invitations are committed before the email goes out; a failed send marks them.
+ALTER TABLE account_group_invitations
+  DROP CONSTRAINT IF EXISTS account_group_invitations_status_check;
+
+ALTER TABLE account_group_invitations
+  ADD CONSTRAINT account_group_invitations_status_check
+    CHECK (status IN ('pending', 'accepted', 'declined', 'revoked', 'failed'));
@@ -2,24 +2,47 @@ use argon2::{
     password_hash::{rand_core::OsRng, SaltString},
     Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
 };
+use std::net::{IpAddr, SocketAddr};
+
 use axum::{
-    extract::{FromRequestParts, State},
-    http::{request::Parts, Method, StatusCode},
+    extract::{ConnectInfo, FromRequestParts, State},
+    http::{request::Parts, HeaderMap, Method, StatusCode},
     Json,
 };
+use base64ct::{Base64UrlUnpadded, Encoding};
 use chrono::{Duration, Utc};
 use hex::encode as hex_encode;
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use rand_core::RngCore;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
-use crate::{audit::record_audit_event, state::AppState};
+use crate::{
+    audit::record_audit_event,
+    routes::{
+        backup::{record_replaced_records, replaced_records},
+        credentials::send_verification_email,
+        two_factor::{record_failed_code, verify_second_factor},
+    },
+    services::{
+        login_throttle::{self, ThrottleScope},
+        token_scopes::{self, PathTarget},
+    },
+    state::AppState,
+};
+
+const ACCESS_TOKEN_MINUTES: i64 = 15;
+const REFRESH_TOKEN_DAYS: i64 = 30;
+const REFRESH_TOKEN_PREFIX: &str = "fcr_";
+const LOGIN_CHALLENGE_MINUTES: i64 = 5;
+const LOGIN_CHALLENGE_MAX_ATTEMPTS: i32 = 5;
 
 #[derive(Serialize, Deserialize)]
 struct Claims {
     sub: String,
+    sid: String,
     exp: usize,
 }
 
@@ -42,22 +65,65 @@ pub struct UpdateProfileRequest {
     pub email: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct TwoFactorLoginRequest {
+    pub challenge_token: String,
+    pub code: String,
+}
+
+#[derive(Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
 #[derive(Serialize)]
 pub struct AuthResponse {
     pub token: String,
+    pub refresh_token: String,
+    pub expires_in: i64,
+    pub session_id: Uuid,
     pub user_id: Uuid,
 }
 
+#[derive(Serialize)]
+pub struct TwoFactorChallenge {
+    pub two_factor_required: bool,
+    pub challenge_token: String,
+    pub expires_in: i64,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated(AuthResponse),
+    TwoFactorRequired(TwoFactorChallenge),
+}
+
 #[derive(Serialize)]
 pub struct UserProfile {
     pub id: Uuid,
     pub name: String,
     pub email: String,
+    pub email_verified: bool,
 }
 
 #[derive(Clone)]
 pub struct AuthenticatedUser {
     pub id: Uuid,
+    pub session_id: Option<Uuid>,
+    pub allowed_accounts: Option<Vec<Uuid>>,
+}
+
+impl AuthenticatedUser {
+    pub fn ensure_account_allowed(&self, account_id: Uuid) -> Result<(), (StatusCode, String)> {
+        match &self.allowed_accounts {
+            Some(allowed) if !allowed.contains(&account_id) => Err((
+                StatusCode::FORBIDDEN,
+                "Token is not allowed to access this account".into(),
+            )),
+            _ => Ok(()),
+        }
+    }
 }
 
 pub async fn is_admin(
@@ -111,8 +177,32 @@ impl FromRequestParts<AppState> for AuthenticatedUser {
             ) {
                 let id = Uuid::parse_str(&claims.claims.sub)
                     .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid user id".into()))?;
+                let session_id = Uuid::parse_str(&claims.claims.sid)
+                    .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid session id".into()))?;
+                let active: bool = sqlx::query_scalar(
+                    r#"
+                    SELECT EXISTS (
+                        SELECT 1
+                        FROM user_sessions
+                        WHERE id = $1
+                          AND user_id = $2
+                          AND revoked_at IS NULL
+                          AND expires_at > NOW()
+                    )
+                    "#,
+                )
+                .bind(session_id)
+                .bind(id)
+                .fetch_one(&pool)
+                .await
+                .map_err(internal_error)?;
+                if !active {
+                    return Err((StatusCode::UNAUTHORIZED, "Session revoked".into()));
+                }
                 return Ok(Self {
                     id,
+                    session_id: Some(session_id),
+                    allowed_accounts: None,
                 });
             }
 
@@ -123,15 +213,14 @@ impl FromRequestParts<AppState> for AuthenticatedUser {
             let token_hash = hash_api_token(token);
             let record = sqlx::query(
                 r#"
-                SELECT id, user_id, is_read_only
+                SELECT id, user_id, is_read_only, scopes, account_ids, account_group_ids
                 FROM api_keys
-                WHERE (token_hash = $1 OR token = $2)
+                WHERE token_hash = $1
                   AND revoked_at IS NULL
                   AND (expires_at IS NULL OR expires_at > NOW())
                 "#,
             )
             .bind(&token_hash)
-            .bind(token)
             .fetch_optional(&pool)
             .await
             .map_err(internal_error)?;
@@ -143,6 +232,11 @@ impl FromRequestParts<AppState> for AuthenticatedUser {
             let user_id: Uuid = record.try_get("user_id").map_err(internal_error)?;
             let is_read_only: bool = record.try_get("is_read_only").map_err(internal_error)?;
             let token_id: Uuid = record.try_get("id").map_err(internal_error)?;
+            let scopes: Option<Vec<String>> = record.try_get("scopes").map_err(internal_error)?;
+            let account_ids: Vec<Uuid> = record.try_get("account_ids").map_err(internal_error)?;
+            let account_group_ids: Vec<Uuid> = record
+                .try_get("account_group_ids")
+                .map_err(internal_error)?;
 
             let _ = sqlx::query(
                 r#"
@@ -155,19 +249,169 @@ impl FromRequestParts<AppState> for AuthenticatedUser {
             .execute(&pool)
             .await;
 
-            if is_read_only && !is_read_only_method(&parts.method) {
-                return Err((StatusCode::FORBIDDEN, "Read-only token".into()));
+            let path = parts.uri.path();
+            let is_write = !is_read_only_method(&parts.method);
+            match scopes {
+                Some(scopes) => match token_scopes::required_scope(path, is_write) {
+                    Some(scope) if scopes.iter().any(|granted| granted == scope) => {}
+                    Some(scope) => {
+                        return Err((
+                            StatusCode::FORBIDDEN,
+                            format!("Token is missing the {scope} scope"),
+                        ))
+                    }
+                    None => {
+                        return Err((
+                            StatusCode::FORBIDDEN,
+                            "Scoped tokens cannot use this endpoint".into(),
+                        ))
+                    }
+                },
+                None if is_read_only && is_write => {
+                    return Err((StatusCode::FORBIDDEN, "Read-only token".into()));
+                }
+                None => {}
+            }
+
+            let allowed_accounts = if account_ids.is_empty() && account_group_ids.is_empty() {
+                None
+            } else {
+                let allowed = restricted_accounts(&pool, &account_ids, &account_group_ids).await?;
+                ensure_request_within_accounts(
+                    &pool,
+                    parts,
+                    is_write,
+                    &allowed,
+                    &account_group_ids,
+                )
+                .await?;
+                Some(allowed)
+            };
+
+            Ok(Self {
+                id: user_id,
+                session_id: None,
+                allowed_accounts,
+            })
+        }
+    }
+}
+
+async fn restricted_accounts(
+    pool: &PgPool,
+    account_ids: &[Uuid],
+    account_group_ids: &[Uuid],
+) -> Result<Vec<Uuid>, (StatusCode, String)> {
+    sqlx::query_scalar(
+        r#"
+        SELECT id
+        FROM accounts
+        WHERE id = ANY($1)
+        UNION
+        SELECT account_id
+        FROM account_group_members
+        WHERE group_id = ANY($2)
+        "#,
+    )
+    .bind(account_ids)
+    .bind(account_group_ids)
+    .fetch_all(pool)
+    .await
+    .map_err(internal_error)
+}
+
+async fn ensure_request_within_accounts(
+    pool: &PgPool,
+    parts: &Parts,
+    is_write: bool,
+    allowed: &[Uuid],
+    account_group_ids: &[Uuid],
+) -> Result<(), (StatusCode, String)> {
+    let forbidden = || {
+        (
+            StatusCode::FORBIDDEN,
+            "Token is not allowed to access this account".to_string(),
+        )
+    };
+    let path = parts.uri.path();
+
+    let mut has_account_filter = false;
+    for (key, value) in url::form_urlencoded::parse(parts.uri.query().unwrap_or("").as_bytes()) {
+        let id = Uuid::parse_str(&value).ok();
+        match key.as_ref() {
+            "account_id" => {
+                if !id.is_some_and(|id| allowed.contains(&id)) {
+                    return Err(forbidden());
+                }
+                has_account_filter = true;
             }
+            "account_group_id" => {
+                if !id.is_some_and(|id| account_group_ids.contains(&id)) {
+                    return Err(forbidden());
+                }
+                has_account_filter = true;
+            }
+            _ => {}
+        }
+    }
 
-            Ok(Self { id: user_id })
+    let lookup = match token_scopes::path_target(path) {
+        Some(PathTarget::Account(id)) => {
+            return if allowed.contains(&id) {
+                Ok(())
+            } else {
+                Err(forbidden())
+            };
+        }
+        Some(PathTarget::AccountGroup(id)) => {
+            return if account_group_ids.contains(&id) {
+                Ok(())
+            } else {
+                Err(forbidden())
+            };
+        }
+        Some(PathTarget::Transaction(id)) => {
+            ("SELECT account_id FROM transactions WHERE id = $1", id)
         }
+        Some(PathTarget::RecurringTransaction(id)) => (
+            "SELECT account_id FROM recurring_transactions WHERE id = $1",
+            id,
+        ),
+        Some(PathTarget::Asset(id)) => ("SELECT account_id FROM assets WHERE id = $1", id),
+        Some(PathTarget::Liability(id)) => ("SELECT account_id FROM liabilities WHERE id = $1", id),
+        Some(PathTarget::BillReminder(id)) => {
+            ("SELECT account_id FROM bill_reminders WHERE id = $1", id)
+        }
+        None => {
+            return if token_scopes::allows_without_target(path, is_write, has_account_filter) {
+                Ok(())
+            } else {
+                Err(forbidden())
+            };
+        }
+    };
+
+    let (sql, id) = lookup;
+    let account_id: Option<Uuid> = sqlx::query_scalar(sql)
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(internal_error)?;
+    match account_id {
+        // Unknown records fall through to the handler's own not-found response.
+        None => Ok(()),
+        Some(account_id) if allowed.contains(&account_id) => Ok(()),
+        Some(_) => Err(forbidden()),
     }
 }
 
 pub async fn register(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(payload): Json<RegisterRequest>,
 ) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+    ensure_password_login(&state)?;
     let email = payload.email.clone();
     let password_hash = hash_password(&payload.password)?;
     let user_id = Uuid::new_v4();
@@ -194,15 +438,27 @@ pub async fn register(
     )
     .await;
 
-    let token = issue_token(&state.jwt_secret, user_id)?;
-    Ok(Json(AuthResponse { token, user_id }))
+    if let Err(error) = send_verification_email(&state, user_id, &email).await {
+        tracing::warn!(error = %error.1, "failed to send verification email");
+    }
+
+    let response = start_session(&state, user_id, &headers, addr).await?;
+    Ok(Json(response))
 }
 
 pub async fn login(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
-) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+) -> Result<Json<LoginResponse>, (StatusCode, String)> {
+    ensure_password_login(&state)?;
     let email = payload.email.clone();
+    let account_key = email.trim().to_lowercase();
+    let (_, ip_address) = client_details(&state, &headers, addr);
+    let ip_address = ip_address.unwrap_or_default();
+    ensure_not_locked_out(&state.pool, &account_key, &ip_address).await?;
+
     let record = sqlx::query(
         r#"
         SELECT id, password_hash
@@ -211,35 +467,169 @@ pub async fn login(
         "#,
     )
     .bind(payload.email)
-    .fetch_one(&state.pool)
+    .fetch_optional(&state.pool)
     .await
-    .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid credentials".into()))?;
+    .map_err(internal_error)?;
 
-    let password_hash: String = record
-        .try_get("password_hash")
-        .map_err(internal_error)?;
-    let user_id: Uuid = record.try_get("id").map_err(internal_error)?;
+    let mut user_id = None;
+    let mut verified = false;
+    if let Some(record) = record {
+        let password_hash: Option<String> = record
+            .try_get("password_hash")
+            .map_err(internal_error)?;
+        user_id = Some(record.try_get::<Uuid, _>("id").map_err(internal_error)?);
+        // Accounts provisioned through single sign-on have no password.
+        if let Some(password_hash) = password_hash {
+            verified = match verify_password(&payload.password, &password_hash) {
+                Ok(()) => true,
+                Err((StatusCode::UNAUTHORIZED, _)) => false,
+                Err(error) => return Err(error),
+            };
+        }
+    }
+    let (Some(user_id), true) = (user_id, verified) else {
+        record_login_failure(&state.pool, &account_key, &ip_address, user_id).await;
+        return Err((StatusCode::UNAUTHORIZED, "Invalid credentials".into()));
+    };
 
-    verify_password(&payload.password, &password_hash)?;
+    // With two-factor enabled the password alone proves nothing yet, so failures
+    // are only cleared once the code is accepted.
+    if let Some(challenge) = two_factor_challenge(&state.pool, user_id).await? {
+        return Ok(Json(LoginResponse::TwoFactorRequired(challenge)));
+    }
+    let _ = login_throttle::clear_failures(&state.pool, ThrottleScope::Account, &account_key).await;
+
+    let response = start_session(&state, user_id, &headers, addr).await?;
 
     let _ = record_audit_event(
         &state.pool,
         Some(user_id),
         "user.login",
-        serde_json::json!({ "email": email }),
+        serde_json::json!({ "email": email, "session_id": response.session_id }),
     )
     .await;
 
-    let token = issue_token(&state.jwt_secret, user_id)?;
-    Ok(Json(AuthResponse {
-        token,
-        user_id,
+    Ok(Json(LoginResponse::Authenticated(response)))
+}
+
+pub(crate) async fn two_factor_challenge(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Option<TwoFactorChallenge>, (StatusCode, String)> {
+    let two_factor_enabled: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1
+            FROM user_totp
+            WHERE user_id = $1 AND confirmed_at IS NOT NULL
+        )
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+    .map_err(internal_error)?;
+    if !two_factor_enabled {
+        return Ok(None);
+    }
+
+    let challenge_token = generate_refresh_token();
+    sqlx::query(
+        r#"
+        INSERT INTO login_challenges (id, user_id, token_hash, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(hash_api_token(&challenge_token))
+    .bind(Utc::now() + Duration::minutes(LOGIN_CHALLENGE_MINUTES))
+    .execute(pool)
+    .await
+    .map_err(internal_error)?;
+
+    Ok(Some(TwoFactorChallenge {
+        two_factor_required: true,
+        challenge_token,
+        expires_in: LOGIN_CHALLENGE_MINUTES * 60,
     }))
 }
 
+pub async fn login_two_factor(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<TwoFactorLoginRequest>,
+) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+    let record = sqlx::query(
+        r#"
+        UPDATE login_challenges
+        SET attempts = attempts + 1
+        WHERE token_hash = $1
+          AND expires_at > NOW()
+          AND attempts < $2
+        RETURNING id, user_id
+        "#,
+    )
+    .bind(hash_api_token(payload.challenge_token.trim()))
+    .bind(LOGIN_CHALLENGE_MAX_ATTEMPTS)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(record) = record else {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            "Login challenge expired; sign in again".into(),
+        ));
+    };
+    let challenge_id: Uuid = record.try_get("id").map_err(internal_error)?;
+    let user_id: Uuid = record.try_get("user_id").map_err(internal_error)?;
+
+    // Codes count towards the same lockout as passwords, so a stolen password
+    // cannot be paired with unlimited fresh challenges.
+    let email: String = sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(internal_error)?;
+    let account_key = email.trim().to_lowercase();
+    let (_, ip_address) = client_details(&state, &headers, addr);
+    let ip_address = ip_address.unwrap_or_default();
+    ensure_not_locked_out(&state.pool, &account_key, &ip_address).await?;
+
+    if !verify_second_factor(&state.pool, user_id, &payload.code).await? {
+        record_failed_code(&state.pool, user_id, "login").await;
+        record_login_failure(&state.pool, &account_key, &ip_address, Some(user_id)).await;
+        return Err((StatusCode::UNAUTHORIZED, "Invalid code".into()));
+    }
+    let _ = login_throttle::clear_failures(&state.pool, ThrottleScope::Account, &account_key).await;
+
+    sqlx::query("DELETE FROM login_challenges WHERE id = $1 OR expires_at < NOW()")
+        .bind(challenge_id)
+        .execute(&state.pool)
+        .await
+        .map_err(internal_error)?;
+
+    let response = start_session(&state, user_id, &headers, addr).await?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user_id),
+        "user.login",
+        serde_json::json!({ "session_id": response.session_id, "two_factor": true }),
+    )
+    .await;
+
+    Ok(Json(response))
+}
+
 pub async fn demo_login(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
 ) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+    ensure_password_login(&state)?;
     let demo_email = "demo@firecash.app";
     let demo_name = "Demo User";
     let demo_password = "demo-password";
@@ -258,8 +648,12 @@ pub async fn demo_login(
 
     let user_id = if let Some(record) = record {
         let user_id: Uuid = record.try_get("id").map_err(internal_error)?;
-        let password_hash: String = record.try_get("password_hash").map_err(internal_error)?;
-        if verify_password(demo_password, &password_hash).is_err() {
+        let password_hash: Option<String> =
+            record.try_get("password_hash").map_err(internal_error)?;
+        let matches = password_hash
+            .as_deref()
+            .is_some_and(|hash| verify_password(demo_password, hash).is_ok());
+        if !matches {
             let next_hash = hash_password(demo_password)?;
             sqlx::query(
                 r#"
@@ -304,8 +698,8 @@ pub async fn demo_login(
     )
     .await;
 
-    let token = issue_token(&state.jwt_secret, user_id)?;
-    Ok(Json(AuthResponse { token, user_id }))
+    let response = start_session(&state, user_id, &headers, addr).await?;
+    Ok(Json(response))
 }
 
 pub async fn me(
@@ -314,7 +708,7 @@ pub async fn me(
 ) -> Result<Json<UserProfile>, (StatusCode, String)> {
     let record = sqlx::query(
         r#"
-        SELECT id, name, email
+        SELECT id, name, email, email_verified_at IS NOT NULL as email_verified
         FROM users
         WHERE id = $1
         "#,
@@ -328,6 +722,7 @@ pub async fn me(
         id: record.try_get("id").map_err(internal_error)?,
         name: record.try_get("name").map_err(internal_error)?,
         email: record.try_get("email").map_err(internal_error)?,
+        email_verified: record.try_get("email_verified").map_err(internal_error)?,
     }))
 }
 
@@ -336,22 +731,42 @@ pub async fn update_me(
     user: AuthenticatedUser,
     Json(payload): Json<UpdateProfileRequest>,
 ) -> Result<Json<UserProfile>, (StatusCode, String)> {
+    let previous_email: String = sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
+        .bind(user.id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(internal_error)?;
+    let email_changed = payload
+        .email
+        .as_deref()
+        .is_some_and(|email| email != previous_email);
+
+    // A new address has to be verified again.
     let record = sqlx::query(
         r#"
         UPDATE users
         SET name = COALESCE($1, name),
-            email = COALESCE($2, email)
+            email = COALESCE($2, email),
+            email_verified_at = CASE WHEN $4 THEN NULL ELSE email_verified_at END
         WHERE id = $3
-        RETURNING id, name, email
+        RETURNING id, name, email, email_verified_at IS NOT NULL as email_verified
         "#,
     )
     .bind(payload.name)
     .bind(payload.email)
     .bind(user.id)
+    .bind(email_changed)
     .fetch_one(&state.pool)
     .await
     .map_err(internal_error)?;
 
+    if email_changed {
+        let email: String = record.try_get("email").map_err(internal_error)?;
+        if let Err(error) = send_verification_email(&state, user.id, &email).await {
+            tracing::warn!(error = %error.1, "failed to send verification email");
+        }
+    }
+
     let _ = record_audit_event(
         &state.pool,
         Some(user.id),
@@ -364,13 +779,316 @@ pub async fn update_me(
         id: record.try_get("id").map_err(internal_error)?,
         name: record.try_get("name").map_err(internal_error)?,
         email: record.try_get("email").map_err(internal_error)?,
+        email_verified: record.try_get("email_verified").map_err(internal_error)?,
+    }))
+}
+
+pub async fn refresh(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+    let token_hash = hash_api_token(payload.refresh_token.trim());
+    let (user_agent, ip_address) = client_details(&state, &headers, addr);
+    let refresh_token = generate_refresh_token();
+
+    let record = sqlx::query(
+        r#"
+        UPDATE user_sessions
+        SET previous_refresh_token_hash = refresh_token_hash,
+            refresh_token_hash = $2,
+            last_used_at = NOW(),
+            expires_at = NOW() + $3 * INTERVAL '1 day',
+            user_agent = COALESCE($4, user_agent),
+            ip_address = COALESCE($5, ip_address)
+        WHERE refresh_token_hash = $1
+          AND revoked_at IS NULL
+          AND expires_at > NOW()
+        RETURNING id, user_id
+        "#,
+    )
+    .bind(&token_hash)
+    .bind(hash_api_token(&refresh_token))
+    .bind(REFRESH_TOKEN_DAYS as f64)
+    .bind(user_agent)
+    .bind(ip_address)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(record) = record else {
+        let reused = sqlx::query(
+            r#"
+            UPDATE user_sessions
+            SET revoked_at = NOW()
+            WHERE previous_refresh_token_hash = $1
+              AND revoked_at IS NULL
+            RETURNING id, user_id
+            "#,
+        )
+        .bind(&token_hash)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(internal_error)?;
+        if let Some(reused) = reused {
+            let session_id: Uuid = reused.try_get("id").map_err(internal_error)?;
+            let user_id: Uuid = reused.try_get("user_id").map_err(internal_error)?;
+            tracing::warn!(%session_id, "refresh token reused; session revoked");
+            let _ = record_audit_event(
+                &state.pool,
+                Some(user_id),
+                "session.refresh_reused",
+                serde_json::json!({ "session_id": session_id }),
+            )
+            .await;
+        }
+        return Err((StatusCode::UNAUTHORIZED, "Invalid refresh token".into()));
+    };
+
+    let session_id: Uuid = record.try_get("id").map_err(internal_error)?;
+    let user_id: Uuid = record.try_get("user_id").map_err(internal_error)?;
+    let token = issue_token(&state.jwt_secret, user_id, session_id)?;
+
+    Ok(Json(AuthResponse {
+        token,
+        refresh_token,
+        expires_in: ACCESS_TOKEN_MINUTES * 60,
+        session_id,
+        user_id,
     }))
 }
 
-fn issue_token(secret: &str, user_id: Uuid) -> Result<String, (StatusCode, String)> {
-    let exp = (Utc::now() + Duration::days(7)).timestamp() as usize;
+pub async fn logout(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let Some(session_id) = user.session_id else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "API tokens are revoked from the token list".into(),
+        ));
+    };
+
+    revoke_session(&state.pool, user.id, session_id).await?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "user.logout",
+        serde_json::json!({ "session_id": session_id }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub(crate) async fn revoke_user_sessions(
+    pool: &PgPool,
+    user_id: Uuid,
+    keep: Option<Uuid>,
+) -> Result<u64, (StatusCode, String)> {
+    let result = sqlx::query(
+        r#"
+        UPDATE user_sessions
+        SET revoked_at = NOW()
+        WHERE user_id = $1
+          AND revoked_at IS NULL
+          AND id IS DISTINCT FROM $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(keep)
+    .execute(pool)
+    .await
+    .map_err(internal_error)?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn revoke_session(
+    pool: &PgPool,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<(), (StatusCode, String)> {
+    let result = sqlx::query(
+        r#"
+        UPDATE user_sessions
+        SET revoked_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+        "#,
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .map_err(internal_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Session not found".into()));
+    }
+
+    Ok(())
+}
+
+fn ensure_password_login(state: &AppState) -> Result<(), (StatusCode, String)> {
+    if state.password_login_enabled {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::FORBIDDEN,
+            "Password login is disabled; sign in with single sign-on".into(),
+        ))
+    }
+}
+
+pub(crate) async fn start_session(
+    state: &AppState,
+    user_id: Uuid,
+    headers: &HeaderMap,
+    addr: SocketAddr,
+) -> Result<AuthResponse, (StatusCode, String)> {
+    let session_id = Uuid::new_v4();
+    let refresh_token = generate_refresh_token();
+    let (user_agent, ip_address) = client_details(state, headers, addr);
+
+    sqlx::query(
+        r#"
+        INSERT INTO user_sessions (
+            id, user_id, refresh_token_hash, user_agent, ip_address, expires_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .bind(hash_api_token(&refresh_token))
+    .bind(user_agent)
+    .bind(ip_address)
+    .bind(Utc::now() + Duration::days(REFRESH_TOKEN_DAYS))
+    .execute(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let token = issue_token(&state.jwt_secret, user_id, session_id)?;
+    Ok(AuthResponse {
+        token,
+        refresh_token,
+        expires_in: ACCESS_TOKEN_MINUTES * 60,
+        session_id,
+        user_id,
+    })
+}
+
+async fn ensure_not_locked_out(
+    pool: &PgPool,
+    account_key: &str,
+    ip_address: &str,
+) -> Result<(), (StatusCode, String)> {
+    for (scope, key) in [
+        (ThrottleScope::Account, account_key),
+        (ThrottleScope::Ip, ip_address),
+    ] {
+        let locked_until = login_throttle::active_lockout(pool, scope, key)
+            .await
+            .map_err(internal_error)?;
+        if let Some(locked_until) = locked_until {
+            let minutes = (locked_until - Utc::now()).num_minutes() + 1;
+            return Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("Too many failed sign-in attempts; try again in {minutes} minute(s)"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+async fn record_login_failure(
+    pool: &PgPool,
+    account_key: &str,
+    ip_address: &str,
+    user_id: Option<Uuid>,
+) {
+    let mut delay = std::time::Duration::ZERO;
+    for (scope, key) in [
+        (ThrottleScope::Account, account_key),
+        (ThrottleScope::Ip, ip_address),
+    ] {
+        let failure = match login_throttle::record_failure(pool, scope, key).await {
+            Ok(failure) => failure,
+            Err(error) => {
+                tracing::warn!(error = %error, "failed to record login failure");
+                continue;
+            }
+        };
+        delay = delay.max(login_throttle::failure_delay(failure.failures));
+        if let Some(locked_until) = failure.locked_until {
+            let _ = record_audit_event(
+                pool,
+                user_id.filter(|_| scope == ThrottleScope::Account),
+                "user.login_locked",
+                serde_json::json!({
+                    "scope": scope.as_str(),
+                    "key": key,
+                    "failures": failure.failures,
+                    "locked_until": locked_until,
+                }),
+            )
+            .await;
+        }
+    }
+    tokio::time::sleep(delay).await;
+}
+
+fn client_details(
+    state: &AppState,
+    headers: &HeaderMap,
+    addr: SocketAddr,
+) -> (Option<String>, Option<String>) {
+    let user_agent = headers
+        .get(axum::http::header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.chars().take(256).collect::<String>());
+    let forwarded_for = headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok());
+    let ip_address = client_ip(forwarded_for, addr.ip(), &state.trusted_proxies);
+    (user_agent, Some(ip_address.to_string()))
+}
+
+fn client_ip(forwarded_for: Option<&str>, peer: IpAddr, trusted_proxies: &[IpAddr]) -> IpAddr {
+    let mut client = peer;
+    for hop in forwarded_for.unwrap_or_default().rsplit(',') {
+        if !trusted_proxies.contains(&client) {
+            break;
+        }
+        match hop.trim().parse() {
+            Ok(ip) => client = ip,
+            Err(_) => break,
+        }
+    }
+    client
+}
+
+fn generate_refresh_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    format!(
+        "{}{}",
+        REFRESH_TOKEN_PREFIX,
+        Base64UrlUnpadded::encode_string(&bytes)
+    )
+}
+
+fn issue_token(
+    secret: &str,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<String, (StatusCode, String)> {
+    let exp = (Utc::now() + Duration::minutes(ACCESS_TOKEN_MINUTES)).timestamp() as usize;
     let claims = Claims {
         sub: user_id.to_string(),
+        sid: session_id.to_string(),
         exp,
     };
     encode(
@@ -381,7 +1099,7 @@ fn issue_token(secret: &str, user_id: Uuid) -> Result<String, (StatusCode, Strin
     .map_err(internal_error)
 }
 
-fn hash_password(password: &str) -> Result<String, (StatusCode, String)> {
+pub(crate) fn hash_password(password: &str) -> Result<String, (StatusCode, String)> {
     let salt = SaltString::generate(&mut OsRng);
     let hash = Argon2::default()
         .hash_password(password.as_bytes(), &salt)
@@ -399,7 +1117,7 @@ pub fn hash_api_token(token: &str) -> String {
     hex_encode(hasher.finalize())
 }
 
-fn verify_password(password: &str, hash: &str) -> Result<(), (StatusCode, String)> {
+pub(crate) fn verify_password(password: &str, hash: &str) -> Result<(), (StatusCode, String)> {
     let parsed = PasswordHash::new(hash).map_err(internal_error)?;
     Argon2::default()
         .verify_password(password.as_bytes(), &parsed)
@@ -525,6 +1243,7 @@ async fn seed_demo_data(
     let group_id = Uuid::new_v4();
 
     let mut tx = pool.begin().await.map_err(internal_error)?;
+    let replaced = replaced_records(&mut tx, user_id).await?;
 
     sqlx::query(
         r#"
@@ -656,9 +1375,9 @@ async fn seed_demo_data(
 
     sqlx::query(
         r#"
-        INSERT INTO accounts (id, user_id, name, currency_code)
-        VALUES ($1, $2, $3, $4),
-               ($5, $6, $7, $8)
+        INSERT INTO accounts (id, user_id, name, currency_code, account_type)
+        VALUES ($1, $2, $3, $4, 'checking'),
+               ($5, $6, $7, $8, 'brokerage')
         "#,
     )
     .bind(checking_id)
@@ -726,12 +1445,13 @@ async fn seed_demo_data(
     .await
     .map_err(internal_error)?;
 
-    sqlx::query(
+    let transaction_ids: Vec<Uuid> = sqlx::query_scalar(
         r#"
         INSERT INTO transactions (id, account_id, amount, currency_code, transaction_type, description, occurred_at)
         VALUES ($1, $2, $3, $4, $5, $6, NOW() - INTERVAL '14 days'),
                ($7, $8, $9, $10, $11, $12, NOW() - INTERVAL '7 days'),
                ($13, $14, $15, $16, $17, $18, NOW() - INTERVAL '1 day')
+        RETURNING id
         "#,
     )
     .bind(Uuid::new_v4())
@@ -752,7 +1472,7 @@ async fn seed_demo_data(
     .bind("USD")
     .bind("expense")
     .bind("Utilities")
-    .execute(&mut *tx)
+    .fetch_all(&mut *tx)
     .await
     .map_err(internal_error)?;
 
@@ -771,7 +1491,185 @@ async fn seed_demo_data(
     .await
     .map_err(internal_error)?;
 
+    let seeded = [vec![checking_id, brokerage_id], transaction_ids, vec![asset_id]];
+    record_replaced_records(&mut tx, user_id, replaced, seeded).await?;
+
     tx.commit().await.map_err(internal_error)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        client_ip, decode, generate_refresh_token, hash_api_token, hash_password, issue_token,
+        login, login_two_factor, seed_demo_data, Claims, DecodingKey, LoginRequest,
+        LoginResponse, TwoFactorLoginRequest, Validation, REFRESH_TOKEN_PREFIX,
+    };
+    use crate::test_support::{create_account, create_user, test_pool, test_state};
+    use axum::{
+        extract::{ConnectInfo, State},
+        http::{HeaderMap, StatusCode},
+        Json,
+    };
+    use std::net::{IpAddr, SocketAddr};
+    use uuid::Uuid;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn forwarded_for_is_ignored_from_untrusted_peers() {
+        let peer = ip("203.0.113.7");
+        assert_eq!(client_ip(Some("198.51.100.1"), peer, &[]), peer);
+        assert_eq!(client_ip(Some("198.51.100.1"), peer, &[ip("10.0.0.2")]), peer);
+    }
+
+    #[test]
+    fn forwarded_for_skips_trusted_proxies_from_the_right() {
+        let trusted = [ip("10.0.0.2"), ip("10.0.0.3")];
+        // The left-most entry is whatever the client sent and is not believed.
+        assert_eq!(
+            client_ip(Some("1.1.1.1, 198.51.100.1"), ip("10.0.0.2"), &trusted),
+            ip("198.51.100.1")
+        );
+        assert_eq!(
+            client_ip(Some("198.51.100.1, 10.0.0.3"), ip("10.0.0.2"), &trusted),
+            ip("198.51.100.1")
+        );
+        assert_eq!(client_ip(Some("not-an-ip"), ip("10.0.0.2"), &trusted), ip("10.0.0.2"));
+        assert_eq!(client_ip(None, ip("10.0.0.2"), &trusted), ip("10.0.0.2"));
+    }
+
+    #[test]
+    fn access_tokens_carry_the_session_id() {
+        let user_id = Uuid::new_v4();
+        let session_id = Uuid::new_v4();
+        let token = issue_token("secret", user_id, session_id).unwrap();
+        let claims = decode::<Claims>(
+            &token,
+            &DecodingKey::from_secret(b"secret"),
+            &Validation::default(),
+        )
+        .unwrap()
+        .claims;
+        assert_eq!(claims.sub, user_id.to_string());
+        assert_eq!(claims.sid, session_id.to_string());
+        let lifetime = claims.exp as i64 - chrono::Utc::now().timestamp();
+        assert!(lifetime > 14 * 60 && lifetime <= 15 * 60);
+        assert!(decode::<Claims>(
+            &token,
+            &DecodingKey::from_secret(b"other"),
+            &Validation::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn refresh_tokens_are_random_and_stored_hashed() {
+        let first = generate_refresh_token();
+        let second = generate_refresh_token();
+        assert!(first.starts_with(REFRESH_TOKEN_PREFIX));
+        assert_ne!(first, second);
+        assert_eq!(hash_api_token(&first), hash_api_token(&first));
+        assert_ne!(hash_api_token(&first), first);
+    }
+
+    #[tokio::test]
+    async fn wrong_codes_after_right_passwords_end_in_lockout() {
+        let Some(pool) = test_pool().await else { return };
+        let state = test_state(pool);
+        let user = create_user(&state.pool, "totp").await;
+        let email: String = sqlx::query_scalar(
+            "UPDATE users SET password_hash = $2 WHERE id = $1 RETURNING email",
+        )
+        .bind(user.id)
+        .bind(hash_password("correct horse").unwrap())
+        .fetch_one(&state.pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO user_totp (user_id, secret, confirmed_at) VALUES ($1, 'JBSWY3DPEHPK3PXP', NOW())",
+        )
+        .bind(user.id)
+        .execute(&state.pool)
+        .await
+        .unwrap();
+        let addr = SocketAddr::from(([203, 0, 113, 9], 443));
+
+        let mut outcomes = Vec::new();
+        for _ in 0..6 {
+            let result = login(
+                State(state.clone()),
+                ConnectInfo(addr),
+                HeaderMap::new(),
+                Json(LoginRequest {
+                    email: email.clone(),
+                    password: "correct horse".into(),
+                }),
+            )
+            .await;
+            let challenge_token = match result {
+                Ok(Json(LoginResponse::TwoFactorRequired(challenge))) => challenge.challenge_token,
+                Ok(Json(LoginResponse::Authenticated(_))) => panic!("second factor skipped"),
+                Err((status, _)) => {
+                    outcomes.push(status);
+                    break;
+                }
+            };
+            let result = login_two_factor(
+                State(state.clone()),
+                ConnectInfo(addr),
+                HeaderMap::new(),
+                Json(TwoFactorLoginRequest {
+                    challenge_token,
+                    code: "not-a-code".into(),
+                }),
+            )
+            .await;
+            outcomes.push(result.err().unwrap().0);
+        }
+        assert_eq!(
+            outcomes,
+            [
+                StatusCode::UNAUTHORIZED,
+                StatusCode::UNAUTHORIZED,
+                StatusCode::UNAUTHORIZED,
+                StatusCode::UNAUTHORIZED,
+                StatusCode::UNAUTHORIZED,
+                StatusCode::TOO_MANY_REQUESTS,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn demo_reseed_records_history() {
+        let Some(pool) = test_pool().await else { return };
+        let user = create_user(&pool, "demo").await;
+        let stale_account = create_account(&pool, &user).await;
+
+        seed_demo_data(&pool, user.id).await.unwrap();
+
+        let rows: Vec<(String, Uuid, String)> = sqlx::query_as(
+            r#"
+            SELECT entity_type, entity_id, action
+            FROM change_history
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user.id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let count = |entity: &str, action: &str| {
+            rows.iter()
+                .filter(|(entity_type, _, row_action)| entity_type == entity && row_action == action)
+                .count()
+        };
+        assert!(rows.contains(&("account".into(), stale_account, "deleted".into())));
+        assert_eq!(count("account", "created"), 2);
+        assert_eq!(count("transaction", "created"), 3);
+        assert_eq!(count("asset", "created"), 1);
+    }
+}
@@ -1,8 +1,17 @@
 use sqlx::postgres::PgPoolOptions;
-use std::time::Duration;
+use std::sync::Arc;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use firecash_api::services::{forex::refresh_fx_rates, pricing::refresh_asset_prices};
+use firecash_api::services::{
+    backfill::backfill_pending_assets,
+    benchmark::refresh_benchmarks,
+    change_history::{record_changes, ChangeEntity},
+    credit_cards::record_bill_reminders,
+    forex::refresh_fx_rates,
+    pricing::refresh_due_asset_prices,
+    scheduler::{JobError, JobHandler, Scheduler, WORKER_JOBS},
+    valuation::apply_valuation_schedules,
+};
 
 #[tokio::main]
 async fn main() {
@@ -16,7 +25,7 @@ async fn main() {
     let database_url = std::env::var("DATABASE_URL")
         .unwrap_or_else(|_| "postgres://firecash:firecash@db:5432/firecash".into());
     let pool = PgPoolOptions::new()
-        .max_connections(2)
+        .max_connections(5)
         .connect(&database_url)
         .await
         .expect("failed to connect to database");
@@ -29,25 +38,65 @@ async fn main() {
         .await
         .expect("failed to run migrations");
 
-    let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
-
-    loop {
-        interval.tick().await;
-        if let Err(error) = refresh_recurring_transactions(&pool).await {
-            tracing::error!(?error, "failed to refresh recurring transactions");
-        }
-        if let Err(error) = refresh_fx_rates(&pool).await {
-            tracing::error!(?error, "failed to refresh FX rates");
-        }
-        if let Err(error) = refresh_asset_prices(&pool, None).await {
-            tracing::error!(?error, "failed to refresh prices");
-        }
+    let mut scheduler = Scheduler::new(pool);
+    for spec in &WORKER_JOBS {
+        let handler: JobHandler = match spec.name {
+            "recurring_transactions" => Arc::new(|pool| {
+                Box::pin(async move {
+                    refresh_recurring_transactions(&pool).await?;
+                    Ok(())
+                })
+            }),
+            "fx_rates" => Arc::new(|pool| Box::pin(async move { refresh_fx_rates(&pool).await })),
+            "asset_prices" => Arc::new(|pool| {
+                Box::pin(async move {
+                    let updated = refresh_due_asset_prices(&pool).await?;
+                    tracing::info!(updated, "refreshed asset prices");
+                    Ok::<(), JobError>(())
+                })
+            }),
+            "price_backfill" => Arc::new(|pool| {
+                Box::pin(async move {
+                    let inserted = backfill_pending_assets(&pool).await?;
+                    tracing::info!(inserted, "backfilled price history");
+                    Ok::<(), JobError>(())
+                })
+            }),
+            "benchmark_prices" => Arc::new(|pool| {
+                Box::pin(async move {
+                    let written = refresh_benchmarks(&pool).await?;
+                    tracing::info!(written, "refreshed benchmark series");
+                    Ok::<(), JobError>(())
+                })
+            }),
+            "manual_valuations" => Arc::new(|pool| {
+                Box::pin(async move {
+                    let written = apply_valuation_schedules(&pool, None).await?;
+                    tracing::info!(written, "applied valuation schedules");
+                    Ok::<(), JobError>(())
+                })
+            }),
+            "bill_reminders" => Arc::new(|pool| {
+                Box::pin(async move {
+                    let created = record_bill_reminders(&pool).await?;
+                    tracing::info!(created, "recorded bill reminders");
+                    Ok::<(), JobError>(())
+                })
+            }),
+            other => panic!("no handler registered for job {other}"),
+        };
+        scheduler
+            .register(spec, handler)
+            .expect("failed to register job");
     }
+
+    scheduler.run().await;
 }
 
 async fn refresh_recurring_transactions(pool: &sqlx::PgPool) -> Result<(), sqlx::Error> {
     tracing::info!("refreshing recurring transactions");
-    sqlx::query(
+    let mut tx = pool.begin().await?;
+    let created: Vec<uuid::Uuid> = sqlx::query_scalar(
         r#"
         WITH due AS (
             SELECT id, account_id, amount, currency_code, transaction_type, description,
@@ -64,14 +113,25 @@ async fn refresh_recurring_transactions(pool: &sqlx::PgPool) -> Result<(), sqlx:
             SELECT gen_random_uuid(), account_id, amount, currency_code, transaction_type,
                    description, next_occurs_at
             FROM due
+            RETURNING id
+        ),
+        advanced AS (
+            UPDATE recurring_transactions rt
+            SET next_occurs_at = rt.next_occurs_at + make_interval(days => rt.interval_days)
+            FROM due
+            WHERE rt.id = due.id
         )
-        UPDATE recurring_transactions rt
-        SET next_occurs_at = rt.next_occurs_at + make_interval(days => rt.interval_days)
-        FROM due
-        WHERE rt.id = due.id
+        SELECT id FROM inserted
         "#,
     )
-    .execute(pool)
+    .fetch_all(&mut *tx)
+    .await?;
+    record_changes(
+        &mut tx,
+        None,
+        ChangeEntity::Transaction,
+        created.into_iter().map(|id| (id, None)).collect(),
+    )
     .await?;
-    Ok(())
+    tx.commit().await
 }
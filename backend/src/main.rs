@@ -1,18 +1,22 @@
 mod audit;
 mod auth;
 mod models;
+mod policy;
 mod routes;
-mod services;
 mod state;
+#[cfg(test)]
+mod test_support;
 
 use axum::{
     middleware::from_fn_with_state,
+    routing::delete,
     routing::get,
     routing::post,
     routing::put,
     Router,
 };
 use axum::{body::Body, extract::State, http::Request, middleware::Next, response::Response};
+use firecash_api::services;
 use sqlx::postgres::PgPoolOptions;
 use std::net::SocketAddr;
 use tower_governor::{governor::GovernorConfigBuilder, GovernorLayer};
@@ -55,6 +59,41 @@ async fn main() {
         .filter(|email| !email.is_empty())
         .collect::<Vec<_>>();
 
+    let oidc = services::oidc::OidcConfig::from_env();
+    let password_login_enabled = !std::env::var("DISABLE_PASSWORD_LOGIN")
+        .map(|value| matches!(value.trim(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    if !password_login_enabled && oidc.is_none() {
+        tracing::warn!("password login is disabled but OIDC is not configured");
+    }
+
+    let mailer = services::mail::mail_sender_from_env();
+    tracing::info!(transport = mailer.name(), "mail sender configured");
+    let app_url = std::env::var("APP_BASE_URL")
+        .unwrap_or_else(|_| "http://localhost:5173".into())
+        .trim_end_matches('/')
+        .to_string();
+    let trusted_proxies = std::env::var("TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .filter_map(|value| match value.parse() {
+            Ok(ip) => Some(ip),
+            Err(_) => {
+                tracing::warn!(value, "ignoring invalid TRUSTED_PROXIES entry");
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // Sign-in and credential endpoints get a much tighter per-IP budget than
+    // data routes: a burst of 10, then one request every 6 seconds.
+    let auth_governor_conf = GovernorConfigBuilder::default()
+        .per_second(6)
+        .burst_size(10)
+        .finish()
+        .expect("failed to build auth rate limit config");
     let governor_conf = GovernorConfigBuilder::default()
         .per_second(5)
         .burst_size(20)
@@ -65,13 +104,55 @@ async fn main() {
         pool,
         jwt_secret,
         admin_emails,
+        oidc,
+        password_login_enabled,
+        mailer,
+        app_url,
+        trusted_proxies,
     };
 
-    let app = Router::new()
-        .route("/health", get(health))
+    let auth_routes = Router::new()
         .route("/api/register", post(auth::register))
         .route("/api/login", post(auth::login))
         .route("/api/demo-login", post(auth::demo_login))
+        .route("/api/login/2fa", post(auth::login_two_factor))
+        .route("/api/oidc/authorize", get(routes::oidc::authorize))
+        .route("/api/oidc/callback", post(routes::oidc::callback))
+        .route("/api/refresh", post(auth::refresh))
+        .route("/api/me/password", put(routes::credentials::change_password))
+        .route(
+            "/api/password/forgot",
+            post(routes::credentials::request_password_reset),
+        )
+        .route("/api/password/reset", post(routes::credentials::reset_password))
+        .route(
+            "/api/email/verification",
+            post(routes::credentials::send_email_verification),
+        )
+        .route("/api/email/verify", post(routes::credentials::verify_email))
+        .route(
+            "/api/invitations/preview",
+            get(routes::group_invitations::preview_invitation),
+        )
+        .layer(GovernorLayer::new(auth_governor_conf));
+
+    let data_routes = Router::new()
+        .route("/health", get(health))
+        .route("/api/auth/config", get(routes::oidc::get_auth_config))
+        .route("/api/logout", post(auth::logout))
+        .route("/api/sessions", get(routes::sessions::list_sessions))
+        .route("/api/2fa", get(routes::two_factor::get_two_factor_status))
+        .route("/api/2fa/enroll", post(routes::two_factor::enroll_two_factor))
+        .route("/api/2fa/confirm", post(routes::two_factor::confirm_two_factor))
+        .route("/api/2fa/disable", post(routes::two_factor::disable_two_factor))
+        .route(
+            "/api/2fa/recovery-codes",
+            post(routes::two_factor::regenerate_recovery_codes),
+        )
+        .route(
+            "/api/sessions/{id}/revoke",
+            post(routes::sessions::revoke_session),
+        )
         .route("/api/me", get(auth::me).put(auth::update_me))
         .route(
             "/api/tokens",
@@ -82,6 +163,10 @@ async fn main() {
             "/api/tokens/{id}/revoke",
             post(routes::api_tokens::revoke_api_token),
         )
+        .route(
+            "/api/tokens/{id}/rotate",
+            post(routes::api_tokens::rotate_api_token),
+        )
         .route(
             "/api/backup/export",
             get(routes::backup::export_backup),
@@ -94,11 +179,45 @@ async fn main() {
             "/api/admin/audit-logs",
             get(routes::admin::list_audit_logs),
         )
+        .route("/api/admin/jobs", get(routes::admin::list_jobs))
+        .route(
+            "/api/admin/jobs/{name}/runs",
+            get(routes::admin::list_job_runs),
+        )
         .route("/api/accounts", get(routes::accounts::list_accounts).post(routes::accounts::create_account))
         .route(
             "/api/accounts/{id}",
             put(routes::accounts::update_account).delete(routes::accounts::delete_account),
         )
+        .route(
+            "/api/accounts/{id}/opening-balance",
+            get(routes::balances::get_opening_balance)
+                .put(routes::balances::set_opening_balance)
+                .delete(routes::balances::delete_opening_balance),
+        )
+        .route(
+            "/api/accounts/{id}/balance-assertions",
+            get(routes::balances::list_balance_assertions)
+                .post(routes::balances::create_balance_assertion),
+        )
+        .route(
+            "/api/accounts/{id}/balance-assertions/{assertion_id}",
+            delete(routes::balances::delete_balance_assertion),
+        )
+        .route(
+            "/api/balance-assertions/check",
+            get(routes::balances::check_balance_assertions),
+        )
+        .route(
+            "/api/accounts/{id}/statement",
+            get(routes::credit_cards::get_account_statement),
+        )
+        .route("/api/bill-reminders", get(routes::credit_cards::list_bill_reminders))
+        .route(
+            "/api/bill-reminders/{id}/dismiss",
+            post(routes::credit_cards::dismiss_bill_reminder),
+        )
+        .route("/api/activity", get(routes::activity::list_activity))
         .route(
             "/api/account-groups",
             get(routes::account_groups::list_account_groups)
@@ -123,6 +242,27 @@ async fn main() {
             put(routes::account_groups::update_account_group_user)
                 .delete(routes::account_groups::delete_account_group_user),
         )
+        .route(
+            "/api/account-groups/{id}/invitations",
+            get(routes::group_invitations::list_group_invitations)
+                .post(routes::group_invitations::create_group_invitation),
+        )
+        .route(
+            "/api/account-groups/{id}/invitations/{invitation_id}",
+            delete(routes::group_invitations::revoke_group_invitation),
+        )
+        .route(
+            "/api/invitations",
+            get(routes::group_invitations::list_my_invitations),
+        )
+        .route(
+            "/api/invitations/accept",
+            post(routes::group_invitations::accept_invitation),
+        )
+        .route(
+            "/api/invitations/decline",
+            post(routes::group_invitations::decline_invitation),
+        )
         .route(
             "/api/transactions",
             get(routes::transactions::list_transactions)
@@ -142,6 +282,14 @@ async fn main() {
             get(routes::recurring_transactions::list_recurring_transactions)
                 .post(routes::recurring_transactions::create_recurring_transaction),
         )
+        .route(
+            "/api/recurring-transactions/suggestions",
+            get(routes::recurring_transactions::list_recurring_suggestions),
+        )
+        .route(
+            "/api/recurring-transactions/suggestions/accept",
+            post(routes::recurring_transactions::accept_recurring_suggestion),
+        )
         .route(
             "/api/recurring-transactions/{id}",
             put(routes::recurring_transactions::update_recurring_transaction)
@@ -160,10 +308,84 @@ async fn main() {
         .route("/api/assets/price-status", get(routes::assets::asset_price_status))
         .route("/api/assets/refresh-prices", post(routes::assets::refresh_prices))
         .route("/api/assets/candles", get(routes::assets::list_candles))
+        .route("/api/assets/returns", get(routes::asset_returns::portfolio_returns))
         .route(
             "/api/assets/{id}",
             put(routes::assets::update_asset).delete(routes::assets::delete_asset),
         )
+        .route(
+            "/api/assets/{id}/backfill",
+            post(routes::assets::backfill_asset_history),
+        )
+        .route(
+            "/api/assets/{id}/trades",
+            get(routes::asset_trades::list_asset_trades)
+                .post(routes::asset_trades::create_asset_trade),
+        )
+        .route(
+            "/api/assets/{id}/trades/{trade_id}",
+            delete(routes::asset_trades::delete_asset_trade),
+        )
+        .route(
+            "/api/assets/{id}/income",
+            get(routes::asset_events::list_asset_income)
+                .post(routes::asset_events::create_asset_income),
+        )
+        .route(
+            "/api/assets/{id}/income/{event_id}",
+            delete(routes::asset_events::delete_asset_income),
+        )
+        .route(
+            "/api/assets/{id}/splits",
+            get(routes::asset_events::list_asset_splits)
+                .post(routes::asset_events::create_asset_split),
+        )
+        .route(
+            "/api/assets/{id}/splits/{split_id}",
+            delete(routes::asset_events::delete_asset_split),
+        )
+        .route(
+            "/api/assets/{id}/valuations",
+            get(routes::asset_valuations::list_asset_valuations)
+                .post(routes::asset_valuations::create_asset_valuation),
+        )
+        .route(
+            "/api/assets/{id}/valuations/{valuation_id}",
+            delete(routes::asset_valuations::delete_asset_valuation),
+        )
+        .route(
+            "/api/assets/{id}/valuation-schedule",
+            get(routes::asset_valuations::get_asset_valuation_schedule)
+                .put(routes::asset_valuations::update_asset_valuation_schedule)
+                .delete(routes::asset_valuations::delete_asset_valuation_schedule),
+        )
+        .route(
+            "/api/liabilities",
+            get(routes::liabilities::list_liabilities).post(routes::liabilities::create_liability),
+        )
+        .route(
+            "/api/liabilities/{id}",
+            delete(routes::liabilities::delete_liability),
+        )
+        .route(
+            "/api/liabilities/{id}/schedule",
+            get(routes::liabilities::get_liability_schedule),
+        )
+        .route(
+            "/api/liabilities/{id}/payments",
+            get(routes::liabilities::list_liability_payments)
+                .post(routes::liabilities::create_liability_payment),
+        )
+        .route(
+            "/api/liabilities/{id}/payments/{payment_id}",
+            delete(routes::liabilities::delete_liability_payment),
+        )
+        .route("/api/allocation", get(routes::allocation::get_allocation))
+        .route(
+            "/api/allocation/targets",
+            get(routes::allocation::get_allocation_targets)
+                .put(routes::allocation::update_allocation_targets),
+        )
         .route("/api/dashboard", get(routes::dashboard::dashboard))
         .route("/api/totals", get(routes::metrics::totals))
         .route("/api/history", get(routes::metrics::history))
@@ -191,8 +413,12 @@ async fn main() {
             "/api/plugins",
             get(routes::plugins::list_plugins).post(routes::plugins::register_plugin),
         )
+        .layer(GovernorLayer::new(governor_conf));
+
+    let app = Router::new()
+        .merge(auth_routes)
+        .merge(data_routes)
         .layer(CorsLayer::new().allow_origin(Any).allow_headers(Any))
-        .layer(GovernorLayer::new(governor_conf))
         .layer(from_fn_with_state(state.clone(), monitoring_middleware))
         .with_state(state);
 
@@ -8,19 +8,39 @@ pub struct Account {
     pub id: Uuid,
     pub name: String,
     pub currency_code: String,
+    #[serde(default = "default_account_type")]
+    pub account_type: String,
+    #[serde(default)]
+    pub credit_limit: Option<f64>,
+    #[serde(default)]
+    pub statement_closing_day: Option<i32>,
+    #[serde(default)]
+    pub payment_due_day: Option<i32>,
     pub created_at: DateTime<Utc>,
 }
 
+fn default_account_type() -> String {
+    "checking".to_string()
+}
+
 #[derive(Deserialize)]
 pub struct CreateAccountRequest {
     pub name: String,
     pub currency_code: String,
+    pub account_type: Option<String>,
+    pub credit_limit: Option<f64>,
+    pub statement_closing_day: Option<i32>,
+    pub payment_due_day: Option<i32>,
 }
 
 #[derive(Deserialize)]
 pub struct UpdateAccountRequest {
     pub name: Option<String>,
     pub currency_code: Option<String>,
+    pub account_type: Option<String>,
+    pub credit_limit: Option<f64>,
+    pub statement_closing_day: Option<i32>,
+    pub payment_due_day: Option<i32>,
 }
 
 #[derive(Serialize, FromRow)]
@@ -28,6 +48,10 @@ pub struct UpdateAccountResponse {
     pub id: Uuid,
     pub name: String,
     pub currency_code: String,
+    pub account_type: String,
+    pub credit_limit: Option<f64>,
+    pub statement_closing_day: Option<i32>,
+    pub payment_due_day: Option<i32>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -197,9 +221,23 @@ pub struct Asset {
     pub asset_type: String,
     pub quantity: f64,
     pub currency_code: String,
+    #[serde(default)]
+    pub data_source: Option<String>,
+    #[serde(default = "default_cost_basis_method")]
+    pub cost_basis_method: String,
+    #[serde(default = "default_valuation_mode")]
+    pub valuation_mode: String,
     pub created_at: DateTime<Utc>,
 }
 
+fn default_cost_basis_method() -> String {
+    "fifo".to_string()
+}
+
+fn default_valuation_mode() -> String {
+    "market".to_string()
+}
+
 #[derive(Deserialize)]
 pub struct CreateAssetRequest {
     pub account_id: Uuid,
@@ -207,6 +245,9 @@ pub struct CreateAssetRequest {
     pub asset_type: String,
     pub quantity: f64,
     pub currency_code: String,
+    pub data_source: Option<String>,
+    pub cost_basis_method: Option<String>,
+    pub valuation_mode: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -216,6 +257,9 @@ pub struct UpdateAssetRequest {
     pub asset_type: Option<String>,
     pub quantity: Option<f64>,
     pub currency_code: Option<String>,
+    pub data_source: Option<String>,
+    pub cost_basis_method: Option<String>,
+    pub valuation_mode: Option<String>,
 }
 
 #[derive(Serialize, FromRow)]
@@ -226,5 +270,187 @@ pub struct UpdateAssetResponse {
     pub asset_type: String,
     pub quantity: f64,
     pub currency_code: String,
+    pub data_source: Option<String>,
+    pub cost_basis_method: String,
+    pub valuation_mode: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, FromRow)]
+pub struct AssetTrade {
+    pub id: Uuid,
+    pub asset_id: Uuid,
+    pub side: String,
+    pub quantity: f64,
+    pub price: f64,
+    pub fees: f64,
+    pub currency_code: String,
+    pub traded_at: DateTime<Utc>,
+    pub lot_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateAssetTradeRequest {
+    pub side: String,
+    pub quantity: f64,
+    pub price: f64,
+    pub fees: Option<f64>,
+    pub currency_code: Option<String>,
+    pub traded_at: DateTime<Utc>,
+    pub lot_id: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, FromRow)]
+pub struct AssetIncomeEvent {
+    pub id: Uuid,
+    pub asset_id: Uuid,
+    pub kind: String,
+    pub amount: f64,
+    pub currency_code: String,
+    pub paid_at: DateTime<Utc>,
+    pub description: Option<String>,
+    pub transaction_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateAssetIncomeRequest {
+    pub kind: String,
+    pub amount: Option<f64>,
+    pub amount_per_share: Option<f64>,
+    pub currency_code: Option<String>,
+    pub paid_at: DateTime<Utc>,
+    pub description: Option<String>,
+    pub create_transaction: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, FromRow)]
+pub struct AssetSplit {
+    pub id: Uuid,
+    pub asset_id: Uuid,
+    pub numerator: f64,
+    pub denominator: f64,
+    pub kind: String,
+    pub effective_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateAssetSplitRequest {
+    pub numerator: f64,
+    pub denominator: f64,
+    pub effective_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, FromRow)]
+pub struct AssetValuation {
+    pub id: Uuid,
+    pub asset_id: Uuid,
+    pub price: f64,
+    pub currency_code: String,
+    pub valued_at: DateTime<Utc>,
+    pub note: Option<String>,
     pub created_at: DateTime<Utc>,
 }
+
+#[derive(Deserialize)]
+pub struct CreateAssetValuationRequest {
+    pub price: Option<f64>,
+    pub value: Option<f64>,
+    pub valued_at: Option<DateTime<Utc>>,
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, FromRow)]
+pub struct AssetValuationSchedule {
+    pub asset_id: Uuid,
+    pub method: String,
+    pub annual_rate: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateAssetValuationScheduleRequest {
+    pub method: Option<String>,
+    pub annual_rate: f64,
+}
+
+#[derive(Serialize, Deserialize, FromRow)]
+pub struct Liability {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub name: String,
+    pub principal: f64,
+    pub annual_rate: f64,
+    pub term_months: i32,
+    pub payment_frequency: String,
+    pub currency_code: String,
+    pub start_date: NaiveDate,
+    #[serde(default)]
+    pub outstanding_balance: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateLiabilityRequest {
+    pub account_id: Uuid,
+    pub name: String,
+    pub principal: f64,
+    pub annual_rate: f64,
+    pub term_months: i32,
+    pub payment_frequency: Option<String>,
+    pub currency_code: Option<String>,
+    pub start_date: NaiveDate,
+}
+
+#[derive(Serialize, Deserialize, FromRow)]
+pub struct LiabilityPayment {
+    pub id: Uuid,
+    pub liability_id: Uuid,
+    pub period: i32,
+    pub principal_amount: f64,
+    pub interest_amount: f64,
+    pub paid_at: DateTime<Utc>,
+    pub principal_transaction_id: Option<Uuid>,
+    pub interest_transaction_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateLiabilityPaymentRequest {
+    pub period: Option<i32>,
+    pub paid_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct OpeningBalance {
+    pub account_id: Uuid,
+    pub amount: f64,
+    pub as_of: NaiveDate,
+    pub transaction_id: Uuid,
+}
+
+#[derive(Deserialize)]
+pub struct SetOpeningBalanceRequest {
+    pub amount: f64,
+    pub as_of: NaiveDate,
+}
+
+#[derive(Serialize, Deserialize, FromRow)]
+pub struct BalanceAssertion {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub asserted_on: NaiveDate,
+    pub balance: f64,
+    pub currency_code: String,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateBalanceAssertionRequest {
+    pub asserted_on: NaiveDate,
+    pub balance: f64,
+    pub note: Option<String>,
+}
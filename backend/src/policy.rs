@@ -0,0 +1,814 @@
+use axum::http::StatusCode;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::auth::{internal_error, AuthenticatedUser};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AccountRole {
+    Viewer,
+    Editor,
+    Admin,
+    Owner,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AccountAction {
+    View,
+    Edit,
+    Own,
+}
+
+impl AccountAction {
+    fn minimum_role(&self) -> AccountRole {
+        match self {
+            Self::View => AccountRole::Viewer,
+            Self::Edit => AccountRole::Editor,
+            Self::Own => AccountRole::Owner,
+        }
+    }
+}
+
+pub fn permits(role: Option<AccountRole>, action: AccountAction) -> bool {
+    role.is_some_and(|role| role >= action.minimum_role())
+}
+
+fn group_role(value: &str) -> Option<AccountRole> {
+    match value {
+        "view" => Some(AccountRole::Viewer),
+        "edit" => Some(AccountRole::Editor),
+        "admin" => Some(AccountRole::Admin),
+        _ => None,
+    }
+}
+
+pub async fn account_role(
+    pool: &PgPool,
+    user_id: Uuid,
+    account_id: Uuid,
+) -> Result<Option<AccountRole>, (StatusCode, String)> {
+    let record = sqlx::query(
+        r#"
+        SELECT a.user_id,
+               ARRAY_REMOVE(ARRAY_AGG(agu.role), NULL) as group_roles
+        FROM accounts a
+        LEFT JOIN account_group_members agm ON a.id = agm.account_id
+        LEFT JOIN account_group_users agu ON agm.group_id = agu.group_id AND agu.user_id = $1
+        WHERE a.id = $2
+        GROUP BY a.user_id
+        "#,
+    )
+    .bind(user_id)
+    .bind(account_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(record) = record else {
+        return Ok(None);
+    };
+    let owner_id: Uuid = record.try_get("user_id").map_err(internal_error)?;
+    if owner_id == user_id {
+        return Ok(Some(AccountRole::Owner));
+    }
+    let group_roles: Vec<String> = record.try_get("group_roles").map_err(internal_error)?;
+    Ok(group_roles.iter().filter_map(|role| group_role(role)).max())
+}
+
+pub async fn accessible_account_ids(
+    pool: &PgPool,
+    user: &AuthenticatedUser,
+) -> Result<Vec<Uuid>, (StatusCode, String)> {
+    let account_ids: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT id
+        FROM accounts
+        WHERE user_id = $1
+        UNION
+        SELECT agm.account_id
+        FROM account_group_members agm
+        INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
+        WHERE agu.user_id = $1
+        "#,
+    )
+    .bind(user.id)
+    .fetch_all(pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(match &user.allowed_accounts {
+        Some(allowed) => account_ids
+            .into_iter()
+            .filter(|account_id| allowed.contains(account_id))
+            .collect(),
+        None => account_ids,
+    })
+}
+
+pub async fn authorize_account(
+    pool: &PgPool,
+    user: &AuthenticatedUser,
+    account_id: Uuid,
+    action: AccountAction,
+) -> Result<AccountRole, (StatusCode, String)> {
+    user.ensure_account_allowed(account_id)?;
+    let role = account_role(pool, user.id, account_id).await?;
+    match role {
+        None => Err((StatusCode::NOT_FOUND, "Account not found".into())),
+        Some(role) if permits(Some(role), action) => Ok(role),
+        Some(_) => Err((StatusCode::FORBIDDEN, "Forbidden".into())),
+    }
+}
+
+pub async fn authorize_asset(
+    pool: &PgPool,
+    user: &AuthenticatedUser,
+    asset_id: Uuid,
+    action: AccountAction,
+) -> Result<Uuid, (StatusCode, String)> {
+    let account_id: Option<Uuid> =
+        sqlx::query_scalar("SELECT account_id FROM assets WHERE id = $1")
+            .bind(asset_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(internal_error)?;
+    let Some(account_id) = account_id else {
+        return Err((StatusCode::NOT_FOUND, "Asset not found".into()));
+    };
+    match authorize_account(pool, user, account_id, action).await {
+        Err((StatusCode::NOT_FOUND, _)) => Err((StatusCode::NOT_FOUND, "Asset not found".into())),
+        Err(error) => Err(error),
+        Ok(_) => Ok(account_id),
+    }
+}
+
+pub fn ensure_backup_access(user: &AuthenticatedUser) -> Result<(), (StatusCode, String)> {
+    if user.allowed_accounts.is_some() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Backups are not available to account-restricted tokens".into(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        accessible_account_ids, authorize_account, group_role, AccountAction, AccountRole,
+    };
+    use crate::auth::AuthenticatedUser;
+    use crate::models::{
+        CreateAssetRequest, CreateAssetTradeRequest, CreateRecurringTransactionRequest,
+        CreateTransactionRequest, UpdateAccountRequest, UpdateAssetRequest,
+        UpdateRecurringTransactionRequest, UpdateTransactionRequest,
+    };
+    use crate::routes::{
+        accounts, accounts::PaginationParams, asset_trades, assets, assets::AssetQueryParams,
+        backup, backup::BackupPayload, backup::ExportQuery, backup::RestoreRequest, liabilities,
+        recurring_transactions, transactions, transactions::TransactionQueryParams,
+    };
+    use crate::test_support::{
+        create_account, create_user, share_account, test_pool, test_state,
+    };
+    use axum::{
+        extract::{Path, Query, State},
+        http::StatusCode,
+        Json,
+    };
+    use chrono::Utc;
+    use serde_json::json;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    struct Fixture {
+        account_id: Uuid,
+        owner: AuthenticatedUser,
+        admin: AuthenticatedUser,
+        editor: AuthenticatedUser,
+        viewer: AuthenticatedUser,
+        outsider: AuthenticatedUser,
+    }
+
+    impl Fixture {
+        async fn seed(pool: &PgPool) -> Self {
+            let owner = create_user(pool, "owner").await;
+            let account_id = create_account(pool, &owner).await;
+            let admin = create_user(pool, "admin").await;
+            let editor = create_user(pool, "editor").await;
+            let viewer = create_user(pool, "viewer").await;
+            let outsider = create_user(pool, "outsider").await;
+            share_account(pool, &owner, account_id, &admin, "admin").await;
+            share_account(pool, &owner, account_id, &editor, "edit").await;
+            share_account(pool, &owner, account_id, &viewer, "view").await;
+            Self {
+                account_id,
+                owner,
+                admin,
+                editor,
+                viewer,
+                outsider,
+            }
+        }
+
+        fn users(&self) -> [&AuthenticatedUser; 5] {
+            [
+                &self.owner,
+                &self.admin,
+                &self.editor,
+                &self.viewer,
+                &self.outsider,
+            ]
+        }
+    }
+
+    fn status<T>(result: Result<T, (StatusCode, String)>) -> StatusCode {
+        match result {
+            Ok(_) => StatusCode::OK,
+            Err((status, _)) => status,
+        }
+    }
+
+    const OK: StatusCode = StatusCode::OK;
+    const FORBIDDEN: StatusCode = StatusCode::FORBIDDEN;
+    const NOT_FOUND: StatusCode = StatusCode::NOT_FOUND;
+
+    fn expense(account_id: Uuid) -> CreateTransactionRequest {
+        CreateTransactionRequest {
+            account_id,
+            amount: 12.5,
+            currency_code: "USD".into(),
+            transaction_type: "expense".into(),
+            category: Some("Groceries".into()),
+            merchant: None,
+            description: None,
+            occurred_at: Utc::now(),
+        }
+    }
+
+    fn stock(account_id: Uuid) -> CreateAssetRequest {
+        CreateAssetRequest {
+            account_id,
+            symbol: "VTI".into(),
+            asset_type: "stock".into(),
+            quantity: 0.0,
+            currency_code: "USD".into(),
+            data_source: None,
+            cost_basis_method: None,
+            valuation_mode: None,
+        }
+    }
+
+    fn buy() -> CreateAssetTradeRequest {
+        CreateAssetTradeRequest {
+            side: "buy".into(),
+            quantity: 1.0,
+            price: 200.0,
+            fees: None,
+            currency_code: None,
+            traded_at: Utc::now(),
+            lot_id: None,
+        }
+    }
+
+    fn subscription(account_id: Uuid) -> CreateRecurringTransactionRequest {
+        CreateRecurringTransactionRequest {
+            account_id,
+            amount: 9.99,
+            currency_code: "USD".into(),
+            transaction_type: "expense".into(),
+            description: Some("Streaming".into()),
+            interval_days: 30,
+            next_occurs_at: Utc::now(),
+            is_enabled: None,
+        }
+    }
+
+    fn restore(payload: serde_json::Value) -> RestoreRequest {
+        let mut backup = json!({
+            "metadata": {
+                "schema_version": 1,
+                "exported_at": Utc::now(),
+                "format": "json",
+                "include_pii": true,
+            },
+            "accounts": [],
+            "account_groups": [],
+            "account_group_members": [],
+            "transactions": [],
+            "recurring_transactions": [],
+            "assets": [],
+            "preferences": [],
+        });
+        for (key, value) in payload.as_object().unwrap() {
+            backup[key] = value.clone();
+        }
+        RestoreRequest {
+            confirm: true,
+            payload: serde_json::from_value(backup).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn roles_grant_their_actions() {
+        let Some(pool) = test_pool().await else { return };
+        let fixture = Fixture::seed(&pool).await;
+        let expected = [
+            (AccountAction::View, [OK, OK, OK, OK, NOT_FOUND]),
+            (AccountAction::Edit, [OK, OK, OK, FORBIDDEN, NOT_FOUND]),
+            (AccountAction::Own, [OK, FORBIDDEN, FORBIDDEN, FORBIDDEN, NOT_FOUND]),
+        ];
+        for (action, statuses) in expected {
+            for (user, expected) in fixture.users().into_iter().zip(statuses) {
+                let result = authorize_account(&pool, user, fixture.account_id, action).await;
+                assert_eq!(status(result), expected, "{action:?} for {}", user.id);
+            }
+        }
+        assert_eq!(
+            authorize_account(&pool, &fixture.viewer, fixture.account_id, AccountAction::View)
+                .await
+                .unwrap(),
+            AccountRole::Viewer
+        );
+    }
+
+    #[tokio::test]
+    async fn account_restricted_tokens_only_reach_their_accounts() {
+        let Some(pool) = test_pool().await else { return };
+        let fixture = Fixture::seed(&pool).await;
+        let other_account = create_account(&pool, &fixture.owner).await;
+        let token = AuthenticatedUser {
+            allowed_accounts: Some(vec![other_account]),
+            ..fixture.owner.clone()
+        };
+
+        assert_eq!(
+            accessible_account_ids(&pool, &token).await.unwrap(),
+            vec![other_account]
+        );
+        let result = authorize_account(&pool, &token, fixture.account_id, AccountAction::View);
+        assert_eq!(status(result.await), FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn account_handlers_enforce_roles() {
+        let Some(pool) = test_pool().await else { return };
+        let state = test_state(pool);
+        let fixture = Fixture::seed(&state.pool).await;
+
+        let mut listed = Vec::new();
+        for user in fixture.users() {
+            let Json(accounts) = accounts::list_accounts(
+                State(state.clone()),
+                user.clone(),
+                Query(PaginationParams {
+                    limit: None,
+                    offset: None,
+                }),
+            )
+            .await
+            .unwrap();
+            listed.push(accounts.iter().any(|account| account.id == fixture.account_id));
+        }
+        assert_eq!(listed, [true, true, true, true, false]);
+
+        let mut renamed = Vec::new();
+        for user in fixture.users() {
+            let result = accounts::update_account(
+                State(state.clone()),
+                user.clone(),
+                Path(fixture.account_id),
+                Json(UpdateAccountRequest {
+                    name: Some("Joint".into()),
+                    currency_code: None,
+                    account_type: None,
+                    credit_limit: None,
+                    statement_closing_day: None,
+                    payment_due_day: None,
+                }),
+            )
+            .await;
+            renamed.push(status(result));
+        }
+        assert_eq!(renamed, [OK, OK, OK, FORBIDDEN, NOT_FOUND]);
+
+        for user in [&fixture.admin, &fixture.editor, &fixture.viewer] {
+            let result =
+                accounts::delete_account(State(state.clone()), user.clone(), Path(fixture.account_id))
+                    .await;
+            assert_eq!(status(result), FORBIDDEN);
+        }
+    }
+
+    #[tokio::test]
+    async fn transaction_handlers_enforce_roles() {
+        let Some(pool) = test_pool().await else { return };
+        let state = test_state(pool);
+        let fixture = Fixture::seed(&state.pool).await;
+
+        let mut created = Vec::new();
+        for user in fixture.users() {
+            let result = transactions::create_transaction(
+                State(state.clone()),
+                user.clone(),
+                Json(expense(fixture.account_id)),
+            )
+            .await;
+            created.push(status(result));
+        }
+        assert_eq!(created, [OK, OK, OK, FORBIDDEN, NOT_FOUND]);
+
+        let mut listed = Vec::new();
+        for user in fixture.users() {
+            let Json(transactions) = transactions::list_transactions(
+                State(state.clone()),
+                user.clone(),
+                Query(TransactionQueryParams {
+                    limit: None,
+                    offset: None,
+                    start_date: None,
+                    end_date: None,
+                    account_id: None,
+                    account_group_id: None,
+                    transaction_type: None,
+                    currency_code: None,
+                    category: None,
+                    merchant: None,
+                }),
+            )
+            .await
+            .unwrap();
+            listed.push(
+                transactions
+                    .iter()
+                    .any(|transaction| transaction.account_id == fixture.account_id),
+            );
+        }
+        assert_eq!(listed, [true, true, true, true, false]);
+
+        let mut updated = Vec::new();
+        let mut deleted = Vec::new();
+        for user in fixture.users() {
+            let Json(transaction) = transactions::create_transaction(
+                State(state.clone()),
+                fixture.owner.clone(),
+                Json(expense(fixture.account_id)),
+            )
+            .await
+            .unwrap();
+            let result = transactions::update_transaction(
+                State(state.clone()),
+                user.clone(),
+                Path(transaction.id),
+                Json(UpdateTransactionRequest {
+                    account_id: None,
+                    amount: Some(20.0),
+                    currency_code: None,
+                    transaction_type: None,
+                    category: None,
+                    merchant: None,
+                    description: None,
+                    occurred_at: None,
+                }),
+            )
+            .await;
+            updated.push(status(result));
+            let result =
+                transactions::delete_transaction(State(state.clone()), user.clone(), Path(transaction.id))
+                    .await;
+            deleted.push(status(result));
+        }
+        assert_eq!(updated, [OK, OK, OK, FORBIDDEN, NOT_FOUND]);
+        assert_eq!(deleted, [OK, OK, OK, FORBIDDEN, NOT_FOUND]);
+    }
+
+    #[tokio::test]
+    async fn asset_handlers_enforce_roles() {
+        let Some(pool) = test_pool().await else { return };
+        let state = test_state(pool);
+        let fixture = Fixture::seed(&state.pool).await;
+
+        let mut created = Vec::new();
+        for user in fixture.users() {
+            let result =
+                assets::create_asset(State(state.clone()), user.clone(), Json(stock(fixture.account_id)))
+                    .await;
+            created.push(status(result));
+        }
+        assert_eq!(created, [OK, OK, OK, FORBIDDEN, NOT_FOUND]);
+
+        let mut listed = Vec::new();
+        for user in fixture.users() {
+            let Json(assets) = assets::list_assets(
+                State(state.clone()),
+                user.clone(),
+                Query(AssetQueryParams {
+                    limit: None,
+                    offset: None,
+                    start_date: None,
+                    end_date: None,
+                    account_id: None,
+                    account_group_id: None,
+                    currency_code: None,
+                }),
+            )
+            .await
+            .unwrap();
+            listed.push(assets.iter().any(|asset| asset.account_id == fixture.account_id));
+        }
+        assert_eq!(listed, [true, true, true, true, false]);
+
+        let mut updated = Vec::new();
+        let mut deleted = Vec::new();
+        for user in fixture.users() {
+            let Json(asset) = assets::create_asset(
+                State(state.clone()),
+                fixture.owner.clone(),
+                Json(stock(fixture.account_id)),
+            )
+            .await
+            .unwrap();
+            let result = assets::update_asset(
+                State(state.clone()),
+                user.clone(),
+                Path(asset.id),
+                Json(UpdateAssetRequest {
+                    account_id: None,
+                    symbol: Some("VXUS".into()),
+                    asset_type: None,
+                    quantity: None,
+                    currency_code: None,
+                    data_source: None,
+                    cost_basis_method: None,
+                    valuation_mode: None,
+                }),
+            )
+            .await;
+            updated.push(status(result));
+            let result =
+                assets::delete_asset(State(state.clone()), user.clone(), Path(asset.id)).await;
+            deleted.push(status(result));
+        }
+        assert_eq!(updated, [OK, OK, OK, FORBIDDEN, NOT_FOUND]);
+        assert_eq!(deleted, [OK, OK, OK, FORBIDDEN, NOT_FOUND]);
+
+        let Json(asset) = assets::create_asset(
+            State(state.clone()),
+            fixture.owner.clone(),
+            Json(stock(fixture.account_id)),
+        )
+        .await
+        .unwrap();
+        let mut bought = Vec::new();
+        let mut removed = Vec::new();
+        for user in fixture.users() {
+            let result =
+                asset_trades::create_asset_trade(State(state.clone()), user.clone(), Path(asset.id), Json(buy()))
+                    .await;
+            bought.push(status(result));
+            let Json(trade) = asset_trades::create_asset_trade(
+                State(state.clone()),
+                fixture.owner.clone(),
+                Path(asset.id),
+                Json(buy()),
+            )
+            .await
+            .unwrap();
+            let result = asset_trades::delete_asset_trade(
+                State(state.clone()),
+                user.clone(),
+                Path((asset.id, trade.id)),
+            )
+            .await;
+            removed.push(status(result));
+        }
+        assert_eq!(bought, [OK, OK, OK, FORBIDDEN, NOT_FOUND]);
+        assert_eq!(removed, [OK, OK, OK, FORBIDDEN, NOT_FOUND]);
+    }
+
+    #[tokio::test]
+    async fn recurring_handlers_enforce_roles() {
+        let Some(pool) = test_pool().await else { return };
+        let state = test_state(pool);
+        let fixture = Fixture::seed(&state.pool).await;
+
+        let mut created = Vec::new();
+        for user in fixture.users() {
+            let result = recurring_transactions::create_recurring_transaction(
+                State(state.clone()),
+                user.clone(),
+                Json(subscription(fixture.account_id)),
+            )
+            .await;
+            created.push(status(result));
+        }
+        assert_eq!(created, [OK, OK, OK, FORBIDDEN, NOT_FOUND]);
+
+        let mut listed = Vec::new();
+        for user in fixture.users() {
+            let Json(recurring) = recurring_transactions::list_recurring_transactions(
+                State(state.clone()),
+                user.clone(),
+                Query(recurring_transactions::PaginationParams {
+                    limit: None,
+                    offset: None,
+                }),
+            )
+            .await
+            .unwrap();
+            listed.push(
+                recurring
+                    .iter()
+                    .any(|recurring| recurring.account_id == fixture.account_id),
+            );
+        }
+        assert_eq!(listed, [true, true, true, true, false]);
+
+        let mut updated = Vec::new();
+        let mut deleted = Vec::new();
+        for user in fixture.users() {
+            let Json(recurring) = recurring_transactions::create_recurring_transaction(
+                State(state.clone()),
+                fixture.owner.clone(),
+                Json(subscription(fixture.account_id)),
+            )
+            .await
+            .unwrap();
+            let result = recurring_transactions::update_recurring_transaction(
+                State(state.clone()),
+                user.clone(),
+                Path(recurring.id),
+                Json(UpdateRecurringTransactionRequest {
+                    account_id: None,
+                    amount: Some(12.99),
+                    currency_code: None,
+                    transaction_type: None,
+                    description: None,
+                    interval_days: None,
+                    next_occurs_at: None,
+                    is_enabled: None,
+                }),
+            )
+            .await;
+            updated.push(status(result));
+            let result = recurring_transactions::delete_recurring_transaction(
+                State(state.clone()),
+                user.clone(),
+                Path(recurring.id),
+            )
+            .await;
+            deleted.push(status(result));
+        }
+        assert_eq!(updated, [OK, OK, OK, FORBIDDEN, NOT_FOUND]);
+        assert_eq!(deleted, [OK, OK, OK, FORBIDDEN, NOT_FOUND]);
+    }
+
+    #[tokio::test]
+    async fn backups_only_reach_owned_accounts() {
+        let Some(pool) = test_pool().await else { return };
+        let state = test_state(pool);
+        let fixture = Fixture::seed(&state.pool).await;
+        let Json(_) = transactions::create_transaction(
+            State(state.clone()),
+            fixture.owner.clone(),
+            Json(expense(fixture.account_id)),
+        )
+        .await
+        .unwrap();
+
+        let mut exported = Vec::new();
+        for user in fixture.users() {
+            let response = backup::export_backup(
+                State(state.clone()),
+                user.clone(),
+                Query(ExportQuery {
+                    format: None,
+                    include_pii: None,
+                }),
+            )
+            .await
+            .unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let payload: BackupPayload = serde_json::from_slice(&body).unwrap();
+            exported.push(
+                payload
+                    .transactions
+                    .iter()
+                    .any(|transaction| transaction.account_id == fixture.account_id),
+            );
+        }
+        assert_eq!(exported, [true, false, false, false, false]);
+
+        let group_id = Uuid::new_v4();
+        for user in [&fixture.admin, &fixture.editor, &fixture.viewer, &fixture.outsider] {
+            let injected = restore(json!({
+                "transactions": [{
+                    "id": Uuid::new_v4(),
+                    "account_id": fixture.account_id,
+                    "amount": 500.0,
+                    "currency_code": "USD",
+                    "transaction_type": "income",
+                    "category": "Gift",
+                    "merchant": null,
+                    "description": null,
+                    "occurred_at": Utc::now(),
+                }],
+            }));
+            let result =
+                backup::restore_backup(State(state.clone()), user.clone(), Json(injected)).await;
+            assert_eq!(status(result), StatusCode::BAD_REQUEST);
+
+            let shared = restore(json!({
+                "account_groups": [{ "id": group_id, "name": "Mine now" }],
+                "account_group_members": [{ "group_id": group_id, "account_id": fixture.account_id }],
+            }));
+            let result =
+                backup::restore_backup(State(state.clone()), user.clone(), Json(shared)).await;
+            assert_eq!(status(result), StatusCode::BAD_REQUEST);
+
+            let result =
+                backup::restore_backup(State(state.clone()), user.clone(), Json(restore(json!({}))))
+                    .await;
+            assert_eq!(status(result), OK);
+        }
+        let result =
+            authorize_account(&state.pool, &fixture.outsider, fixture.account_id, AccountAction::View);
+        assert_eq!(status(result.await), NOT_FOUND);
+        let remaining: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM transactions WHERE account_id = $1")
+                .bind(fixture.account_id)
+                .fetch_one(&state.pool)
+                .await
+                .unwrap();
+        assert_eq!(remaining, 1);
+
+        let result = backup::restore_backup(
+            State(state.clone()),
+            fixture.owner.clone(),
+            Json(restore(json!({}))),
+        )
+        .await;
+        assert_eq!(status(result), OK);
+        let result =
+            authorize_account(&state.pool, &fixture.owner, fixture.account_id, AccountAction::View);
+        assert_eq!(status(result.await), NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn liability_reads_require_access_to_the_account() {
+        let Some(pool) = test_pool().await else { return };
+        let state = test_state(pool);
+        let fixture = Fixture::seed(&state.pool).await;
+        let liability_id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO liabilities (
+                id, account_id, name, principal, annual_rate, term_months, currency_code,
+                start_date
+            )
+            VALUES ($1, $2, 'Car loan', 12000, 0.05, 24, 'USD', CURRENT_DATE)
+            "#,
+        )
+        .bind(liability_id)
+        .bind(fixture.account_id)
+        .execute(&state.pool)
+        .await
+        .unwrap();
+
+        for (user, expected) in fixture.users().into_iter().zip([OK, OK, OK, OK, NOT_FOUND]) {
+            let schedule = liabilities::get_liability_schedule(
+                State(state.clone()),
+                user.clone(),
+                Path(liability_id),
+            )
+            .await;
+            assert_eq!(status(schedule), expected);
+            let payments = liabilities::list_liability_payments(
+                State(state.clone()),
+                user.clone(),
+                Path(liability_id),
+            )
+            .await;
+            assert_eq!(status(payments), expected);
+        }
+        let deleted = liabilities::delete_liability(
+            State(state.clone()),
+            fixture.viewer.clone(),
+            Path(liability_id),
+        )
+        .await;
+        assert_eq!(status(deleted), FORBIDDEN);
+    }
+
+    #[test]
+    fn strongest_group_role_wins() {
+        let roles = ["view", "admin", "edit"];
+        assert_eq!(
+            roles.iter().filter_map(|role| group_role(role)).max(),
+            Some(AccountRole::Admin)
+        );
+        assert_eq!(group_role("owner"), None);
+        assert!(AccountRole::Owner > AccountRole::Admin);
+    }
+}
@@ -27,7 +27,7 @@ pub async fn list_account_groups(
     user: AuthenticatedUser,
     Query(params): Query<PaginationParams>,
 ) -> Result<Json<Vec<AccountGroup>>, (axum::http::StatusCode, String)> {
-    let limit = params.limit.unwrap_or(100).max(1).min(200);
+    let limit = params.limit.unwrap_or(100).clamp(1, 200);
     let offset = params.offset.unwrap_or(0).max(0);
     let records = sqlx::query_as::<_, AccountGroup>(
         r#"
@@ -390,7 +390,7 @@ pub async fn create_account_group_user(
     )
     .bind(group_id)
     .bind(user_id)
-    .bind(role)
+    .bind(&role)
     .execute(&state.pool)
     .await
     .map_err(crate::auth::internal_error)?;
@@ -399,7 +399,7 @@ pub async fn create_account_group_user(
         &state.pool,
         Some(user.id),
         "account_group.member_added",
-        serde_json::json!({ "group_id": group_id, "user_id": user_id }),
+        serde_json::json!({ "group_id": group_id, "user_id": user_id, "role": role }),
     )
     .await;
 
@@ -422,7 +422,7 @@ pub async fn update_account_group_user(
         WHERE group_id = $2 AND user_id = $3
         "#,
     )
-    .bind(role)
+    .bind(&role)
     .bind(group_id)
     .bind(target_user_id)
     .execute(&state.pool)
@@ -433,7 +433,7 @@ pub async fn update_account_group_user(
         &state.pool,
         Some(user.id),
         "account_group.member_updated",
-        serde_json::json!({ "group_id": group_id, "user_id": target_user_id }),
+        serde_json::json!({ "group_id": group_id, "user_id": target_user_id, "role": role }),
     )
     .await;
 
@@ -536,7 +536,7 @@ async fn ensure_group_member(
     Ok(())
 }
 
-async fn ensure_group_admin(
+pub(crate) async fn ensure_group_admin(
     state: &AppState,
     group_id: Uuid,
     user_id: Uuid,
@@ -571,7 +571,7 @@ async fn ensure_group_admin(
     Ok(())
 }
 
-fn normalize_role(role: &str) -> Result<String, (StatusCode, String)> {
+pub(crate) fn normalize_role(role: &str) -> Result<String, (StatusCode, String)> {
     let normalized = role.trim().to_lowercase();
     match normalized.as_str() {
         "view" | "edit" | "admin" => Ok(normalized),
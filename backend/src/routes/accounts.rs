@@ -1,9 +1,13 @@
 use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
-use sqlx::Row;
 use uuid::Uuid;
 use crate::{
     auth::AuthenticatedUser,
     models::{Account, CreateAccountRequest, UpdateAccountRequest, UpdateAccountResponse},
+    policy::{accessible_account_ids, authorize_account, AccountAction},
+    services::{
+        change_history::{record_change, snapshot, ChangeEntity},
+        credit_cards::{ACCOUNT_TYPES, CREDIT_CARD},
+    },
     state::AppState,
 };
 
@@ -18,28 +22,20 @@ pub async fn list_accounts(
     user: AuthenticatedUser,
     Query(params): Query<PaginationParams>,
 ) -> Result<Json<Vec<Account>>, (axum::http::StatusCode, String)> {
-    let limit = params.limit.unwrap_or(100).max(1).min(200);
+    let limit = params.limit.unwrap_or(100).clamp(1, 200);
     let offset = params.offset.unwrap_or(0).max(0);
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
     let records = sqlx::query_as::<_, Account>(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id = $1
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id = $1
-        )
-        SELECT id, name, currency_code, created_at
+        SELECT id, name, currency_code, account_type, credit_limit, statement_closing_day,
+               payment_due_day, created_at
         FROM accounts
-        WHERE id IN (SELECT id FROM accessible_accounts)
+        WHERE id = ANY($1)
         ORDER BY created_at DESC
         LIMIT $2 OFFSET $3
         "#,
     )
-    .bind(user.id)
+    .bind(&account_ids)
     .bind(limit)
     .bind(offset)
     .fetch_all(&state.pool)
@@ -54,22 +50,48 @@ pub async fn create_account(
     user: AuthenticatedUser,
     Json(payload): Json<CreateAccountRequest>,
 ) -> Result<Json<Account>, (axum::http::StatusCode, String)> {
+    let account_type = normalize_account_type(payload.account_type.as_deref())?
+        .unwrap_or_else(|| "checking".to_string());
+    validate_card_fields(
+        payload.credit_limit,
+        payload.statement_closing_day,
+        payload.payment_due_day,
+    )?;
+    let is_card = account_type == CREDIT_CARD;
     let id = Uuid::new_v4();
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
     let record = sqlx::query_as::<_, Account>(
         r#"
-        INSERT INTO accounts (id, user_id, name, currency_code)
-        VALUES ($1, $2, $3, $4)
-        RETURNING id, name, currency_code, created_at
+        INSERT INTO accounts (
+            id, user_id, name, currency_code, account_type, credit_limit, statement_closing_day,
+            payment_due_day
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, name, currency_code, account_type, credit_limit, statement_closing_day,
+                  payment_due_day, created_at
         "#,
     )
     .bind(id)
     .bind(user.id)
     .bind(payload.name)
     .bind(payload.currency_code)
-    .fetch_one(&state.pool)
+    .bind(account_type)
+    .bind(payload.credit_limit.filter(|_| is_card))
+    .bind(payload.statement_closing_day.filter(|_| is_card))
+    .bind(payload.payment_due_day.filter(|_| is_card))
+    .fetch_one(&mut *tx)
     .await
     .map_err(crate::auth::internal_error)?;
 
+    record_change(&mut tx, user.id, ChangeEntity::Account, id, None)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
     Ok(Json(record))
 }
 
@@ -79,28 +101,61 @@ pub async fn update_account(
     Path(account_id): Path<Uuid>,
     Json(payload): Json<UpdateAccountRequest>,
 ) -> Result<Json<UpdateAccountResponse>, (axum::http::StatusCode, String)> {
-    ensure_account_edit_access(&state, user.id, account_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
+    let account_type = normalize_account_type(payload.account_type.as_deref())?;
+    validate_card_fields(
+        payload.credit_limit,
+        payload.statement_closing_day,
+        payload.payment_due_day,
+    )?;
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let before = snapshot(&mut tx, ChangeEntity::Account, account_id)
+        .await
+        .map_err(crate::auth::internal_error)?;
 
+    // Card details are cleared when an account stops being a credit card.
     let record = sqlx::query_as::<_, UpdateAccountResponse>(
         r#"
         UPDATE accounts
         SET name = COALESCE($1, name),
-            currency_code = COALESCE($2, currency_code)
+            currency_code = COALESCE($2, currency_code),
+            account_type = COALESCE($4, account_type),
+            credit_limit = CASE WHEN COALESCE($4, account_type) = 'credit_card'
+                                THEN COALESCE($5, credit_limit) END,
+            statement_closing_day = CASE WHEN COALESCE($4, account_type) = 'credit_card'
+                                         THEN COALESCE($6, statement_closing_day) END,
+            payment_due_day = CASE WHEN COALESCE($4, account_type) = 'credit_card'
+                                   THEN COALESCE($7, payment_due_day) END
         WHERE id = $3
-        RETURNING id, name, currency_code, created_at
+        RETURNING id, name, currency_code, account_type, credit_limit, statement_closing_day,
+                  payment_due_day, created_at
         "#,
     )
     .bind(payload.name)
     .bind(payload.currency_code)
     .bind(account_id)
-    .fetch_optional(&state.pool)
+    .bind(account_type)
+    .bind(payload.credit_limit)
+    .bind(payload.statement_closing_day)
+    .bind(payload.payment_due_day)
+    .fetch_optional(&mut *tx)
     .await
     .map_err(crate::auth::internal_error)?;
 
-    match record {
-        Some(record) => Ok(Json(record)),
-        None => Err((StatusCode::NOT_FOUND, "Account not found".into())),
-    }
+    let Some(record) = record else {
+        return Err((StatusCode::NOT_FOUND, "Account not found".into()));
+    };
+    record_change(&mut tx, user.id, ChangeEntity::Account, account_id, before)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    Ok(Json(record))
 }
 
 pub async fn delete_account(
@@ -108,8 +163,16 @@ pub async fn delete_account(
     user: AuthenticatedUser,
     Path(account_id): Path<Uuid>,
 ) -> Result<StatusCode, (axum::http::StatusCode, String)> {
-    ensure_account_edit_access(&state, user.id, account_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Own).await?;
 
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let before = snapshot(&mut tx, ChangeEntity::Account, account_id)
+        .await
+        .map_err(crate::auth::internal_error)?;
     let result = sqlx::query(
         r#"
         DELETE FROM accounts
@@ -117,7 +180,7 @@ pub async fn delete_account(
         "#,
     )
     .bind(account_id)
-    .execute(&state.pool)
+    .execute(&mut *tx)
     .await
     .map_err(crate::auth::internal_error)?;
 
@@ -125,45 +188,49 @@ pub async fn delete_account(
         return Err((StatusCode::NOT_FOUND, "Account not found".into()));
     }
 
+    record_change(&mut tx, user.id, ChangeEntity::Account, account_id, before)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
     Ok(StatusCode::NO_CONTENT)
 }
 
-async fn ensure_account_edit_access(
-    state: &AppState,
-    user_id: Uuid,
-    account_id: Uuid,
-) -> Result<(), (StatusCode, String)> {
-    let record = sqlx::query(
-        r#"
-        SELECT a.user_id,
-               MAX(CASE WHEN agu.role IN ('edit', 'admin') THEN 1 ELSE 0 END) as can_edit
-        FROM accounts a
-        LEFT JOIN account_group_members agm ON a.id = agm.account_id
-        LEFT JOIN account_group_users agu ON agm.group_id = agu.group_id AND agu.user_id = $1
-        WHERE a.id = $2
-        GROUP BY a.user_id
-        "#,
-    )
-    .bind(user_id)
-    .bind(account_id)
-    .fetch_optional(&state.pool)
-    .await
-    .map_err(crate::auth::internal_error)?;
-
-    let Some(record) = record else {
-        return Err((StatusCode::NOT_FOUND, "Account not found".into()));
+fn normalize_account_type(
+    value: Option<&str>,
+) -> Result<Option<String>, (axum::http::StatusCode, String)> {
+    let Some(value) = value.map(|value| value.trim().to_lowercase()) else {
+        return Ok(None);
     };
-
-    let owner_id: Uuid = record
-        .try_get("user_id")
-        .map_err(crate::auth::internal_error)?;
-    let can_edit: i64 = record
-        .try_get("can_edit")
-        .map_err(crate::auth::internal_error)?;
-
-    if owner_id != user_id && can_edit == 0 {
-        return Err((StatusCode::FORBIDDEN, "Forbidden".into()));
+    if !ACCOUNT_TYPES.contains(&value.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "account_type must be checking, savings, credit_card, brokerage, cash or loan".into(),
+        ));
     }
+    Ok(Some(value))
+}
 
+fn validate_card_fields(
+    credit_limit: Option<f64>,
+    statement_closing_day: Option<i32>,
+    payment_due_day: Option<i32>,
+) -> Result<(), (axum::http::StatusCode, String)> {
+    if credit_limit.is_some_and(|limit| !(limit.is_finite() && limit >= 0.0)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "credit_limit must not be negative".into(),
+        ));
+    }
+    if [statement_closing_day, payment_due_day]
+        .into_iter()
+        .flatten()
+        .any(|day| !(1..=31).contains(&day))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Statement and due days must be between 1 and 31".into(),
+        ));
+    }
     Ok(())
 }
@@ -0,0 +1,229 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{auth::AuthenticatedUser, policy::accessible_account_ids, state::AppState};
+
+#[derive(serde::Deserialize)]
+pub struct ActivityQueryParams {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<Uuid>,
+    pub account_id: Option<Uuid>,
+}
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct ActivityEntry {
+    pub id: Uuid,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub action: String,
+    pub changes: serde_json::Value,
+    pub account_ids: Vec<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub user_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub async fn list_activity(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<ActivityQueryParams>,
+) -> Result<Json<Vec<ActivityEntry>>, (StatusCode, String)> {
+    let limit = params.limit.unwrap_or(50).clamp(1, 200);
+    let offset = params.offset.unwrap_or(0).max(0);
+    if params
+        .entity_type
+        .as_deref()
+        .is_some_and(|value| !matches!(value, "account" | "transaction" | "asset"))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "entity_type must be account, transaction or asset".into(),
+        ));
+    }
+
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
+    let records = sqlx::query_as::<_, ActivityEntry>(
+        r#"
+        SELECT ch.id, ch.entity_type, ch.entity_id, ch.action, ch.changes, ch.account_ids,
+               ch.user_id, u.name as user_name, ch.created_at
+        FROM change_history ch
+        LEFT JOIN users u ON ch.user_id = u.id
+        WHERE (ch.user_id = $1 OR ch.account_ids && $2)
+          AND ($3::TEXT IS NULL OR ch.entity_type = $3)
+          AND ($4::UUID IS NULL OR ch.entity_id = $4)
+          AND ($5::UUID IS NULL OR $5 = ANY(ch.account_ids))
+        ORDER BY ch.created_at DESC
+        LIMIT $6 OFFSET $7
+        "#,
+    )
+    .bind(user.id)
+    .bind(&account_ids)
+    .bind(params.entity_type)
+    .bind(params.entity_id)
+    .bind(params.account_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    Ok(Json(records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{list_activity, ActivityQueryParams};
+    use crate::models::CreateTransactionRequest;
+    use crate::routes::{preferences, preferences::PreferencesUpdate, transactions};
+    use crate::services::change_history::{record_changes, ChangeEntity};
+    use crate::test_support::{
+        create_account, create_user, share_account, test_pool, test_state,
+    };
+    use axum::extract::{Query, State};
+    use axum::Json;
+    use chrono::{Duration, Utc};
+    use uuid::Uuid;
+
+    fn params(account_id: Uuid) -> Query<ActivityQueryParams> {
+        Query(ActivityQueryParams {
+            limit: None,
+            offset: None,
+            entity_type: None,
+            entity_id: None,
+            account_id: Some(account_id),
+        })
+    }
+
+    #[tokio::test]
+    async fn group_members_see_changes_to_shared_accounts() {
+        let Some(pool) = test_pool().await else { return };
+        let state = test_state(pool);
+        let owner = create_user(&state.pool, "owner").await;
+        let member = create_user(&state.pool, "member").await;
+        let outsider = create_user(&state.pool, "outsider").await;
+        let account_id = create_account(&state.pool, &owner).await;
+        share_account(&state.pool, &owner, account_id, &member, "view").await;
+
+        let Json(transaction) = transactions::create_transaction(
+            State(state.clone()),
+            owner.clone(),
+            Json(CreateTransactionRequest {
+                account_id,
+                amount: 12.5,
+                currency_code: "USD".into(),
+                transaction_type: "expense".into(),
+                category: Some("Groceries".into()),
+                merchant: None,
+                description: None,
+                occurred_at: Utc::now(),
+            }),
+        )
+        .await
+        .unwrap();
+
+        let Json(entries) = list_activity(State(state.clone()), member, params(account_id))
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].entity_id, transaction.id);
+        assert_eq!(entries[0].action, "created");
+        assert_eq!(entries[0].user_id, Some(owner.id));
+
+        let Json(entries) = list_activity(State(state), outsider, params(account_id))
+            .await
+            .unwrap();
+        assert!(entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn background_changes_have_no_author() {
+        let Some(pool) = test_pool().await else { return };
+        let state = test_state(pool);
+        let owner = create_user(&state.pool, "owner").await;
+        let account_id = create_account(&state.pool, &owner).await;
+        let transaction_id = Uuid::new_v4();
+        let mut tx = state.pool.begin().await.unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO transactions (
+                id, account_id, amount, currency_code, transaction_type, occurred_at
+            )
+            VALUES ($1, $2, 9.99, 'USD', 'expense', NOW())
+            "#,
+        )
+        .bind(transaction_id)
+        .bind(account_id)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        record_changes(&mut tx, None, ChangeEntity::Transaction, vec![(transaction_id, None)])
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let Json(entries) = list_activity(State(state), owner, params(account_id))
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].entity_id, transaction_id);
+        assert_eq!(entries[0].user_id, None);
+        assert_eq!(entries[0].user_name, None);
+    }
+
+    #[tokio::test]
+    async fn retention_purges_are_recorded() {
+        let Some(pool) = test_pool().await else { return };
+        let state = test_state(pool);
+        let owner = create_user(&state.pool, "owner").await;
+        let account_id = create_account(&state.pool, &owner).await;
+        let Json(transaction) = transactions::create_transaction(
+            State(state.clone()),
+            owner.clone(),
+            Json(CreateTransactionRequest {
+                account_id,
+                amount: 42.0,
+                currency_code: "USD".into(),
+                transaction_type: "expense".into(),
+                category: Some("Travel".into()),
+                merchant: None,
+                description: None,
+                occurred_at: Utc::now() - Duration::days(400),
+            }),
+        )
+        .await
+        .unwrap();
+
+        let Json(preferences) = preferences::update_preferences(
+            State(state.clone()),
+            owner.clone(),
+            Json(PreferencesUpdate {
+                categories: None,
+                strategies: None,
+                holding_strategies: None,
+                retention_days: Some(365),
+                export_redaction: None,
+                asset_refresh_cadence: None,
+                asset_data_source: None,
+                benchmark_symbol: None,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(preferences.retention_days, Some(365));
+
+        let Json(entries) = list_activity(State(state), owner.clone(), params(account_id))
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].entity_id, transaction.id);
+        assert_eq!(entries[0].action, "deleted");
+        assert_eq!(entries[0].user_id, Some(owner.id));
+    }
+}
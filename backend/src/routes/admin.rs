@@ -1,10 +1,15 @@
-use axum::{extract::Query, extract::State, http::StatusCode, Json};
+use axum::{extract::Path, extract::Query, extract::State, http::StatusCode, Json};
 use chrono::{DateTime, Utc};
 use serde::Serialize;
 use sqlx::Row;
+use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::{auth::{is_admin, AuthenticatedUser}, state::AppState};
+use crate::{
+    auth::{is_admin, AuthenticatedUser},
+    services::scheduler::{Schedule, WORKER_JOBS},
+    state::AppState,
+};
 
 #[derive(serde::Deserialize)]
 pub struct AuditLogQuery {
@@ -33,7 +38,7 @@ pub async fn list_audit_logs(
         return Err((StatusCode::FORBIDDEN, "Forbidden".into()));
     }
 
-    let limit = params.limit.unwrap_or(100).max(1).min(200);
+    let limit = params.limit.unwrap_or(100).clamp(1, 200);
     let offset = params.offset.unwrap_or(0).max(0);
 
     let rows = sqlx::query(
@@ -85,3 +90,162 @@ pub async fn list_audit_logs(
 
     Ok(Json(entries))
 }
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct JobRun {
+    pub id: Uuid,
+    pub job_name: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub status: String,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct JobStatus {
+    pub name: String,
+    pub schedule: Option<String>,
+    pub last_run: Option<JobRun>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub next_run_at: Option<DateTime<Utc>>,
+}
+
+pub async fn list_jobs(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<JobStatus>>, (StatusCode, String)> {
+    if !is_admin(&state, user.id).await? {
+        return Err((StatusCode::FORBIDDEN, "Forbidden".into()));
+    }
+
+    let mut last_runs = sqlx::query_as::<_, JobRun>(
+        r#"
+        SELECT DISTINCT ON (job_name) id, job_name, started_at, finished_at, status, error
+        FROM job_runs
+        ORDER BY job_name, started_at DESC
+        "#,
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let last_successes = sqlx::query(
+        r#"
+        SELECT job_name, MAX(finished_at) as last_success_at
+        FROM job_runs
+        WHERE status = 'succeeded'
+        GROUP BY job_name
+        "#,
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let schedules: HashMap<String, String> =
+        sqlx::query_as::<_, (String, String)>("SELECT job_name, schedule FROM job_schedules")
+            .fetch_all(&state.pool)
+            .await
+            .map_err(crate::auth::internal_error)?
+            .into_iter()
+            .collect();
+
+    let mut jobs = Vec::new();
+    for spec in &WORKER_JOBS {
+        let schedule = schedules.get(spec.name).cloned();
+        let last_run = last_runs
+            .iter()
+            .position(|run| run.job_name == spec.name)
+            .map(|index| last_runs.swap_remove(index));
+        let mut last_success_at = None;
+        for row in &last_successes {
+            let job_name: String = row
+                .try_get("job_name")
+                .map_err(crate::auth::internal_error)?;
+            if job_name == spec.name {
+                last_success_at = row
+                    .try_get("last_success_at")
+                    .map_err(crate::auth::internal_error)?;
+            }
+        }
+        let parsed = schedule.as_deref().map(Schedule::parse);
+        let next_run_at = match (&last_run, parsed) {
+            (Some(run), Some(Ok(parsed))) => parsed.next_after(run.started_at),
+            (None, Some(Ok(_))) => Some(Utc::now()),
+            _ => None,
+        };
+        jobs.push(JobStatus {
+            name: spec.name.to_string(),
+            schedule,
+            last_run,
+            last_success_at,
+            next_run_at,
+        });
+    }
+
+    Ok(Json(jobs))
+}
+
+pub async fn list_job_runs(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(job_name): Path<String>,
+    Query(params): Query<AuditLogQuery>,
+) -> Result<Json<Vec<JobRun>>, (StatusCode, String)> {
+    if !is_admin(&state, user.id).await? {
+        return Err((StatusCode::FORBIDDEN, "Forbidden".into()));
+    }
+
+    let limit = params.limit.unwrap_or(100).clamp(1, 200);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let runs = sqlx::query_as::<_, JobRun>(
+        r#"
+        SELECT id, job_name, started_at, finished_at, status, error
+        FROM job_runs
+        WHERE job_name = $1
+        ORDER BY started_at DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(job_name)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    Ok(Json(runs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::list_jobs;
+    use crate::services::scheduler::WORKER_JOBS;
+    use crate::test_support::{create_user, test_pool, test_state};
+    use axum::{extract::State, Json};
+
+    #[tokio::test]
+    async fn jobs_report_the_schedule_the_worker_recorded() {
+        let Some(pool) = test_pool().await else { return };
+        let mut state = test_state(pool);
+        let admin = create_user(&state.pool, "admin").await;
+        let email: String = sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
+            .bind(admin.id)
+            .fetch_one(&state.pool)
+            .await
+            .unwrap();
+        state.admin_emails = vec![email];
+        sqlx::query("INSERT INTO job_schedules (job_name, schedule) VALUES ($1, '@every 7m')")
+            .bind(WORKER_JOBS[0].name)
+            .execute(&state.pool)
+            .await
+            .unwrap();
+
+        let Json(jobs) = list_jobs(State(state), admin).await.unwrap();
+        assert_eq!(jobs[0].name, WORKER_JOBS[0].name);
+        assert_eq!(jobs[0].schedule.as_deref(), Some("@every 7m"));
+        assert!(jobs[0].next_run_at.is_some());
+        assert_eq!(jobs[1].schedule, None);
+        assert_eq!(jobs[1].next_run_at, None);
+    }
+}
@@ -0,0 +1,295 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, Row};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use crate::{
+    audit::record_audit_event,
+    auth::AuthenticatedUser,
+    policy::accessible_account_ids,
+    services::allocation::{
+        rebalance, validate_targets, AllocationHolding, BucketAllocation, RebalanceTrade,
+        ALLOCATION_DIMENSIONS, UNASSIGNED_BUCKET,
+    },
+    state::AppState,
+};
+
+#[derive(Deserialize)]
+pub struct AllocationQuery {
+    pub dimension: Option<String>,
+    pub min_trade: Option<f64>,
+    pub account_id: Option<Uuid>,
+    pub account_group_id: Option<Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct TargetsQuery {
+    pub dimension: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AllocationTargets {
+    pub dimension: String,
+    pub targets: BTreeMap<String, f64>,
+}
+
+#[derive(Serialize)]
+pub struct AllocationResponse {
+    pub dimension: String,
+    pub currency_code: String,
+    pub total_value: f64,
+    pub min_trade: f64,
+    pub buckets: Vec<BucketAllocation>,
+    pub trades: Vec<RebalanceTrade>,
+    pub unpriced: Vec<String>,
+}
+
+pub async fn get_allocation_targets(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<TargetsQuery>,
+) -> Result<Json<AllocationTargets>, (StatusCode, String)> {
+    let dimension = normalize_dimension(params.dimension)?;
+    let targets = load_targets(&state, user.id, &dimension).await?;
+    Ok(Json(AllocationTargets { dimension, targets }))
+}
+
+pub async fn update_allocation_targets(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<AllocationTargets>,
+) -> Result<Json<AllocationTargets>, (StatusCode, String)> {
+    let dimension = normalize_dimension(Some(payload.dimension))?;
+    let targets: BTreeMap<String, f64> = payload
+        .targets
+        .into_iter()
+        .map(|(bucket, weight)| (bucket.trim().to_string(), weight))
+        .filter(|(bucket, _)| !bucket.is_empty())
+        .collect();
+    validate_targets(&targets).map_err(|err| (StatusCode::BAD_REQUEST, err))?;
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    sqlx::query("DELETE FROM allocation_targets WHERE user_id = $1 AND dimension = $2")
+        .bind(user.id)
+        .bind(&dimension)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    for (bucket, weight) in &targets {
+        sqlx::query(
+            r#"
+            INSERT INTO allocation_targets (user_id, dimension, bucket, target_weight)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(user.id)
+        .bind(&dimension)
+        .bind(bucket)
+        .bind(weight)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    }
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "allocation.targets_update",
+        serde_json::json!({ "dimension": dimension, "buckets": targets.len() }),
+    )
+    .await;
+
+    Ok(Json(AllocationTargets { dimension, targets }))
+}
+
+pub async fn get_allocation(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<AllocationQuery>,
+) -> Result<Json<AllocationResponse>, (StatusCode, String)> {
+    let dimension = normalize_dimension(params.dimension)?;
+    let min_trade = params.min_trade.unwrap_or(0.0);
+    if !(min_trade.is_finite() && min_trade >= 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "min_trade must not be negative".into(),
+        ));
+    }
+
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
+    let mut query = QueryBuilder::new(
+        r#"
+        WITH latest_prices AS (
+            SELECT DISTINCT ON (asset_id) asset_id, price, recorded_at
+            FROM price_history
+            ORDER BY asset_id, recorded_at DESC
+        )
+        SELECT a.id, a.symbol, a.asset_type, a.quantity, a.currency_code,
+               lp.price / COALESCE(sf.factor, 1) as price
+        FROM assets a
+        LEFT JOIN latest_prices lp ON lp.asset_id = a.id
+        LEFT JOIN asset_split_factors sf ON sf.asset_id = a.id
+            AND lp.recorded_at >= sf.valid_from AND lp.recorded_at < sf.valid_until
+        WHERE a.account_id = ANY(
+        "#,
+    );
+    query.push_bind(account_ids.clone());
+    query.push(")");
+    if let Some(account_id) = params.account_id {
+        query.push(" AND a.account_id = ");
+        query.push_bind(account_id);
+    }
+    if let Some(group_id) = params.account_group_id {
+        query.push(
+            r#"
+            AND EXISTS (
+                SELECT 1
+                FROM account_group_members agm
+                WHERE agm.group_id =
+            "#,
+        );
+        query.push_bind(group_id);
+        query.push(" AND agm.account_id = a.account_id)");
+    }
+    query.push(" ORDER BY a.symbol");
+    let rows = query
+        .build()
+        .fetch_all(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+
+    let fx_rows = sqlx::query(
+        r#"
+        SELECT DISTINCT ON (base_currency) base_currency, rate
+        FROM fx_rates
+        WHERE quote_currency = 'USD'
+        ORDER BY base_currency, recorded_on DESC
+        "#,
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    let mut fx_map = HashMap::<String, f64>::new();
+    for row in fx_rows {
+        let base_currency: String = row
+            .try_get("base_currency")
+            .map_err(crate::auth::internal_error)?;
+        let rate: f64 = row.try_get("rate").map_err(crate::auth::internal_error)?;
+        fx_map.insert(base_currency, rate);
+    }
+
+    let holding_strategies: HashMap<String, String> = if dimension == "strategy" {
+        sqlx::query_scalar::<_, serde_json::Value>(
+            r#"
+            SELECT value
+            FROM user_preferences
+            WHERE user_id = $1 AND key = 'holding_strategies'
+            "#,
+        )
+        .bind(user.id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+    } else {
+        HashMap::new()
+    };
+
+    let mut holdings = Vec::new();
+    let mut unpriced = Vec::new();
+    for row in rows {
+        let asset_id: Uuid = row.try_get("id").map_err(crate::auth::internal_error)?;
+        let symbol: String = row.try_get("symbol").map_err(crate::auth::internal_error)?;
+        let asset_type: String = row
+            .try_get("asset_type")
+            .map_err(crate::auth::internal_error)?;
+        let quantity: f64 = row
+            .try_get("quantity")
+            .map_err(crate::auth::internal_error)?;
+        let currency_code: String = row
+            .try_get("currency_code")
+            .map_err(crate::auth::internal_error)?;
+        let price: Option<f64> = row.try_get("price").map_err(crate::auth::internal_error)?;
+        let rate = if currency_code == "USD" {
+            Some(1.0)
+        } else {
+            fx_map.get(&currency_code).copied()
+        };
+        let Some(unit_price) = price.zip(rate).map(|(price, rate)| price * rate) else {
+            unpriced.push(symbol);
+            continue;
+        };
+        let bucket = if dimension == "strategy" {
+            holding_strategies
+                .get(&asset_id.to_string())
+                .cloned()
+                .unwrap_or_else(|| UNASSIGNED_BUCKET.to_string())
+        } else {
+            asset_type
+        };
+        holdings.push(AllocationHolding {
+            asset_id,
+            symbol,
+            bucket,
+            value: quantity * unit_price,
+            unit_price,
+        });
+    }
+
+    let targets = load_targets(&state, user.id, &dimension).await?;
+    let report = rebalance(&holdings, &targets, min_trade);
+
+    Ok(Json(AllocationResponse {
+        dimension,
+        currency_code: "USD".to_string(),
+        total_value: report.total_value,
+        min_trade,
+        buckets: report.buckets,
+        trades: report.trades,
+        unpriced,
+    }))
+}
+
+fn normalize_dimension(dimension: Option<String>) -> Result<String, (StatusCode, String)> {
+    let dimension = dimension
+        .map(|value| value.trim().to_lowercase())
+        .unwrap_or_else(|| "strategy".to_string());
+    if !ALLOCATION_DIMENSIONS.contains(&dimension.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "dimension must be strategy or asset_type".into(),
+        ));
+    }
+    Ok(dimension)
+}
+
+async fn load_targets(
+    state: &AppState,
+    user_id: Uuid,
+    dimension: &str,
+) -> Result<BTreeMap<String, f64>, (StatusCode, String)> {
+    let rows = sqlx::query_as::<_, (String, f64)>(
+        r#"
+        SELECT bucket, target_weight
+        FROM allocation_targets
+        WHERE user_id = $1 AND dimension = $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(dimension)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    Ok(rows.into_iter().collect())
+}
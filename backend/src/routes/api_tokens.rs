@@ -7,14 +7,28 @@ use uuid::Uuid;
 use crate::{
     audit::record_audit_event,
     auth::{hash_api_token, AuthenticatedUser},
+    services::token_scopes::is_known_scope,
     state::AppState,
 };
 
+const DEFAULT_ROTATION_GRACE_HOURS: i32 = 24;
+const MAX_ROTATION_GRACE_HOURS: i32 = 24 * 7;
+
 #[derive(serde::Deserialize)]
 pub struct CreateApiTokenRequest {
     pub name: String,
     pub is_read_only: Option<bool>,
     pub expires_at: Option<DateTime<Utc>>,
+    pub scopes: Option<Vec<String>>,
+    #[serde(default)]
+    pub account_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub account_group_ids: Vec<Uuid>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct RotateApiTokenRequest {
+    pub grace_period_hours: Option<i32>,
 }
 
 #[derive(serde::Serialize, sqlx::FromRow)]
@@ -23,10 +37,14 @@ pub struct ApiTokenSummary {
     pub name: String,
     pub token_prefix: Option<String>,
     pub is_read_only: bool,
+    pub scopes: Option<Vec<String>>,
+    pub account_ids: Vec<Uuid>,
+    pub account_group_ids: Vec<Uuid>,
     pub created_at: DateTime<Utc>,
     pub last_used_at: Option<DateTime<Utc>>,
     pub revoked_at: Option<DateTime<Utc>>,
     pub expires_at: Option<DateTime<Utc>>,
+    pub replaced_by: Option<Uuid>,
 }
 
 #[derive(serde::Serialize, sqlx::FromRow)]
@@ -35,6 +53,9 @@ pub struct ApiTokenCreated {
     pub token: String,
     pub token_prefix: String,
     pub is_read_only: bool,
+    pub scopes: Option<Vec<String>>,
+    pub account_ids: Vec<Uuid>,
+    pub account_group_ids: Vec<Uuid>,
     pub created_at: DateTime<Utc>,
     pub expires_at: Option<DateTime<Utc>>,
 }
@@ -49,10 +70,14 @@ pub async fn list_api_tokens(
                name,
                token_prefix,
                is_read_only,
+               scopes,
+               account_ids,
+               account_group_ids,
                created_at,
                last_used_at,
                revoked_at,
-               expires_at
+               expires_at,
+               replaced_by
         FROM api_keys
         WHERE user_id = $1
         ORDER BY created_at DESC
@@ -69,7 +94,7 @@ pub async fn list_api_tokens(
 pub async fn create_api_token(
     State(state): State<AppState>,
     user: AuthenticatedUser,
-    Json(payload): Json<CreateApiTokenRequest>,
+    Json(mut payload): Json<CreateApiTokenRequest>,
 ) -> Result<Json<ApiTokenCreated>, (StatusCode, String)> {
     let name = payload.name.trim();
     if name.is_empty() {
@@ -82,15 +107,40 @@ pub async fn create_api_token(
         }
     }
 
-    let mut bytes = [0u8; 32];
-    OsRng.fill_bytes(&mut bytes);
-    let token_suffix = Base64UrlUnpadded::encode_string(&bytes);
-    let token = format!("fc_{}", token_suffix);
-    let token_hash = hash_api_token(&token);
-    let token_prefix = token.chars().take(10).collect::<String>();
+    let scopes = match payload.scopes {
+        Some(scopes) => {
+            let mut scopes = scopes
+                .into_iter()
+                .map(|scope| scope.trim().to_lowercase())
+                .collect::<Vec<_>>();
+            scopes.sort();
+            scopes.dedup();
+            if scopes.is_empty() {
+                return Err((StatusCode::BAD_REQUEST, "At least one scope is required".into()));
+            }
+            if let Some(unknown) = scopes.iter().find(|scope| !is_known_scope(scope)) {
+                return Err((StatusCode::BAD_REQUEST, format!("Unknown scope: {unknown}")));
+            }
+            Some(scopes)
+        }
+        None => None,
+    };
+    payload.account_ids.sort();
+    payload.account_ids.dedup();
+    payload.account_group_ids.sort();
+    payload.account_group_ids.dedup();
+    ensure_restriction_access(&state, user.id, &payload.account_ids, &payload.account_group_ids)
+        .await?;
+
+    let (token, token_hash, token_prefix) = generate_api_token();
 
     let id = Uuid::new_v4();
-    let is_read_only = payload.is_read_only.unwrap_or(false);
+    let is_read_only = match &scopes {
+        Some(scopes) => !scopes
+            .iter()
+            .any(|scope| scope.ends_with(":write") || scope == "backup:restore"),
+        None => payload.is_read_only.unwrap_or(false),
+    };
 
     let record = sqlx::query_as::<_, ApiTokenCreated>(
         r#"
@@ -101,13 +151,19 @@ pub async fn create_api_token(
             token_hash,
             token_prefix,
             is_read_only,
-            expires_at
+            expires_at,
+            scopes,
+            account_ids,
+            account_group_ids
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         RETURNING id,
-                  $8 as token,
+                  $11 as token,
                   token_prefix,
                   is_read_only,
+                  scopes,
+                  account_ids,
+                  account_group_ids,
                   created_at,
                   expires_at
         "#,
@@ -119,6 +175,9 @@ pub async fn create_api_token(
     .bind(&token_prefix)
     .bind(is_read_only)
     .bind(payload.expires_at)
+    .bind(&scopes)
+    .bind(&payload.account_ids)
+    .bind(&payload.account_group_ids)
     .bind(&token)
     .fetch_one(&state.pool)
     .await
@@ -128,7 +187,137 @@ pub async fn create_api_token(
         &state.pool,
         Some(user.id),
         "api_token.created",
-        serde_json::json!({ "token_id": id, "name": name, "read_only": is_read_only }),
+        serde_json::json!({
+            "token_id": id,
+            "name": name,
+            "read_only": is_read_only,
+            "scopes": scopes,
+            "account_ids": payload.account_ids,
+            "account_group_ids": payload.account_group_ids,
+        }),
+    )
+    .await;
+
+    Ok(Json(record))
+}
+
+pub async fn rotate_api_token(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(token_id): Path<Uuid>,
+    payload: Option<Json<RotateApiTokenRequest>>,
+) -> Result<Json<ApiTokenCreated>, (StatusCode, String)> {
+    let grace_period_hours = payload
+        .and_then(|Json(payload)| payload.grace_period_hours)
+        .unwrap_or(DEFAULT_ROTATION_GRACE_HOURS);
+    if !(0..=MAX_ROTATION_GRACE_HOURS).contains(&grace_period_hours) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("grace_period_hours must be between 0 and {MAX_ROTATION_GRACE_HOURS}"),
+        ));
+    }
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+
+    // Lock the old token so concurrent rotations serialize and the loser sees
+    // the replacement instead of racing the insert.
+    let current = sqlx::query_as::<_, (Option<Uuid>, bool)>(
+        r#"
+        SELECT replaced_by,
+               revoked_at IS NULL AND (expires_at IS NULL OR expires_at > NOW()) as is_active
+        FROM api_keys
+        WHERE id = $1 AND user_id = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(token_id)
+    .bind(user.id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    match current {
+        None => return Err((StatusCode::NOT_FOUND, "Token not found".into())),
+        Some((Some(_), _)) => {
+            return Err((StatusCode::CONFLICT, "Token was already rotated".into()))
+        }
+        Some((None, false)) => return Err((StatusCode::NOT_FOUND, "Token not found".into())),
+        Some((None, true)) => {}
+    }
+
+    let (token, token_hash, token_prefix) = generate_api_token();
+    let replacement_id = Uuid::new_v4();
+    let record = sqlx::query_as::<_, ApiTokenCreated>(
+        r#"
+        INSERT INTO api_keys (
+            id,
+            user_id,
+            name,
+            token_hash,
+            token_prefix,
+            is_read_only,
+            expires_at,
+            scopes,
+            account_ids,
+            account_group_ids
+        )
+        SELECT $1, user_id, name, $2, $3, is_read_only, expires_at, scopes, account_ids,
+               account_group_ids
+        FROM api_keys
+        WHERE id = $4
+        RETURNING id,
+                  $5 as token,
+                  token_prefix,
+                  is_read_only,
+                  scopes,
+                  account_ids,
+                  account_group_ids,
+                  created_at,
+                  expires_at
+        "#,
+    )
+    .bind(replacement_id)
+    .bind(token_hash)
+    .bind(&token_prefix)
+    .bind(token_id)
+    .bind(&token)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    sqlx::query(
+        r#"
+        UPDATE api_keys
+        SET replaced_by = $2,
+            expires_at = LEAST(
+                COALESCE(expires_at, 'infinity'::timestamptz),
+                NOW() + make_interval(hours => $3)
+            )
+        WHERE id = $1
+        "#,
+    )
+    .bind(token_id)
+    .bind(replacement_id)
+    .bind(grace_period_hours)
+    .execute(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "api_token.rotated",
+        serde_json::json!({
+            "token_id": token_id,
+            "replacement_id": replacement_id,
+            "grace_period_hours": grace_period_hours,
+        }),
     )
     .await;
 
@@ -167,3 +356,291 @@ pub async fn revoke_api_token(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+async fn ensure_restriction_access(
+    state: &AppState,
+    user_id: Uuid,
+    account_ids: &[Uuid],
+    account_group_ids: &[Uuid],
+) -> Result<(), (StatusCode, String)> {
+    if !account_ids.is_empty() {
+        let accessible: i64 = sqlx::query_scalar(
+            r#"
+            WITH accessible_accounts AS (
+                SELECT id
+                FROM accounts
+                WHERE user_id = $1
+                UNION
+                SELECT agm.account_id
+                FROM account_group_members agm
+                INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
+                WHERE agu.user_id = $1
+            )
+            SELECT COUNT(DISTINCT id)
+            FROM accessible_accounts
+            WHERE id = ANY($2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(account_ids)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+        if accessible != account_ids.len() as i64 {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Unknown account in account_ids".into(),
+            ));
+        }
+    }
+
+    if !account_group_ids.is_empty() {
+        let accessible: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(DISTINCT ag.id)
+            FROM account_groups ag
+            LEFT JOIN account_group_users agu
+              ON agu.group_id = ag.id AND agu.user_id = $1
+            WHERE ag.id = ANY($2)
+              AND (ag.user_id = $1 OR agu.user_id IS NOT NULL)
+            "#,
+        )
+        .bind(user_id)
+        .bind(account_group_ids)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+        if accessible != account_group_ids.len() as i64 {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Unknown account group in account_group_ids".into(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn generate_api_token() -> (String, String, String) {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let token = format!("fc_{}", Base64UrlUnpadded::encode_string(&bytes));
+    let token_hash = hash_api_token(&token);
+    let token_prefix = token.chars().take(10).collect::<String>();
+    (token, token_hash, token_prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{create_api_token, rotate_api_token, CreateApiTokenRequest, RotateApiTokenRequest};
+    use crate::auth::{hash_api_token, AuthenticatedUser};
+    use crate::state::AppState;
+    use crate::test_support::{create_user, fresh_database, test_pool, test_state};
+    use axum::{
+        extract::{FromRequestParts, Path, State},
+        http::{header::AUTHORIZATION, Request, StatusCode},
+        Json,
+    };
+    use chrono::{DateTime, Duration, Utc};
+    use uuid::Uuid;
+
+    async fn issue(state: &AppState, user: &AuthenticatedUser) -> (Uuid, String) {
+        let Json(created) = create_api_token(
+            State(state.clone()),
+            user.clone(),
+            Json(CreateApiTokenRequest {
+                name: "CI".into(),
+                is_read_only: Some(true),
+                expires_at: None,
+                scopes: None,
+                account_ids: Vec::new(),
+                account_group_ids: Vec::new(),
+            }),
+        )
+        .await
+        .unwrap();
+        (created.id, created.token)
+    }
+
+    async fn rotate(
+        state: &AppState,
+        user: &AuthenticatedUser,
+        token_id: Uuid,
+        grace_period_hours: Option<i32>,
+    ) -> Result<(Uuid, String), StatusCode> {
+        rotate_api_token(
+            State(state.clone()),
+            user.clone(),
+            Path(token_id),
+            grace_period_hours.map(|hours| {
+                Json(RotateApiTokenRequest {
+                    grace_period_hours: Some(hours),
+                })
+            }),
+        )
+        .await
+        .map(|Json(created)| (created.id, created.token))
+        .map_err(|(status, _)| status)
+    }
+
+    async fn authenticates(state: &AppState, token: &str) -> bool {
+        let (mut parts, _) = Request::builder()
+            .header(AUTHORIZATION, format!("Bearer {token}"))
+            .body(())
+            .unwrap()
+            .into_parts();
+        AuthenticatedUser::from_request_parts(&mut parts, state)
+            .await
+            .is_ok()
+    }
+
+    async fn old_token(state: &AppState, token_id: Uuid) -> (Option<Uuid>, Option<DateTime<Utc>>) {
+        sqlx::query_as("SELECT replaced_by, expires_at FROM api_keys WHERE id = $1")
+            .bind(token_id)
+            .fetch_one(&state.pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn rotation_links_the_replacement_and_caps_the_old_expiry() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let state = test_state(pool);
+        let user = create_user(&state.pool, "rotator").await;
+        let (token_id, token) = issue(&state, &user).await;
+
+        let (replacement_id, replacement) = rotate(&state, &user, token_id, None).await.unwrap();
+        let (replaced_by, expires_at) = old_token(&state, token_id).await;
+        assert_eq!(replaced_by, Some(replacement_id));
+        let expires_at = expires_at.expect("old token gets a grace expiry");
+        assert!(expires_at > Utc::now() + Duration::hours(23));
+        assert!(expires_at <= Utc::now() + Duration::hours(24));
+        assert!(authenticates(&state, &token).await);
+        assert!(authenticates(&state, &replacement).await);
+    }
+
+    #[tokio::test]
+    async fn rotation_without_grace_retires_the_old_token_immediately() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let state = test_state(pool);
+        let user = create_user(&state.pool, "rotator").await;
+        let (token_id, token) = issue(&state, &user).await;
+
+        let (_, replacement) = rotate(&state, &user, token_id, Some(0)).await.unwrap();
+        assert!(!authenticates(&state, &token).await);
+        assert!(authenticates(&state, &replacement).await);
+        assert_eq!(
+            rotate(&state, &user, token_id, Some(24 * 8)).await,
+            Err(StatusCode::BAD_REQUEST)
+        );
+    }
+
+    #[tokio::test]
+    async fn rotating_twice_conflicts_and_other_users_see_not_found() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let state = test_state(pool);
+        let user = create_user(&state.pool, "rotator").await;
+        let outsider = create_user(&state.pool, "outsider").await;
+        let (token_id, _) = issue(&state, &user).await;
+
+        assert_eq!(
+            rotate(&state, &outsider, token_id, None).await,
+            Err(StatusCode::NOT_FOUND)
+        );
+        let (replacement_id, _) = rotate(&state, &user, token_id, None).await.unwrap();
+        assert_eq!(
+            rotate(&state, &user, token_id, None).await,
+            Err(StatusCode::CONFLICT)
+        );
+        assert!(rotate(&state, &user, replacement_id, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn concurrent_rotations_issue_one_replacement() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let state = test_state(pool);
+        let user = create_user(&state.pool, "rotator").await;
+        let (token_id, _) = issue(&state, &user).await;
+
+        let (first, second) = tokio::join!(
+            rotate(&state, &user, token_id, None),
+            rotate(&state, &user, token_id, None)
+        );
+        let mut outcomes = [
+            first.map(|_| StatusCode::OK),
+            second.map(|_| StatusCode::OK),
+        ]
+        .map(|outcome| outcome.unwrap_or_else(|status| status));
+        outcomes.sort();
+        assert_eq!(outcomes, [StatusCode::OK, StatusCode::CONFLICT]);
+    }
+
+    #[tokio::test]
+    async fn database_sha256_matches_hash_api_token() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let token = "fc_AbC-123_xyz";
+        let hashed: String =
+            sqlx::query_scalar("SELECT encode(sha256(convert_to($1, 'UTF8')), 'hex')")
+                .bind(token)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(hashed, hash_api_token(token));
+    }
+
+    #[tokio::test]
+    async fn plaintext_tokens_still_authenticate_after_the_hashing_migration() {
+        let Some(options) = fresh_database().await else {
+            return;
+        };
+        let pool = crate::test_support::connect(&options).await;
+        let migrator = sqlx::migrate!("./migrations");
+        for migration in migrator.migrations.iter().filter(|m| m.version < 37) {
+            sqlx::raw_sql(&migration.sql).execute(&pool).await.unwrap();
+        }
+        let user_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, name, email) VALUES ($1, 'legacy', 'legacy@example.com')",
+        )
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let token = "fc_legacyPlaintextToken";
+        sqlx::query(
+            r#"
+            INSERT INTO api_keys (id, user_id, name, token, token_hash, token_prefix)
+            VALUES ($1, $2, 'legacy', $3, '', '')
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(token)
+        .execute(&pool)
+        .await
+        .unwrap();
+        for migration in migrator.migrations.iter().filter(|m| m.version >= 37) {
+            sqlx::raw_sql(&migration.sql).execute(&pool).await.unwrap();
+        }
+
+        let (token_hash, token_prefix): (String, String) =
+            sqlx::query_as("SELECT token_hash, token_prefix FROM api_keys WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(token_hash, hash_api_token(token));
+        assert_eq!(token_prefix, "fc_legacyP");
+        assert!(authenticates(&test_state(pool), token).await);
+    }
+}
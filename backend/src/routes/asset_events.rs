@@ -0,0 +1,528 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    audit::record_audit_event,
+    auth::AuthenticatedUser,
+    models::{
+        AssetIncomeEvent, AssetSplit, AssetTrade, CreateAssetIncomeRequest, CreateAssetSplitRequest,
+    },
+    policy::{authorize_account, authorize_asset, AccountAction},
+    routes::asset_trades::{load_asset, sync_asset_quantity, trade_inputs},
+    services::{
+        change_history::{record_change, snapshot, ChangeEntity},
+        corporate_actions::{held_quantity, income_category, SplitRatio, INCOME_KINDS},
+    },
+    state::AppState,
+};
+
+pub async fn list_asset_income(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(asset_id): Path<Uuid>,
+) -> Result<Json<Vec<AssetIncomeEvent>>, (StatusCode, String)> {
+    authorize_asset(&state.pool, &user, asset_id, AccountAction::View).await?;
+
+    let events = sqlx::query_as::<_, AssetIncomeEvent>(
+        r#"
+        SELECT id, asset_id, kind, amount, currency_code, paid_at, description, transaction_id,
+               created_at
+        FROM asset_income_events
+        WHERE asset_id = $1
+        ORDER BY paid_at, created_at
+        "#,
+    )
+    .bind(asset_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    Ok(Json(events))
+}
+
+pub async fn create_asset_income(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(asset_id): Path<Uuid>,
+    Json(payload): Json<CreateAssetIncomeRequest>,
+) -> Result<Json<AssetIncomeEvent>, (StatusCode, String)> {
+    let kind = payload.kind.trim().to_lowercase();
+    if !INCOME_KINDS.contains(&kind.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "kind must be dividend or interest".into(),
+        ));
+    }
+
+    let (account_id, asset_currency) = load_asset(&state, asset_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
+
+    let currency_code = payload
+        .currency_code
+        .map(|code| code.trim().to_uppercase())
+        .unwrap_or_else(|| asset_currency.clone());
+    if currency_code != asset_currency {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Income currency must match the asset currency".into(),
+        ));
+    }
+
+    let amount = match (payload.amount, payload.amount_per_share) {
+        (Some(amount), None) => amount,
+        (None, Some(per_share)) => {
+            per_share * quantity_held(&state, asset_id, payload.paid_at).await?
+        }
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Provide either amount or amount_per_share".into(),
+            ))
+        }
+    };
+    if !(amount.is_finite() && amount > 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Income amount must be positive".into(),
+        ));
+    }
+
+    let symbol: String = sqlx::query_scalar("SELECT symbol FROM assets WHERE id = $1")
+        .bind(asset_id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let description = payload
+        .description
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let transaction_id = if payload.create_transaction.unwrap_or(false) {
+        let transaction_id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO transactions (
+                id, account_id, amount, currency_code, transaction_type, category, merchant,
+                description, occurred_at
+            )
+            VALUES ($1, $2, $3, $4, 'income', $5, $6, $7, $8)
+            "#,
+        )
+        .bind(transaction_id)
+        .bind(account_id)
+        .bind(amount)
+        .bind(&currency_code)
+        .bind(income_category(&kind))
+        .bind(&symbol)
+        .bind(
+            description
+                .clone()
+                .unwrap_or_else(|| format!("{symbol} {kind}")),
+        )
+        .bind(payload.paid_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+        record_change(&mut tx, user.id, ChangeEntity::Transaction, transaction_id, None)
+            .await
+            .map_err(crate::auth::internal_error)?;
+        Some(transaction_id)
+    } else {
+        None
+    };
+
+    let event = sqlx::query_as::<_, AssetIncomeEvent>(
+        r#"
+        INSERT INTO asset_income_events (
+            id, asset_id, kind, amount, currency_code, paid_at, description, transaction_id
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, asset_id, kind, amount, currency_code, paid_at, description, transaction_id,
+                  created_at
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(asset_id)
+    .bind(&kind)
+    .bind(amount)
+    .bind(&currency_code)
+    .bind(payload.paid_at)
+    .bind(&description)
+    .bind(transaction_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "asset_income.create",
+        serde_json::json!({
+            "asset_id": asset_id,
+            "event_id": event.id,
+            "kind": kind,
+            "transaction_id": transaction_id,
+        }),
+    )
+    .await;
+
+    Ok(Json(event))
+}
+
+pub async fn delete_asset_income(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path((asset_id, event_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let (account_id, _) = load_asset(&state, asset_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let transaction_id: Option<Option<Uuid>> = sqlx::query_scalar(
+        r#"
+        DELETE FROM asset_income_events
+        WHERE id = $1 AND asset_id = $2
+        RETURNING transaction_id
+        "#,
+    )
+    .bind(event_id)
+    .bind(asset_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let Some(transaction_id) = transaction_id else {
+        return Err((StatusCode::NOT_FOUND, "Income event not found".into()));
+    };
+
+    // The income transaction was created with the event, so it goes with it.
+    if let Some(transaction_id) = transaction_id {
+        let before = snapshot(&mut tx, ChangeEntity::Transaction, transaction_id)
+            .await
+            .map_err(crate::auth::internal_error)?;
+        sqlx::query("DELETE FROM transactions WHERE id = $1")
+            .bind(transaction_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(crate::auth::internal_error)?;
+        record_change(&mut tx, user.id, ChangeEntity::Transaction, transaction_id, before)
+            .await
+            .map_err(crate::auth::internal_error)?;
+    }
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "asset_income.delete",
+        serde_json::json!({ "asset_id": asset_id, "event_id": event_id }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_asset_splits(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(asset_id): Path<Uuid>,
+) -> Result<Json<Vec<AssetSplit>>, (StatusCode, String)> {
+    authorize_asset(&state.pool, &user, asset_id, AccountAction::View).await?;
+
+    let splits = sqlx::query_as::<_, AssetSplit>(
+        r#"
+        SELECT id, asset_id, numerator, denominator,
+               CASE WHEN numerator > denominator THEN 'split' ELSE 'reverse_split' END as kind,
+               effective_at, created_at
+        FROM asset_splits
+        WHERE asset_id = $1
+        ORDER BY effective_at
+        "#,
+    )
+    .bind(asset_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    Ok(Json(splits))
+}
+
+pub async fn create_asset_split(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(asset_id): Path<Uuid>,
+    Json(payload): Json<CreateAssetSplitRequest>,
+) -> Result<Json<AssetSplit>, (StatusCode, String)> {
+    let Some(ratio) = SplitRatio::new(payload.numerator, payload.denominator) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "numerator and denominator must be positive and differ".into(),
+        ));
+    };
+    if payload.effective_at > Utc::now() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "effective_at must not be in the future".into(),
+        ));
+    }
+
+    let (account_id, _) = load_asset(&state, asset_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let split = sqlx::query_as::<_, AssetSplit>(
+        r#"
+        INSERT INTO asset_splits (id, asset_id, numerator, denominator, effective_at)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, asset_id, numerator, denominator, $6::TEXT as kind, effective_at,
+                  created_at
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(asset_id)
+    .bind(ratio.numerator)
+    .bind(ratio.denominator)
+    .bind(payload.effective_at)
+    .bind(ratio.kind())
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    apply_split(&mut tx, user.id, asset_id, ratio).await?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "asset_split.create",
+        serde_json::json!({
+            "asset_id": asset_id,
+            "split_id": split.id,
+            "numerator": ratio.numerator,
+            "denominator": ratio.denominator,
+        }),
+    )
+    .await;
+
+    Ok(Json(split))
+}
+
+pub async fn delete_asset_split(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path((asset_id, split_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let (account_id, _) = load_asset(&state, asset_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let record = sqlx::query_as::<_, (f64, f64)>(
+        r#"
+        DELETE FROM asset_splits
+        WHERE id = $1 AND asset_id = $2
+        RETURNING numerator, denominator
+        "#,
+    )
+    .bind(split_id)
+    .bind(asset_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let Some((numerator, denominator)) = record else {
+        return Err((StatusCode::NOT_FOUND, "Split not found".into()));
+    };
+    if let Some(ratio) = SplitRatio::new(numerator, denominator) {
+        apply_split(&mut tx, user.id, asset_id, ratio.inverse()).await?;
+    }
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "asset_split.delete",
+        serde_json::json!({ "asset_id": asset_id, "split_id": split_id }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn apply_split(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+    asset_id: Uuid,
+    ratio: SplitRatio,
+) -> Result<(), (StatusCode, String)> {
+    let has_trades: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM asset_trades WHERE asset_id = $1)")
+            .bind(asset_id)
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(crate::auth::internal_error)?;
+    if has_trades {
+        sync_asset_quantity(tx, user_id, asset_id).await?;
+    } else {
+        // Without trades the stored quantity was held through the split.
+        let before = snapshot(tx, ChangeEntity::Asset, asset_id)
+            .await
+            .map_err(crate::auth::internal_error)?;
+        sqlx::query("UPDATE assets SET quantity = quantity * $2 WHERE id = $1")
+            .bind(asset_id)
+            .bind(ratio.factor())
+            .execute(&mut **tx)
+            .await
+            .map_err(crate::auth::internal_error)?;
+        record_change(tx, user_id, ChangeEntity::Asset, asset_id, before)
+            .await
+            .map_err(crate::auth::internal_error)?;
+    }
+
+    Ok(())
+}
+
+async fn quantity_held(
+    state: &AppState,
+    asset_id: Uuid,
+    at: chrono::DateTime<Utc>,
+) -> Result<f64, (StatusCode, String)> {
+    let factor_at: f64 = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(MAX(factor), 1)
+        FROM asset_split_factors
+        WHERE asset_id = $1 AND $2 >= valid_from AND $2 < valid_until
+        "#,
+    )
+    .bind(asset_id)
+    .bind(at)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    let trades = sqlx::query_as::<_, AssetTrade>(
+        r#"
+        SELECT t.id, t.asset_id, t.side,
+               t.quantity * COALESCE(sf.factor, 1) / $2 as quantity,
+               t.price, t.fees, t.currency_code, t.traded_at, t.lot_id, t.created_at
+        FROM asset_trades t
+        LEFT JOIN asset_split_factors sf ON sf.asset_id = t.asset_id
+            AND t.traded_at >= sf.valid_from AND t.traded_at < sf.valid_until
+        WHERE t.asset_id = $1
+        ORDER BY t.traded_at, t.created_at
+        "#,
+    )
+    .bind(asset_id)
+    .bind(factor_at)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    if trades.is_empty() {
+        let quantity: f64 = sqlx::query_scalar("SELECT quantity FROM assets WHERE id = $1")
+            .bind(asset_id)
+            .fetch_one(&state.pool)
+            .await
+            .map_err(crate::auth::internal_error)?;
+        return Ok(quantity / factor_at);
+    }
+    Ok(held_quantity(&trade_inputs(&trades), at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::quantity_held;
+    use crate::test_support::{create_account, create_user, test_pool, test_state};
+    use chrono::{Duration, TimeZone, Utc};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    async fn create_asset(pool: &PgPool, account_id: Uuid, quantity: f64) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO assets (id, account_id, symbol, asset_type, quantity, currency_code)
+            VALUES ($1, $2, 'ACME', 'Stock', $3, 'USD')
+            "#,
+        )
+        .bind(id)
+        .bind(account_id)
+        .bind(quantity)
+        .execute(pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    async fn split(pool: &PgPool, asset_id: Uuid, numerator: f64, at: chrono::DateTime<Utc>) {
+        sqlx::query(
+            r#"
+            INSERT INTO asset_splits (id, asset_id, numerator, denominator, effective_at)
+            VALUES ($1, $2, $3, 1, $4)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(asset_id)
+        .bind(numerator)
+        .bind(at)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn held_quantity_follows_each_split() {
+        let Some(pool) = test_pool().await else { return };
+        let state = test_state(pool);
+        let owner = create_user(&state.pool, "owner").await;
+        let account_id = create_account(&state.pool, &owner).await;
+        let first = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let second = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let day = Duration::days(1);
+
+        let traded = create_asset(&state.pool, account_id, 0.0).await;
+        sqlx::query(
+            r#"
+            INSERT INTO asset_trades (id, asset_id, side, quantity, price, currency_code, traded_at)
+            VALUES ($1, $2, 'buy', 10, 100, 'USD', $3)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(traded)
+        .bind(first - day * 10)
+        .execute(&state.pool)
+        .await
+        .unwrap();
+        let untraded = create_asset(&state.pool, account_id, 60.0).await;
+        for asset_id in [traded, untraded] {
+            split(&state.pool, asset_id, 2.0, first).await;
+            split(&state.pool, asset_id, 3.0, second).await;
+        }
+
+        for (at, expected) in [(first - day, 10.0), (first, 20.0), (second + day, 60.0)] {
+            assert_eq!(quantity_held(&state, traded, at).await.unwrap(), expected);
+            assert_eq!(quantity_held(&state, untraded, at).await.unwrap(), expected);
+        }
+    }
+}
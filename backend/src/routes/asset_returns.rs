@@ -0,0 +1,272 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    policy::accessible_account_ids,
+    services::returns::{
+        annualize, daily_valuations, investor_cashflows, time_weighted_return, xirr, HoldingInput,
+        HoldingTrade,
+    },
+    state::AppState,
+};
+
+const MAX_RANGE_DAYS: i64 = 366 * 10;
+
+#[derive(Deserialize)]
+pub struct ReturnsQuery {
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub account_id: Option<Uuid>,
+    pub account_group_id: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+pub struct ReturnsResponse {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub currency_code: String,
+    pub start_value: f64,
+    pub end_value: f64,
+    pub net_contributions: f64,
+    pub time_weighted_return: Option<f64>,
+    pub time_weighted_return_annualized: Option<f64>,
+    pub money_weighted_return: Option<f64>,
+    pub unpriced: Vec<String>,
+}
+
+pub async fn portfolio_returns(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<ReturnsQuery>,
+) -> Result<Json<ReturnsResponse>, (StatusCode, String)> {
+    let end_date = params.end_date.unwrap_or_else(|| Utc::now().date_naive());
+    let start_date = params
+        .start_date
+        .unwrap_or_else(|| end_date - Duration::days(365));
+    if start_date > end_date {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "start_date must not be after end_date".into(),
+        ));
+    }
+    if (end_date - start_date).num_days() > MAX_RANGE_DAYS {
+        return Err((StatusCode::BAD_REQUEST, "Date range is too long".into()));
+    }
+
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
+    let mut query = QueryBuilder::new(
+        r#"
+        SELECT a.id, a.symbol, a.quantity, a.currency_code
+        FROM assets a
+        WHERE a.account_id = ANY(
+        "#,
+    );
+    query.push_bind(account_ids);
+    query.push(")");
+    if let Some(account_id) = params.account_id {
+        query.push(" AND a.account_id = ");
+        query.push_bind(account_id);
+    }
+    if let Some(group_id) = params.account_group_id {
+        query.push(
+            r#"
+            AND EXISTS (
+                SELECT 1
+                FROM account_group_members agm
+                WHERE agm.group_id =
+            "#,
+        );
+        query.push_bind(group_id);
+        query.push(" AND agm.account_id = a.account_id)");
+    }
+    let asset_rows = query
+        .build()
+        .fetch_all(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+
+    let mut asset_ids = Vec::new();
+    let mut assets: HashMap<Uuid, (String, f64, String)> = HashMap::new();
+    for row in asset_rows {
+        let id: Uuid = row.try_get("id").map_err(crate::auth::internal_error)?;
+        let symbol: String = row.try_get("symbol").map_err(crate::auth::internal_error)?;
+        let quantity: f64 = row
+            .try_get("quantity")
+            .map_err(crate::auth::internal_error)?;
+        let currency_code: String = row
+            .try_get("currency_code")
+            .map_err(crate::auth::internal_error)?;
+        asset_ids.push(id);
+        assets.insert(id, (symbol, quantity, currency_code));
+    }
+
+    let end_exclusive = (end_date + Duration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .map(|value| value.and_utc());
+    let price_rows = sqlx::query(
+        r#"
+        SELECT DISTINCT ON (ph.asset_id, (ph.recorded_at AT TIME ZONE 'UTC')::date)
+               ph.asset_id,
+               (ph.recorded_at AT TIME ZONE 'UTC')::date as price_date,
+               ph.price / COALESCE(sf.factor, 1) as price
+        FROM price_history ph
+        LEFT JOIN asset_split_factors sf ON sf.asset_id = ph.asset_id
+            AND ph.recorded_at >= sf.valid_from AND ph.recorded_at < sf.valid_until
+        WHERE ph.asset_id = ANY($1)
+          AND ph.recorded_at < $2
+        ORDER BY ph.asset_id, (ph.recorded_at AT TIME ZONE 'UTC')::date, ph.recorded_at DESC
+        "#,
+    )
+    .bind(&asset_ids)
+    .bind(end_exclusive)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let mut prices: HashMap<Uuid, HashMap<NaiveDate, f64>> = HashMap::new();
+    for row in price_rows {
+        let asset_id: Uuid = row
+            .try_get("asset_id")
+            .map_err(crate::auth::internal_error)?;
+        let price_date: NaiveDate = row
+            .try_get("price_date")
+            .map_err(crate::auth::internal_error)?;
+        let price: f64 = row.try_get("price").map_err(crate::auth::internal_error)?;
+        prices
+            .entry(asset_id)
+            .or_default()
+            .insert(price_date, price);
+    }
+
+    let trade_rows = sqlx::query(
+        r#"
+        SELECT t.asset_id, t.side,
+               t.quantity * COALESCE(sf.factor, 1) as quantity,
+               t.price / COALESCE(sf.factor, 1) as price,
+               t.fees,
+               (t.traded_at AT TIME ZONE 'UTC')::date as trade_date
+        FROM asset_trades t
+        LEFT JOIN asset_split_factors sf ON sf.asset_id = t.asset_id
+            AND t.traded_at >= sf.valid_from AND t.traded_at < sf.valid_until
+        WHERE t.asset_id = ANY($1)
+          AND t.traded_at < $2
+        ORDER BY t.traded_at, t.created_at
+        "#,
+    )
+    .bind(&asset_ids)
+    .bind(end_exclusive)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let mut trades: HashMap<Uuid, Vec<HoldingTrade>> = HashMap::new();
+    for row in trade_rows {
+        let asset_id: Uuid = row
+            .try_get("asset_id")
+            .map_err(crate::auth::internal_error)?;
+        let side: String = row.try_get("side").map_err(crate::auth::internal_error)?;
+        let quantity: f64 = row
+            .try_get("quantity")
+            .map_err(crate::auth::internal_error)?;
+        let price: f64 = row.try_get("price").map_err(crate::auth::internal_error)?;
+        let fees: f64 = row.try_get("fees").map_err(crate::auth::internal_error)?;
+        let date: NaiveDate = row
+            .try_get("trade_date")
+            .map_err(crate::auth::internal_error)?;
+        // Trade prices stand in for days without a stored close.
+        prices
+            .entry(asset_id)
+            .or_default()
+            .entry(date)
+            .or_insert(price);
+        let trade = if side == "sell" {
+            HoldingTrade {
+                date,
+                quantity: -quantity,
+                cash: -(quantity * price - fees),
+            }
+        } else {
+            HoldingTrade {
+                date,
+                quantity,
+                cash: quantity * price + fees,
+            }
+        };
+        trades.entry(asset_id).or_default().push(trade);
+    }
+
+    let fx_rows = sqlx::query(
+        r#"
+        SELECT DISTINCT ON (base_currency) base_currency, rate
+        FROM fx_rates
+        WHERE quote_currency = 'USD'
+        ORDER BY base_currency, recorded_on DESC
+        "#,
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    let mut fx_map = HashMap::<String, f64>::new();
+    for row in fx_rows {
+        let base_currency: String = row
+            .try_get("base_currency")
+            .map_err(crate::auth::internal_error)?;
+        let rate: f64 = row.try_get("rate").map_err(crate::auth::internal_error)?;
+        fx_map.insert(base_currency, rate);
+    }
+
+    let mut holdings = Vec::new();
+    let mut unpriced = Vec::new();
+    for asset_id in &asset_ids {
+        let Some((symbol, quantity, currency_code)) = assets.remove(asset_id) else {
+            continue;
+        };
+        let fx_rate = if currency_code == "USD" {
+            Some(1.0)
+        } else {
+            fx_map.get(&currency_code).copied()
+        };
+        let mut asset_prices: Vec<(NaiveDate, f64)> = prices
+            .remove(asset_id)
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        let Some(fx_rate) = fx_rate.filter(|_| !asset_prices.is_empty()) else {
+            unpriced.push(symbol);
+            continue;
+        };
+        asset_prices.sort_by_key(|(date, _)| *date);
+        holdings.push(HoldingInput {
+            base_quantity: quantity,
+            trades: trades.remove(asset_id).unwrap_or_default(),
+            prices: asset_prices,
+            fx_rate,
+        });
+    }
+
+    let points = daily_valuations(&holdings, start_date, end_date);
+    let time_weighted = time_weighted_return(&points);
+    let days = (end_date - start_date).num_days();
+
+    Ok(Json(ReturnsResponse {
+        start_date,
+        end_date,
+        currency_code: "USD".to_string(),
+        start_value: points.first().map_or(0.0, |point| point.value),
+        end_value: points.last().map_or(0.0, |point| point.value),
+        net_contributions: points.iter().skip(1).map(|point| point.flow).sum(),
+        time_weighted_return: time_weighted,
+        time_weighted_return_annualized: time_weighted.and_then(|value| annualize(value, days)),
+        money_weighted_return: xirr(&investor_cashflows(&points)),
+        unpriced,
+    }))
+}
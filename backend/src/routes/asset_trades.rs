@@ -0,0 +1,275 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use sqlx::{Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    audit::record_audit_event,
+    auth::AuthenticatedUser,
+    models::{AssetTrade, CreateAssetTradeRequest},
+    policy::{authorize_account, authorize_asset, AccountAction},
+    services::{
+        change_history::{record_change, snapshot, ChangeEntity},
+        cost_basis::{compute_position, CostBasisMethod, TradeInput, TradeSide},
+    },
+    state::AppState,
+};
+
+pub async fn list_asset_trades(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(asset_id): Path<Uuid>,
+) -> Result<Json<Vec<AssetTrade>>, (StatusCode, String)> {
+    authorize_asset(&state.pool, &user, asset_id, AccountAction::View).await?;
+
+    let trades = sqlx::query_as::<_, AssetTrade>(
+        r#"
+        SELECT id, asset_id, side, quantity, price, fees, currency_code, traded_at, lot_id,
+               created_at
+        FROM asset_trades
+        WHERE asset_id = $1
+        ORDER BY traded_at, created_at
+        "#,
+    )
+    .bind(asset_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    Ok(Json(trades))
+}
+
+pub async fn create_asset_trade(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(asset_id): Path<Uuid>,
+    Json(payload): Json<CreateAssetTradeRequest>,
+) -> Result<Json<AssetTrade>, (StatusCode, String)> {
+    let side = payload.side.trim().to_lowercase();
+    if TradeSide::parse(&side).is_none() {
+        return Err((StatusCode::BAD_REQUEST, "side must be buy or sell".into()));
+    }
+    let fees = payload.fees.unwrap_or(0.0);
+    if !(payload.quantity.is_finite() && payload.quantity > 0.0) {
+        return Err((StatusCode::BAD_REQUEST, "quantity must be positive".into()));
+    }
+    let valid_amounts = payload.price.is_finite() && payload.price >= 0.0;
+    if !(valid_amounts && fees.is_finite() && fees >= 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "price and fees must not be negative".into(),
+        ));
+    }
+
+    let (account_id, asset_currency) = load_asset(&state, asset_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
+
+    let currency_code = payload
+        .currency_code
+        .map(|code| code.trim().to_uppercase())
+        .unwrap_or_else(|| asset_currency.clone());
+    if currency_code != asset_currency {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Trade currency must match the asset currency".into(),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await.map_err(crate::auth::internal_error)?;
+    if let Some(lot_id) = payload.lot_id {
+        let lot_side: Option<String> = sqlx::query_scalar(
+            "SELECT side FROM asset_trades WHERE id = $1 AND asset_id = $2",
+        )
+        .bind(lot_id)
+        .bind(asset_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+        if side != "sell" || lot_side.as_deref() != Some("buy") {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "lot_id must reference a buy of this asset from a sell".into(),
+            ));
+        }
+    }
+
+    let trade = sqlx::query_as::<_, AssetTrade>(
+        r#"
+        INSERT INTO asset_trades (
+            id, asset_id, side, quantity, price, fees, currency_code, traded_at, lot_id
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id, asset_id, side, quantity, price, fees, currency_code, traded_at, lot_id,
+                  created_at
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(asset_id)
+    .bind(&side)
+    .bind(payload.quantity)
+    .bind(payload.price)
+    .bind(fees)
+    .bind(&currency_code)
+    .bind(payload.traded_at)
+    .bind(payload.lot_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    sync_asset_quantity(&mut tx, user.id, asset_id).await?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "asset_trade.create",
+        serde_json::json!({ "asset_id": asset_id, "trade_id": trade.id, "side": side }),
+    )
+    .await;
+
+    Ok(Json(trade))
+}
+
+pub async fn delete_asset_trade(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path((asset_id, trade_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let (account_id, _) = load_asset(&state, asset_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
+
+    let mut tx = state.pool.begin().await.map_err(crate::auth::internal_error)?;
+    let result = sqlx::query(
+        r#"
+        DELETE FROM asset_trades
+        WHERE id = $1 AND asset_id = $2
+        "#,
+    )
+    .bind(trade_id)
+    .bind(asset_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Trade not found".into()));
+    }
+
+    sync_asset_quantity(&mut tx, user.id, asset_id).await?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "asset_trade.delete",
+        serde_json::json!({ "asset_id": asset_id, "trade_id": trade_id }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub(crate) fn trade_inputs(trades: &[AssetTrade]) -> Vec<TradeInput> {
+    trades
+        .iter()
+        .filter_map(|trade| {
+            Some(TradeInput {
+                id: trade.id,
+                side: TradeSide::parse(&trade.side)?,
+                quantity: trade.quantity,
+                price: trade.price,
+                fees: trade.fees,
+                traded_at: trade.traded_at,
+                lot_id: trade.lot_id,
+            })
+        })
+        .collect()
+}
+
+pub(crate) async fn sync_asset_quantity(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    asset_id: Uuid,
+) -> Result<(), (StatusCode, String)> {
+    let before = snapshot(tx, ChangeEntity::Asset, asset_id)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let method: String = sqlx::query_scalar("SELECT cost_basis_method FROM assets WHERE id = $1")
+        .bind(asset_id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let trades = sqlx::query_as::<_, AssetTrade>(
+        r#"
+        SELECT t.id, t.asset_id, t.side,
+               t.quantity * COALESCE(sf.factor, 1) as quantity,
+               t.price / COALESCE(sf.factor, 1) as price,
+               t.fees, t.currency_code, t.traded_at, t.lot_id, t.created_at
+        FROM asset_trades t
+        LEFT JOIN asset_split_factors sf ON sf.asset_id = t.asset_id
+            AND t.traded_at >= sf.valid_from AND t.traded_at < sf.valid_until
+        WHERE t.asset_id = $1
+        ORDER BY t.traded_at, t.created_at
+        "#,
+    )
+    .bind(asset_id)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let quantity = if trades.is_empty() {
+        0.0
+    } else {
+        compute_position(
+            &trade_inputs(&trades),
+            CostBasisMethod::parse(&method).unwrap_or(CostBasisMethod::Fifo),
+        )
+        .map_err(|err| (StatusCode::BAD_REQUEST, err))?
+        .quantity
+    };
+
+    sqlx::query("UPDATE assets SET quantity = $1 WHERE id = $2")
+        .bind(quantity)
+        .bind(asset_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    record_change(tx, user_id, ChangeEntity::Asset, asset_id, before)
+        .await
+        .map_err(crate::auth::internal_error)?;
+
+    Ok(())
+}
+
+pub(crate) async fn load_asset(
+    state: &AppState,
+    asset_id: Uuid,
+) -> Result<(Uuid, String), (StatusCode, String)> {
+    let record = sqlx::query(
+        r#"
+        SELECT account_id, currency_code
+        FROM assets
+        WHERE id = $1
+        "#,
+    )
+    .bind(asset_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let Some(record) = record else {
+        return Err((StatusCode::NOT_FOUND, "Asset not found".into()));
+    };
+
+    Ok((
+        record
+            .try_get("account_id")
+            .map_err(crate::auth::internal_error)?,
+        record
+            .try_get("currency_code")
+            .map_err(crate::auth::internal_error)?,
+    ))
+}
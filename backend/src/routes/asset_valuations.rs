@@ -0,0 +1,356 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::{
+    audit::record_audit_event,
+    auth::AuthenticatedUser,
+    models::{
+        AssetValuation, AssetValuationSchedule, CreateAssetValuationRequest,
+        UpdateAssetValuationScheduleRequest,
+    },
+    policy::{authorize_account, authorize_asset, AccountAction},
+    services::valuation::{apply_valuation_schedules, ScheduleMethod, MANUAL_VALUATION},
+    state::AppState,
+};
+
+pub async fn list_asset_valuations(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(asset_id): Path<Uuid>,
+) -> Result<Json<Vec<AssetValuation>>, (StatusCode, String)> {
+    authorize_asset(&state.pool, &user, asset_id, AccountAction::View).await?;
+
+    let valuations = sqlx::query_as::<_, AssetValuation>(
+        r#"
+        SELECT id, asset_id, price, currency_code, valued_at, note, created_at
+        FROM asset_valuations
+        WHERE asset_id = $1
+        ORDER BY valued_at, created_at
+        "#,
+    )
+    .bind(asset_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    Ok(Json(valuations))
+}
+
+pub async fn create_asset_valuation(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(asset_id): Path<Uuid>,
+    Json(payload): Json<CreateAssetValuationRequest>,
+) -> Result<Json<AssetValuation>, (StatusCode, String)> {
+    let asset = load_manual_asset(&state, &user, asset_id).await?;
+
+    let price = match (payload.price, payload.value) {
+        (Some(price), None) => price,
+        (None, Some(value)) if asset.quantity > 0.0 => value / asset.quantity,
+        (None, Some(_)) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "value needs a positive asset quantity; post a price instead".into(),
+            ))
+        }
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Provide either price or value".into(),
+            ))
+        }
+    };
+    if !(price.is_finite() && price >= 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Valuation must not be negative".into(),
+        ));
+    }
+    let valued_at = payload.valued_at.unwrap_or_else(Utc::now);
+    if valued_at > Utc::now() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Valuations cannot be in the future".into(),
+        ));
+    }
+    let note = payload
+        .note
+        .map(|note| note.trim().to_string())
+        .filter(|note| !note.is_empty());
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let price_history_id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO price_history (id, asset_id, price, currency_code, recorded_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(price_history_id)
+    .bind(asset_id)
+    .bind(price)
+    .bind(&asset.currency_code)
+    .bind(valued_at)
+    .execute(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    let valuation = sqlx::query_as::<_, AssetValuation>(
+        r#"
+        INSERT INTO asset_valuations (
+            id, asset_id, price, currency_code, valued_at, note, price_history_id
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, asset_id, price, currency_code, valued_at, note, created_at
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(asset_id)
+    .bind(price)
+    .bind(&asset.currency_code)
+    .bind(valued_at)
+    .bind(note)
+    .bind(price_history_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "asset_valuation.create",
+        serde_json::json!({
+            "asset_id": asset_id,
+            "valuation_id": valuation.id,
+            "price": price,
+        }),
+    )
+    .await;
+
+    Ok(Json(valuation))
+}
+
+pub async fn delete_asset_valuation(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path((asset_id, valuation_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    load_manual_asset(&state, &user, asset_id).await?;
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let price_history_id: Option<Option<Uuid>> = sqlx::query_scalar(
+        r#"
+        DELETE FROM asset_valuations
+        WHERE id = $1 AND asset_id = $2
+        RETURNING price_history_id
+        "#,
+    )
+    .bind(valuation_id)
+    .bind(asset_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let Some(price_history_id) = price_history_id else {
+        return Err((StatusCode::NOT_FOUND, "Valuation not found".into()));
+    };
+
+    if let Some(price_history_id) = price_history_id {
+        sqlx::query("DELETE FROM price_history WHERE id = $1")
+            .bind(price_history_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(crate::auth::internal_error)?;
+    }
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "asset_valuation.delete",
+        serde_json::json!({ "asset_id": asset_id, "valuation_id": valuation_id }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_asset_valuation_schedule(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(asset_id): Path<Uuid>,
+) -> Result<Json<AssetValuationSchedule>, (StatusCode, String)> {
+    authorize_asset(&state.pool, &user, asset_id, AccountAction::View).await?;
+
+    let schedule = sqlx::query_as::<_, AssetValuationSchedule>(
+        r#"
+        SELECT asset_id, method, annual_rate, updated_at
+        FROM asset_valuation_schedules
+        WHERE asset_id = $1
+        "#,
+    )
+    .bind(asset_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    schedule
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "No valuation schedule".into()))
+}
+
+pub async fn update_asset_valuation_schedule(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(asset_id): Path<Uuid>,
+    Json(payload): Json<UpdateAssetValuationScheduleRequest>,
+) -> Result<Json<AssetValuationSchedule>, (StatusCode, String)> {
+    load_manual_asset(&state, &user, asset_id).await?;
+
+    let method = payload
+        .method
+        .map(|value| value.trim().to_lowercase())
+        .unwrap_or_else(|| "compound".to_string());
+    if ScheduleMethod::parse(&method).is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "method must be compound or straight_line".into(),
+        ));
+    }
+    if !(payload.annual_rate.is_finite()
+        && payload.annual_rate > -1.0
+        && payload.annual_rate < 10.0)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "annual_rate must be a fraction above -1".into(),
+        ));
+    }
+
+    let schedule = sqlx::query_as::<_, AssetValuationSchedule>(
+        r#"
+        INSERT INTO asset_valuation_schedules (asset_id, method, annual_rate)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (asset_id)
+        DO UPDATE SET method = EXCLUDED.method,
+                      annual_rate = EXCLUDED.annual_rate,
+                      updated_at = NOW()
+        RETURNING asset_id, method, annual_rate, updated_at
+        "#,
+    )
+    .bind(asset_id)
+    .bind(&method)
+    .bind(payload.annual_rate)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    apply_valuation_schedules(&state.pool, Some(asset_id))
+        .await
+        .map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "asset_valuation.schedule_update",
+        serde_json::json!({
+            "asset_id": asset_id,
+            "method": method,
+            "annual_rate": payload.annual_rate,
+        }),
+    )
+    .await;
+
+    Ok(Json(schedule))
+}
+
+pub async fn delete_asset_valuation_schedule(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(asset_id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    load_manual_asset(&state, &user, asset_id).await?;
+
+    let result = sqlx::query("DELETE FROM asset_valuation_schedules WHERE asset_id = $1")
+        .bind(asset_id)
+        .execute(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "No valuation schedule".into()));
+    }
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "asset_valuation.schedule_delete",
+        serde_json::json!({ "asset_id": asset_id }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+struct ManualAsset {
+    quantity: f64,
+    currency_code: String,
+}
+
+async fn load_manual_asset(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    asset_id: Uuid,
+) -> Result<ManualAsset, (StatusCode, String)> {
+    let record = sqlx::query(
+        r#"
+        SELECT account_id, quantity, currency_code, valuation_mode
+        FROM assets
+        WHERE id = $1
+        "#,
+    )
+    .bind(asset_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let Some(record) = record else {
+        return Err((StatusCode::NOT_FOUND, "Asset not found".into()));
+    };
+    let account_id: Uuid = record
+        .try_get("account_id")
+        .map_err(crate::auth::internal_error)?;
+    authorize_account(&state.pool, user, account_id, AccountAction::Edit).await?;
+
+    let valuation_mode: String = record
+        .try_get("valuation_mode")
+        .map_err(crate::auth::internal_error)?;
+    if valuation_mode != MANUAL_VALUATION {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Valuations can only be posted for manually valued assets".into(),
+        ));
+    }
+
+    Ok(ManualAsset {
+        quantity: record
+            .try_get("quantity")
+            .map_err(crate::auth::internal_error)?,
+        currency_code: record
+            .try_get("currency_code")
+            .map_err(crate::auth::internal_error)?,
+    })
+}
@@ -3,15 +3,32 @@ use axum::{
     http::StatusCode,
     Json,
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
 use sqlx::{QueryBuilder, Row};
 use uuid::Uuid;
 
 use crate::{
     audit::record_audit_event,
     auth::AuthenticatedUser,
-    models::{Asset, CreateAssetRequest, UpdateAssetRequest, UpdateAssetResponse},
-    services::pricing::{fetch_stooq_candles, refresh_asset_prices, Candle},
+    models::{Asset, AssetTrade, CreateAssetRequest, UpdateAssetRequest, UpdateAssetResponse},
+    policy::{accessible_account_ids, authorize_account, AccountAction},
+    routes::asset_trades::trade_inputs,
+    services::{
+        backfill::{backfill_asset, configured_backfill_start, BackfillError},
+        benchmark::{
+            load_benchmark_series, period_return, weighted_return,
+            DEFAULT_BENCHMARK,
+        },
+        change_history::{record_change, snapshot, ChangeEntity},
+        cost_basis::{compute_position, CostBasisMethod, OpenLot},
+        market_data::{
+            fetch_candles, is_known_source, provider_chain, resolve_symbol, AssetClass,
+            MANUAL_SOURCE,
+        },
+        pricing::{refresh_asset_prices, Candle, SymbolFailure},
+        valuation::{MANUAL_VALUATION, VALUATION_MODES},
+    },
     state::AppState,
 };
 
@@ -30,6 +47,17 @@ pub struct AssetQueryParams {
 pub struct AssetPriceStatus {
     pub missing_count: i64,
     pub total_count: i64,
+    pub failing: Vec<AssetPriceFailure>,
+}
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct AssetPriceFailure {
+    pub asset_id: Uuid,
+    pub symbol: String,
+    pub error: Option<String>,
+    pub consecutive_failures: i32,
+    pub last_attempt_at: DateTime<Utc>,
+    pub last_success_at: Option<DateTime<Utc>>,
 }
 
 #[derive(serde::Serialize)]
@@ -52,18 +80,56 @@ pub struct AssetPerformance {
     pub start_at: Option<DateTime<Utc>>,
     pub latest_at: Option<DateTime<Utc>>,
     pub return_pct: Option<f64>,
+    pub cost_basis_method: String,
+    pub cost_basis: Option<f64>,
+    pub average_cost: Option<f64>,
+    pub market_value: Option<f64>,
+    pub realized_gain: Option<f64>,
+    pub unrealized_gain: Option<f64>,
+    pub income: f64,
+    pub open_lots: Vec<OpenLot>,
     pub benchmark_label: String,
     pub benchmark_return: Option<f64>,
+    pub relative_return: Option<f64>,
+    pub portfolio_return: Option<f64>,
+    pub portfolio_benchmark_return: Option<f64>,
+    pub portfolio_relative_return: Option<f64>,
+}
+
+#[derive(Default)]
+struct PositionGains {
+    invested: Option<f64>,
+    cost_basis: Option<f64>,
+    average_cost: Option<f64>,
+    realized_gain: Option<f64>,
+    unrealized_gain: Option<f64>,
+    return_pct: Option<f64>,
+    open_lots: Vec<OpenLot>,
 }
 
 #[derive(serde::Serialize)]
 pub struct RefreshPricesResponse {
     pub updated: usize,
+    pub failures: Vec<SymbolFailure>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct BackfillRequest {
+    pub start_date: Option<NaiveDate>,
+}
+
+#[derive(serde::Serialize)]
+pub struct BackfillResponse {
+    pub asset_id: Uuid,
+    pub start_date: NaiveDate,
+    pub inserted: usize,
 }
 
 #[derive(serde::Deserialize)]
 pub struct CandleQuery {
     pub symbol: String,
+    pub asset_type: Option<String>,
+    pub currency_code: Option<String>,
 }
 
 #[derive(serde::Serialize)]
@@ -77,36 +143,20 @@ pub async fn list_assets(
     user: AuthenticatedUser,
     Query(params): Query<AssetQueryParams>,
 ) -> Result<Json<Vec<Asset>>, (axum::http::StatusCode, String)> {
-    let limit = params.limit.unwrap_or(100).max(1).min(200);
+    let limit = params.limit.unwrap_or(100).clamp(1, 200);
     let offset = params.offset.unwrap_or(0).max(0);
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
     let mut query = QueryBuilder::new(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id =
-        "#,
-    );
-    query.push_bind(user.id);
-    query.push(
-        r#"
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id =
-        "#,
-    );
-    query.push_bind(user.id);
-    query.push(
-        r#"
-        )
-        SELECT a.id, a.account_id, a.symbol, a.asset_type, a.quantity, a.currency_code, a.created_at
+        SELECT a.id, a.account_id, a.symbol, a.asset_type, a.quantity, a.currency_code,
+               a.data_source, a.cost_basis_method, a.valuation_mode, a.created_at
         FROM assets a
         INNER JOIN accounts acc ON a.account_id = acc.id
-        WHERE a.account_id IN (SELECT id FROM accessible_accounts)
+        WHERE a.account_id = ANY(
         "#,
     );
+    query.push_bind(account_ids);
+    query.push(")");
 
     if let Some(account_id) = params.account_id {
         query.push(" AND a.account_id = ");
@@ -160,19 +210,10 @@ pub async fn list_asset_prices(
     State(state): State<AppState>,
     user: AuthenticatedUser,
 ) -> Result<Json<Vec<AssetPrice>>, (axum::http::StatusCode, String)> {
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
     let records = sqlx::query(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id = $1
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id = $1
-        ),
-        latest_prices AS (
+        WITH latest_prices AS (
             SELECT ph.asset_id,
                    ph.price,
                    ph.currency_code,
@@ -183,17 +224,19 @@ pub async fn list_asset_prices(
         SELECT a.id as asset_id,
                a.symbol,
                a.currency_code as asset_currency,
-               lp.price,
+               lp.price / COALESCE(sf.factor, 1) as price,
                lp.currency_code as price_currency,
                lp.recorded_at
         FROM assets a
         INNER JOIN accounts acc ON a.account_id = acc.id
         LEFT JOIN latest_prices lp ON lp.asset_id = a.id AND lp.rn = 1
-        WHERE a.account_id IN (SELECT id FROM accessible_accounts)
+        LEFT JOIN asset_split_factors sf ON sf.asset_id = a.id
+            AND lp.recorded_at >= sf.valid_from AND lp.recorded_at < sf.valid_until
+        WHERE a.account_id = ANY($1)
         ORDER BY a.symbol
         "#,
     )
-    .bind(user.id)
+    .bind(&account_ids)
     .fetch_all(&state.pool)
     .await
     .map_err(crate::auth::internal_error)?;
@@ -236,19 +279,10 @@ pub async fn list_asset_performance(
     State(state): State<AppState>,
     user: AuthenticatedUser,
 ) -> Result<Json<Vec<AssetPerformance>>, (axum::http::StatusCode, String)> {
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
     let records = sqlx::query(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id = $1
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id = $1
-        ),
-        price_bounds AS (
+        WITH price_bounds AS (
             SELECT asset_id,
                    FIRST_VALUE(price) OVER (PARTITION BY asset_id ORDER BY recorded_at) as start_price,
                    FIRST_VALUE(recorded_at) OVER (PARTITION BY asset_id ORDER BY recorded_at) as start_at,
@@ -269,83 +303,228 @@ pub async fn list_asset_performance(
                a.symbol,
                a.quantity,
                a.currency_code,
-               ps.start_price,
-               ps.latest_price,
+               a.cost_basis_method,
+               ps.start_price / COALESCE(start_split.factor, 1) as start_price,
+               ps.latest_price / COALESCE(latest_split.factor, 1) as latest_price,
                ps.start_at,
                ps.latest_at
         FROM assets a
         LEFT JOIN price_summary ps ON ps.asset_id = a.id
-        WHERE a.account_id IN (SELECT id FROM accessible_accounts)
+        LEFT JOIN asset_split_factors start_split ON start_split.asset_id = a.id
+            AND ps.start_at >= start_split.valid_from AND ps.start_at < start_split.valid_until
+        LEFT JOIN asset_split_factors latest_split ON latest_split.asset_id = a.id
+            AND ps.latest_at >= latest_split.valid_from AND ps.latest_at < latest_split.valid_until
+        WHERE a.account_id = ANY($1)
         ORDER BY a.symbol
         "#,
     )
-    .bind(user.id)
+    .bind(&account_ids)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let trades = sqlx::query_as::<_, AssetTrade>(
+        r#"
+        SELECT t.id, t.asset_id, t.side,
+               t.quantity * COALESCE(sf.factor, 1) as quantity,
+               t.price / COALESCE(sf.factor, 1) as price,
+               t.fees, t.currency_code, t.traded_at, t.lot_id, t.created_at
+        FROM asset_trades t
+        INNER JOIN assets a ON a.id = t.asset_id
+        LEFT JOIN asset_split_factors sf ON sf.asset_id = t.asset_id
+            AND t.traded_at >= sf.valid_from AND t.traded_at < sf.valid_until
+        WHERE a.account_id = ANY($1)
+        ORDER BY t.traded_at, t.created_at
+        "#,
+    )
+    .bind(&account_ids)
     .fetch_all(&state.pool)
     .await
     .map_err(crate::auth::internal_error)?;
+    let mut trades_by_asset: HashMap<Uuid, Vec<AssetTrade>> = HashMap::new();
+    for trade in trades {
+        trades_by_asset.entry(trade.asset_id).or_default().push(trade);
+    }
+
+    let income_by_asset: HashMap<Uuid, f64> = sqlx::query_as::<_, (Uuid, f64)>(
+        r#"
+        SELECT e.asset_id, SUM(e.amount)
+        FROM asset_income_events e
+        INNER JOIN assets a ON a.id = e.asset_id
+        WHERE a.account_id = ANY($1)
+        GROUP BY e.asset_id
+        "#,
+    )
+    .bind(&account_ids)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?
+    .into_iter()
+    .collect();
+
+    let benchmark_symbol: String = sqlx::query_scalar(
+        r#"
+        SELECT value #>> '{}'
+        FROM user_preferences
+        WHERE user_id = $1 AND key = 'benchmark_symbol'
+        "#,
+    )
+    .bind(user.id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?
+    .flatten()
+    .unwrap_or_else(|| DEFAULT_BENCHMARK.to_string());
+    let benchmark_series = load_benchmark_series(&state.pool, &benchmark_symbol)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let today = Utc::now().date_naive();
 
     let mut performances = Vec::new();
-    let mut benchmark_start_total = 0.0;
-    let mut benchmark_end_total = 0.0;
+    let mut portfolio_gain = 0.0;
+    let mut portfolio_invested = 0.0;
+    let mut benchmark_holdings = Vec::new();
 
-    for row in &records {
+    for row in records {
+        let asset_id: Uuid = row
+            .try_get("asset_id")
+            .map_err(crate::auth::internal_error)?;
         let quantity: f64 = row
             .try_get("quantity")
             .map_err(crate::auth::internal_error)?;
+        let cost_basis_method: String = row
+            .try_get("cost_basis_method")
+            .map_err(crate::auth::internal_error)?;
         let start_price: Option<f64> = row
             .try_get("start_price")
             .map_err(crate::auth::internal_error)?;
         let latest_price: Option<f64> = row
             .try_get("latest_price")
             .map_err(crate::auth::internal_error)?;
-
-        if let Some(start_price) = start_price {
-            benchmark_start_total += quantity * start_price;
-        }
-        if let Some(latest_price) = latest_price {
-            benchmark_end_total += quantity * latest_price;
-        }
-    }
-
-    let benchmark_return = if benchmark_start_total > 0.0 {
-        Some((benchmark_end_total - benchmark_start_total) / benchmark_start_total)
-    } else {
-        None
-    };
-
-    for row in records {
-        let start_price: Option<f64> = row
-            .try_get("start_price")
+        let start_at: Option<DateTime<Utc>> = row
+            .try_get("start_at")
             .map_err(crate::auth::internal_error)?;
-        let latest_price: Option<f64> = row
-            .try_get("latest_price")
+        let latest_at: Option<DateTime<Utc>> = row
+            .try_get("latest_at")
             .map_err(crate::auth::internal_error)?;
-        let return_pct = match (start_price, latest_price) {
-            (Some(start), Some(latest)) if start > 0.0 => Some((latest - start) / start),
-            _ => None,
-        };
+        let market_value = latest_price.map(|price| quantity * price);
+        let income = income_by_asset.get(&asset_id).copied().unwrap_or(0.0);
+        let held_since = trades_by_asset
+            .get(&asset_id)
+            .and_then(|trades| trades.first())
+            .map(|trade| trade.traded_at)
+            .or(start_at);
+
+        let gains = match trades_by_asset.get(&asset_id) {
+            Some(trades) => {
+                let method =
+                    CostBasisMethod::parse(&cost_basis_method).unwrap_or(CostBasisMethod::Fifo);
+                compute_position(&trade_inputs(trades), method)
+                    .ok()
+                    .map(|position| {
+                        let invested: f64 = trades
+                            .iter()
+                            .filter(|trade| trade.side == "buy")
+                            .map(|trade| trade.quantity * trade.price + trade.fees)
+                            .sum();
+                        let unrealized_gain =
+                            latest_price.map(|price| position.unrealized_gain(price));
+                        let return_pct = match unrealized_gain {
+                            Some(unrealized) if invested > 0.0 => {
+                                Some((position.realized_gain + unrealized + income) / invested)
+                            }
+                            _ => None,
+                        };
+                        PositionGains {
+                            invested: Some(invested),
+                            cost_basis: Some(position.cost_basis),
+                            average_cost: position.average_cost(),
+                            realized_gain: Some(position.realized_gain),
+                            unrealized_gain,
+                            return_pct,
+                            open_lots: position.open_lots,
+                        }
+                    })
+            }
+            // Without trades, treat the first recorded price as the purchase price.
+            None => start_price.map(|start| PositionGains {
+                invested: Some(quantity * start),
+                cost_basis: Some(quantity * start),
+                average_cost: Some(start),
+                realized_gain: Some(0.0),
+                unrealized_gain: latest_price.map(|latest| quantity * (latest - start)),
+                return_pct: latest_price.filter(|_| start > 0.0).map(|latest| {
+                    let income_return = if quantity > 0.0 {
+                        income / (quantity * start)
+                    } else {
+                        0.0
+                    };
+                    (latest - start) / start + income_return
+                }),
+                open_lots: Vec::new(),
+            }),
+        }
+        .unwrap_or_default();
+
+        let benchmark_return = held_since.and_then(|held_since| {
+            period_return(
+                &benchmark_series,
+                held_since.date_naive(),
+                latest_at.map_or(today, |latest_at| latest_at.date_naive()),
+            )
+        });
+        let relative_return = gains
+            .return_pct
+            .zip(benchmark_return)
+            .map(|(holding, benchmark)| holding - benchmark);
+        if let (Some(invested), Some(holding), Some(benchmark)) =
+            (gains.invested, gains.return_pct, benchmark_return)
+        {
+            portfolio_gain += invested * holding;
+            portfolio_invested += invested;
+            benchmark_holdings.push((invested, benchmark));
+        }
 
         performances.push(AssetPerformance {
-            asset_id: row
-                .try_get("asset_id")
-                .map_err(crate::auth::internal_error)?,
+            asset_id,
             symbol: row.try_get("symbol").map_err(crate::auth::internal_error)?,
-            quantity: row.try_get("quantity").map_err(crate::auth::internal_error)?,
+            quantity,
             currency_code: row
                 .try_get("currency_code")
                 .map_err(crate::auth::internal_error)?,
             start_price,
             latest_price,
-            start_at: row.try_get("start_at").map_err(crate::auth::internal_error)?,
-            latest_at: row
-                .try_get("latest_at")
-                .map_err(crate::auth::internal_error)?,
-            return_pct,
-            benchmark_label: "Firecash Composite".to_string(),
+            start_at,
+            latest_at,
+            return_pct: gains.return_pct,
+            cost_basis_method,
+            cost_basis: gains.cost_basis,
+            average_cost: gains.average_cost,
+            market_value,
+            realized_gain: gains.realized_gain,
+            unrealized_gain: gains.unrealized_gain,
+            income,
+            open_lots: gains.open_lots,
+            benchmark_label: benchmark_symbol.clone(),
             benchmark_return,
+            relative_return,
+            portfolio_return: None,
+            portfolio_benchmark_return: None,
+            portfolio_relative_return: None,
         });
     }
 
+    let portfolio_return = (portfolio_invested > 0.0).then(|| portfolio_gain / portfolio_invested);
+    let portfolio_benchmark_return = weighted_return(&benchmark_holdings);
+    let portfolio_relative_return = portfolio_return
+        .zip(portfolio_benchmark_return)
+        .map(|(portfolio, benchmark)| portfolio - benchmark);
+    for performance in &mut performances {
+        performance.portfolio_return = portfolio_return;
+        performance.portfolio_benchmark_return = portfolio_benchmark_return;
+        performance.portfolio_relative_return = portfolio_relative_return;
+    }
+
     Ok(Json(performances))
 }
 
@@ -353,33 +532,25 @@ pub async fn asset_price_status(
     State(state): State<AppState>,
     user: AuthenticatedUser,
 ) -> Result<Json<AssetPriceStatus>, (axum::http::StatusCode, String)> {
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
     let record = sqlx::query(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id = $1
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id = $1
-        ),
-        latest_prices AS (
+        WITH latest_prices AS (
             SELECT ph.asset_id,
                    ph.price,
                    ROW_NUMBER() OVER (PARTITION BY ph.asset_id ORDER BY ph.recorded_at DESC) as rn
             FROM price_history ph
         )
-        SELECT COUNT(*) FILTER (WHERE lp.price IS NULL) as missing_count,
+        SELECT COUNT(*) FILTER (WHERE lp.price IS NULL AND a.valuation_mode = 'market')
+                   as missing_count,
                COUNT(*) as total_count
         FROM assets a
         INNER JOIN accounts acc ON a.account_id = acc.id
         LEFT JOIN latest_prices lp ON lp.asset_id = a.id AND lp.rn = 1
-        WHERE a.account_id IN (SELECT id FROM accessible_accounts)
+        WHERE a.account_id = ANY($1)
         "#,
     )
-    .bind(user.id)
+    .bind(&account_ids)
     .fetch_one(&state.pool)
     .await
     .map_err(crate::auth::internal_error)?;
@@ -391,18 +562,48 @@ pub async fn asset_price_status(
         .try_get("total_count")
         .map_err(crate::auth::internal_error)?;
 
+    let failing = list_price_failures(&state.pool, &user).await?;
+
     Ok(Json(AssetPriceStatus {
         missing_count,
         total_count,
+        failing,
     }))
 }
 
+pub(crate) async fn list_price_failures(
+    pool: &sqlx::PgPool,
+    user: &AuthenticatedUser,
+) -> Result<Vec<AssetPriceFailure>, (axum::http::StatusCode, String)> {
+    let account_ids = accessible_account_ids(pool, user).await?;
+    sqlx::query_as::<_, AssetPriceFailure>(
+        r#"
+        SELECT s.asset_id,
+               s.symbol,
+               s.last_error as error,
+               s.consecutive_failures,
+               s.last_attempt_at,
+               s.last_success_at
+        FROM asset_price_fetch_status s
+        INNER JOIN assets a ON a.id = s.asset_id
+        WHERE s.consecutive_failures > 0
+          AND a.valuation_mode = 'market'
+          AND a.account_id = ANY($1)
+        ORDER BY s.consecutive_failures DESC, s.symbol
+        "#,
+    )
+    .bind(&account_ids)
+    .fetch_all(pool)
+    .await
+    .map_err(crate::auth::internal_error)
+}
+
 pub async fn refresh_prices(
     State(state): State<AppState>,
     user: AuthenticatedUser,
 ) -> Result<Json<RefreshPricesResponse>, (axum::http::StatusCode, String)> {
-    let updated = match refresh_asset_prices(&state.pool, Some(user.id)).await {
-        Ok(updated) => updated,
+    let summary = match refresh_asset_prices(&state.pool, Some(user.id)).await {
+        Ok(summary) => summary,
         Err(err) => {
             let _ = record_audit_event(
                 &state.pool,
@@ -415,19 +616,97 @@ pub async fn refresh_prices(
         }
     };
 
-    Ok(Json(RefreshPricesResponse { updated }))
+    Ok(Json(RefreshPricesResponse {
+        updated: summary.updated,
+        failures: summary.failures,
+    }))
+}
+
+pub async fn backfill_asset_history(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(asset_id): Path<Uuid>,
+    payload: Option<Json<BackfillRequest>>,
+) -> Result<Json<BackfillResponse>, (axum::http::StatusCode, String)> {
+    let asset = sqlx::query_as::<_, (Uuid, String)>(
+        r#"
+        SELECT account_id, valuation_mode
+        FROM assets
+        WHERE id = $1
+        "#,
+    )
+    .bind(asset_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let Some((account_id, valuation_mode)) = asset else {
+        return Err((StatusCode::NOT_FOUND, "Asset not found".into()));
+    };
+
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
+    if valuation_mode == MANUAL_VALUATION {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Manually valued assets have no market history".into(),
+        ));
+    }
+
+    let start_date = payload
+        .and_then(|Json(payload)| payload.start_date)
+        .unwrap_or_else(configured_backfill_start);
+    let inserted = backfill_asset(&state.pool, asset_id, start_date)
+        .await
+        .map_err(|err| match err {
+            BackfillError::Provider(message) => (StatusCode::BAD_GATEWAY, message),
+            BackfillError::Database(err) => crate::auth::internal_error(err),
+        })?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "asset.backfill",
+        serde_json::json!({
+            "asset_id": asset_id,
+            "start_date": start_date,
+            "inserted": inserted,
+        }),
+    )
+    .await;
+
+    Ok(Json(BackfillResponse {
+        asset_id,
+        start_date,
+        inserted,
+    }))
 }
 
 pub async fn list_candles(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
     Query(query): Query<CandleQuery>,
 ) -> Result<Json<CandleResponse>, (axum::http::StatusCode, String)> {
-    let symbol = query.symbol.trim().to_uppercase();
-    if symbol.is_empty() {
+    let class = AssetClass::from_asset_type(query.asset_type.as_deref().unwrap_or_default());
+    let quote = query.currency_code.as_deref().unwrap_or("USD");
+    let Some(symbol) = resolve_symbol(class, &query.symbol, quote) else {
         return Err((StatusCode::BAD_REQUEST, "symbol is required".into()));
-    }
-    let candles = fetch_stooq_candles(&symbol)
+    };
+    let data_source: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT value #>> '{}'
+        FROM user_preferences
+        WHERE user_id = $1 AND key = 'asset_data_source'
+        "#,
+    )
+    .bind(user.id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?
+    .flatten()
+    .filter(|source| source != MANUAL_SOURCE);
+    let candles = fetch_candles(&provider_chain(data_source.as_deref(), class), &symbol)
         .await
-        .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+        .map_err(|err| (StatusCode::BAD_REQUEST, err))?;
     Ok(Json(CandleResponse { symbol, candles }))
 }
 
@@ -437,16 +716,33 @@ pub async fn create_asset(
     Json(payload): Json<CreateAssetRequest>,
 ) -> Result<Json<Asset>, (axum::http::StatusCode, String)> {
     let symbol = payload.symbol.trim().to_uppercase();
-    ensure_account_edit_access(&state, user.id, payload.account_id).await?;
+    let data_source = normalize_data_source(payload.data_source.as_deref())?;
+    let cost_basis_method = payload
+        .cost_basis_method
+        .map(|value| value.trim().to_lowercase())
+        .unwrap_or_else(|| "fifo".to_string());
+    if CostBasisMethod::parse(&cost_basis_method).is_none() {
+        return Err((StatusCode::BAD_REQUEST, "Invalid cost basis method".into()));
+    }
+    let valuation_mode = normalize_valuation_mode(payload.valuation_mode.as_deref())?
+        .unwrap_or_else(|| "market".to_string());
+    authorize_account(&state.pool, &user, payload.account_id, AccountAction::Edit).await?;
 
     let id = Uuid::new_v4();
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
     let record = sqlx::query_as::<_, Asset>(
         r#"
         INSERT INTO assets (
-            id, account_id, symbol, asset_type, quantity, currency_code
+            id, account_id, symbol, asset_type, quantity, currency_code, data_source,
+            cost_basis_method, valuation_mode
         )
-        VALUES ($1, $2, $3, $4, $5, $6)
-        RETURNING id, account_id, symbol, asset_type, quantity, currency_code, created_at
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id, account_id, symbol, asset_type, quantity, currency_code, data_source,
+                  cost_basis_method, valuation_mode, created_at
         "#,
     )
     .bind(id)
@@ -455,10 +751,18 @@ pub async fn create_asset(
     .bind(payload.asset_type)
     .bind(payload.quantity)
     .bind(payload.currency_code)
-    .fetch_one(&state.pool)
+    .bind(data_source)
+    .bind(cost_basis_method)
+    .bind(valuation_mode)
+    .fetch_one(&mut *tx)
     .await
     .map_err(crate::auth::internal_error)?;
 
+    record_change(&mut tx, user.id, ChangeEntity::Asset, id, None)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
     Ok(Json(record))
 }
 
@@ -484,16 +788,54 @@ pub async fn update_asset(
         return Err((StatusCode::NOT_FOUND, "Asset not found".into()));
     };
 
-    ensure_account_edit_access(&state, user.id, account_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
 
     if let Some(account_id) = payload.account_id {
-        ensure_account_edit_access(&state, user.id, account_id).await?;
+        authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
     }
 
     let normalized_symbol = payload
         .symbol
         .as_ref()
         .map(|symbol| symbol.trim().to_uppercase());
+    let data_source = match payload.data_source.as_deref() {
+        Some(value) => Some(normalize_data_source(Some(value))?.unwrap_or_default()),
+        None => None,
+    };
+    let cost_basis_method = match payload.cost_basis_method.as_deref() {
+        Some(value) => {
+            let value = value.trim().to_lowercase();
+            if CostBasisMethod::parse(&value).is_none() {
+                return Err((StatusCode::BAD_REQUEST, "Invalid cost basis method".into()));
+            }
+            Some(value)
+        }
+        None => None,
+    };
+    let valuation_mode = normalize_valuation_mode(payload.valuation_mode.as_deref())?;
+    if payload.quantity.is_some() {
+        let has_trades: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM asset_trades WHERE asset_id = $1)",
+        )
+        .bind(asset_id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+        if has_trades {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Quantity is derived from trades for this asset".into(),
+            ));
+        }
+    }
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let before = snapshot(&mut tx, ChangeEntity::Asset, asset_id)
+        .await
+        .map_err(crate::auth::internal_error)?;
     let record = sqlx::query_as::<_, UpdateAssetResponse>(
         r#"
         UPDATE assets
@@ -501,9 +843,13 @@ pub async fn update_asset(
             symbol = COALESCE($2, symbol),
             asset_type = COALESCE($3, asset_type),
             quantity = COALESCE($4, quantity),
-            currency_code = COALESCE($5, currency_code)
+            currency_code = COALESCE($5, currency_code),
+            data_source = CASE WHEN $7::TEXT IS NULL THEN data_source ELSE NULLIF($7, '') END,
+            cost_basis_method = COALESCE($8, cost_basis_method),
+            valuation_mode = COALESCE($9, valuation_mode)
         WHERE id = $6
-        RETURNING id, account_id, symbol, asset_type, quantity, currency_code, created_at
+        RETURNING id, account_id, symbol, asset_type, quantity, currency_code, data_source,
+                  cost_basis_method, valuation_mode, created_at
         "#,
     )
     .bind(payload.account_id)
@@ -512,10 +858,18 @@ pub async fn update_asset(
     .bind(payload.quantity)
     .bind(payload.currency_code)
     .bind(asset_id)
-    .fetch_one(&state.pool)
+    .bind(data_source)
+    .bind(cost_basis_method)
+    .bind(valuation_mode)
+    .fetch_one(&mut *tx)
     .await
     .map_err(crate::auth::internal_error)?;
 
+    record_change(&mut tx, user.id, ChangeEntity::Asset, asset_id, before)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
     Ok(Json(record))
 }
 
@@ -540,8 +894,16 @@ pub async fn delete_asset(
         return Err((StatusCode::NOT_FOUND, "Asset not found".into()));
     };
 
-    ensure_account_edit_access(&state, user.id, account_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
 
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let before = snapshot(&mut tx, ChangeEntity::Asset, asset_id)
+        .await
+        .map_err(crate::auth::internal_error)?;
     let result = sqlx::query(
         r#"
         DELETE FROM assets
@@ -550,7 +912,7 @@ pub async fn delete_asset(
     )
     .bind(asset_id)
     .bind(account_id)
-    .execute(&state.pool)
+    .execute(&mut *tx)
     .await
     .map_err(crate::auth::internal_error)?;
 
@@ -558,45 +920,40 @@ pub async fn delete_asset(
         return Err((StatusCode::NOT_FOUND, "Asset not found".into()));
     }
 
+    record_change(&mut tx, user.id, ChangeEntity::Asset, asset_id, before)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
     Ok(StatusCode::NO_CONTENT)
 }
 
-async fn ensure_account_edit_access(
-    state: &AppState,
-    user_id: Uuid,
-    account_id: Uuid,
-) -> Result<(), (StatusCode, String)> {
-    let record = sqlx::query(
-        r#"
-        SELECT a.user_id,
-               MAX(CASE WHEN agu.role IN ('edit', 'admin') THEN 1 ELSE 0 END) as can_edit
-        FROM accounts a
-        LEFT JOIN account_group_members agm ON a.id = agm.account_id
-        LEFT JOIN account_group_users agu ON agm.group_id = agu.group_id AND agu.user_id = $1
-        WHERE a.id = $2
-        GROUP BY a.user_id
-        "#,
-    )
-    .bind(user_id)
-    .bind(account_id)
-    .fetch_optional(&state.pool)
-    .await
-    .map_err(crate::auth::internal_error)?;
-
-    let Some(record) = record else {
-        return Err((StatusCode::NOT_FOUND, "Account not found".into()));
+fn normalize_data_source(
+    value: Option<&str>,
+) -> Result<Option<String>, (axum::http::StatusCode, String)> {
+    let Some(value) = value.map(|value| value.trim().to_lowercase()) else {
+        return Ok(None);
     };
-
-    let owner_id: Uuid = record
-        .try_get("user_id")
-        .map_err(crate::auth::internal_error)?;
-    let can_edit: i64 = record
-        .try_get("can_edit")
-        .map_err(crate::auth::internal_error)?;
-
-    if owner_id != user_id && can_edit == 0 {
-        return Err((StatusCode::FORBIDDEN, "Forbidden".into()));
+    if value.is_empty() {
+        return Ok(None);
+    }
+    if !is_known_source(&value) {
+        return Err((StatusCode::BAD_REQUEST, "Invalid data source".into()));
     }
+    Ok(Some(value))
+}
 
-    Ok(())
+fn normalize_valuation_mode(
+    value: Option<&str>,
+) -> Result<Option<String>, (axum::http::StatusCode, String)> {
+    let Some(value) = value.map(|value| value.trim().to_lowercase()) else {
+        return Ok(None);
+    };
+    if !VALUATION_MODES.contains(&value.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "valuation_mode must be market or manual".into(),
+        ));
+    }
+    Ok(Some(value))
 }
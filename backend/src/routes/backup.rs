@@ -6,13 +6,20 @@ use axum::{
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::Row;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use sqlx::{PgConnection, Row};
 use uuid::Uuid;
 
 use crate::{
     audit::record_audit_event,
     auth::AuthenticatedUser,
-    models::{Account, AccountGroup, Asset, RecurringTransaction, Transaction},
+    models::{
+        Account, AccountGroup, Asset, AssetIncomeEvent, AssetSplit, AssetTrade, AssetValuation,
+        AssetValuationSchedule, BalanceAssertion, Liability, LiabilityPayment, RecurringTransaction, Transaction,
+    },
+    policy::ensure_backup_access,
+    services::change_history::{record_changes, snapshots, ChangeEntity},
     state::AppState,
 };
 
@@ -41,6 +48,22 @@ pub struct BackupPayload {
     pub transactions: Vec<Transaction>,
     pub recurring_transactions: Vec<RecurringTransaction>,
     pub assets: Vec<Asset>,
+    #[serde(default)]
+    pub asset_trades: Vec<AssetTrade>,
+    #[serde(default)]
+    pub asset_income_events: Vec<AssetIncomeEvent>,
+    #[serde(default)]
+    pub asset_splits: Vec<AssetSplit>,
+    #[serde(default)]
+    pub asset_valuations: Vec<AssetValuation>,
+    #[serde(default)]
+    pub asset_valuation_schedules: Vec<AssetValuationSchedule>,
+    #[serde(default)]
+    pub liabilities: Vec<Liability>,
+    #[serde(default)]
+    pub liability_payments: Vec<LiabilityPayment>,
+    #[serde(default)]
+    pub balance_assertions: Vec<BalanceAssertion>,
     pub preferences: Vec<BackupPreference>,
 }
 
@@ -67,6 +90,7 @@ pub async fn export_backup(
     user: AuthenticatedUser,
     Query(params): Query<ExportQuery>,
 ) -> Result<Response, (StatusCode, String)> {
+    ensure_backup_access(&user)?;
     let format = params
         .format
         .unwrap_or_else(|| "json".to_string())
@@ -146,7 +170,8 @@ pub async fn export_backup(
 
     let accounts = sqlx::query_as::<_, Account>(
         r#"
-        SELECT id, name, currency_code, created_at
+        SELECT id, name, currency_code, account_type, credit_limit, statement_closing_day,
+               payment_due_day, created_at
         FROM accounts
         WHERE user_id = $1
         ORDER BY created_at DESC
@@ -222,7 +247,8 @@ pub async fn export_backup(
 
     let assets = sqlx::query_as::<_, Asset>(
         r#"
-        SELECT a.id, a.account_id, a.symbol, a.asset_type, a.quantity, a.currency_code, a.created_at
+        SELECT a.id, a.account_id, a.symbol, a.asset_type, a.quantity, a.currency_code,
+               a.data_source, a.cost_basis_method, a.valuation_mode, a.created_at
         FROM assets a
         INNER JOIN accounts acc ON a.account_id = acc.id
         WHERE acc.user_id = $1
@@ -234,6 +260,135 @@ pub async fn export_backup(
     .await
     .map_err(crate::auth::internal_error)?;
 
+    let asset_trades = sqlx::query_as::<_, AssetTrade>(
+        r#"
+        SELECT t.id, t.asset_id, t.side, t.quantity, t.price, t.fees, t.currency_code,
+               t.traded_at, t.lot_id, t.created_at
+        FROM asset_trades t
+        INNER JOIN assets a ON t.asset_id = a.id
+        INNER JOIN accounts acc ON a.account_id = acc.id
+        WHERE acc.user_id = $1
+        ORDER BY t.traded_at, t.created_at
+        "#,
+    )
+    .bind(user.id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let asset_income_events = sqlx::query_as::<_, AssetIncomeEvent>(
+        r#"
+        SELECT e.id, e.asset_id, e.kind, e.amount, e.currency_code, e.paid_at, e.description,
+               e.transaction_id, e.created_at
+        FROM asset_income_events e
+        INNER JOIN assets a ON e.asset_id = a.id
+        INNER JOIN accounts acc ON a.account_id = acc.id
+        WHERE acc.user_id = $1
+        ORDER BY e.paid_at, e.created_at
+        "#,
+    )
+    .bind(user.id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let asset_splits = sqlx::query_as::<_, AssetSplit>(
+        r#"
+        SELECT s.id, s.asset_id, s.numerator, s.denominator,
+               CASE WHEN s.numerator > s.denominator THEN 'split' ELSE 'reverse_split' END as kind,
+               s.effective_at, s.created_at
+        FROM asset_splits s
+        INNER JOIN assets a ON s.asset_id = a.id
+        INNER JOIN accounts acc ON a.account_id = acc.id
+        WHERE acc.user_id = $1
+        ORDER BY s.effective_at
+        "#,
+    )
+    .bind(user.id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let asset_valuations = sqlx::query_as::<_, AssetValuation>(
+        r#"
+        SELECT v.id, v.asset_id, v.price, v.currency_code, v.valued_at, v.note, v.created_at
+        FROM asset_valuations v
+        INNER JOIN assets a ON v.asset_id = a.id
+        INNER JOIN accounts acc ON a.account_id = acc.id
+        WHERE acc.user_id = $1
+        ORDER BY v.valued_at
+        "#,
+    )
+    .bind(user.id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let asset_valuation_schedules = sqlx::query_as::<_, AssetValuationSchedule>(
+        r#"
+        SELECT s.asset_id, s.method, s.annual_rate, s.updated_at
+        FROM asset_valuation_schedules s
+        INNER JOIN assets a ON s.asset_id = a.id
+        INNER JOIN accounts acc ON a.account_id = acc.id
+        WHERE acc.user_id = $1
+        "#,
+    )
+    .bind(user.id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let liabilities = sqlx::query_as::<_, Liability>(
+        r#"
+        SELECT l.id, l.account_id, l.name, l.principal, l.annual_rate, l.term_months,
+               l.payment_frequency, l.currency_code, l.start_date,
+               l.principal - COALESCE((
+                   SELECT SUM(p.principal_amount)
+                   FROM liability_payments p
+                   WHERE p.liability_id = l.id
+               ), 0.0) as outstanding_balance,
+               l.created_at
+        FROM liabilities l
+        INNER JOIN accounts acc ON l.account_id = acc.id
+        WHERE acc.user_id = $1
+        ORDER BY l.created_at
+        "#,
+    )
+    .bind(user.id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let liability_payments = sqlx::query_as::<_, LiabilityPayment>(
+        r#"
+        SELECT p.id, p.liability_id, p.period, p.principal_amount, p.interest_amount, p.paid_at,
+               p.principal_transaction_id, p.interest_transaction_id, p.created_at
+        FROM liability_payments p
+        INNER JOIN liabilities l ON p.liability_id = l.id
+        INNER JOIN accounts acc ON l.account_id = acc.id
+        WHERE acc.user_id = $1
+        ORDER BY p.liability_id, p.period
+        "#,
+    )
+    .bind(user.id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let balance_assertions = sqlx::query_as::<_, BalanceAssertion>(
+        r#"
+        SELECT b.id, b.account_id, b.asserted_on, b.balance, b.currency_code, b.note, b.created_at
+        FROM balance_assertions b
+        INNER JOIN accounts acc ON b.account_id = acc.id
+        WHERE acc.user_id = $1
+        ORDER BY b.account_id, b.asserted_on
+        "#,
+    )
+    .bind(user.id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
     let prefs_rows = sqlx::query(
         r#"
         SELECT key, value
@@ -272,6 +427,14 @@ pub async fn export_backup(
         transactions,
         recurring_transactions,
         assets,
+        asset_trades,
+        asset_income_events,
+        asset_splits,
+        asset_valuations,
+        asset_valuation_schedules,
+        liabilities,
+        liability_payments,
+        balance_assertions,
         preferences,
     };
 
@@ -291,6 +454,7 @@ pub async fn restore_backup(
     user: AuthenticatedUser,
     Json(payload): Json<RestoreRequest>,
 ) -> Result<StatusCode, (StatusCode, String)> {
+    ensure_backup_access(&user)?;
     if !payload.confirm {
         return Err((StatusCode::BAD_REQUEST, "Restore confirmation required".into()));
     }
@@ -299,12 +463,19 @@ pub async fn restore_backup(
     if metadata.schema_version != SCHEMA_VERSION {
         return Err((StatusCode::BAD_REQUEST, "Unsupported schema version".into()));
     }
+    if references_outside_backup(&payload.payload) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Backup references records it does not contain".into(),
+        ));
+    }
 
     let mut tx = state
         .pool
         .begin()
         .await
         .map_err(crate::auth::internal_error)?;
+    let replaced = replaced_records(&mut tx, user.id).await?;
 
     let group_ids: Vec<Uuid> = sqlx::query_scalar(
         r#"
@@ -417,14 +588,21 @@ pub async fn restore_backup(
     for account in &payload.payload.accounts {
         sqlx::query(
             r#"
-            INSERT INTO accounts (id, user_id, name, currency_code, created_at)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO accounts (
+                id, user_id, name, currency_code, account_type, credit_limit,
+                statement_closing_day, payment_due_day, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             "#,
         )
         .bind(account.id)
         .bind(user.id)
         .bind(&account.name)
         .bind(&account.currency_code)
+        .bind(&account.account_type)
+        .bind(account.credit_limit)
+        .bind(account.statement_closing_day)
+        .bind(account.payment_due_day)
         .bind(account.created_at)
         .execute(&mut *tx)
         .await
@@ -526,9 +704,10 @@ pub async fn restore_backup(
         sqlx::query(
             r#"
             INSERT INTO assets (
-                id, account_id, symbol, asset_type, quantity, currency_code, created_at
+                id, account_id, symbol, asset_type, quantity, currency_code, data_source,
+                cost_basis_method, valuation_mode, created_at
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
             "#,
         )
         .bind(asset.id)
@@ -537,12 +716,209 @@ pub async fn restore_backup(
         .bind(&asset.asset_type)
         .bind(asset.quantity)
         .bind(&asset.currency_code)
+        .bind(&asset.data_source)
+        .bind(&asset.cost_basis_method)
+        .bind(&asset.valuation_mode)
         .bind(asset.created_at)
         .execute(&mut *tx)
         .await
         .map_err(crate::auth::internal_error)?;
     }
 
+    // Buys are inserted before sells so specific-lot references resolve.
+    let mut asset_trades: Vec<&AssetTrade> = payload.payload.asset_trades.iter().collect();
+    asset_trades.sort_by_key(|trade| (trade.side != "buy", trade.traded_at));
+    for trade in asset_trades {
+        sqlx::query(
+            r#"
+            INSERT INTO asset_trades (
+                id, asset_id, side, quantity, price, fees, currency_code, traded_at, lot_id,
+                created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(trade.id)
+        .bind(trade.asset_id)
+        .bind(&trade.side)
+        .bind(trade.quantity)
+        .bind(trade.price)
+        .bind(trade.fees)
+        .bind(&trade.currency_code)
+        .bind(trade.traded_at)
+        .bind(trade.lot_id)
+        .bind(trade.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    }
+
+    for event in &payload.payload.asset_income_events {
+        sqlx::query(
+            r#"
+            INSERT INTO asset_income_events (
+                id, asset_id, kind, amount, currency_code, paid_at, description, transaction_id,
+                created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(event.id)
+        .bind(event.asset_id)
+        .bind(&event.kind)
+        .bind(event.amount)
+        .bind(&event.currency_code)
+        .bind(event.paid_at)
+        .bind(&event.description)
+        .bind(event.transaction_id)
+        .bind(event.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    }
+
+    for split in &payload.payload.asset_splits {
+        sqlx::query(
+            r#"
+            INSERT INTO asset_splits (id, asset_id, numerator, denominator, effective_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(split.id)
+        .bind(split.asset_id)
+        .bind(split.numerator)
+        .bind(split.denominator)
+        .bind(split.effective_at)
+        .bind(split.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    }
+
+    // Price history is not exported, so posted valuations re-create their prices.
+    for valuation in &payload.payload.asset_valuations {
+        let price_history_id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO price_history (id, asset_id, price, currency_code, recorded_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(price_history_id)
+        .bind(valuation.asset_id)
+        .bind(valuation.price)
+        .bind(&valuation.currency_code)
+        .bind(valuation.valued_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+        sqlx::query(
+            r#"
+            INSERT INTO asset_valuations (
+                id, asset_id, price, currency_code, valued_at, note, price_history_id, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(valuation.id)
+        .bind(valuation.asset_id)
+        .bind(valuation.price)
+        .bind(&valuation.currency_code)
+        .bind(valuation.valued_at)
+        .bind(&valuation.note)
+        .bind(price_history_id)
+        .bind(valuation.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    }
+
+    for schedule in &payload.payload.asset_valuation_schedules {
+        sqlx::query(
+            r#"
+            INSERT INTO asset_valuation_schedules (asset_id, method, annual_rate, updated_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(schedule.asset_id)
+        .bind(&schedule.method)
+        .bind(schedule.annual_rate)
+        .bind(schedule.updated_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    }
+
+    for liability in &payload.payload.liabilities {
+        sqlx::query(
+            r#"
+            INSERT INTO liabilities (
+                id, account_id, name, principal, annual_rate, term_months, payment_frequency,
+                currency_code, start_date, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(liability.id)
+        .bind(liability.account_id)
+        .bind(&liability.name)
+        .bind(liability.principal)
+        .bind(liability.annual_rate)
+        .bind(liability.term_months)
+        .bind(&liability.payment_frequency)
+        .bind(&liability.currency_code)
+        .bind(liability.start_date)
+        .bind(liability.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    }
+
+    for payment in &payload.payload.liability_payments {
+        sqlx::query(
+            r#"
+            INSERT INTO liability_payments (
+                id, liability_id, period, principal_amount, interest_amount, paid_at,
+                principal_transaction_id, interest_transaction_id, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(payment.id)
+        .bind(payment.liability_id)
+        .bind(payment.period)
+        .bind(payment.principal_amount)
+        .bind(payment.interest_amount)
+        .bind(payment.paid_at)
+        .bind(payment.principal_transaction_id)
+        .bind(payment.interest_transaction_id)
+        .bind(payment.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    }
+
+    for assertion in &payload.payload.balance_assertions {
+        sqlx::query(
+            r#"
+            INSERT INTO balance_assertions (
+                id, account_id, asserted_on, balance, currency_code, note, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(assertion.id)
+        .bind(assertion.account_id)
+        .bind(assertion.asserted_on)
+        .bind(assertion.balance)
+        .bind(&assertion.currency_code)
+        .bind(&assertion.note)
+        .bind(assertion.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    }
+
     for preference in &payload.payload.preferences {
         sqlx::query(
             r#"
@@ -560,6 +936,13 @@ pub async fn restore_backup(
         .map_err(crate::auth::internal_error)?;
     }
 
+    let restored = [
+        payload.payload.accounts.iter().map(|account| account.id).collect::<Vec<_>>(),
+        payload.payload.transactions.iter().map(|transaction| transaction.id).collect(),
+        payload.payload.assets.iter().map(|asset| asset.id).collect(),
+    ];
+    record_replaced_records(&mut tx, user.id, replaced, restored).await?;
+
     tx.commit().await.map_err(crate::auth::internal_error)?;
 
     let _ = record_audit_event(
@@ -572,3 +955,97 @@ pub async fn restore_backup(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+// Restored rows are only checked by foreign keys, which would happily attach them to
+// another user's accounts, groups or assets.
+fn references_outside_backup(payload: &BackupPayload) -> bool {
+    let accounts: HashSet<Uuid> = payload.accounts.iter().map(|account| account.id).collect();
+    let groups: HashSet<Uuid> = payload.account_groups.iter().map(|group| group.id).collect();
+    let assets: HashSet<Uuid> = payload.assets.iter().map(|asset| asset.id).collect();
+    let liabilities: HashSet<Uuid> =
+        payload.liabilities.iter().map(|liability| liability.id).collect();
+
+    let account_ids = payload
+        .account_group_members
+        .iter()
+        .map(|membership| membership.account_id)
+        .chain(payload.transactions.iter().map(|transaction| transaction.account_id))
+        .chain(payload.recurring_transactions.iter().map(|recurring| recurring.account_id))
+        .chain(payload.assets.iter().map(|asset| asset.account_id))
+        .chain(payload.liabilities.iter().map(|liability| liability.account_id))
+        .chain(payload.balance_assertions.iter().map(|assertion| assertion.account_id));
+    let asset_ids = payload
+        .asset_trades
+        .iter()
+        .map(|trade| trade.asset_id)
+        .chain(payload.asset_income_events.iter().map(|event| event.asset_id))
+        .chain(payload.asset_splits.iter().map(|split| split.asset_id))
+        .chain(payload.asset_valuations.iter().map(|valuation| valuation.asset_id))
+        .chain(payload.asset_valuation_schedules.iter().map(|schedule| schedule.asset_id));
+
+    payload
+        .account_group_members
+        .iter()
+        .any(|membership| !groups.contains(&membership.group_id))
+        || account_ids.into_iter().any(|id| !accounts.contains(&id))
+        || asset_ids.into_iter().any(|id| !assets.contains(&id))
+        || payload
+            .liability_payments
+            .iter()
+            .any(|payment| !liabilities.contains(&payment.liability_id))
+}
+
+pub(crate) async fn replaced_records(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+) -> Result<Vec<(ChangeEntity, HashMap<Uuid, Value>)>, (StatusCode, String)> {
+    let account_ids: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM accounts WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let transaction_ids: Vec<Uuid> =
+        sqlx::query_scalar("SELECT id FROM transactions WHERE account_id = ANY($1)")
+            .bind(&account_ids)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(crate::auth::internal_error)?;
+    let asset_ids: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM assets WHERE account_id = ANY($1)")
+        .bind(&account_ids)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(crate::auth::internal_error)?;
+
+    let mut replaced = Vec::new();
+    for (entity, ids) in [
+        (ChangeEntity::Account, account_ids),
+        (ChangeEntity::Transaction, transaction_ids),
+        (ChangeEntity::Asset, asset_ids),
+    ] {
+        let before = snapshots(&mut *conn, entity, &ids)
+            .await
+            .map_err(crate::auth::internal_error)?;
+        replaced.push((entity, before));
+    }
+    Ok(replaced)
+}
+
+pub(crate) async fn record_replaced_records(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    replaced: Vec<(ChangeEntity, HashMap<Uuid, Value>)>,
+    written: [Vec<Uuid>; 3],
+) -> Result<(), (StatusCode, String)> {
+    for ((entity, mut before), written) in replaced.into_iter().zip(written) {
+        // Records that come back unchanged leave no history.
+        let mut changes: Vec<(Uuid, Option<Value>)> = written
+            .into_iter()
+            .map(|id| (id, before.remove(&id)))
+            .collect();
+        changes.extend(before.into_iter().map(|(id, snapshot)| (id, Some(snapshot))));
+        record_changes(&mut *conn, Some(user_id), entity, changes)
+            .await
+            .map_err(crate::auth::internal_error)?;
+    }
+    Ok(())
+}
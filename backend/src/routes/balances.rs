@@ -0,0 +1,410 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::{
+    audit::record_audit_event,
+    auth::AuthenticatedUser,
+    models::{
+        BalanceAssertion, CreateBalanceAssertionRequest, OpeningBalance, SetOpeningBalanceRequest,
+    },
+    policy::{accessible_account_ids, authorize_account, AccountAction},
+    services::{
+        balances::{balance_discrepancy, balance_entry, OPENING_BALANCE_CATEGORY},
+        change_history::{record_change, record_changes, snapshots, ChangeEntity},
+    },
+    state::AppState,
+};
+
+#[derive(Deserialize)]
+pub struct BalanceCheckQuery {
+    pub account_id: Option<Uuid>,
+    pub account_group_id: Option<Uuid>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+}
+
+#[derive(Serialize)]
+pub struct BalanceCheck {
+    pub assertion_id: Uuid,
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub asserted_on: NaiveDate,
+    pub currency_code: String,
+    pub asserted_balance: f64,
+    pub computed_balance: f64,
+    pub difference: f64,
+    pub matches: bool,
+}
+
+#[derive(Serialize)]
+pub struct BalanceCheckResponse {
+    pub checked: usize,
+    pub discrepancies: usize,
+    pub results: Vec<BalanceCheck>,
+}
+
+pub async fn get_opening_balance(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(account_id): Path<Uuid>,
+) -> Result<Json<OpeningBalance>, (StatusCode, String)> {
+    authorize_account(&state.pool, &user, account_id, AccountAction::View).await?;
+
+    let record = sqlx::query(
+        r#"
+        SELECT id,
+               CASE WHEN transaction_type = 'income' THEN amount ELSE -amount END as amount,
+               occurred_at::date as as_of
+        FROM transactions
+        WHERE account_id = $1 AND category = $2
+        ORDER BY occurred_at
+        LIMIT 1
+        "#,
+    )
+    .bind(account_id)
+    .bind(OPENING_BALANCE_CATEGORY)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let Some(record) = record else {
+        return Err((StatusCode::NOT_FOUND, "No opening balance".into()));
+    };
+
+    Ok(Json(OpeningBalance {
+        account_id,
+        amount: record
+            .try_get("amount")
+            .map_err(crate::auth::internal_error)?,
+        as_of: record
+            .try_get("as_of")
+            .map_err(crate::auth::internal_error)?,
+        transaction_id: record.try_get("id").map_err(crate::auth::internal_error)?,
+    }))
+}
+
+pub async fn set_opening_balance(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(account_id): Path<Uuid>,
+    Json(payload): Json<SetOpeningBalanceRequest>,
+) -> Result<Json<OpeningBalance>, (StatusCode, String)> {
+    if !payload.amount.is_finite() {
+        return Err((StatusCode::BAD_REQUEST, "amount must be a number".into()));
+    }
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
+
+    let currency_code: String =
+        sqlx::query_scalar("SELECT currency_code FROM accounts WHERE id = $1")
+            .bind(account_id)
+            .fetch_one(&state.pool)
+            .await
+            .map_err(crate::auth::internal_error)?;
+    let (transaction_type, amount) = balance_entry(payload.amount);
+    let transaction_id = Uuid::new_v4();
+
+    // An account has one opening balance; setting it again replaces the entry.
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    remove_opening_balance(&mut tx, user.id, account_id).await?;
+    sqlx::query(
+        r#"
+        INSERT INTO transactions (
+            id, account_id, amount, currency_code, transaction_type, category, description,
+            occurred_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, 'Opening balance', $7)
+        "#,
+    )
+    .bind(transaction_id)
+    .bind(account_id)
+    .bind(amount)
+    .bind(&currency_code)
+    .bind(transaction_type)
+    .bind(OPENING_BALANCE_CATEGORY)
+    .bind(payload.as_of.and_time(NaiveTime::MIN).and_utc())
+    .execute(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    record_change(&mut tx, user.id, ChangeEntity::Transaction, transaction_id, None)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "account.opening_balance_set",
+        serde_json::json!({
+            "account_id": account_id,
+            "amount": payload.amount,
+            "as_of": payload.as_of,
+        }),
+    )
+    .await;
+
+    Ok(Json(OpeningBalance {
+        account_id,
+        amount: payload.amount,
+        as_of: payload.as_of,
+        transaction_id,
+    }))
+}
+
+pub async fn delete_opening_balance(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(account_id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    if remove_opening_balance(&mut tx, user.id, account_id).await? == 0 {
+        return Err((StatusCode::NOT_FOUND, "No opening balance".into()));
+    }
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "account.opening_balance_delete",
+        serde_json::json!({ "account_id": account_id }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn remove_opening_balance(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    account_id: Uuid,
+) -> Result<usize, (StatusCode, String)> {
+    let ids: Vec<Uuid> =
+        sqlx::query_scalar("SELECT id FROM transactions WHERE account_id = $1 AND category = $2")
+            .bind(account_id)
+            .bind(OPENING_BALANCE_CATEGORY)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(crate::auth::internal_error)?;
+    let mut before = snapshots(&mut *conn, ChangeEntity::Transaction, &ids)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    sqlx::query("DELETE FROM transactions WHERE id = ANY($1)")
+        .bind(&ids)
+        .execute(&mut *conn)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let removed = ids.len();
+    let before = ids.into_iter().map(|id| (id, before.remove(&id))).collect();
+    record_changes(conn, Some(user_id), ChangeEntity::Transaction, before)
+        .await
+        .map_err(crate::auth::internal_error)?;
+
+    Ok(removed)
+}
+
+pub async fn list_balance_assertions(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(account_id): Path<Uuid>,
+) -> Result<Json<Vec<BalanceAssertion>>, (StatusCode, String)> {
+    authorize_account(&state.pool, &user, account_id, AccountAction::View).await?;
+
+    let assertions = sqlx::query_as::<_, BalanceAssertion>(
+        r#"
+        SELECT id, account_id, asserted_on, balance, currency_code, note, created_at
+        FROM balance_assertions
+        WHERE account_id = $1
+        ORDER BY asserted_on
+        "#,
+    )
+    .bind(account_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    Ok(Json(assertions))
+}
+
+pub async fn create_balance_assertion(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(account_id): Path<Uuid>,
+    Json(payload): Json<CreateBalanceAssertionRequest>,
+) -> Result<Json<BalanceAssertion>, (StatusCode, String)> {
+    if !payload.balance.is_finite() {
+        return Err((StatusCode::BAD_REQUEST, "balance must be a number".into()));
+    }
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
+    let note = payload
+        .note
+        .map(|note| note.trim().to_string())
+        .filter(|note| !note.is_empty());
+
+    // Asserting the same day again replaces the earlier snapshot.
+    let assertion = sqlx::query_as::<_, BalanceAssertion>(
+        r#"
+        INSERT INTO balance_assertions (id, account_id, asserted_on, balance, currency_code, note)
+        SELECT $1, id, $3, $4, currency_code, $5
+        FROM accounts
+        WHERE id = $2
+        ON CONFLICT (account_id, asserted_on)
+        DO UPDATE SET balance = EXCLUDED.balance,
+                      currency_code = EXCLUDED.currency_code,
+                      note = EXCLUDED.note
+        RETURNING id, account_id, asserted_on, balance, currency_code, note, created_at
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(account_id)
+    .bind(payload.asserted_on)
+    .bind(payload.balance)
+    .bind(note)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "account.balance_assert",
+        serde_json::json!({
+            "account_id": account_id,
+            "asserted_on": assertion.asserted_on,
+            "balance": assertion.balance,
+        }),
+    )
+    .await;
+
+    Ok(Json(assertion))
+}
+
+pub async fn delete_balance_assertion(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path((account_id, assertion_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
+
+    let result = sqlx::query("DELETE FROM balance_assertions WHERE id = $1 AND account_id = $2")
+        .bind(assertion_id)
+        .bind(account_id)
+        .execute(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Balance assertion not found".into()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn check_balance_assertions(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<BalanceCheckQuery>,
+) -> Result<Json<BalanceCheckResponse>, (StatusCode, String)> {
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
+    let mut query = QueryBuilder::new(
+        r#"
+        SELECT ba.id, ba.account_id, a.name as account_name, ba.asserted_on, ba.balance,
+               ba.currency_code,
+               COALESCE((
+                   SELECT SUM(
+                       CASE WHEN t.transaction_type = 'income' THEN t.amount ELSE -t.amount END
+                   )
+                   FROM transactions t
+                   WHERE t.account_id = ba.account_id
+                     AND t.currency_code = ba.currency_code
+                     AND t.occurred_at < (ba.asserted_on + 1)::timestamp AT TIME ZONE 'UTC'
+               ), 0.0) as computed_balance
+        FROM balance_assertions ba
+        INNER JOIN accounts a ON a.id = ba.account_id
+        WHERE ba.account_id = ANY(
+        "#,
+    );
+    query.push_bind(account_ids);
+    query.push(")");
+    if let Some(account_id) = params.account_id {
+        query.push(" AND ba.account_id = ");
+        query.push_bind(account_id);
+    }
+    if let Some(group_id) = params.account_group_id {
+        query.push(
+            r#"
+            AND EXISTS (
+                SELECT 1
+                FROM account_group_members agm
+                WHERE agm.group_id =
+            "#,
+        );
+        query.push_bind(group_id);
+        query.push(" AND agm.account_id = ba.account_id)");
+    }
+    if let Some(start_date) = params.start_date {
+        query.push(" AND ba.asserted_on >= ");
+        query.push_bind(start_date);
+    }
+    if let Some(end_date) = params.end_date {
+        query.push(" AND ba.asserted_on <= ");
+        query.push_bind(end_date);
+    }
+    query.push(" ORDER BY a.name, ba.asserted_on");
+
+    let rows = query
+        .build()
+        .fetch_all(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+
+    let mut results = Vec::with_capacity(rows.len());
+    for row in rows {
+        let asserted_balance: f64 = row
+            .try_get("balance")
+            .map_err(crate::auth::internal_error)?;
+        let computed_balance: f64 = row
+            .try_get("computed_balance")
+            .map_err(crate::auth::internal_error)?;
+        let discrepancy = balance_discrepancy(asserted_balance, computed_balance);
+        results.push(BalanceCheck {
+            assertion_id: row.try_get("id").map_err(crate::auth::internal_error)?,
+            account_id: row
+                .try_get("account_id")
+                .map_err(crate::auth::internal_error)?,
+            account_name: row
+                .try_get("account_name")
+                .map_err(crate::auth::internal_error)?,
+            asserted_on: row
+                .try_get("asserted_on")
+                .map_err(crate::auth::internal_error)?,
+            currency_code: row
+                .try_get("currency_code")
+                .map_err(crate::auth::internal_error)?,
+            asserted_balance,
+            computed_balance,
+            difference: discrepancy.unwrap_or(0.0),
+            matches: discrepancy.is_none(),
+        });
+    }
+
+    Ok(Json(BalanceCheckResponse {
+        checked: results.len(),
+        discrepancies: results.iter().filter(|result| !result.matches).count(),
+        results,
+    }))
+}
@@ -0,0 +1,385 @@
+use axum::{extract::State, http::StatusCode, Json};
+use base64ct::{Base64UrlUnpadded, Encoding};
+use chrono::{Duration, Utc};
+use rand_core::{OsRng, RngCore};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::{
+    audit::record_audit_event,
+    auth::{
+        hash_api_token, hash_password, revoke_user_sessions, verify_password, AuthenticatedUser,
+    },
+    services::mail::{email_verification_message, password_reset_message},
+    state::AppState,
+};
+
+const PASSWORD_RESET: &str = "password_reset";
+const EMAIL_VERIFICATION: &str = "email_verification";
+const RESET_TOKEN_MINUTES: i64 = 60;
+const VERIFICATION_TOKEN_HOURS: i64 = 48;
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+#[derive(serde::Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+pub async fn request_password_reset(
+    State(state): State<AppState>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let record = sqlx::query("SELECT id, email FROM users WHERE LOWER(email) = LOWER($1)")
+        .bind(payload.email.trim())
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+
+    if let Some(record) = record {
+        let user_id: Uuid = record.try_get("id").map_err(crate::auth::internal_error)?;
+        let email: String = record
+            .try_get("email")
+            .map_err(crate::auth::internal_error)?;
+        let token = issue_user_token(
+            &state.pool,
+            user_id,
+            PASSWORD_RESET,
+            &email,
+            Duration::minutes(RESET_TOKEN_MINUTES),
+        )
+        .await?;
+        let link = format!("{}/reset-password?token={}", state.app_url, token);
+        let message = password_reset_message(&email, &link, RESET_TOKEN_MINUTES);
+        if let Err(error) = state.mailer.send(&message).await {
+            tracing::warn!(error = %error, "failed to send password reset email");
+        }
+
+        let _ = record_audit_event(
+            &state.pool,
+            Some(user_id),
+            "user.password_reset_requested",
+            serde_json::json!({}),
+        )
+        .await;
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    validate_new_password(&payload.new_password)?;
+    let password_hash = hash_password(&payload.new_password)?;
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let Some((user_id, email)) =
+        consume_user_token(&mut tx, PASSWORD_RESET, &payload.token).await?
+    else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "This reset link is invalid or has expired".into(),
+        ));
+    };
+    // Receiving the reset email also proves the address is theirs.
+    sqlx::query(
+        r#"
+        UPDATE users
+        SET password_hash = $2,
+            email_verified_at = CASE
+                WHEN email = $3 THEN COALESCE(email_verified_at, NOW())
+                ELSE email_verified_at
+            END
+        WHERE id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(password_hash)
+    .bind(&email)
+    .execute(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    sqlx::query(
+        r#"
+        DELETE FROM user_tokens
+        WHERE user_id = $1 AND purpose = $2 AND used_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(PASSWORD_RESET)
+    .execute(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let revoked = revoke_user_sessions(&state.pool, user_id, None).await?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user_id),
+        "user.password_reset",
+        serde_json::json!({ "sessions_revoked": revoked }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn change_password(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    validate_new_password(&payload.new_password)?;
+
+    let current_hash: Option<String> =
+        sqlx::query_scalar("SELECT password_hash FROM users WHERE id = $1")
+            .bind(user.id)
+            .fetch_one(&state.pool)
+            .await
+            .map_err(crate::auth::internal_error)?;
+    let Some(current_hash) = current_hash else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "No password is set; use password reset to create one".into(),
+        ));
+    };
+    // A 401 here would read as an expired session to clients.
+    if verify_password(&payload.current_password, &current_hash).is_err() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Current password is incorrect".into(),
+        ));
+    }
+
+    sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1")
+        .bind(user.id)
+        .bind(hash_password(&payload.new_password)?)
+        .execute(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+
+    let revoked = revoke_user_sessions(&state.pool, user.id, user.session_id).await?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "user.password_changed",
+        serde_json::json!({ "sessions_revoked": revoked }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn send_email_verification(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let record = sqlx::query(
+        r#"
+        SELECT email, email_verified_at IS NOT NULL as email_verified
+        FROM users
+        WHERE id = $1
+        "#,
+    )
+    .bind(user.id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    let email: String = record
+        .try_get("email")
+        .map_err(crate::auth::internal_error)?;
+    let email_verified: bool = record
+        .try_get("email_verified")
+        .map_err(crate::auth::internal_error)?;
+    if email_verified {
+        return Err((StatusCode::CONFLICT, "Email is already verified".into()));
+    }
+
+    send_verification_email(&state, user.id, &email).await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+pub async fn verify_email(
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let Some((user_id, email)) =
+        consume_user_token(&mut tx, EMAIL_VERIFICATION, &payload.token).await?
+    else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "This verification link is invalid or has expired".into(),
+        ));
+    };
+    let result = sqlx::query(
+        r#"
+        UPDATE users
+        SET email_verified_at = COALESCE(email_verified_at, NOW())
+        WHERE id = $1 AND email = $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(&email)
+    .execute(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    if result.rows_affected() == 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "The email address changed after this link was sent".into(),
+        ));
+    }
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user_id),
+        "user.email_verified",
+        serde_json::json!({ "email": email }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub(crate) async fn send_verification_email(
+    state: &AppState,
+    user_id: Uuid,
+    email: &str,
+) -> Result<(), (StatusCode, String)> {
+    let token = issue_user_token(
+        &state.pool,
+        user_id,
+        EMAIL_VERIFICATION,
+        email,
+        Duration::hours(VERIFICATION_TOKEN_HOURS),
+    )
+    .await?;
+    let link = format!("{}/verify-email?token={}", state.app_url, token);
+    let message = email_verification_message(email, &link, VERIFICATION_TOKEN_HOURS);
+    state.mailer.send(&message).await.map_err(|error| {
+        tracing::warn!(error = %error, "failed to send verification email");
+        (
+            StatusCode::BAD_GATEWAY,
+            "Unable to send email right now".into(),
+        )
+    })
+}
+
+fn validate_new_password(password: &str) -> Result<(), (StatusCode, String)> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Password must be at least {MIN_PASSWORD_LENGTH} characters"),
+        ));
+    }
+    Ok(())
+}
+
+async fn issue_user_token(
+    pool: &PgPool,
+    user_id: Uuid,
+    purpose: &str,
+    email: &str,
+    ttl: Duration,
+) -> Result<String, (StatusCode, String)> {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let token = Base64UrlUnpadded::encode_string(&bytes);
+
+    let mut tx = pool.begin().await.map_err(crate::auth::internal_error)?;
+    sqlx::query(
+        r#"
+        DELETE FROM user_tokens
+        WHERE user_id = $1 AND purpose = $2 AND used_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(purpose)
+    .execute(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    sqlx::query(
+        r#"
+        INSERT INTO user_tokens (id, user_id, purpose, token_hash, email, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(purpose)
+    .bind(hash_api_token(&token))
+    .bind(email)
+    .bind(Utc::now() + ttl)
+    .execute(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    Ok(token)
+}
+
+async fn consume_user_token(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    purpose: &str,
+    token: &str,
+) -> Result<Option<(Uuid, String)>, (StatusCode, String)> {
+    let record = sqlx::query(
+        r#"
+        UPDATE user_tokens
+        SET used_at = NOW()
+        WHERE token_hash = $1
+          AND purpose = $2
+          AND used_at IS NULL
+          AND expires_at > NOW()
+        RETURNING user_id, email
+        "#,
+    )
+    .bind(hash_api_token(token.trim()))
+    .bind(purpose)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    record
+        .map(|record| {
+            Ok((
+                record
+                    .try_get("user_id")
+                    .map_err(crate::auth::internal_error)?,
+                record
+                    .try_get("email")
+                    .map_err(crate::auth::internal_error)?,
+            ))
+        })
+        .transpose()
+}
@@ -0,0 +1,193 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, Row};
+use uuid::Uuid;
+
+use crate::{
+    audit::record_audit_event,
+    auth::AuthenticatedUser,
+    policy::{accessible_account_ids, authorize_account, AccountAction},
+    services::credit_cards::{card_balances, statement_cycle, CREDIT_CARD},
+    state::AppState,
+};
+
+#[derive(Serialize)]
+pub struct CreditCardStatement {
+    pub account_id: Uuid,
+    pub currency_code: String,
+    pub credit_limit: Option<f64>,
+    pub statement_period_start: NaiveDate,
+    pub statement_closing_date: NaiveDate,
+    pub statement_balance: f64,
+    pub payments_since_statement: f64,
+    pub remaining_statement_balance: f64,
+    pub current_balance: f64,
+    pub available_credit: Option<f64>,
+    pub utilization: Option<f64>,
+    pub next_due_date: NaiveDate,
+    pub days_until_due: i64,
+}
+
+#[derive(Serialize, FromRow)]
+pub struct BillReminder {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub due_date: NaiveDate,
+    pub statement_balance: f64,
+    pub currency_code: String,
+    pub created_at: DateTime<Utc>,
+}
+
+pub async fn get_account_statement(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(account_id): Path<Uuid>,
+) -> Result<Json<CreditCardStatement>, (StatusCode, String)> {
+    authorize_account(&state.pool, &user, account_id, AccountAction::View).await?;
+    let record = sqlx::query(
+        r#"
+        SELECT currency_code, account_type, credit_limit, statement_closing_day, payment_due_day
+        FROM accounts
+        WHERE id = $1
+        "#,
+    )
+    .bind(account_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let Some(record) = record else {
+        return Err((StatusCode::NOT_FOUND, "Account not found".into()));
+    };
+    let account_type: String = record
+        .try_get("account_type")
+        .map_err(crate::auth::internal_error)?;
+    if account_type != CREDIT_CARD {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Statements are only available for credit card accounts".into(),
+        ));
+    }
+    let closing_day: Option<i32> = record
+        .try_get("statement_closing_day")
+        .map_err(crate::auth::internal_error)?;
+    let due_day: Option<i32> = record
+        .try_get("payment_due_day")
+        .map_err(crate::auth::internal_error)?;
+    let (Some(closing_day), Some(due_day)) = (closing_day, due_day) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Set statement_closing_day and payment_due_day first".into(),
+        ));
+    };
+    let credit_limit: Option<f64> = record
+        .try_get("credit_limit")
+        .map_err(crate::auth::internal_error)?;
+
+    let today = Utc::now().date_naive();
+    let cycle = statement_cycle(closing_day as u32, due_day as u32, today);
+    let balances = card_balances(&state.pool, account_id, cycle.closing_date)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let next_due_date = cycle.next_due_date(due_day as u32, today);
+
+    Ok(Json(CreditCardStatement {
+        account_id,
+        currency_code: record
+            .try_get("currency_code")
+            .map_err(crate::auth::internal_error)?,
+        credit_limit,
+        statement_period_start: cycle.period_start,
+        statement_closing_date: cycle.closing_date,
+        statement_balance: balances.statement_balance,
+        payments_since_statement: balances.payments_since_statement,
+        remaining_statement_balance: balances.remaining_statement_balance(),
+        current_balance: balances.current_balance,
+        available_credit: credit_limit.map(|limit| limit - balances.current_balance),
+        utilization: credit_limit
+            .filter(|limit| *limit > 0.0)
+            .map(|limit| balances.current_balance.max(0.0) / limit),
+        next_due_date,
+        days_until_due: (next_due_date - today).num_days(),
+    }))
+}
+
+pub async fn list_bill_reminders(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<BillReminder>>, (StatusCode, String)> {
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
+    let reminders = sqlx::query_as::<_, BillReminder>(
+        r#"
+        SELECT br.id, br.account_id, a.name as account_name, br.due_date, br.statement_balance,
+               br.currency_code, br.created_at
+        FROM bill_reminders br
+        INNER JOIN accounts a ON a.id = br.account_id
+        WHERE br.account_id = ANY($1)
+          AND br.dismissed_at IS NULL
+        ORDER BY br.due_date, a.name
+        "#,
+    )
+    .bind(&account_ids)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    Ok(Json(reminders))
+}
+
+pub async fn dismiss_bill_reminder(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(reminder_id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let account_id: Option<Uuid> =
+        sqlx::query_scalar("SELECT account_id FROM bill_reminders WHERE id = $1")
+            .bind(reminder_id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(crate::auth::internal_error)?;
+    let Some(account_id) = account_id else {
+        return Err((StatusCode::NOT_FOUND, "Reminder not found".into()));
+    };
+    match authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await {
+        Err((StatusCode::NOT_FOUND, _)) => {
+            return Err((StatusCode::NOT_FOUND, "Reminder not found".into()))
+        }
+        Err(error) => return Err(error),
+        Ok(_) => {}
+    }
+
+    let result = sqlx::query(
+        r#"
+        UPDATE bill_reminders
+        SET dismissed_at = NOW()
+        WHERE id = $1
+          AND dismissed_at IS NULL
+        "#,
+    )
+    .bind(reminder_id)
+    .execute(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Reminder not found".into()));
+    }
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "bill_reminder.dismiss",
+        serde_json::json!({ "reminder_id": reminder_id }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
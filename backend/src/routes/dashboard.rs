@@ -5,7 +5,9 @@ use sqlx::{QueryBuilder, Row};
 use crate::{
     auth::AuthenticatedUser,
     models::{Account, AccountGroup, Asset, CurrencyTotal, FxRate, HistoryPoint, TotalsResponse, Transaction},
-    routes::{account_groups::AccountGroupMembership, assets::AssetPriceStatus},
+    policy::accessible_account_ids,
+    routes::{account_groups::AccountGroupMembership, assets::{list_price_failures, AssetPriceStatus}},
+    services::balances::OPENING_BALANCE_CATEGORY,
     state::AppState,
 };
 
@@ -26,26 +28,18 @@ pub async fn dashboard(
     State(state): State<AppState>,
     user: AuthenticatedUser,
 ) -> Result<Json<DashboardResponse>, (axum::http::StatusCode, String)> {
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
     let accounts = sqlx::query_as::<_, Account>(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id = $1
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id = $1
-        )
-        SELECT id, name, currency_code, created_at
+        SELECT id, name, currency_code, account_type, credit_limit, statement_closing_day,
+               payment_due_day, created_at
         FROM accounts
-        WHERE id IN (SELECT id FROM accessible_accounts)
+        WHERE id = ANY($1)
         ORDER BY created_at DESC
         LIMIT $2 OFFSET $3
         "#,
     )
-    .bind(user.id)
+    .bind(&account_ids)
     .bind(100_i64)
     .bind(0_i64)
     .fetch_all(&state.pool)
@@ -85,30 +79,17 @@ pub async fn dashboard(
 
     let mut asset_query = QueryBuilder::new(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id =
-        "#,
-    );
-    asset_query.push_bind(user.id);
-    asset_query.push(
-        r#"
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id =
+        SELECT a.id, a.account_id, a.symbol, a.asset_type, a.quantity, a.currency_code,
+               a.data_source, a.cost_basis_method, a.created_at
+        FROM assets a
+        INNER JOIN accounts acc ON a.account_id = acc.id
+        WHERE a.account_id = ANY(
         "#,
     );
-    asset_query.push_bind(user.id);
+    asset_query.push_bind(account_ids.clone());
     asset_query.push(
         r#"
         )
-        SELECT a.id, a.account_id, a.symbol, a.asset_type, a.quantity, a.currency_code, a.created_at
-        FROM assets a
-        INNER JOIN accounts acc ON a.account_id = acc.id
-        WHERE a.account_id IN (SELECT id FROM accessible_accounts)
         ORDER BY a.symbol
         LIMIT
         "#,
@@ -125,31 +106,17 @@ pub async fn dashboard(
 
     let mut transaction_query = QueryBuilder::new(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id =
-        "#,
-    );
-    transaction_query.push_bind(user.id);
-    transaction_query.push(
-        r#"
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id =
+        SELECT t.id, t.account_id, t.amount, t.currency_code, t.transaction_type,
+               t.category, t.merchant, t.description, t.occurred_at
+        FROM transactions t
+        INNER JOIN accounts a ON t.account_id = a.id
+        WHERE t.account_id = ANY(
         "#,
     );
-    transaction_query.push_bind(user.id);
+    transaction_query.push_bind(account_ids.clone());
     transaction_query.push(
         r#"
         )
-        SELECT t.id, t.account_id, t.amount, t.currency_code, t.transaction_type,
-               t.category, t.merchant, t.description, t.occurred_at
-        FROM transactions t
-        INNER JOIN accounts a ON t.account_id = a.id
-        WHERE t.account_id IN (SELECT id FROM accessible_accounts)
         ORDER BY t.occurred_at DESC
         LIMIT
         "#,
@@ -168,73 +135,45 @@ pub async fn dashboard(
     let start = today - Duration::days(30);
     let history = sqlx::query_as::<_, HistoryPoint>(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id = $1
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id = $1
-        )
         SELECT DATE(t.occurred_at) as date, COALESCE(SUM(
             CASE WHEN t.transaction_type = 'income' THEN t.amount ELSE -t.amount END
         ), 0.0) as value
         FROM transactions t
         INNER JOIN accounts a ON t.account_id = a.id
-        WHERE t.account_id IN (SELECT id FROM accessible_accounts)
+        WHERE t.account_id = ANY($1)
           AND t.occurred_at >= $2
+          AND t.category <> $3
         GROUP BY DATE(t.occurred_at)
         ORDER BY DATE(t.occurred_at)
         "#,
     )
-    .bind(user.id)
+    .bind(&account_ids)
     .bind(start)
+    .bind(OPENING_BALANCE_CATEGORY)
     .fetch_all(&state.pool)
     .await
     .map_err(crate::auth::internal_error)?;
 
     let transaction_totals = sqlx::query(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id = $1
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id = $1
-        )
         SELECT t.currency_code,
                COALESCE(SUM(
                     CASE WHEN t.transaction_type = 'income' THEN t.amount ELSE -t.amount END
                ), 0.0) as total
         FROM transactions t
         INNER JOIN accounts a ON t.account_id = a.id
-        WHERE t.account_id IN (SELECT id FROM accessible_accounts)
+        WHERE t.account_id = ANY($1)
         GROUP BY t.currency_code
         "#,
     )
-    .bind(user.id)
+    .bind(&account_ids)
     .fetch_all(&state.pool)
     .await
     .map_err(crate::auth::internal_error)?;
 
     let asset_totals = sqlx::query(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id = $1
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id = $1
-        ),
-        latest_prices AS (
+        WITH latest_prices AS (
             SELECT ph.asset_id,
                    ph.price,
                    ph.recorded_at,
@@ -243,16 +182,39 @@ pub async fn dashboard(
         )
         SELECT a.currency_code,
                COALESCE(SUM(
-                    CASE WHEN lp.rn = 1 THEN a.quantity * lp.price ELSE 0 END
+                    CASE WHEN lp.rn = 1
+                         THEN a.quantity * lp.price / COALESCE(sf.factor, 1)
+                         ELSE 0 END
                ), 0.0) as total
         FROM assets a
         INNER JOIN accounts ac ON a.account_id = ac.id
-        LEFT JOIN latest_prices lp ON lp.asset_id = a.id
-        WHERE a.account_id IN (SELECT id FROM accessible_accounts)
+        LEFT JOIN latest_prices lp ON lp.asset_id = a.id AND lp.rn = 1
+        LEFT JOIN asset_split_factors sf ON sf.asset_id = a.id
+            AND lp.recorded_at >= sf.valid_from AND lp.recorded_at < sf.valid_until
+        WHERE a.account_id = ANY($1)
         GROUP BY a.currency_code
         "#,
     )
-    .bind(user.id)
+    .bind(&account_ids)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let liability_totals = sqlx::query(
+        r#"
+        SELECT l.currency_code,
+               COALESCE(SUM(l.principal - COALESCE(p.repaid, 0.0)), 0.0) as total
+        FROM liabilities l
+        LEFT JOIN (
+            SELECT liability_id, SUM(principal_amount) as repaid
+            FROM liability_payments
+            GROUP BY liability_id
+        ) p ON p.liability_id = l.id
+        WHERE l.account_id = ANY($1)
+        GROUP BY l.currency_code
+        "#,
+    )
+    .bind(&account_ids)
     .fetch_all(&state.pool)
     .await
     .map_err(crate::auth::internal_error)?;
@@ -276,6 +238,15 @@ pub async fn dashboard(
             .map_err(crate::auth::internal_error)?;
         totals.entry(currency_code).and_modify(|t| *t += total).or_insert(total);
     }
+    for record in liability_totals {
+        let currency_code: String = record
+            .try_get("currency_code")
+            .map_err(crate::auth::internal_error)?;
+        let total: f64 = record
+            .try_get("total")
+            .map_err(crate::auth::internal_error)?;
+        totals.entry(currency_code).and_modify(|t| *t -= total).or_insert(-total);
+    }
 
     let fx_totals = sqlx::query(
         r#"
@@ -324,31 +295,22 @@ pub async fn dashboard(
 
     let price_status_record = sqlx::query(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id = $1
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id = $1
-        ),
-        latest_prices AS (
+        WITH latest_prices AS (
             SELECT ph.asset_id,
                    ph.price,
                    ROW_NUMBER() OVER (PARTITION BY ph.asset_id ORDER BY ph.recorded_at DESC) as rn
             FROM price_history ph
         )
-        SELECT COUNT(*) FILTER (WHERE lp.price IS NULL) as missing_count,
+        SELECT COUNT(*) FILTER (WHERE lp.price IS NULL AND a.valuation_mode = 'market')
+                   as missing_count,
                COUNT(*) as total_count
         FROM assets a
         INNER JOIN accounts acc ON a.account_id = acc.id
         LEFT JOIN latest_prices lp ON lp.asset_id = a.id AND lp.rn = 1
-        WHERE a.account_id IN (SELECT id FROM accessible_accounts)
+        WHERE a.account_id = ANY($1)
         "#,
     )
-    .bind(user.id)
+    .bind(&account_ids)
     .fetch_one(&state.pool)
     .await
     .map_err(crate::auth::internal_error)?;
@@ -362,6 +324,7 @@ pub async fn dashboard(
     let price_status = AssetPriceStatus {
         missing_count,
         total_count,
+        failing: list_price_failures(&state.pool, &user).await?,
     };
 
     let fx_rates = sqlx::query_as::<_, FxRate>(
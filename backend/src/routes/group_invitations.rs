@@ -0,0 +1,751 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use base64ct::{Base64UrlUnpadded, Encoding};
+use chrono::{DateTime, Duration, Utc};
+use rand_core::{OsRng, RngCore};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::{
+    audit::record_audit_event,
+    auth::{hash_api_token, AuthenticatedUser},
+    routes::account_groups::{ensure_group_admin, normalize_role},
+    services::mail::group_invitation_message,
+    state::AppState,
+};
+
+const INVITATION_DAYS: i64 = 7;
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct GroupInvitation {
+    pub id: Uuid,
+    pub group_id: Uuid,
+    pub group_name: String,
+    pub email: String,
+    pub role: String,
+    pub status: String,
+    pub invited_by_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub responded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct CreateGroupInvitationRequest {
+    pub email: String,
+    pub role: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct InvitationTokenQuery {
+    pub token: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct RespondToInvitationRequest {
+    pub token: Option<String>,
+    pub invitation_id: Option<Uuid>,
+}
+
+pub async fn list_group_invitations(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(group_id): Path<Uuid>,
+) -> Result<Json<Vec<GroupInvitation>>, (StatusCode, String)> {
+    ensure_group_admin(&state, group_id, user.id).await?;
+
+    let records = sqlx::query_as::<_, GroupInvitation>(
+        r#"
+        SELECT i.id, i.group_id, ag.name as group_name, i.email, i.role,
+               CASE WHEN i.status = 'pending' AND i.expires_at <= NOW() THEN 'expired'
+                    ELSE i.status END as status,
+               u.name as invited_by_name, i.created_at, i.expires_at, i.responded_at
+        FROM account_group_invitations i
+        INNER JOIN account_groups ag ON i.group_id = ag.id
+        LEFT JOIN users u ON i.invited_by = u.id
+        WHERE i.group_id = $1
+        ORDER BY i.created_at DESC
+        LIMIT 100
+        "#,
+    )
+    .bind(group_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    Ok(Json(records))
+}
+
+pub async fn create_group_invitation(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(group_id): Path<Uuid>,
+    Json(payload): Json<CreateGroupInvitationRequest>,
+) -> Result<(StatusCode, Json<GroupInvitation>), (StatusCode, String)> {
+    ensure_group_admin(&state, group_id, user.id).await?;
+    let role = normalize_role(&payload.role)?;
+    let email = payload.email.trim().to_lowercase();
+    if email.is_empty() || !email.contains('@') {
+        return Err((StatusCode::BAD_REQUEST, "Invalid email".into()));
+    }
+
+    let already_member: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1
+            FROM account_group_users agu
+            INNER JOIN users u ON agu.user_id = u.id
+            WHERE agu.group_id = $1 AND LOWER(u.email) = $2
+        )
+        "#,
+    )
+    .bind(group_id)
+    .bind(&email)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    if already_member {
+        return Err((
+            StatusCode::CONFLICT,
+            "This person is already a member of the group".into(),
+        ));
+    }
+
+    let record = sqlx::query(
+        r#"
+        SELECT ag.name as group_name, u.name as inviter_name
+        FROM account_groups ag, users u
+        WHERE ag.id = $1 AND u.id = $2
+        "#,
+    )
+    .bind(group_id)
+    .bind(user.id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    let group_name: String = record
+        .try_get("group_name")
+        .map_err(crate::auth::internal_error)?;
+    let inviter_name: String = record
+        .try_get("inviter_name")
+        .map_err(crate::auth::internal_error)?;
+
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let token = Base64UrlUnpadded::encode_string(&bytes);
+    let id = Uuid::new_v4();
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    sqlx::query(
+        r#"
+        UPDATE account_group_invitations
+        SET status = 'revoked', responded_at = NOW()
+        WHERE group_id = $1 AND email = $2 AND status = 'pending'
+        "#,
+    )
+    .bind(group_id)
+    .bind(&email)
+    .execute(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    let invitation = sqlx::query_as::<_, GroupInvitation>(
+        r#"
+        INSERT INTO account_group_invitations
+            (id, group_id, email, role, token_hash, invited_by, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, group_id, $8 as group_name, email, role, status,
+                  $9 as invited_by_name, created_at, expires_at, responded_at
+        "#,
+    )
+    .bind(id)
+    .bind(group_id)
+    .bind(&email)
+    .bind(&role)
+    .bind(hash_api_token(&token))
+    .bind(user.id)
+    .bind(Utc::now() + Duration::days(INVITATION_DAYS))
+    .bind(&group_name)
+    .bind(&inviter_name)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|error| {
+        // A concurrent invitation for the same address committed first.
+        if error
+            .as_database_error()
+            .and_then(|error| error.constraint())
+            == Some("account_group_invitations_pending_idx")
+        {
+            (
+                StatusCode::CONFLICT,
+                "An invitation for this address is already pending".into(),
+            )
+        } else {
+            crate::auth::internal_error(error)
+        }
+    })?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    // Send after committing so no row locks are held while the mail server
+    // responds; a failed send marks the invitation so its link never works.
+    let link = format!("{}/invitations?token={}", state.app_url, token);
+    let message = group_invitation_message(
+        &email,
+        &inviter_name,
+        &group_name,
+        &role,
+        &link,
+        INVITATION_DAYS,
+    );
+    if let Err(error) = state.mailer.send(&message).await {
+        tracing::warn!(error = %error, "failed to send group invitation");
+        sqlx::query(
+            r#"
+            UPDATE account_group_invitations
+            SET status = 'failed', responded_at = NOW()
+            WHERE id = $1 AND status = 'pending'
+            "#,
+        )
+        .bind(id)
+        .execute(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+        return Err((
+            StatusCode::BAD_GATEWAY,
+            "Unable to send email right now".into(),
+        ));
+    }
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "account_group.invitation_sent",
+        serde_json::json!({
+            "group_id": group_id,
+            "invitation_id": id,
+            "email": email,
+            "role": role,
+        }),
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(invitation)))
+}
+
+pub async fn revoke_group_invitation(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path((group_id, invitation_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    ensure_group_admin(&state, group_id, user.id).await?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE account_group_invitations
+        SET status = 'revoked', responded_at = NOW()
+        WHERE id = $1 AND group_id = $2 AND status = 'pending'
+        "#,
+    )
+    .bind(invitation_id)
+    .bind(group_id)
+    .execute(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Invitation not found".into()));
+    }
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "account_group.invitation_revoked",
+        serde_json::json!({ "group_id": group_id, "invitation_id": invitation_id }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_my_invitations(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<GroupInvitation>>, (StatusCode, String)> {
+    let records = sqlx::query_as::<_, GroupInvitation>(
+        r#"
+        SELECT i.id, i.group_id, ag.name as group_name, i.email, i.role, i.status,
+               inviter.name as invited_by_name, i.created_at, i.expires_at, i.responded_at
+        FROM account_group_invitations i
+        INNER JOIN account_groups ag ON i.group_id = ag.id
+        INNER JOIN users u ON i.email = LOWER(u.email)
+        LEFT JOIN users inviter ON i.invited_by = inviter.id
+        WHERE u.id = $1
+          AND u.email_verified_at IS NOT NULL
+          AND i.status = 'pending'
+          AND i.expires_at > NOW()
+        ORDER BY i.created_at DESC
+        "#,
+    )
+    .bind(user.id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    Ok(Json(records))
+}
+
+pub async fn preview_invitation(
+    State(state): State<AppState>,
+    Query(query): Query<InvitationTokenQuery>,
+) -> Result<Json<GroupInvitation>, (StatusCode, String)> {
+    let record = sqlx::query_as::<_, GroupInvitation>(
+        r#"
+        SELECT i.id, i.group_id, ag.name as group_name, i.email, i.role,
+               CASE WHEN i.status = 'pending' AND i.expires_at <= NOW() THEN 'expired'
+                    ELSE i.status END as status,
+               u.name as invited_by_name, i.created_at, i.expires_at, i.responded_at
+        FROM account_group_invitations i
+        INNER JOIN account_groups ag ON i.group_id = ag.id
+        LEFT JOIN users u ON i.invited_by = u.id
+        WHERE i.token_hash = $1
+        "#,
+    )
+    .bind(hash_api_token(query.token.trim()))
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    record
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "Invitation not found".into()))
+}
+
+pub async fn accept_invitation(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<RespondToInvitationRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    respond_to_invitation(&state, &user, payload, true).await
+}
+
+pub async fn decline_invitation(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<RespondToInvitationRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    respond_to_invitation(&state, &user, payload, false).await
+}
+
+async fn respond_to_invitation(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    payload: RespondToInvitationRequest,
+    accept: bool,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let query = match (payload.token.as_deref(), payload.invitation_id) {
+        (Some(token), None) => sqlx::query(
+            r#"
+            SELECT id, group_id, role, status, expires_at
+            FROM account_group_invitations
+            WHERE token_hash = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(hash_api_token(token.trim())),
+        (None, Some(invitation_id)) => sqlx::query(
+            r#"
+            SELECT i.id, i.group_id, i.role, i.status, i.expires_at
+            FROM account_group_invitations i
+            INNER JOIN users u ON i.email = LOWER(u.email)
+            WHERE i.id = $1
+              AND u.id = $2
+              AND u.email_verified_at IS NOT NULL
+            FOR UPDATE OF i
+            "#,
+        )
+        .bind(invitation_id)
+        .bind(user.id),
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Provide either token or invitation_id".into(),
+            ))
+        }
+    };
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let Some(record) = query
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?
+    else {
+        return Err((StatusCode::NOT_FOUND, "Invitation not found".into()));
+    };
+    let invitation_id: Uuid = record.try_get("id").map_err(crate::auth::internal_error)?;
+    let group_id: Uuid = record
+        .try_get("group_id")
+        .map_err(crate::auth::internal_error)?;
+    let role: String = record
+        .try_get("role")
+        .map_err(crate::auth::internal_error)?;
+    let status: String = record
+        .try_get("status")
+        .map_err(crate::auth::internal_error)?;
+    let expires_at: DateTime<Utc> = record
+        .try_get("expires_at")
+        .map_err(crate::auth::internal_error)?;
+
+    if status != "pending" {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("This invitation was already {status}"),
+        ));
+    }
+    if expires_at <= Utc::now() {
+        return Err((StatusCode::GONE, "This invitation has expired".into()));
+    }
+
+    let mut joined = false;
+    if accept {
+        joined = sqlx::query(
+            r#"
+            INSERT INTO account_group_users (group_id, user_id, role)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (group_id, user_id) DO NOTHING
+            "#,
+        )
+        .bind(group_id)
+        .bind(user.id)
+        .bind(&role)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?
+        .rows_affected()
+            > 0;
+    }
+    sqlx::query(
+        r#"
+        UPDATE account_group_invitations
+        SET status = $2, responded_by = $3, responded_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(invitation_id)
+    .bind(if accept { "accepted" } else { "declined" })
+    .bind(user.id)
+    .execute(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let action = if accept {
+        "account_group.invitation_accepted"
+    } else {
+        "account_group.invitation_declined"
+    };
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        action,
+        serde_json::json!({ "group_id": group_id, "invitation_id": invitation_id }),
+    )
+    .await;
+    if joined {
+        let _ = record_audit_event(
+            &state.pool,
+            Some(user.id),
+            "account_group.member_added",
+            serde_json::json!({
+                "group_id": group_id,
+                "user_id": user.id,
+                "role": role,
+                "invitation_id": invitation_id,
+            }),
+        )
+        .await;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        accept_invitation, create_group_invitation, decline_invitation, list_group_invitations,
+        revoke_group_invitation, CreateGroupInvitationRequest, RespondToInvitationRequest,
+    };
+    use crate::auth::AuthenticatedUser;
+    use crate::services::mail::{MailFuture, MailMessage, MailSender};
+    use crate::state::AppState;
+    use crate::test_support::{create_account, create_user, share_account, test_pool, test_state};
+    use axum::{
+        extract::{Path, State},
+        http::StatusCode,
+        Json,
+    };
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    #[derive(Default)]
+    struct Outbox(Mutex<Vec<MailMessage>>);
+
+    impl MailSender for Outbox {
+        fn name(&self) -> &'static str {
+            "outbox"
+        }
+
+        fn send<'a>(&'a self, message: &'a MailMessage) -> MailFuture<'a> {
+            self.0.lock().unwrap().push(message.clone());
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    struct Unreachable;
+
+    impl MailSender for Unreachable {
+        fn name(&self) -> &'static str {
+            "unreachable"
+        }
+
+        fn send<'a>(&'a self, _message: &'a MailMessage) -> MailFuture<'a> {
+            Box::pin(async { Err("connection refused".into()) })
+        }
+    }
+
+    struct Fixture {
+        state: AppState,
+        outbox: Arc<Outbox>,
+        admin: AuthenticatedUser,
+        invitee: AuthenticatedUser,
+        group_id: Uuid,
+    }
+
+    impl Fixture {
+        async fn seed() -> Option<Self> {
+            let pool = test_pool().await?;
+            let outbox = Arc::new(Outbox::default());
+            let state = AppState {
+                mailer: outbox.clone(),
+                ..test_state(pool)
+            };
+            let admin = create_user(&state.pool, "admin").await;
+            let invitee = create_user(&state.pool, "invitee").await;
+            let account_id = create_account(&state.pool, &admin).await;
+            let group_id = share_account(&state.pool, &admin, account_id, &admin, "admin").await;
+            Some(Self {
+                state,
+                outbox,
+                admin,
+                invitee,
+                group_id,
+            })
+        }
+
+        async fn invite(&self, email: &str, role: &str) -> Result<Uuid, StatusCode> {
+            create_group_invitation(
+                State(self.state.clone()),
+                self.admin.clone(),
+                Path(self.group_id),
+                Json(CreateGroupInvitationRequest {
+                    email: email.into(),
+                    role: role.into(),
+                }),
+            )
+            .await
+            .map(|(_, Json(invitation))| invitation.id)
+            .map_err(|(status, _)| status)
+        }
+
+        fn last_token(&self) -> String {
+            let outbox = self.outbox.0.lock().unwrap();
+            let body = &outbox.last().expect("an invitation was sent").body;
+            let start = body.find("token=").expect("link carries a token") + "token=".len();
+            body[start..].lines().next().unwrap().to_string()
+        }
+
+        async fn respond(&self, token: &str, accept: bool) -> StatusCode {
+            let payload = Json(RespondToInvitationRequest {
+                token: Some(token.into()),
+                invitation_id: None,
+            });
+            let state = State(self.state.clone());
+            let result = if accept {
+                accept_invitation(state, self.invitee.clone(), payload).await
+            } else {
+                decline_invitation(state, self.invitee.clone(), payload).await
+            };
+            result.unwrap_or_else(|(status, _)| status)
+        }
+
+        async fn statuses(&self) -> Vec<String> {
+            let Json(invitations) = list_group_invitations(
+                State(self.state.clone()),
+                self.admin.clone(),
+                Path(self.group_id),
+            )
+            .await
+            .unwrap();
+            invitations
+                .into_iter()
+                .rev()
+                .map(|invitation| invitation.status)
+                .collect()
+        }
+
+        async fn member_role(&self) -> Option<String> {
+            sqlx::query_scalar(
+                "SELECT role FROM account_group_users WHERE group_id = $1 AND user_id = $2",
+            )
+            .bind(self.group_id)
+            .bind(self.invitee.id)
+            .fetch_optional(&self.state.pool)
+            .await
+            .unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn accepting_joins_with_the_invited_role_once() {
+        let Some(fixture) = Fixture::seed().await else { return };
+        fixture.invite("Friend@Example.com", "edit").await.unwrap();
+        let token = fixture.last_token();
+
+        assert_eq!(fixture.respond(&token, true).await, StatusCode::NO_CONTENT);
+        assert_eq!(fixture.member_role().await.as_deref(), Some("edit"));
+        assert_eq!(fixture.respond(&token, true).await, StatusCode::CONFLICT);
+        assert_eq!(fixture.respond(&token, false).await, StatusCode::CONFLICT);
+        assert_eq!(fixture.statuses().await, ["accepted"]);
+    }
+
+    #[tokio::test]
+    async fn declining_adds_no_member() {
+        let Some(fixture) = Fixture::seed().await else { return };
+        fixture.invite("friend@example.com", "view").await.unwrap();
+        let token = fixture.last_token();
+
+        assert_eq!(fixture.respond(&token, false).await, StatusCode::NO_CONTENT);
+        assert_eq!(fixture.member_role().await, None);
+        assert_eq!(fixture.respond(&token, true).await, StatusCode::CONFLICT);
+        assert_eq!(fixture.statuses().await, ["declined"]);
+    }
+
+    #[tokio::test]
+    async fn expired_invitations_cannot_be_answered() {
+        let Some(fixture) = Fixture::seed().await else { return };
+        let id = fixture.invite("friend@example.com", "view").await.unwrap();
+        let token = fixture.last_token();
+        sqlx::query(
+            "UPDATE account_group_invitations SET expires_at = NOW() - INTERVAL '1 hour' WHERE id = $1",
+        )
+        .bind(id)
+        .execute(&fixture.state.pool)
+        .await
+        .unwrap();
+
+        assert_eq!(fixture.respond(&token, true).await, StatusCode::GONE);
+        assert_eq!(fixture.member_role().await, None);
+        assert_eq!(fixture.statuses().await, ["expired"]);
+    }
+
+    #[tokio::test]
+    async fn revoked_invitations_cannot_be_answered() {
+        let Some(fixture) = Fixture::seed().await else { return };
+        let id = fixture.invite("friend@example.com", "view").await.unwrap();
+        let token = fixture.last_token();
+        let revoke = || {
+            revoke_group_invitation(
+                State(fixture.state.clone()),
+                fixture.admin.clone(),
+                Path((fixture.group_id, id)),
+            )
+        };
+
+        assert_eq!(revoke().await, Ok(StatusCode::NO_CONTENT));
+        assert_eq!(revoke().await.unwrap_err().0, StatusCode::NOT_FOUND);
+        assert_eq!(fixture.respond(&token, true).await, StatusCode::CONFLICT);
+        assert_eq!(fixture.statuses().await, ["revoked"]);
+    }
+
+    #[tokio::test]
+    async fn reinviting_replaces_the_pending_invitation() {
+        let Some(fixture) = Fixture::seed().await else { return };
+        fixture.invite("friend@example.com", "view").await.unwrap();
+        let first = fixture.last_token();
+        fixture.invite("friend@example.com", "admin").await.unwrap();
+        let second = fixture.last_token();
+
+        assert_eq!(fixture.statuses().await, ["revoked", "pending"]);
+        assert_eq!(fixture.respond(&first, true).await, StatusCode::CONFLICT);
+        assert_eq!(fixture.respond(&second, true).await, StatusCode::NO_CONTENT);
+        assert_eq!(fixture.member_role().await.as_deref(), Some("admin"));
+
+        let email: String = sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
+            .bind(fixture.invitee.id)
+            .fetch_one(&fixture.state.pool)
+            .await
+            .unwrap();
+        assert_eq!(fixture.invite(&email, "view").await, Err(StatusCode::CONFLICT));
+    }
+
+    #[tokio::test]
+    async fn failed_sends_mark_the_invitation_failed() {
+        let Some(fixture) = Fixture::seed().await else { return };
+        let fixture = Fixture {
+            state: AppState {
+                mailer: Arc::new(Unreachable),
+                ..fixture.state.clone()
+            },
+            ..fixture
+        };
+
+        assert_eq!(
+            fixture.invite("friend@example.com", "view").await,
+            Err(StatusCode::BAD_GATEWAY)
+        );
+        assert_eq!(fixture.statuses().await, ["failed"]);
+        assert_eq!(
+            fixture.invite("friend@example.com", "view").await,
+            Err(StatusCode::BAD_GATEWAY)
+        );
+        assert_eq!(fixture.statuses().await, ["failed", "failed"]);
+    }
+
+    #[tokio::test]
+    async fn concurrent_invitations_for_one_address_conflict() {
+        let Some(fixture) = Fixture::seed().await else { return };
+        let mut tx = fixture.state.pool.begin().await.unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO account_group_invitations
+                (id, group_id, email, role, token_hash, invited_by, expires_at)
+            VALUES ($1, $2, 'friend@example.com', 'view', $3, $4, NOW() + INTERVAL '7 days')
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(fixture.group_id)
+        .bind(Uuid::new_v4().to_string())
+        .bind(fixture.admin.id)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+        // The handler's insert waits on the uncommitted row, then collides with it.
+        let invite = fixture.invite("friend@example.com", "edit");
+        let commit = async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            tx.commit().await.unwrap();
+        };
+        let (invited, ()) = tokio::join!(invite, commit);
+        assert_eq!(invited, Err(StatusCode::CONFLICT));
+    }
+}
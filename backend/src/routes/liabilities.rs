@@ -0,0 +1,481 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::QueryBuilder;
+use uuid::Uuid;
+
+use crate::{
+    audit::record_audit_event,
+    auth::AuthenticatedUser,
+    models::{CreateLiabilityPaymentRequest, CreateLiabilityRequest, Liability, LiabilityPayment},
+    policy::{accessible_account_ids, authorize_account, AccountAction},
+    services::{
+        change_history::{record_change, snapshot, ChangeEntity},
+        liabilities::{
+            amortization_schedule, PaymentFrequency, ScheduledPayment, INTEREST_CATEGORY,
+            PRINCIPAL_CATEGORY,
+        },
+    },
+    state::AppState,
+};
+
+const MAX_TERM_MONTHS: i32 = 600;
+
+#[derive(Deserialize)]
+pub struct LiabilityQuery {
+    pub account_id: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+pub struct LiabilityScheduleEntry {
+    #[serde(flatten)]
+    pub scheduled: ScheduledPayment,
+    pub payment_id: Option<Uuid>,
+    pub paid_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize)]
+pub struct LiabilityScheduleResponse {
+    pub liability_id: Uuid,
+    pub currency_code: String,
+    pub outstanding_balance: f64,
+    pub total_interest: f64,
+    pub payments: Vec<LiabilityScheduleEntry>,
+}
+
+pub async fn list_liabilities(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<LiabilityQuery>,
+) -> Result<Json<Vec<Liability>>, (StatusCode, String)> {
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
+    let mut query = QueryBuilder::new(
+        r#"
+        SELECT l.id, l.account_id, l.name, l.principal, l.annual_rate, l.term_months,
+               l.payment_frequency, l.currency_code, l.start_date,
+               l.principal - COALESCE((
+                   SELECT SUM(p.principal_amount)
+                   FROM liability_payments p
+                   WHERE p.liability_id = l.id
+               ), 0.0) as outstanding_balance,
+               l.created_at
+        FROM liabilities l
+        WHERE l.account_id = ANY(
+        "#,
+    );
+    query.push_bind(account_ids);
+    query.push(")");
+    if let Some(account_id) = params.account_id {
+        query.push(" AND l.account_id = ");
+        query.push_bind(account_id);
+    }
+    query.push(" ORDER BY l.start_date, l.name");
+
+    let liabilities = query
+        .build_query_as::<Liability>()
+        .fetch_all(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+
+    Ok(Json(liabilities))
+}
+
+pub async fn create_liability(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<CreateLiabilityRequest>,
+) -> Result<Json<Liability>, (StatusCode, String)> {
+    let name = payload.name.trim().to_string();
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "name is required".into()));
+    }
+    if !(payload.principal.is_finite() && payload.principal > 0.0) {
+        return Err((StatusCode::BAD_REQUEST, "principal must be positive".into()));
+    }
+    if !(payload.annual_rate.is_finite() && (0.0..1.0).contains(&payload.annual_rate)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "annual_rate must be a fraction between 0 and 1".into(),
+        ));
+    }
+    if !(1..=MAX_TERM_MONTHS).contains(&payload.term_months) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("term_months must be between 1 and {MAX_TERM_MONTHS}"),
+        ));
+    }
+    let payment_frequency = payload
+        .payment_frequency
+        .map(|value| value.trim().to_lowercase())
+        .unwrap_or_else(|| "monthly".to_string());
+    if PaymentFrequency::parse(&payment_frequency).is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "payment_frequency must be weekly, biweekly, monthly or quarterly".into(),
+        ));
+    }
+    authorize_account(&state.pool, &user, payload.account_id, AccountAction::Edit).await?;
+
+    let currency_code = match payload.currency_code {
+        Some(code) => code.trim().to_uppercase(),
+        None => sqlx::query_scalar("SELECT currency_code FROM accounts WHERE id = $1")
+            .bind(payload.account_id)
+            .fetch_one(&state.pool)
+            .await
+            .map_err(crate::auth::internal_error)?,
+    };
+
+    let liability = sqlx::query_as::<_, Liability>(
+        r#"
+        INSERT INTO liabilities (
+            id, account_id, name, principal, annual_rate, term_months, payment_frequency,
+            currency_code, start_date
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id, account_id, name, principal, annual_rate, term_months, payment_frequency,
+                  currency_code, start_date, principal as outstanding_balance, created_at
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(payload.account_id)
+    .bind(name)
+    .bind(payload.principal)
+    .bind(payload.annual_rate)
+    .bind(payload.term_months)
+    .bind(&payment_frequency)
+    .bind(currency_code)
+    .bind(payload.start_date)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "liability.create",
+        serde_json::json!({
+            "liability_id": liability.id,
+            "account_id": liability.account_id,
+            "principal": liability.principal,
+        }),
+    )
+    .await;
+
+    Ok(Json(liability))
+}
+
+pub async fn delete_liability(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(liability_id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    load_liability(&state, &user, liability_id, AccountAction::Edit).await?;
+
+    // Payment transactions stay on the account; they record money that moved.
+    sqlx::query("DELETE FROM liabilities WHERE id = $1")
+        .bind(liability_id)
+        .execute(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "liability.delete",
+        serde_json::json!({ "liability_id": liability_id }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_liability_schedule(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(liability_id): Path<Uuid>,
+) -> Result<Json<LiabilityScheduleResponse>, (StatusCode, String)> {
+    let liability = load_liability(&state, &user, liability_id, AccountAction::View).await?;
+    let payments = load_payments(&state, liability_id).await?;
+
+    let schedule = liability_schedule(&liability)?;
+    let total_interest = schedule.iter().map(|payment| payment.interest).sum();
+    let payments = schedule
+        .into_iter()
+        .map(|scheduled| {
+            let paid = payments
+                .iter()
+                .find(|payment| payment.period as u32 == scheduled.period);
+            LiabilityScheduleEntry {
+                payment_id: paid.map(|payment| payment.id),
+                paid_at: paid.map(|payment| payment.paid_at),
+                scheduled,
+            }
+        })
+        .collect();
+
+    Ok(Json(LiabilityScheduleResponse {
+        liability_id,
+        currency_code: liability.currency_code,
+        outstanding_balance: liability.outstanding_balance,
+        total_interest,
+        payments,
+    }))
+}
+
+pub async fn list_liability_payments(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(liability_id): Path<Uuid>,
+) -> Result<Json<Vec<LiabilityPayment>>, (StatusCode, String)> {
+    load_liability(&state, &user, liability_id, AccountAction::View).await?;
+    Ok(Json(load_payments(&state, liability_id).await?))
+}
+
+pub async fn create_liability_payment(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(liability_id): Path<Uuid>,
+    Json(payload): Json<CreateLiabilityPaymentRequest>,
+) -> Result<Json<LiabilityPayment>, (StatusCode, String)> {
+    let liability = load_liability(&state, &user, liability_id, AccountAction::Edit).await?;
+
+    let schedule = liability_schedule(&liability)?;
+    let paid_periods: Vec<i32> = load_payments(&state, liability_id)
+        .await?
+        .into_iter()
+        .map(|payment| payment.period)
+        .collect();
+    let period = match payload.period {
+        Some(period) => period,
+        None => schedule
+            .iter()
+            .map(|payment| payment.period as i32)
+            .find(|period| !paid_periods.contains(period))
+            .ok_or((
+                StatusCode::BAD_REQUEST,
+                "Liability is fully repaid".to_string(),
+            ))?,
+    };
+    if paid_periods.contains(&period) {
+        return Err((StatusCode::CONFLICT, "Period is already paid".into()));
+    }
+    let Some(scheduled) = schedule
+        .iter()
+        .find(|payment| payment.period as i32 == period)
+    else {
+        return Err((StatusCode::BAD_REQUEST, "Period is outside the term".into()));
+    };
+    let paid_at = payload
+        .paid_at
+        .unwrap_or_else(|| scheduled.due_date.and_time(NaiveTime::MIN).and_utc());
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let mut transaction_ids = [None, None];
+    for (slot, (category, amount)) in transaction_ids.iter_mut().zip([
+        (PRINCIPAL_CATEGORY, scheduled.principal),
+        (INTEREST_CATEGORY, scheduled.interest),
+    ]) {
+        if amount <= 0.0 {
+            continue;
+        }
+        let transaction_id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO transactions (
+                id, account_id, amount, currency_code, transaction_type, category, merchant,
+                description, occurred_at
+            )
+            VALUES ($1, $2, $3, $4, 'expense', $5, $6, $7, $8)
+            "#,
+        )
+        .bind(transaction_id)
+        .bind(liability.account_id)
+        .bind(amount)
+        .bind(&liability.currency_code)
+        .bind(category)
+        .bind(&liability.name)
+        .bind(format!("{} payment {period}", liability.name))
+        .bind(paid_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+        record_change(&mut tx, user.id, ChangeEntity::Transaction, transaction_id, None)
+            .await
+            .map_err(crate::auth::internal_error)?;
+        *slot = Some(transaction_id);
+    }
+    let payment = sqlx::query_as::<_, LiabilityPayment>(
+        r#"
+        INSERT INTO liability_payments (
+            id, liability_id, period, principal_amount, interest_amount, paid_at,
+            principal_transaction_id, interest_transaction_id
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, liability_id, period, principal_amount, interest_amount, paid_at,
+                  principal_transaction_id, interest_transaction_id, created_at
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(liability_id)
+    .bind(period)
+    .bind(scheduled.principal)
+    .bind(scheduled.interest)
+    .bind(paid_at)
+    .bind(transaction_ids[0])
+    .bind(transaction_ids[1])
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "liability.payment_create",
+        serde_json::json!({
+            "liability_id": liability_id,
+            "payment_id": payment.id,
+            "period": period,
+        }),
+    )
+    .await;
+
+    Ok(Json(payment))
+}
+
+pub async fn delete_liability_payment(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path((liability_id, payment_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    load_liability(&state, &user, liability_id, AccountAction::Edit).await?;
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let transaction_ids = sqlx::query_as::<_, (Option<Uuid>, Option<Uuid>)>(
+        r#"
+        DELETE FROM liability_payments
+        WHERE id = $1 AND liability_id = $2
+        RETURNING principal_transaction_id, interest_transaction_id
+        "#,
+    )
+    .bind(payment_id)
+    .bind(liability_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let Some((principal_transaction_id, interest_transaction_id)) = transaction_ids else {
+        return Err((StatusCode::NOT_FOUND, "Payment not found".into()));
+    };
+
+    // The split transactions were posted with the payment, so they go with it.
+    for transaction_id in [principal_transaction_id, interest_transaction_id]
+        .into_iter()
+        .flatten()
+    {
+        let before = snapshot(&mut tx, ChangeEntity::Transaction, transaction_id)
+            .await
+            .map_err(crate::auth::internal_error)?;
+        sqlx::query("DELETE FROM transactions WHERE id = $1")
+            .bind(transaction_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(crate::auth::internal_error)?;
+        record_change(&mut tx, user.id, ChangeEntity::Transaction, transaction_id, before)
+            .await
+            .map_err(crate::auth::internal_error)?;
+    }
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "liability.payment_delete",
+        serde_json::json!({ "liability_id": liability_id, "payment_id": payment_id }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn liability_schedule(
+    liability: &Liability,
+) -> Result<Vec<ScheduledPayment>, (StatusCode, String)> {
+    let frequency = PaymentFrequency::parse(&liability.payment_frequency).ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Unknown payment frequency".to_string(),
+    ))?;
+    Ok(amortization_schedule(
+        liability.principal,
+        liability.annual_rate,
+        liability.term_months as u32,
+        frequency,
+        liability.start_date,
+    ))
+}
+
+async fn load_liability(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    liability_id: Uuid,
+    action: AccountAction,
+) -> Result<Liability, (StatusCode, String)> {
+    let liability = sqlx::query_as::<_, Liability>(
+        r#"
+        SELECT l.id, l.account_id, l.name, l.principal, l.annual_rate, l.term_months,
+               l.payment_frequency, l.currency_code, l.start_date,
+               l.principal - COALESCE((
+                   SELECT SUM(p.principal_amount)
+                   FROM liability_payments p
+                   WHERE p.liability_id = l.id
+               ), 0.0) as outstanding_balance,
+               l.created_at
+        FROM liabilities l
+        WHERE l.id = $1
+        "#,
+    )
+    .bind(liability_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?
+    .ok_or((StatusCode::NOT_FOUND, "Liability not found".into()))?;
+
+    match authorize_account(&state.pool, user, liability.account_id, action).await {
+        Err((StatusCode::NOT_FOUND, _)) => {
+            Err((StatusCode::NOT_FOUND, "Liability not found".into()))
+        }
+        Err(error) => Err(error),
+        Ok(_) => Ok(liability),
+    }
+}
+
+async fn load_payments(
+    state: &AppState,
+    liability_id: Uuid,
+) -> Result<Vec<LiabilityPayment>, (StatusCode, String)> {
+    sqlx::query_as::<_, LiabilityPayment>(
+        r#"
+        SELECT id, liability_id, period, principal_amount, interest_amount, paid_at,
+               principal_transaction_id, interest_transaction_id, created_at
+        FROM liability_payments
+        WHERE liability_id = $1
+        ORDER BY period
+        "#,
+    )
+    .bind(liability_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)
+}
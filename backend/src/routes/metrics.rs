@@ -6,7 +6,8 @@ use crate::{
     audit::record_audit_event,
     auth::AuthenticatedUser,
     models::{CurrencyTotal, FxRate, HistoryPoint, TotalsResponse},
-    services::forex::refresh_fx_rates,
+    policy::accessible_account_ids,
+    services::{balances::OPENING_BALANCE_CATEGORY, forex::refresh_fx_rates},
     state::AppState,
 };
 
@@ -14,46 +15,27 @@ pub async fn totals(
     State(state): State<AppState>,
     user: AuthenticatedUser,
 ) -> Result<Json<TotalsResponse>, (axum::http::StatusCode, String)> {
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
     let transaction_totals = sqlx::query(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id = $1
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id = $1
-        )
         SELECT t.currency_code,
                COALESCE(SUM(
                     CASE WHEN t.transaction_type = 'income' THEN t.amount ELSE -t.amount END
                ), 0.0) as total
         FROM transactions t
         INNER JOIN accounts a ON t.account_id = a.id
-        WHERE t.account_id IN (SELECT id FROM accessible_accounts)
+        WHERE t.account_id = ANY($1)
         GROUP BY t.currency_code
         "#,
     )
-    .bind(user.id)
+    .bind(&account_ids)
     .fetch_all(&state.pool)
     .await
     .map_err(crate::auth::internal_error)?;
 
     let asset_totals = sqlx::query(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id = $1
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id = $1
-        ),
-        latest_prices AS (
+        WITH latest_prices AS (
             SELECT ph.asset_id,
                    ph.price,
                    ph.recorded_at,
@@ -62,16 +44,39 @@ pub async fn totals(
         )
         SELECT a.currency_code,
                COALESCE(SUM(
-                    CASE WHEN lp.rn = 1 THEN a.quantity * lp.price ELSE 0 END
+                    CASE WHEN lp.rn = 1
+                         THEN a.quantity * lp.price / COALESCE(sf.factor, 1)
+                         ELSE 0 END
                ), 0.0) as total
         FROM assets a
         INNER JOIN accounts ac ON a.account_id = ac.id
-        LEFT JOIN latest_prices lp ON lp.asset_id = a.id
-        WHERE a.account_id IN (SELECT id FROM accessible_accounts)
+        LEFT JOIN latest_prices lp ON lp.asset_id = a.id AND lp.rn = 1
+        LEFT JOIN asset_split_factors sf ON sf.asset_id = a.id
+            AND lp.recorded_at >= sf.valid_from AND lp.recorded_at < sf.valid_until
+        WHERE a.account_id = ANY($1)
         GROUP BY a.currency_code
         "#,
     )
-    .bind(user.id)
+    .bind(&account_ids)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let liability_totals = sqlx::query(
+        r#"
+        SELECT l.currency_code,
+               COALESCE(SUM(l.principal - COALESCE(p.repaid, 0.0)), 0.0) as total
+        FROM liabilities l
+        LEFT JOIN (
+            SELECT liability_id, SUM(principal_amount) as repaid
+            FROM liability_payments
+            GROUP BY liability_id
+        ) p ON p.liability_id = l.id
+        WHERE l.account_id = ANY($1)
+        GROUP BY l.currency_code
+        "#,
+    )
+    .bind(&account_ids)
     .fetch_all(&state.pool)
     .await
     .map_err(crate::auth::internal_error)?;
@@ -95,6 +100,15 @@ pub async fn totals(
             .map_err(crate::auth::internal_error)?;
         totals.entry(currency_code).and_modify(|t| *t += total).or_insert(total);
     }
+    for record in liability_totals {
+        let currency_code: String = record
+            .try_get("currency_code")
+            .map_err(crate::auth::internal_error)?;
+        let total: f64 = record
+            .try_get("total")
+            .map_err(crate::auth::internal_error)?;
+        totals.entry(currency_code).and_modify(|t| *t -= total).or_insert(-total);
+    }
 
     let fx_rates = sqlx::query(
         r#"
@@ -151,31 +165,24 @@ pub async fn history(
     let today = Utc::now().date_naive();
     let start = today - Duration::days(30);
 
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
     let records = sqlx::query_as::<_, HistoryPoint>(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id = $1
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id = $1
-        )
         SELECT DATE(t.occurred_at) as date, COALESCE(SUM(
             CASE WHEN t.transaction_type = 'income' THEN t.amount ELSE -t.amount END
         ), 0.0) as value
         FROM transactions t
         INNER JOIN accounts a ON t.account_id = a.id
-        WHERE t.account_id IN (SELECT id FROM accessible_accounts)
+        WHERE t.account_id = ANY($1)
           AND t.occurred_at >= $2
+          AND t.category <> $3
         GROUP BY DATE(t.occurred_at)
         ORDER BY DATE(t.occurred_at)
         "#,
     )
-    .bind(user.id)
+    .bind(&account_ids)
     .bind(start)
+    .bind(OPENING_BALANCE_CATEGORY)
     .fetch_all(&state.pool)
     .await
     .map_err(crate::auth::internal_error)?;
@@ -1,13 +1,27 @@
 pub mod account_groups;
 pub mod accounts;
+pub mod activity;
+pub mod allocation;
 pub mod api_tokens;
+pub mod asset_events;
+pub mod asset_returns;
+pub mod asset_trades;
+pub mod asset_valuations;
 pub mod assets;
 pub mod backup;
+pub mod balances;
+pub mod credentials;
+pub mod credit_cards;
 pub mod admin;
 pub mod dashboard;
+pub mod group_invitations;
 pub mod integrations;
+pub mod liabilities;
 pub mod metrics;
+pub mod oidc;
 pub mod plugins;
 pub mod preferences;
 pub mod recurring_transactions;
+pub mod sessions;
 pub mod transactions;
+pub mod two_factor;
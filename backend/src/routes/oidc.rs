@@ -0,0 +1,360 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use chrono::{Duration, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::{
+    audit::record_audit_event,
+    auth::{hash_api_token, start_session, two_factor_challenge, LoginResponse},
+    services::oidc::{
+        authorization_url, discover, exchange_code, random_token, verify_id_token, IdTokenClaims,
+        OidcConfig, OidcError,
+    },
+    state::AppState,
+};
+
+const LOGIN_STATE_MINUTES: i64 = 10;
+
+#[derive(serde::Serialize)]
+pub struct AuthConfig {
+    pub password_login_enabled: bool,
+    pub oidc_enabled: bool,
+    pub oidc_provider_name: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct AuthorizeResponse {
+    pub authorization_url: String,
+    pub binding: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct CallbackRequest {
+    pub code: String,
+    pub state: String,
+    pub binding: String,
+}
+
+pub async fn get_auth_config(State(state): State<AppState>) -> Json<AuthConfig> {
+    Json(AuthConfig {
+        password_login_enabled: state.password_login_enabled,
+        oidc_enabled: state.oidc.is_some(),
+        oidc_provider_name: state
+            .oidc
+            .as_ref()
+            .map(|config| config.provider_name.clone()),
+    })
+}
+
+pub async fn authorize(
+    State(state): State<AppState>,
+) -> Result<Json<AuthorizeResponse>, (StatusCode, String)> {
+    let config = oidc_config(&state)?;
+    let metadata = discover(config).await.map_err(oidc_error)?;
+
+    let login_state = random_token();
+    let binding = random_token();
+    let nonce = random_token();
+    let code_verifier = random_token();
+    let authorization_url =
+        authorization_url(&metadata, config, &login_state, &nonce, &code_verifier)
+            .map_err(oidc_error)?;
+
+    sqlx::query("DELETE FROM oidc_login_states WHERE expires_at < NOW()")
+        .execute(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    sqlx::query(
+        r#"
+        INSERT INTO oidc_login_states (state, nonce, code_verifier, expires_at, binding_hash)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(&login_state)
+    .bind(&nonce)
+    .bind(&code_verifier)
+    .bind(Utc::now() + Duration::minutes(LOGIN_STATE_MINUTES))
+    .bind(hash_api_token(&binding))
+    .execute(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    Ok(Json(AuthorizeResponse {
+        authorization_url,
+        binding,
+    }))
+}
+
+pub async fn callback(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<CallbackRequest>,
+) -> Result<Json<LoginResponse>, (StatusCode, String)> {
+    let config = oidc_config(&state)?;
+
+    let record = sqlx::query(
+        r#"
+        DELETE FROM oidc_login_states
+        WHERE state = $1 AND binding_hash = $2 AND expires_at > NOW()
+        RETURNING nonce, code_verifier
+        "#,
+    )
+    .bind(&payload.state)
+    .bind(hash_api_token(&payload.binding))
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    let Some(record) = record else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Login expired; start single sign-on again".into(),
+        ));
+    };
+    let nonce: String = record
+        .try_get("nonce")
+        .map_err(crate::auth::internal_error)?;
+    let code_verifier: String = record
+        .try_get("code_verifier")
+        .map_err(crate::auth::internal_error)?;
+
+    let metadata = discover(config).await.map_err(oidc_error)?;
+    let id_token = exchange_code(&metadata, config, &payload.code, &code_verifier)
+        .await
+        .map_err(oidc_error)?;
+    let claims = verify_id_token(&metadata, config, &id_token, &nonce)
+        .await
+        .map_err(oidc_error)?;
+
+    let (user_id, outcome) = resolve_user(&state, &metadata.issuer, &claims).await?;
+    if let Some(challenge) = two_factor_challenge(&state.pool, user_id).await? {
+        let _ = record_audit_event(
+            &state.pool,
+            Some(user_id),
+            "user.oidc_login",
+            serde_json::json!({
+                "issuer": metadata.issuer,
+                "subject": claims.sub,
+                "outcome": outcome,
+                "two_factor_required": true,
+            }),
+        )
+        .await;
+        return Ok(Json(LoginResponse::TwoFactorRequired(challenge)));
+    }
+    let response = start_session(&state, user_id, &headers, addr).await?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user_id),
+        "user.oidc_login",
+        serde_json::json!({
+            "issuer": metadata.issuer,
+            "subject": claims.sub,
+            "outcome": outcome,
+            "session_id": response.session_id,
+        }),
+    )
+    .await;
+
+    Ok(Json(LoginResponse::Authenticated(response)))
+}
+
+async fn resolve_user(
+    state: &AppState,
+    issuer: &str,
+    claims: &IdTokenClaims,
+) -> Result<(Uuid, &'static str), (StatusCode, String)> {
+    let linked: Option<Uuid> = sqlx::query_scalar(
+        r#"
+        UPDATE user_identities
+        SET last_login_at = NOW(), email = COALESCE($3, email)
+        WHERE issuer = $1 AND subject = $2
+        RETURNING user_id
+        "#,
+    )
+    .bind(issuer)
+    .bind(&claims.sub)
+    .bind(&claims.email)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    if let Some(user_id) = linked {
+        return Ok((user_id, "existing"));
+    }
+
+    let Some(email) = claims
+        .email
+        .as_deref()
+        .map(str::trim)
+        .filter(|email| !email.is_empty())
+    else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "The identity provider did not share an email address".into(),
+        ));
+    };
+
+    let existing: Option<(Uuid, bool)> = sqlx::query_as(
+        "SELECT id, email_verified_at IS NOT NULL FROM users WHERE LOWER(email) = LOWER($1)",
+    )
+    .bind(email)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let (user_id, outcome) = match existing {
+        // Only a verified email proves the person owns the existing account.
+        Some(_) if !claims.email_verified => {
+            return Err((
+                StatusCode::CONFLICT,
+                "An account with this email exists; verify the email with your identity provider to link it".into(),
+            ));
+        }
+        // Anyone can register an address they don't own, so the local side must be verified too.
+        Some((_, false)) => {
+            return Err((
+                StatusCode::CONFLICT,
+                "An account with this email exists; sign in with its password and verify the email to link it".into(),
+            ));
+        }
+        Some((user_id, true)) => (user_id, "linked"),
+        None => {
+            let user_id = Uuid::new_v4();
+            let name = claims
+                .name
+                .clone()
+                .or_else(|| claims.preferred_username.clone())
+                .unwrap_or_else(|| email.to_string());
+            sqlx::query(
+                r#"
+                INSERT INTO users (id, name, email, password_hash, email_verified_at)
+                VALUES ($1, $2, $3, NULL, CASE WHEN $4 THEN NOW() END)
+                "#,
+            )
+            .bind(user_id)
+            .bind(name)
+            .bind(email)
+            .bind(claims.email_verified)
+            .execute(&mut *tx)
+            .await
+            .map_err(crate::auth::internal_error)?;
+            (user_id, "provisioned")
+        }
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO user_identities (id, user_id, issuer, subject, email)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(issuer)
+    .bind(&claims.sub)
+    .bind(email)
+    .execute(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    Ok((user_id, outcome))
+}
+
+fn oidc_config(state: &AppState) -> Result<&OidcConfig, (StatusCode, String)> {
+    state.oidc.as_ref().ok_or((
+        StatusCode::NOT_FOUND,
+        "Single sign-on is not configured".into(),
+    ))
+}
+
+fn oidc_error(error: OidcError) -> (StatusCode, String) {
+    tracing::warn!(error = %error, "single sign-on failed");
+    match error {
+        OidcError::Provider(_) => (
+            StatusCode::BAD_GATEWAY,
+            "The identity provider could not be reached".into(),
+        ),
+        OidcError::InvalidToken(_) => (
+            StatusCode::UNAUTHORIZED,
+            "The identity provider response could not be verified".into(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_user;
+    use crate::services::oidc::IdTokenClaims;
+    use crate::test_support::{create_user, test_pool, test_state};
+    use axum::http::StatusCode;
+    use uuid::Uuid;
+
+    const ISSUER: &str = "https://idp.example.com";
+
+    fn claims(email: &str) -> IdTokenClaims {
+        IdTokenClaims {
+            sub: Uuid::new_v4().to_string(),
+            email: Some(email.to_string()),
+            email_verified: true,
+            name: None,
+            preferred_username: None,
+            nonce: None,
+        }
+    }
+
+    async fn email_of(pool: &sqlx::PgPool, user_id: Uuid) -> String {
+        sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn does_not_link_accounts_with_unverified_email() {
+        let Some(pool) = test_pool().await else { return };
+        let state = test_state(pool);
+        let squatter = create_user(&state.pool, "squatter").await;
+        let claims = claims(&email_of(&state.pool, squatter.id).await);
+
+        let result = resolve_user(&state, ISSUER, &claims).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::CONFLICT);
+        let identities: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM user_identities WHERE user_id = $1")
+                .bind(squatter.id)
+                .fetch_one(&state.pool)
+                .await
+                .unwrap();
+        assert_eq!(identities, 0);
+    }
+
+    #[tokio::test]
+    async fn links_accounts_with_verified_email() {
+        let Some(pool) = test_pool().await else { return };
+        let state = test_state(pool);
+        let user = create_user(&state.pool, "verified").await;
+        sqlx::query("UPDATE users SET email_verified_at = NOW() WHERE id = $1")
+            .bind(user.id)
+            .execute(&state.pool)
+            .await
+            .unwrap();
+        let claims = claims(&email_of(&state.pool, user.id).await);
+
+        let (user_id, outcome) = resolve_user(&state, ISSUER, &claims).await.unwrap();
+        assert_eq!((user_id, outcome), (user.id, "linked"));
+        let (user_id, outcome) = resolve_user(&state, ISSUER, &claims).await.unwrap();
+        assert_eq!((user_id, outcome), (user.id, "existing"));
+    }
+}
@@ -3,11 +3,21 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::Row;
 use std::collections::HashMap;
+use uuid::Uuid;
 
 use chrono::Duration;
-use crate::{audit::record_audit_event, auth::AuthenticatedUser, state::AppState};
+use crate::{
+    audit::record_audit_event,
+    auth::AuthenticatedUser,
+    services::{
+        benchmark::{load_benchmark_series, refresh_benchmark_series, DEFAULT_BENCHMARK},
+        change_history::{record_changes, snapshots, ChangeEntity},
+        market_data::{is_known_source, normalize_symbol},
+    },
+    state::AppState,
+};
 
-const PREFERENCE_KEYS: [&str; 7] = [
+const PREFERENCE_KEYS: [&str; 8] = [
     "categories",
     "strategies",
     "holding_strategies",
@@ -15,6 +25,7 @@ const PREFERENCE_KEYS: [&str; 7] = [
     "export_redaction",
     "asset_refresh_cadence",
     "asset_data_source",
+    "benchmark_symbol",
 ];
 
 #[derive(Serialize)]
@@ -26,6 +37,7 @@ pub struct PreferencesResponse {
     pub export_redaction: String,
     pub asset_refresh_cadence: String,
     pub asset_data_source: String,
+    pub benchmark_symbol: String,
 }
 
 #[derive(Deserialize)]
@@ -37,6 +49,7 @@ pub struct PreferencesUpdate {
     pub export_redaction: Option<String>,
     pub asset_refresh_cadence: Option<String>,
     pub asset_data_source: Option<String>,
+    pub benchmark_symbol: Option<String>,
 }
 
 pub async fn list_preferences(
@@ -64,6 +77,7 @@ pub async fn list_preferences(
     let mut export_redaction = "none".to_string();
     let mut asset_refresh_cadence = "daily".to_string();
     let mut asset_data_source = "stooq".to_string();
+    let mut benchmark_symbol = DEFAULT_BENCHMARK.to_string();
 
     for row in rows {
         let key: String = row.try_get("key").map_err(crate::auth::internal_error)?;
@@ -104,6 +118,11 @@ pub async fn list_preferences(
                     asset_data_source = parsed;
                 }
             }
+            "benchmark_symbol" => {
+                if let Ok(parsed) = serde_json::from_value::<String>(value) {
+                    benchmark_symbol = parsed;
+                }
+            }
             _ => {}
         }
     }
@@ -116,6 +135,7 @@ pub async fn list_preferences(
         export_redaction,
         asset_refresh_cadence,
         asset_data_source,
+        benchmark_symbol,
     }))
 }
 
@@ -167,7 +187,7 @@ pub async fn update_preferences(
     }
     if let Some(asset_data_source) = payload.asset_data_source {
         let normalized = asset_data_source.trim().to_lowercase();
-        if !matches!(normalized.as_str(), "stooq" | "manual" | "broker" | "custom") {
+        if !is_known_source(&normalized) {
             return Err((StatusCode::BAD_REQUEST, "Invalid data source".into()));
         }
         updates.push((
@@ -176,6 +196,18 @@ pub async fn update_preferences(
         ));
     }
 
+    let mut chosen_benchmark = None;
+    if let Some(benchmark_symbol) = payload.benchmark_symbol {
+        let Some(normalized) = normalize_symbol(&benchmark_symbol) else {
+            return Err((StatusCode::BAD_REQUEST, "Invalid benchmark symbol".into()));
+        };
+        updates.push((
+            "benchmark_symbol",
+            serde_json::to_value(&normalized).unwrap_or(Value::Null),
+        ));
+        chosen_benchmark = Some(normalized);
+    }
+
     for (key, value) in updates {
         sqlx::query(
             r#"
@@ -193,29 +225,71 @@ pub async fn update_preferences(
         .map_err(crate::auth::internal_error)?;
     }
 
+    if let Some(symbol) = chosen_benchmark {
+        // Load a newly chosen benchmark now instead of waiting for the nightly job.
+        let pool = state.pool.clone();
+        tokio::spawn(async move {
+            let loaded = load_benchmark_series(&pool, &symbol)
+                .await
+                .is_ok_and(|series| !series.is_empty());
+            if loaded {
+                return;
+            }
+            if let Err(err) = refresh_benchmark_series(&pool, &symbol).await {
+                tracing::warn!(%symbol, error = %err, "benchmark fetch failed");
+            }
+        });
+    }
+
     if let Some(retention_days) = payload.retention_days {
         if retention_days > 0 {
             let cutoff = chrono::Utc::now() - Duration::days(retention_days);
-            let deleted = sqlx::query(
+            let mut tx = state
+                .pool
+                .begin()
+                .await
+                .map_err(crate::auth::internal_error)?;
+            let ids: Vec<Uuid> = sqlx::query_scalar(
                 r#"
-                DELETE FROM transactions
-                USING accounts
-                WHERE transactions.account_id = accounts.id
-                  AND accounts.user_id = $1
+                SELECT transactions.id
+                FROM transactions
+                INNER JOIN accounts ON transactions.account_id = accounts.id
+                WHERE accounts.user_id = $1
                   AND transactions.occurred_at < $2
+                FOR UPDATE OF transactions
                 "#,
             )
             .bind(user.id)
             .bind(cutoff)
-            .execute(&state.pool)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(crate::auth::internal_error)?;
+            let mut before = snapshots(&mut tx, ChangeEntity::Transaction, &ids)
+                .await
+                .map_err(crate::auth::internal_error)?;
+            let deleted: Vec<Uuid> = sqlx::query_scalar(
+                r#"
+                DELETE FROM transactions
+                WHERE id = ANY($1)
+                RETURNING id
+                "#,
+            )
+            .bind(&ids)
+            .fetch_all(&mut *tx)
             .await
             .map_err(crate::auth::internal_error)?;
+            let removed = deleted.len();
+            let changes = deleted.into_iter().map(|id| (id, before.remove(&id))).collect();
+            record_changes(&mut tx, Some(user.id), ChangeEntity::Transaction, changes)
+                .await
+                .map_err(crate::auth::internal_error)?;
+            tx.commit().await.map_err(crate::auth::internal_error)?;
 
             let _ = record_audit_event(
                 &state.pool,
                 Some(user.id),
                 "retention.applied",
-                serde_json::json!({ "retention_days": retention_days, "deleted": deleted.rows_affected() }),
+                serde_json::json!({ "retention_days": retention_days, "deleted": removed }),
             )
             .await;
         }
@@ -3,15 +3,25 @@ use axum::{
     http::StatusCode,
     Json,
 };
+use chrono::{DateTime, Duration, Utc};
 use sqlx::Row;
 use uuid::Uuid;
 
 use crate::{
+    audit::record_audit_event,
     auth::AuthenticatedUser,
     models::{
         CreateRecurringTransactionRequest, RecurringTransaction, UpdateRecurringTransactionRequest,
         UpdateRecurringTransactionResponse,
     },
+    policy::{accessible_account_ids, authorize_account, AccountAction},
+    services::{
+        balances::OPENING_BALANCE_CATEGORY,
+        recurring_detection::{
+            detect_recurring, DetectionTransaction, RecurringSuggestion, AMOUNT_TOLERANCE,
+            DEFAULT_MIN_OCCURRENCES,
+        },
+    },
     state::AppState,
 };
 
@@ -21,35 +31,42 @@ pub struct PaginationParams {
     pub offset: Option<i64>,
 }
 
+#[derive(serde::Deserialize)]
+pub struct SuggestionQueryParams {
+    pub lookback_days: Option<i64>,
+    pub min_occurrences: Option<usize>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct AcceptSuggestionRequest {
+    pub suggestion_id: String,
+    pub lookback_days: Option<i64>,
+    pub min_occurrences: Option<usize>,
+    pub description: Option<String>,
+    pub next_occurs_at: Option<DateTime<Utc>>,
+    pub is_enabled: Option<bool>,
+}
+
 pub async fn list_recurring_transactions(
     State(state): State<AppState>,
     user: AuthenticatedUser,
     Query(params): Query<PaginationParams>,
 ) -> Result<Json<Vec<RecurringTransaction>>, (axum::http::StatusCode, String)> {
-    let limit = params.limit.unwrap_or(100).max(1).min(200);
+    let limit = params.limit.unwrap_or(100).clamp(1, 200);
     let offset = params.offset.unwrap_or(0).max(0);
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
     let records = sqlx::query_as::<_, RecurringTransaction>(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id = $1
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id = $1
-        )
         SELECT rt.id, rt.account_id, rt.amount, rt.currency_code, rt.transaction_type,
                rt.description, rt.interval_days, rt.next_occurs_at, rt.is_enabled
         FROM recurring_transactions rt
         INNER JOIN accounts a ON rt.account_id = a.id
-        WHERE rt.account_id IN (SELECT id FROM accessible_accounts)
+        WHERE rt.account_id = ANY($1)
         ORDER BY rt.next_occurs_at
         LIMIT $2 OFFSET $3
         "#,
     )
-    .bind(user.id)
+    .bind(&account_ids)
     .bind(limit)
     .bind(offset)
     .fetch_all(&state.pool)
@@ -59,12 +76,94 @@ pub async fn list_recurring_transactions(
     Ok(Json(records))
 }
 
+pub async fn list_recurring_suggestions(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<SuggestionQueryParams>,
+) -> Result<Json<Vec<RecurringSuggestion>>, (axum::http::StatusCode, String)> {
+    let suggestions = find_recurring_suggestions(
+        &state,
+        &user,
+        params.lookback_days,
+        params.min_occurrences,
+    )
+    .await?;
+
+    Ok(Json(suggestions))
+}
+
+pub async fn accept_recurring_suggestion(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<AcceptSuggestionRequest>,
+) -> Result<Json<RecurringTransaction>, (axum::http::StatusCode, String)> {
+    let suggestions = find_recurring_suggestions(
+        &state,
+        &user,
+        payload.lookback_days,
+        payload.min_occurrences,
+    )
+    .await?;
+    let Some(suggestion) = suggestions
+        .into_iter()
+        .find(|suggestion| suggestion.suggestion_id == payload.suggestion_id)
+    else {
+        return Err((StatusCode::NOT_FOUND, "Suggestion not found".into()));
+    };
+
+    authorize_account(&state.pool, &user, suggestion.account_id, AccountAction::Edit).await?;
+
+    let mut next_occurs_at = payload.next_occurs_at.unwrap_or(suggestion.next_occurs_at);
+    let now = Utc::now();
+    while next_occurs_at < now {
+        next_occurs_at += Duration::days(suggestion.interval_days as i64);
+    }
+
+    let record = sqlx::query_as::<_, RecurringTransaction>(
+        r#"
+        INSERT INTO recurring_transactions (
+            id, account_id, amount, currency_code, transaction_type, description,
+            interval_days, next_occurs_at, is_enabled
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id, account_id, amount, currency_code, transaction_type,
+                  description, interval_days, next_occurs_at, is_enabled
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(suggestion.account_id)
+    .bind(suggestion.amount)
+    .bind(&suggestion.currency_code)
+    .bind(&suggestion.transaction_type)
+    .bind(payload.description.unwrap_or(suggestion.label))
+    .bind(suggestion.interval_days)
+    .bind(next_occurs_at)
+    .bind(payload.is_enabled.unwrap_or(true))
+    .fetch_one(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "recurring_transaction.suggestion_accepted",
+        serde_json::json!({
+            "recurring_transaction_id": record.id,
+            "suggestion_id": suggestion.suggestion_id,
+            "confidence": suggestion.confidence,
+        }),
+    )
+    .await;
+
+    Ok(Json(record))
+}
+
 pub async fn create_recurring_transaction(
     State(state): State<AppState>,
     user: AuthenticatedUser,
     Json(payload): Json<CreateRecurringTransactionRequest>,
 ) -> Result<Json<RecurringTransaction>, (axum::http::StatusCode, String)> {
-    ensure_account_edit_access(&state, user.id, payload.account_id).await?;
+    authorize_account(&state.pool, &user, payload.account_id, AccountAction::Edit).await?;
 
     let id = Uuid::new_v4();
     let is_enabled = payload.is_enabled.unwrap_or(true);
@@ -117,10 +216,10 @@ pub async fn update_recurring_transaction(
         return Err((StatusCode::NOT_FOUND, "Recurring transaction not found".into()));
     };
 
-    ensure_account_edit_access(&state, user.id, account_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
 
     if let Some(account_id) = payload.account_id {
-        ensure_account_edit_access(&state, user.id, account_id).await?;
+        authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
     }
 
     let record = sqlx::query_as::<_, UpdateRecurringTransactionResponse>(
@@ -176,7 +275,7 @@ pub async fn skip_recurring_transaction(
         return Err((StatusCode::NOT_FOUND, "Recurring transaction not found".into()));
     };
 
-    ensure_account_edit_access(&state, user.id, account_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
 
     let record = sqlx::query_as::<_, UpdateRecurringTransactionResponse>(
         r#"
@@ -222,7 +321,7 @@ pub async fn delete_recurring_transaction(
         return Err((StatusCode::NOT_FOUND, "Recurring transaction not found".into()));
     };
 
-    ensure_account_edit_access(&state, user.id, account_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
 
     let result = sqlx::query(
         r#"
@@ -243,42 +342,98 @@ pub async fn delete_recurring_transaction(
     Ok(StatusCode::NO_CONTENT)
 }
 
-async fn ensure_account_edit_access(
+async fn find_recurring_suggestions(
     state: &AppState,
-    user_id: Uuid,
-    account_id: Uuid,
-) -> Result<(), (StatusCode, String)> {
-    let record = sqlx::query(
+    user: &AuthenticatedUser,
+    lookback_days: Option<i64>,
+    min_occurrences: Option<usize>,
+) -> Result<Vec<RecurringSuggestion>, (StatusCode, String)> {
+    let lookback_days = lookback_days.unwrap_or(365).clamp(30, 1095);
+    let since = Utc::now() - Duration::days(lookback_days);
+    let account_ids = accessible_account_ids(&state.pool, user).await?;
+    let rows = sqlx::query(
         r#"
-        SELECT a.user_id,
-               MAX(CASE WHEN agu.role IN ('edit', 'admin') THEN 1 ELSE 0 END) as can_edit
-        FROM accounts a
-        LEFT JOIN account_group_members agm ON a.id = agm.account_id
-        LEFT JOIN account_group_users agu ON agm.group_id = agu.group_id AND agu.user_id = $1
-        WHERE a.id = $2
-        GROUP BY a.user_id
+        SELECT t.account_id, t.amount, t.currency_code, t.transaction_type,
+               t.merchant, t.description, t.occurred_at
+        FROM transactions t
+        WHERE t.account_id = ANY($1)
+          AND t.occurred_at >= $2
+          AND t.category <> $3
+        ORDER BY t.occurred_at
         "#,
     )
-    .bind(user_id)
-    .bind(account_id)
-    .fetch_optional(&state.pool)
+    .bind(&account_ids)
+    .bind(since)
+    .bind(OPENING_BALANCE_CATEGORY)
+    .fetch_all(&state.pool)
     .await
     .map_err(crate::auth::internal_error)?;
 
-    let Some(record) = record else {
-        return Err((StatusCode::NOT_FOUND, "Account not found".into()));
-    };
+    let transactions = rows
+        .into_iter()
+        .map(|row| {
+            Ok(DetectionTransaction {
+                account_id: row.try_get("account_id").map_err(crate::auth::internal_error)?,
+                amount: row.try_get("amount").map_err(crate::auth::internal_error)?,
+                currency_code: row
+                    .try_get("currency_code")
+                    .map_err(crate::auth::internal_error)?,
+                transaction_type: row
+                    .try_get("transaction_type")
+                    .map_err(crate::auth::internal_error)?,
+                merchant: row.try_get("merchant").map_err(crate::auth::internal_error)?,
+                description: row
+                    .try_get("description")
+                    .map_err(crate::auth::internal_error)?,
+                occurred_at: row
+                    .try_get("occurred_at")
+                    .map_err(crate::auth::internal_error)?,
+            })
+        })
+        .collect::<Result<Vec<_>, (StatusCode, String)>>()?;
 
-    let owner_id: Uuid = record
-        .try_get("user_id")
-        .map_err(crate::auth::internal_error)?;
-    let can_edit: i64 = record
-        .try_get("can_edit")
-        .map_err(crate::auth::internal_error)?;
+    let existing = sqlx::query(
+        r#"
+        SELECT rt.account_id, rt.amount, rt.currency_code, rt.transaction_type, rt.interval_days
+        FROM recurring_transactions rt
+        WHERE rt.is_enabled = TRUE
+          AND rt.account_id = ANY($1)
+        "#,
+    )
+    .bind(
+        transactions
+            .iter()
+            .map(|transaction| transaction.account_id)
+            .collect::<Vec<_>>(),
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
 
-    if owner_id != user_id && can_edit == 0 {
-        return Err((StatusCode::FORBIDDEN, "Forbidden".into()));
+    let mut suggestions = detect_recurring(
+        &transactions,
+        min_occurrences.unwrap_or(DEFAULT_MIN_OCCURRENCES),
+    );
+    for row in existing {
+        let account_id: Uuid = row.try_get("account_id").map_err(crate::auth::internal_error)?;
+        let amount: f64 = row.try_get("amount").map_err(crate::auth::internal_error)?;
+        let currency_code: String = row
+            .try_get("currency_code")
+            .map_err(crate::auth::internal_error)?;
+        let transaction_type: String = row
+            .try_get("transaction_type")
+            .map_err(crate::auth::internal_error)?;
+        let interval_days: i32 = row
+            .try_get("interval_days")
+            .map_err(crate::auth::internal_error)?;
+        suggestions.retain(|suggestion| {
+            !(suggestion.account_id == account_id
+                && suggestion.currency_code == currency_code
+                && suggestion.transaction_type == transaction_type
+                && suggestion.interval_days == interval_days
+                && (suggestion.amount - amount).abs() <= suggestion.amount.abs() * AMOUNT_TOLERANCE)
+        });
     }
 
-    Ok(())
+    Ok(suggestions)
 }
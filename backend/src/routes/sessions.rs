@@ -0,0 +1,67 @@
+use axum::{extract::Path, extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    audit::record_audit_event,
+    auth::{self, AuthenticatedUser},
+    state::AppState,
+};
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct SessionSummary {
+    pub id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub is_current: bool,
+}
+
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<SessionSummary>>, (StatusCode, String)> {
+    let records = sqlx::query_as::<_, SessionSummary>(
+        r#"
+        SELECT id,
+               user_agent,
+               ip_address,
+               created_at,
+               last_used_at,
+               expires_at,
+               id IS NOT DISTINCT FROM $2 as is_current
+        FROM user_sessions
+        WHERE user_id = $1
+          AND revoked_at IS NULL
+          AND expires_at > NOW()
+        ORDER BY last_used_at DESC
+        "#,
+    )
+    .bind(user.id)
+    .bind(user.session_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    Ok(Json(records))
+}
+
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(session_id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    auth::revoke_session(&state.pool, user.id, session_id).await?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "session.revoked",
+        serde_json::json!({ "session_id": session_id }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -4,7 +4,7 @@ use axum::{
     Json,
 };
 use chrono::NaiveDate;
-use sqlx::{QueryBuilder, Row};
+use sqlx::QueryBuilder;
 use uuid::Uuid;
 
 use crate::{
@@ -13,6 +13,11 @@ use crate::{
         CreateTransactionRequest, DailyTransactionTotal, Transaction, UpdateTransactionRequest,
         UpdateTransactionResponse,
     },
+    policy::{accessible_account_ids, authorize_account, AccountAction},
+    services::{
+        balances::OPENING_BALANCE_CATEGORY,
+        change_history::{record_change, snapshot, ChangeEntity},
+    },
     state::AppState,
 };
 
@@ -44,37 +49,20 @@ pub async fn list_transactions(
     user: AuthenticatedUser,
     Query(params): Query<TransactionQueryParams>,
 ) -> Result<Json<Vec<Transaction>>, (axum::http::StatusCode, String)> {
-    let limit = params.limit.unwrap_or(100).max(1).min(200);
+    let limit = params.limit.unwrap_or(100).clamp(1, 200);
     let offset = params.offset.unwrap_or(0).max(0);
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
     let mut query = QueryBuilder::new(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id =
-        "#,
-    );
-    query.push_bind(user.id);
-    query.push(
-        r#"
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id =
-        "#,
-    );
-    query.push_bind(user.id);
-    query.push(
-        r#"
-        )
         SELECT t.id, t.account_id, t.amount, t.currency_code, t.transaction_type,
                t.category, t.merchant, t.description, t.occurred_at
         FROM transactions t
         INNER JOIN accounts a ON t.account_id = a.id
-        WHERE t.account_id IN (SELECT id FROM accessible_accounts)
+        WHERE t.account_id = ANY(
         "#,
     );
+    query.push_bind(account_ids);
+    query.push(")");
 
     if let Some(account_id) = params.account_id {
         query.push(" AND t.account_id = ");
@@ -144,37 +132,26 @@ pub async fn daily_totals(
     user: AuthenticatedUser,
     Query(params): Query<DailyTotalsQueryParams>,
 ) -> Result<Json<Vec<DailyTransactionTotal>>, (axum::http::StatusCode, String)> {
+    let account_ids = accessible_account_ids(&state.pool, &user).await?;
     let mut query = QueryBuilder::new(
         r#"
-        WITH accessible_accounts AS (
-            SELECT id
-            FROM accounts
-            WHERE user_id =
-        "#,
-    );
-    query.push_bind(user.id);
-    query.push(
-        r#"
-            UNION
-            SELECT agm.account_id
-            FROM account_group_members agm
-            INNER JOIN account_group_users agu ON agm.group_id = agu.group_id
-            WHERE agu.user_id =
-        "#,
-    );
-    query.push_bind(user.id);
-    query.push(
-        r#"
-        )
         SELECT DATE(t.occurred_at) as date,
                t.currency_code,
                COALESCE(SUM(t.amount), 0.0) as total
         FROM transactions t
         INNER JOIN accounts a ON t.account_id = a.id
-        WHERE t.account_id IN (SELECT id FROM accessible_accounts)
+        WHERE t.account_id = ANY(
+        "#,
+    );
+    query.push_bind(account_ids);
+    query.push(
+        r#"
+        )
           AND t.transaction_type = 'expense'
+          AND t.category <>
         "#,
     );
+    query.push_bind(OPENING_BALANCE_CATEGORY);
 
     if let Some(account_id) = params.account_id {
         query.push(" AND t.account_id = ");
@@ -230,9 +207,14 @@ pub async fn create_transaction(
     user: AuthenticatedUser,
     Json(payload): Json<CreateTransactionRequest>,
 ) -> Result<Json<Transaction>, (axum::http::StatusCode, String)> {
-    ensure_account_edit_access(&state, user.id, payload.account_id).await?;
+    authorize_account(&state.pool, &user, payload.account_id, AccountAction::Edit).await?;
 
     let id = Uuid::new_v4();
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
     let record = sqlx::query_as::<_, Transaction>(
         r#"
         INSERT INTO transactions (
@@ -253,10 +235,15 @@ pub async fn create_transaction(
     .bind(payload.merchant)
     .bind(payload.description)
     .bind(payload.occurred_at)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(crate::auth::internal_error)?;
 
+    record_change(&mut tx, user.id, ChangeEntity::Transaction, id, None)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
     Ok(Json(record))
 }
 
@@ -282,12 +269,20 @@ pub async fn update_transaction(
         return Err((StatusCode::NOT_FOUND, "Transaction not found".into()));
     };
 
-    ensure_account_edit_access(&state, user.id, account_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
 
     if let Some(account_id) = payload.account_id {
-        ensure_account_edit_access(&state, user.id, account_id).await?;
+        authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
     }
 
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let before = snapshot(&mut tx, ChangeEntity::Transaction, transaction_id)
+        .await
+        .map_err(crate::auth::internal_error)?;
     let record = sqlx::query_as::<_, UpdateTransactionResponse>(
         r#"
         UPDATE transactions
@@ -313,10 +308,15 @@ pub async fn update_transaction(
     .bind(payload.description)
     .bind(payload.occurred_at)
     .bind(transaction_id)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(crate::auth::internal_error)?;
 
+    record_change(&mut tx, user.id, ChangeEntity::Transaction, transaction_id, before)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
     Ok(Json(record))
 }
 
@@ -341,8 +341,16 @@ pub async fn delete_transaction(
         return Err((StatusCode::NOT_FOUND, "Transaction not found".into()));
     };
 
-    ensure_account_edit_access(&state, user.id, account_id).await?;
+    authorize_account(&state.pool, &user, account_id, AccountAction::Edit).await?;
 
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let before = snapshot(&mut tx, ChangeEntity::Transaction, transaction_id)
+        .await
+        .map_err(crate::auth::internal_error)?;
     let result = sqlx::query(
         r#"
         DELETE FROM transactions
@@ -351,7 +359,7 @@ pub async fn delete_transaction(
     )
     .bind(transaction_id)
     .bind(account_id)
-    .execute(&state.pool)
+    .execute(&mut *tx)
     .await
     .map_err(crate::auth::internal_error)?;
 
@@ -359,45 +367,10 @@ pub async fn delete_transaction(
         return Err((StatusCode::NOT_FOUND, "Transaction not found".into()));
     }
 
-    Ok(StatusCode::NO_CONTENT)
-}
-
-async fn ensure_account_edit_access(
-    state: &AppState,
-    user_id: Uuid,
-    account_id: Uuid,
-) -> Result<(), (StatusCode, String)> {
-    let record = sqlx::query(
-        r#"
-        SELECT a.user_id,
-               MAX(CASE WHEN agu.role IN ('edit', 'admin') THEN 1 ELSE 0 END) as can_edit
-        FROM accounts a
-        LEFT JOIN account_group_members agm ON a.id = agm.account_id
-        LEFT JOIN account_group_users agu ON agm.group_id = agu.group_id AND agu.user_id = $1
-        WHERE a.id = $2
-        GROUP BY a.user_id
-        "#,
-    )
-    .bind(user_id)
-    .bind(account_id)
-    .fetch_optional(&state.pool)
-    .await
-    .map_err(crate::auth::internal_error)?;
-
-    let Some(record) = record else {
-        return Err((StatusCode::NOT_FOUND, "Account not found".into()));
-    };
-
-    let owner_id: Uuid = record
-        .try_get("user_id")
-        .map_err(crate::auth::internal_error)?;
-    let can_edit: i64 = record
-        .try_get("can_edit")
+    record_change(&mut tx, user.id, ChangeEntity::Transaction, transaction_id, before)
+        .await
         .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
 
-    if owner_id != user_id && can_edit == 0 {
-        return Err((StatusCode::FORBIDDEN, "Forbidden".into()));
-    }
-
-    Ok(())
+    Ok(StatusCode::NO_CONTENT)
 }
@@ -0,0 +1,362 @@
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::{
+    audit::record_audit_event,
+    auth::{hash_api_token, AuthenticatedUser},
+    services::totp::{
+        base32_decode, generate_recovery_codes, generate_secret, normalize_recovery_code,
+        otpauth_uri, verify_totp,
+    },
+    state::AppState,
+};
+
+const ISSUER: &str = "Firecash";
+const RECOVERY_CODE_COUNT: usize = 10;
+
+#[derive(serde::Deserialize)]
+pub struct TwoFactorCodeRequest {
+    pub code: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub recovery_codes_remaining: i64,
+}
+
+#[derive(serde::Serialize)]
+pub struct TwoFactorEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+pub async fn get_two_factor_status(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<TwoFactorStatus>, (StatusCode, String)> {
+    let confirmed_at: Option<DateTime<Utc>> = sqlx::query_scalar(
+        r#"
+        SELECT confirmed_at
+        FROM user_totp
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user.id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?
+    .flatten();
+
+    let recovery_codes_remaining: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*)
+        FROM user_recovery_codes
+        WHERE user_id = $1 AND used_at IS NULL
+        "#,
+    )
+    .bind(user.id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    Ok(Json(TwoFactorStatus {
+        enabled: confirmed_at.is_some(),
+        confirmed_at,
+        recovery_codes_remaining,
+    }))
+}
+
+pub async fn enroll_two_factor(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<TwoFactorEnrollment>, (StatusCode, String)> {
+    let email: String = sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
+        .bind(user.id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+
+    let secret = generate_secret();
+    let result = sqlx::query(
+        r#"
+        INSERT INTO user_totp (user_id, secret)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE
+        SET secret = EXCLUDED.secret,
+            last_used_step = NULL,
+            created_at = NOW()
+        WHERE user_totp.confirmed_at IS NULL
+        "#,
+    )
+    .bind(user.id)
+    .bind(&secret)
+    .execute(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            "Two-factor authentication is already enabled".into(),
+        ));
+    }
+
+    Ok(Json(TwoFactorEnrollment {
+        otpauth_uri: otpauth_uri(ISSUER, &email, &secret),
+        secret,
+    }))
+}
+
+pub async fn confirm_two_factor(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<TwoFactorCodeRequest>,
+) -> Result<Json<RecoveryCodes>, (StatusCode, String)> {
+    let secret: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT secret
+        FROM user_totp
+        WHERE user_id = $1 AND confirmed_at IS NULL
+        "#,
+    )
+    .bind(user.id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let Some(secret) = secret else {
+        return Err((StatusCode::BAD_REQUEST, "Start enrollment first".into()));
+    };
+    let secret_bytes = base32_decode(&secret)
+        .ok_or_else(|| crate::auth::internal_error("stored TOTP secret is not valid base32"))?;
+    let Some(step) = verify_totp(&secret_bytes, &payload.code, now_unix(), None) else {
+        record_failed_code(&state.pool, user.id, "confirm").await;
+        return Err((StatusCode::BAD_REQUEST, "Invalid code".into()));
+    };
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    sqlx::query(
+        r#"
+        UPDATE user_totp
+        SET confirmed_at = NOW(), last_used_step = $2
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user.id)
+    .bind(step as i64)
+    .execute(&mut *tx)
+    .await
+    .map_err(crate::auth::internal_error)?;
+    let recovery_codes = replace_recovery_codes(&mut tx, user.id).await?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "user.2fa_enabled",
+        serde_json::json!({}),
+    )
+    .await;
+
+    Ok(Json(RecoveryCodes { recovery_codes }))
+}
+
+pub async fn disable_two_factor(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<TwoFactorCodeRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    if !verify_second_factor(&state.pool, user.id, &payload.code).await? {
+        record_failed_code(&state.pool, user.id, "disable").await;
+        return Err((StatusCode::BAD_REQUEST, "Invalid code".into()));
+    }
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    sqlx::query("DELETE FROM user_totp WHERE user_id = $1")
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = $1")
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "user.2fa_disabled",
+        serde_json::json!({}),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn regenerate_recovery_codes(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<TwoFactorCodeRequest>,
+) -> Result<Json<RecoveryCodes>, (StatusCode, String)> {
+    if !verify_second_factor(&state.pool, user.id, &payload.code).await? {
+        record_failed_code(&state.pool, user.id, "recovery_codes").await;
+        return Err((StatusCode::BAD_REQUEST, "Invalid code".into()));
+    }
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(crate::auth::internal_error)?;
+    let recovery_codes = replace_recovery_codes(&mut tx, user.id).await?;
+    tx.commit().await.map_err(crate::auth::internal_error)?;
+
+    let _ = record_audit_event(
+        &state.pool,
+        Some(user.id),
+        "user.2fa_recovery_codes_regenerated",
+        serde_json::json!({}),
+    )
+    .await;
+
+    Ok(Json(RecoveryCodes { recovery_codes }))
+}
+
+pub(crate) async fn verify_second_factor(
+    pool: &PgPool,
+    user_id: Uuid,
+    code: &str,
+) -> Result<bool, (StatusCode, String)> {
+    let record = sqlx::query(
+        r#"
+        SELECT secret, last_used_step
+        FROM user_totp
+        WHERE user_id = $1 AND confirmed_at IS NOT NULL
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    let Some(record) = record else {
+        return Ok(false);
+    };
+    let secret: String = record
+        .try_get("secret")
+        .map_err(crate::auth::internal_error)?;
+    let last_used_step: Option<i64> = record
+        .try_get("last_used_step")
+        .map_err(crate::auth::internal_error)?;
+    let secret_bytes = base32_decode(&secret)
+        .ok_or_else(|| crate::auth::internal_error("stored TOTP secret is not valid base32"))?;
+
+    if let Some(step) = verify_totp(
+        &secret_bytes,
+        code,
+        now_unix(),
+        last_used_step.map(|step| step as u64),
+    ) {
+        // The guard on last_used_step stops two requests racing with one code.
+        let result = sqlx::query(
+            r#"
+            UPDATE user_totp
+            SET last_used_step = $2
+            WHERE user_id = $1
+              AND (last_used_step IS NULL OR last_used_step < $2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(step as i64)
+        .execute(pool)
+        .await
+        .map_err(crate::auth::internal_error)?;
+        return Ok(result.rows_affected() == 1);
+    }
+
+    let normalized = normalize_recovery_code(code);
+    if normalized.is_empty() {
+        return Ok(false);
+    }
+    let result = sqlx::query(
+        r#"
+        UPDATE user_recovery_codes
+        SET used_at = NOW()
+        WHERE id = (
+            SELECT id
+            FROM user_recovery_codes
+            WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+            LIMIT 1
+        )
+        "#,
+    )
+    .bind(user_id)
+    .bind(hash_api_token(&normalized))
+    .execute(pool)
+    .await
+    .map_err(crate::auth::internal_error)?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+pub(crate) async fn record_failed_code(pool: &PgPool, user_id: Uuid, context: &str) {
+    let _ = record_audit_event(
+        pool,
+        Some(user_id),
+        "user.2fa_failed",
+        serde_json::json!({ "context": context }),
+    )
+    .await;
+}
+
+async fn replace_recovery_codes(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+) -> Result<Vec<String>, (StatusCode, String)> {
+    sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+
+    let codes = generate_recovery_codes(RECOVERY_CODE_COUNT);
+    for code in &codes {
+        sqlx::query(
+            r#"
+            INSERT INTO user_recovery_codes (id, user_id, code_hash)
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(hash_api_token(&normalize_recovery_code(code)))
+        .execute(&mut **tx)
+        .await
+        .map_err(crate::auth::internal_error)?;
+    }
+
+    Ok(codes)
+}
+
+fn now_unix() -> u64 {
+    Utc::now().timestamp().max(0) as u64
+}
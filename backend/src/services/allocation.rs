@@ -0,0 +1,224 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+pub const ALLOCATION_DIMENSIONS: [&str; 2] = ["strategy", "asset_type"];
+
+pub const UNASSIGNED_BUCKET: &str = "Unassigned";
+
+pub const WEIGHT_TOLERANCE: f64 = 0.001;
+
+pub struct AllocationHolding {
+    pub asset_id: Uuid,
+    pub symbol: String,
+    pub bucket: String,
+    pub value: f64,
+    pub unit_price: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BucketAllocation {
+    pub bucket: String,
+    pub current_value: f64,
+    pub current_weight: f64,
+    pub target_weight: Option<f64>,
+    pub drift: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RebalanceTrade {
+    pub bucket: String,
+    pub asset_id: Option<Uuid>,
+    pub symbol: Option<String>,
+    pub side: &'static str,
+    pub amount: f64,
+    pub quantity: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AllocationReport {
+    pub total_value: f64,
+    pub buckets: Vec<BucketAllocation>,
+    pub trades: Vec<RebalanceTrade>,
+}
+
+pub fn validate_targets(targets: &BTreeMap<String, f64>) -> Result<(), String> {
+    if targets
+        .values()
+        .any(|weight| !weight.is_finite() || *weight < 0.0 || *weight > 1.0)
+    {
+        return Err("Target weights must be between 0 and 1".to_string());
+    }
+    let total: f64 = targets.values().sum();
+    if !targets.is_empty() && (total - 1.0).abs() > WEIGHT_TOLERANCE {
+        return Err(format!("Target weights must sum to 1 (got {total:.4})"));
+    }
+    Ok(())
+}
+
+pub fn rebalance(
+    holdings: &[AllocationHolding],
+    targets: &BTreeMap<String, f64>,
+    min_trade: f64,
+) -> AllocationReport {
+    let total_value: f64 = holdings.iter().map(|holding| holding.value).sum();
+    let mut values: BTreeMap<&str, f64> = BTreeMap::new();
+    for holding in holdings {
+        *values.entry(holding.bucket.as_str()).or_default() += holding.value;
+    }
+    for bucket in targets.keys() {
+        values.entry(bucket.as_str()).or_default();
+    }
+
+    let has_targets = !targets.is_empty();
+    let mut buckets = Vec::new();
+    let mut trades = Vec::new();
+    for (bucket, current_value) in values {
+        let current_weight = if total_value > 0.0 {
+            current_value / total_value
+        } else {
+            0.0
+        };
+        let target_weight = has_targets.then(|| targets.get(bucket).copied().unwrap_or(0.0));
+        buckets.push(BucketAllocation {
+            bucket: bucket.to_string(),
+            current_value,
+            current_weight,
+            target_weight,
+            drift: target_weight.map(|target| current_weight - target),
+        });
+
+        let Some(target) = target_weight else {
+            continue;
+        };
+        let delta = target * total_value - current_value;
+        if delta.abs() < min_trade || delta.abs() < f64::EPSILON {
+            continue;
+        }
+        let side = if delta > 0.0 { "buy" } else { "sell" };
+        let members: Vec<&AllocationHolding> = holdings
+            .iter()
+            .filter(|holding| holding.bucket == bucket && holding.value > 0.0)
+            .collect();
+        if members.is_empty() {
+            trades.push(RebalanceTrade {
+                bucket: bucket.to_string(),
+                asset_id: None,
+                symbol: None,
+                side,
+                amount: delta.abs(),
+                quantity: None,
+            });
+            continue;
+        }
+        for holding in members {
+            let amount = delta.abs() * holding.value / current_value;
+            if amount < min_trade {
+                continue;
+            }
+            trades.push(RebalanceTrade {
+                bucket: bucket.to_string(),
+                asset_id: Some(holding.asset_id),
+                symbol: Some(holding.symbol.clone()),
+                side,
+                amount,
+                quantity: (holding.unit_price > 0.0).then(|| amount / holding.unit_price),
+            });
+        }
+    }
+
+    AllocationReport {
+        total_value,
+        buckets,
+        trades,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rebalance, validate_targets, AllocationHolding};
+    use std::collections::BTreeMap;
+    use uuid::Uuid;
+
+    fn holding(symbol: &str, bucket: &str, value: f64, unit_price: f64) -> AllocationHolding {
+        AllocationHolding {
+            asset_id: Uuid::new_v4(),
+            symbol: symbol.to_string(),
+            bucket: bucket.to_string(),
+            value,
+            unit_price,
+        }
+    }
+
+    fn targets(entries: &[(&str, f64)]) -> BTreeMap<String, f64> {
+        entries
+            .iter()
+            .map(|(bucket, weight)| (bucket.to_string(), *weight))
+            .collect()
+    }
+
+    #[test]
+    fn validates_target_weights() {
+        assert!(validate_targets(&targets(&[("Stock", 0.6), ("Bond", 0.4)])).is_ok());
+        assert!(validate_targets(&targets(&[("Stock", 0.6), ("Bond", 0.3)])).is_err());
+        assert!(validate_targets(&targets(&[("Stock", 1.2), ("Bond", -0.2)])).is_err());
+        assert!(validate_targets(&BTreeMap::new()).is_ok());
+    }
+
+    #[test]
+    fn reports_drift_and_rebalancing_trades() {
+        let holdings = vec![
+            holding("VTI", "Stock", 5000.0, 250.0),
+            holding("VXUS", "Stock", 2000.0, 50.0),
+            holding("BND", "Bond", 3000.0, 75.0),
+        ];
+        let report = rebalance(&holdings, &targets(&[("Stock", 0.6), ("Bond", 0.4)]), 50.0);
+        assert_eq!(report.total_value, 10000.0);
+
+        let stock = report.buckets.iter().find(|b| b.bucket == "Stock").unwrap();
+        assert!((stock.current_weight - 0.7).abs() < 1e-12);
+        assert!((stock.drift.unwrap() - 0.1).abs() < 1e-12);
+
+        let vti = report
+            .trades
+            .iter()
+            .find(|t| t.symbol.as_deref() == Some("VTI"))
+            .unwrap();
+        assert_eq!(vti.side, "sell");
+        assert!((vti.amount - 1000.0 * 5.0 / 7.0).abs() < 1e-9);
+        let bnd = report
+            .trades
+            .iter()
+            .find(|t| t.symbol.as_deref() == Some("BND"))
+            .unwrap();
+        assert_eq!(bnd.side, "buy");
+        assert!((bnd.amount - 1000.0).abs() < 1e-9);
+        assert!((bnd.quantity.unwrap() - 1000.0 / 75.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn skips_small_trades_and_suggests_empty_buckets() {
+        let holdings = vec![holding("VTI", "Stock", 9950.0, 100.0)];
+        let report = rebalance(
+            &holdings,
+            &targets(&[("Stock", 0.995), ("Cash", 0.005)]),
+            100.0,
+        );
+        assert!(report.trades.is_empty());
+
+        let report = rebalance(&holdings, &targets(&[("Stock", 0.9), ("Cash", 0.1)]), 100.0);
+        let cash = report.trades.iter().find(|t| t.bucket == "Cash").unwrap();
+        assert_eq!(cash.side, "buy");
+        assert!(cash.asset_id.is_none());
+        assert!((cash.amount - 995.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn untargeted_buckets_have_no_trades_without_targets() {
+        let holdings = vec![holding("VTI", "Stock", 100.0, 10.0)];
+        let report = rebalance(&holdings, &BTreeMap::new(), 0.0);
+        assert!(report.trades.is_empty());
+        assert_eq!(report.buckets[0].target_weight, None);
+        assert_eq!(report.buckets[0].current_weight, 1.0);
+    }
+}
@@ -0,0 +1,259 @@
+use chrono::{Duration, NaiveDate, NaiveTime, Utc};
+use sqlx::{postgres::PgPool, Row};
+use std::collections::HashSet;
+use std::fmt;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::services::crypto::{crypto_pair, settlement_currency};
+use crate::services::forex::{convert_amount, load_usd_rates};
+use crate::services::market_data::{fetch_candles, provider_chain, resolve_symbol, AssetClass};
+use crate::services::pricing::Candle;
+
+const DEFAULT_BACKFILL_YEARS: i64 = 5;
+const PENDING_BATCH_SIZE: i64 = 25;
+
+#[derive(Debug)]
+pub enum BackfillError {
+    Provider(String),
+    Database(sqlx::Error),
+}
+
+impl fmt::Display for BackfillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Provider(message) => write!(f, "price provider failed: {message}"),
+            Self::Database(err) => write!(f, "database error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for BackfillError {}
+
+impl From<sqlx::Error> for BackfillError {
+    fn from(err: sqlx::Error) -> Self {
+        Self::Database(err)
+    }
+}
+
+fn close_time() -> NaiveTime {
+    NaiveTime::from_hms_opt(22, 0, 0).expect("valid close time")
+}
+
+pub fn configured_backfill_start() -> NaiveDate {
+    backfill_start(
+        std::env::var("PRICE_BACKFILL_START").ok().as_deref(),
+        Utc::now().date_naive(),
+    )
+}
+
+fn backfill_start(value: Option<&str>, today: NaiveDate) -> NaiveDate {
+    value
+        .and_then(|value| NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok())
+        .unwrap_or_else(|| today - Duration::days(365 * DEFAULT_BACKFILL_YEARS))
+}
+
+fn select_backfill_rows(
+    candles: &[Candle],
+    start: NaiveDate,
+    existing_days: &HashSet<NaiveDate>,
+) -> Vec<(NaiveDate, f64)> {
+    let mut seen = existing_days.clone();
+    let mut rows: Vec<(NaiveDate, f64)> = candles
+        .iter()
+        .filter_map(|candle| {
+            let day = NaiveDate::parse_from_str(&candle.date, "%Y-%m-%d").ok()?;
+            (day >= start && candle.close.is_finite() && seen.insert(day))
+                .then_some((day, candle.close))
+        })
+        .collect();
+    rows.sort_by_key(|(day, _)| *day);
+    rows
+}
+
+pub async fn backfill_asset(
+    pool: &PgPool,
+    asset_id: Uuid,
+    start: NaiveDate,
+) -> Result<usize, BackfillError> {
+    let row = sqlx::query(
+        r#"
+        SELECT a.symbol,
+               a.asset_type,
+               a.currency_code,
+               COALESCE(a.data_source, up.value #>> '{}') as data_source
+        FROM assets a
+        INNER JOIN accounts acc ON a.account_id = acc.id
+        LEFT JOIN user_preferences up
+          ON up.user_id = acc.user_id AND up.key = 'asset_data_source'
+        WHERE a.id = $1
+        "#,
+    )
+    .bind(asset_id)
+    .fetch_one(pool)
+    .await?;
+    let symbol: String = row.try_get("symbol")?;
+    let asset_type: String = row.try_get("asset_type")?;
+    let currency_code: String = row.try_get("currency_code")?;
+    let data_source: Option<String> = row.try_get("data_source")?;
+
+    let class = AssetClass::from_asset_type(&asset_type);
+    let lookup = resolve_symbol(class, &symbol, &currency_code)
+        .ok_or_else(|| BackfillError::Provider(format!("symbol cannot be priced: {symbol}")))?;
+    let chain = provider_chain(data_source.as_deref(), class);
+    let inserted = if chain.is_empty() {
+        0
+    } else {
+        let mut candles = fetch_candles(&chain, &lookup)
+            .await
+            .map_err(BackfillError::Provider)?;
+        // Crypto pairs may be quoted in a currency other than the asset's.
+        let quote_currency = match class {
+            AssetClass::Crypto => crypto_pair(&symbol, &currency_code)
+                .map(|(_, quote)| settlement_currency(&quote).to_string()),
+            AssetClass::Equity => None,
+        };
+        if let Some(quote_currency) = quote_currency.filter(|quote| *quote != currency_code) {
+            let rates = load_usd_rates(pool).await?;
+            for candle in &mut candles {
+                candle.close = convert_amount(candle.close, &quote_currency, &currency_code, &rates)
+                    .ok_or_else(|| {
+                        BackfillError::Provider(format!(
+                            "no FX rate from {quote_currency} to {currency_code}"
+                        ))
+                    })?;
+            }
+        }
+        let existing_days: HashSet<NaiveDate> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT (recorded_at AT TIME ZONE 'UTC')::date
+            FROM price_history
+            WHERE asset_id = $1
+              AND recorded_at >= $2
+            "#,
+        )
+        .bind(asset_id)
+        .bind(start.and_time(NaiveTime::MIN).and_utc())
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+        let rows = select_backfill_rows(&candles, start, &existing_days);
+        let (days, closes): (Vec<NaiveDate>, Vec<f64>) = rows.into_iter().unzip();
+        let recorded_at: Vec<_> = days
+            .iter()
+            .map(|day| day.and_time(close_time()).and_utc())
+            .collect();
+        // Provider closes are split-adjusted; store them in the units of their day.
+        sqlx::query(
+            r#"
+            INSERT INTO price_history (id, asset_id, price, currency_code, recorded_at, close_day)
+            SELECT gen_random_uuid(), $1, c.price * COALESCE(sf.factor, 1), $2,
+                   c.recorded_at, c.close_day
+            FROM UNNEST($3::TIMESTAMPTZ[], $4::DOUBLE PRECISION[], $5::DATE[])
+                AS c(recorded_at, price, close_day)
+            LEFT JOIN asset_split_factors sf ON sf.asset_id = $1
+                AND c.recorded_at >= sf.valid_from AND c.recorded_at < sf.valid_until
+            ON CONFLICT (asset_id, close_day) DO NOTHING
+            "#,
+        )
+        .bind(asset_id)
+        .bind(&currency_code)
+        .bind(&recorded_at)
+        .bind(&closes)
+        .bind(&days)
+        .execute(pool)
+        .await?
+        .rows_affected() as usize
+    };
+
+    sqlx::query("UPDATE assets SET history_backfilled_at = NOW() WHERE id = $1")
+        .bind(asset_id)
+        .execute(pool)
+        .await?;
+    info!(%asset_id, %symbol, inserted, "backfilled price history");
+    Ok(inserted)
+}
+
+pub async fn backfill_pending_assets(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let asset_ids: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT id
+        FROM assets
+        WHERE history_backfilled_at IS NULL
+          AND valuation_mode = 'market'
+        ORDER BY created_at
+        LIMIT $1
+        "#,
+    )
+    .bind(PENDING_BATCH_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    let start = configured_backfill_start();
+    let mut inserted = 0usize;
+    for asset_id in asset_ids {
+        match backfill_asset(pool, asset_id, start).await {
+            Ok(count) => inserted += count,
+            Err(BackfillError::Database(err)) => return Err(err),
+            Err(err) => {
+                warn!(%asset_id, error = %err, "price backfill failed");
+                // Mark the attempt so unknown symbols are not retried every run;
+                // the per-asset endpoint can retry on demand.
+                sqlx::query("UPDATE assets SET history_backfilled_at = NOW() WHERE id = $1")
+                    .bind(asset_id)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+    }
+    Ok(inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backfill_start, select_backfill_rows};
+    use crate::services::pricing::Candle;
+    use chrono::NaiveDate;
+    use std::collections::HashSet;
+
+    fn candle(date: &str, close: f64) -> Candle {
+        Candle {
+            date: date.to_string(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+        }
+    }
+
+    fn day(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn backfill_start_reads_override_or_defaults() {
+        let today = day("2024-06-01");
+        assert_eq!(backfill_start(Some("2020-01-02"), today), day("2020-01-02"));
+        assert_eq!(backfill_start(Some("soon"), today), day("2019-06-03"));
+        assert_eq!(backfill_start(None, today), day("2019-06-03"));
+    }
+
+    #[test]
+    fn select_backfill_rows_skips_existing_and_early_days() {
+        let candles = vec![
+            candle("2023-12-29", 99.0),
+            candle("2024-01-03", 102.0),
+            candle("2024-01-02", 101.0),
+            candle("2024-01-02", 101.5),
+            candle("2024-01-04", 103.0),
+            candle("bad", 1.0),
+        ];
+        let existing = HashSet::from([day("2024-01-04")]);
+        let rows = select_backfill_rows(&candles, day("2024-01-01"), &existing);
+        assert_eq!(
+            rows,
+            vec![(day("2024-01-02"), 101.0), (day("2024-01-03"), 102.0)]
+        );
+    }
+}
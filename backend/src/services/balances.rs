@@ -0,0 +1,34 @@
+pub const OPENING_BALANCE_CATEGORY: &str = "Opening Balance";
+
+pub const BALANCE_TOLERANCE: f64 = 0.005;
+
+pub fn balance_entry(amount: f64) -> (&'static str, f64) {
+    if amount < 0.0 {
+        ("expense", -amount)
+    } else {
+        ("income", amount)
+    }
+}
+
+pub fn balance_discrepancy(asserted: f64, computed: f64) -> Option<f64> {
+    let difference = asserted - computed;
+    (difference.abs() >= BALANCE_TOLERANCE).then_some(difference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{balance_discrepancy, balance_entry};
+
+    #[test]
+    fn negative_opening_balances_are_expenses() {
+        assert_eq!(balance_entry(1250.0), ("income", 1250.0));
+        assert_eq!(balance_entry(-480.5), ("expense", 480.5));
+    }
+
+    #[test]
+    fn reports_discrepancies_beyond_a_cent() {
+        assert_eq!(balance_discrepancy(100.0, 100.001), None);
+        assert_eq!(balance_discrepancy(100.0, 90.0), Some(10.0));
+        assert_eq!(balance_discrepancy(-50.0, -20.0), Some(-30.0));
+    }
+}
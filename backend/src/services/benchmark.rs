@@ -0,0 +1,144 @@
+use chrono::NaiveDate;
+use sqlx::postgres::PgPool;
+use tracing::{info, warn};
+
+use crate::services::backfill::{configured_backfill_start, BackfillError};
+use crate::services::market_data::{fetch_candles, provider_chain, AssetClass};
+
+pub const DEFAULT_BENCHMARK: &str = "SPY";
+
+pub async fn refresh_benchmark_series(pool: &PgPool, symbol: &str) -> Result<usize, BackfillError> {
+    let start = configured_backfill_start();
+    let candles = fetch_candles(&provider_chain(None, AssetClass::Equity), symbol)
+        .await
+        .map_err(BackfillError::Provider)?;
+    let (days, closes): (Vec<NaiveDate>, Vec<f64>) = candles
+        .iter()
+        .filter_map(|candle| {
+            let day = NaiveDate::parse_from_str(&candle.date, "%Y-%m-%d").ok()?;
+            (day >= start && candle.close.is_finite()).then_some((day, candle.close))
+        })
+        .unzip();
+    let written = sqlx::query(
+        r#"
+        INSERT INTO benchmark_prices (symbol, price_date, close)
+        SELECT $1, c.price_date, c.close
+        FROM UNNEST($2::DATE[], $3::DOUBLE PRECISION[]) AS c(price_date, close)
+        ON CONFLICT (symbol, price_date)
+        DO UPDATE SET close = EXCLUDED.close, recorded_at = NOW()
+        "#,
+    )
+    .bind(symbol)
+    .bind(&days)
+    .bind(&closes)
+    .execute(pool)
+    .await?
+    .rows_affected() as usize;
+    info!(symbol, written, "refreshed benchmark series");
+    Ok(written)
+}
+
+pub async fn refresh_benchmarks(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let symbols: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT value #>> '{}'
+        FROM user_preferences
+        WHERE key = 'benchmark_symbol'
+          AND value #>> '{}' IS NOT NULL
+        UNION
+        SELECT $1
+        "#,
+    )
+    .bind(DEFAULT_BENCHMARK)
+    .fetch_all(pool)
+    .await?;
+
+    let mut written = 0usize;
+    for symbol in symbols {
+        match refresh_benchmark_series(pool, &symbol).await {
+            Ok(count) => written += count,
+            Err(BackfillError::Database(err)) => return Err(err),
+            Err(err) => warn!(%symbol, error = %err, "benchmark refresh failed"),
+        }
+    }
+    Ok(written)
+}
+
+pub async fn load_benchmark_series(
+    pool: &PgPool,
+    symbol: &str,
+) -> Result<Vec<(NaiveDate, f64)>, sqlx::Error> {
+    sqlx::query_as::<_, (NaiveDate, f64)>(
+        r#"
+        SELECT price_date, close
+        FROM benchmark_prices
+        WHERE symbol = $1
+        ORDER BY price_date
+        "#,
+    )
+    .bind(symbol)
+    .fetch_all(pool)
+    .await
+}
+
+pub fn close_on(series: &[(NaiveDate, f64)], day: NaiveDate) -> Option<f64> {
+    let index = series.partition_point(|(date, _)| *date <= day);
+    index.checked_sub(1).map(|index| series[index].1)
+}
+
+pub fn period_return(series: &[(NaiveDate, f64)], start: NaiveDate, end: NaiveDate) -> Option<f64> {
+    let start_close = close_on(series, start)?;
+    let end_close = close_on(series, end)?;
+    (start_close > 0.0).then(|| end_close / start_close - 1.0)
+}
+
+pub fn weighted_return(holdings: &[(f64, f64)]) -> Option<f64> {
+    let invested: f64 = holdings.iter().map(|(invested, _)| invested).sum();
+    (invested > 0.0).then(|| {
+        holdings
+            .iter()
+            .map(|(invested, period_return)| invested * period_return)
+            .sum::<f64>()
+            / invested
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{close_on, period_return, weighted_return};
+    use chrono::NaiveDate;
+
+    fn day(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn series() -> Vec<(NaiveDate, f64)> {
+        vec![
+            (day("2024-01-02"), 100.0),
+            (day("2024-01-03"), 102.0),
+            (day("2024-01-05"), 110.0),
+        ]
+    }
+
+    #[test]
+    fn close_on_uses_previous_trading_day() {
+        let series = series();
+        assert_eq!(close_on(&series, day("2024-01-04")), Some(102.0));
+        assert_eq!(close_on(&series, day("2024-01-05")), Some(110.0));
+        assert_eq!(close_on(&series, day("2023-12-01")), None);
+        assert_eq!(close_on(&[], day("2024-01-04")), None);
+    }
+
+    #[test]
+    fn period_return_spans_holding_period() {
+        let value = period_return(&series(), day("2024-01-02"), day("2024-01-06")).unwrap();
+        assert!((value - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn weighted_return_weights_by_invested_capital() {
+        let value = weighted_return(&[(300.0, 0.1), (100.0, -0.2)]).unwrap();
+        assert!((value - 0.025).abs() < 1e-12);
+        assert_eq!(weighted_return(&[]), None);
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+use sqlx::{PgConnection, QueryBuilder};
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChangeEntity {
+    Account,
+    Transaction,
+    Asset,
+}
+
+impl ChangeEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Account => "account",
+            Self::Transaction => "transaction",
+            Self::Asset => "asset",
+        }
+    }
+
+    fn table(&self) -> &'static str {
+        match self {
+            Self::Account => "accounts",
+            Self::Transaction => "transactions",
+            Self::Asset => "assets",
+        }
+    }
+
+    fn account_ids(&self, entity_id: Uuid, snapshot: &Value) -> Vec<Uuid> {
+        match self {
+            Self::Account => vec![entity_id],
+            Self::Transaction | Self::Asset => snapshot
+                .get("account_id")
+                .and_then(Value::as_str)
+                .and_then(|value| Uuid::parse_str(value).ok())
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+const UNTRACKED_FIELDS: [&str; 4] = ["id", "user_id", "created_at", "updated_at"];
+
+const INSERT_BATCH_SIZE: usize = 1000;
+
+pub async fn snapshot(
+    conn: &mut PgConnection,
+    entity: ChangeEntity,
+    entity_id: Uuid,
+) -> Result<Option<Value>, sqlx::Error> {
+    Ok(snapshots(conn, entity, &[entity_id]).await?.remove(&entity_id))
+}
+
+pub async fn snapshots(
+    conn: &mut PgConnection,
+    entity: ChangeEntity,
+    entity_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Value>, sqlx::Error> {
+    if entity_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let query = format!(
+        "SELECT id, to_jsonb(t) FROM {} t WHERE id = ANY($1) FOR UPDATE",
+        entity.table()
+    );
+    let rows: Vec<(Uuid, Value)> = sqlx::query_as(&query)
+        .bind(entity_ids)
+        .fetch_all(conn)
+        .await?;
+    Ok(rows.into_iter().collect())
+}
+
+pub fn field_changes(before: Option<&Value>, after: Option<&Value>) -> Map<String, Value> {
+    let empty = Map::new();
+    let before = before.and_then(Value::as_object).unwrap_or(&empty);
+    let after = after.and_then(Value::as_object).unwrap_or(&empty);
+    let mut fields: Vec<&String> = before.keys().chain(after.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    let mut changes = Map::new();
+    for field in fields {
+        if UNTRACKED_FIELDS.contains(&field.as_str()) {
+            continue;
+        }
+        let old = before.get(field).unwrap_or(&Value::Null);
+        let new = after.get(field).unwrap_or(&Value::Null);
+        if old != new {
+            changes.insert(field.clone(), json!({ "old": old, "new": new }));
+        }
+    }
+    changes
+}
+
+pub async fn record_change(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    entity: ChangeEntity,
+    entity_id: Uuid,
+    before: Option<Value>,
+) -> Result<(), sqlx::Error> {
+    record_changes(conn, Some(user_id), entity, vec![(entity_id, before)]).await
+}
+
+pub async fn record_changes(
+    conn: &mut PgConnection,
+    user_id: Option<Uuid>,
+    entity: ChangeEntity,
+    before: Vec<(Uuid, Option<Value>)>,
+) -> Result<(), sqlx::Error> {
+    let entity_ids: Vec<Uuid> = before.iter().map(|(entity_id, _)| *entity_id).collect();
+    let mut after = snapshots(&mut *conn, entity, &entity_ids).await?;
+
+    let mut rows = Vec::new();
+    for (entity_id, before) in before {
+        let after = after.remove(&entity_id);
+        let action = match (&before, &after) {
+            (None, Some(_)) => "created",
+            (Some(_), Some(_)) => "updated",
+            (Some(_), None) => "deleted",
+            (None, None) => continue,
+        };
+        let changes = field_changes(before.as_ref(), after.as_ref());
+        if changes.is_empty() {
+            continue;
+        }
+        let mut account_ids: Vec<Uuid> = before
+            .iter()
+            .chain(after.iter())
+            .flat_map(|snapshot| entity.account_ids(entity_id, snapshot))
+            .collect();
+        account_ids.sort();
+        account_ids.dedup();
+        rows.push((entity_id, account_ids, action, Value::Object(changes)));
+    }
+
+    for chunk in rows.chunks(INSERT_BATCH_SIZE) {
+        let mut query = QueryBuilder::new(
+            "INSERT INTO change_history \
+             (id, user_id, entity_type, entity_id, account_ids, action, changes) ",
+        );
+        query.push_values(chunk, |mut row, (entity_id, account_ids, action, changes)| {
+            row.push_bind(Uuid::new_v4())
+                .push_bind(user_id)
+                .push_bind(entity.as_str())
+                .push_bind(*entity_id)
+                .push_bind(account_ids)
+                .push_bind(*action)
+                .push_bind(changes);
+        });
+        query.build().execute(&mut *conn).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{field_changes, ChangeEntity};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[test]
+    fn diffs_only_changed_tracked_fields() {
+        let before = json!({
+            "id": "a",
+            "amount": 12.5,
+            "category": "Food",
+            "merchant": null,
+            "created_at": "2024-01-01T00:00:00Z",
+        });
+        let after = json!({
+            "id": "a",
+            "amount": 15.0,
+            "category": "Food",
+            "merchant": "Bakery",
+            "created_at": "2024-02-01T00:00:00Z",
+        });
+        let changes = field_changes(Some(&before), Some(&after));
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes["amount"], json!({ "old": 12.5, "new": 15.0 }));
+        assert_eq!(changes["merchant"], json!({ "old": null, "new": "Bakery" }));
+    }
+
+    #[test]
+    fn creations_and_deletions_diff_against_nothing() {
+        let row = json!({ "id": "a", "name": "Savings" });
+        assert_eq!(
+            field_changes(None, Some(&row))["name"],
+            json!({ "old": null, "new": "Savings" })
+        );
+        assert_eq!(
+            field_changes(Some(&row), None)["name"],
+            json!({ "old": "Savings", "new": null })
+        );
+    }
+
+    #[test]
+    fn history_is_keyed_to_the_owning_account() {
+        let account_id = Uuid::new_v4();
+        let transaction = json!({ "account_id": account_id.to_string() });
+        assert_eq!(
+            ChangeEntity::Transaction.account_ids(Uuid::new_v4(), &transaction),
+            vec![account_id]
+        );
+        assert_eq!(
+            ChangeEntity::Account.account_ids(account_id, &json!({})),
+            vec![account_id]
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+
+use crate::services::cost_basis::{TradeInput, TradeSide};
+
+pub const INCOME_KINDS: [&str; 2] = ["dividend", "interest"];
+
+pub fn income_category(kind: &str) -> &'static str {
+    match kind {
+        "interest" => "Interest",
+        _ => "Dividends",
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SplitRatio {
+    pub numerator: f64,
+    pub denominator: f64,
+}
+
+impl SplitRatio {
+    pub fn new(numerator: f64, denominator: f64) -> Option<Self> {
+        let valid = numerator.is_finite()
+            && denominator.is_finite()
+            && numerator > 0.0
+            && denominator > 0.0
+            && numerator != denominator;
+        valid.then_some(Self {
+            numerator,
+            denominator,
+        })
+    }
+
+    pub fn factor(&self) -> f64 {
+        self.numerator / self.denominator
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            numerator: self.denominator,
+            denominator: self.numerator,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        if self.numerator > self.denominator {
+            "split"
+        } else {
+            "reverse_split"
+        }
+    }
+}
+
+pub fn held_quantity(trades: &[TradeInput], at: DateTime<Utc>) -> f64 {
+    trades
+        .iter()
+        .filter(|trade| trade.traded_at <= at)
+        .map(|trade| match trade.side {
+            TradeSide::Buy => trade.quantity,
+            TradeSide::Sell => -trade.quantity,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{held_quantity, SplitRatio};
+    use crate::services::cost_basis::{TradeInput, TradeSide};
+    use chrono::{Duration, TimeZone, Utc};
+    use uuid::Uuid;
+
+    #[test]
+    fn split_ratio_rejects_invalid_values() {
+        assert!(SplitRatio::new(2.0, 1.0).is_some());
+        assert!(SplitRatio::new(1.0, 1.0).is_none());
+        assert!(SplitRatio::new(0.0, 1.0).is_none());
+        assert!(SplitRatio::new(f64::NAN, 1.0).is_none());
+    }
+
+    #[test]
+    fn split_factor_and_inverse() {
+        let forward = SplitRatio::new(3.0, 1.0).unwrap();
+        assert_eq!(forward.kind(), "split");
+        assert_eq!(forward.factor(), 3.0);
+
+        let reverse = SplitRatio::new(1.0, 10.0).unwrap();
+        assert_eq!(reverse.kind(), "reverse_split");
+        assert!((reverse.factor() - 0.1).abs() < 1e-12);
+        assert_eq!(reverse.inverse().factor(), 10.0);
+        assert_eq!(reverse.inverse().kind(), "split");
+    }
+
+    #[test]
+    fn held_quantity_counts_trades_up_to_the_date() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 15, 0, 0).unwrap();
+        let trade = |side, day, quantity| TradeInput {
+            id: Uuid::new_v4(),
+            side,
+            quantity,
+            price: 10.0,
+            fees: 0.0,
+            traded_at: start + Duration::days(day),
+            lot_id: None,
+        };
+        let trades = vec![
+            trade(TradeSide::Buy, 0, 10.0),
+            trade(TradeSide::Sell, 5, 4.0),
+            trade(TradeSide::Buy, 10, 1.0),
+        ];
+        assert_eq!(held_quantity(&trades, start - Duration::days(1)), 0.0);
+        assert_eq!(held_quantity(&trades, start + Duration::days(5)), 6.0);
+        assert_eq!(held_quantity(&trades, start + Duration::days(30)), 7.0);
+    }
+}
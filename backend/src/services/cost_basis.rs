@@ -0,0 +1,239 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CostBasisMethod {
+    Fifo,
+    Average,
+    SpecificLot,
+}
+
+impl CostBasisMethod {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "fifo" => Some(Self::Fifo),
+            "average" => Some(Self::Average),
+            "specific" => Some(Self::SpecificLot),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+impl TradeSide {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "buy" => Some(Self::Buy),
+            "sell" => Some(Self::Sell),
+            _ => None,
+        }
+    }
+}
+
+pub struct TradeInput {
+    pub id: Uuid,
+    pub side: TradeSide,
+    pub quantity: f64,
+    pub price: f64,
+    pub fees: f64,
+    pub traded_at: DateTime<Utc>,
+    pub lot_id: Option<Uuid>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OpenLot {
+    pub trade_id: Uuid,
+    pub acquired_at: DateTime<Utc>,
+    pub quantity: f64,
+    pub cost: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct Position {
+    pub quantity: f64,
+    pub cost_basis: f64,
+    pub realized_gain: f64,
+    pub open_lots: Vec<OpenLot>,
+}
+
+impl Position {
+    pub fn average_cost(&self) -> Option<f64> {
+        (self.quantity > QUANTITY_EPSILON).then(|| self.cost_basis / self.quantity)
+    }
+
+    pub fn unrealized_gain(&self, price: f64) -> f64 {
+        self.quantity * price - self.cost_basis
+    }
+}
+
+pub fn compute_position(
+    trades: &[TradeInput],
+    method: CostBasisMethod,
+) -> Result<Position, String> {
+    let mut ordered: Vec<&TradeInput> = trades.iter().collect();
+    ordered.sort_by_key(|trade| trade.traded_at);
+
+    let mut lots: VecDeque<OpenLot> = VecDeque::new();
+    let mut realized_gain = 0.0;
+    for trade in ordered {
+        match trade.side {
+            TradeSide::Buy => lots.push_back(OpenLot {
+                trade_id: trade.id,
+                acquired_at: trade.traded_at,
+                quantity: trade.quantity,
+                cost: trade.quantity * trade.price + trade.fees,
+            }),
+            TradeSide::Sell => {
+                let held: f64 = lots.iter().map(|lot| lot.quantity).sum();
+                if trade.quantity > held + QUANTITY_EPSILON {
+                    return Err(format!(
+                        "sell of {} on {} exceeds the {held} units held",
+                        trade.quantity,
+                        trade.traded_at.date_naive()
+                    ));
+                }
+                let cost = match method {
+                    CostBasisMethod::Average => consume_average(&mut lots, trade.quantity, held),
+                    CostBasisMethod::Fifo => consume_in_order(&mut lots, trade.quantity, None),
+                    CostBasisMethod::SpecificLot => {
+                        consume_in_order(&mut lots, trade.quantity, trade.lot_id)
+                    }
+                };
+                realized_gain += trade.quantity * trade.price - trade.fees - cost;
+            }
+        }
+    }
+
+    let open_lots: Vec<OpenLot> = lots.into_iter().collect();
+    Ok(Position {
+        quantity: open_lots.iter().map(|lot| lot.quantity).sum(),
+        cost_basis: open_lots.iter().map(|lot| lot.cost).sum(),
+        realized_gain,
+        open_lots,
+    })
+}
+
+fn consume_average(lots: &mut VecDeque<OpenLot>, quantity: f64, held: f64) -> f64 {
+    let total_cost: f64 = lots.iter().map(|lot| lot.cost).sum();
+    let cost = total_cost * quantity / held;
+    let remaining = 1.0 - quantity / held;
+    for lot in lots.iter_mut() {
+        lot.quantity *= remaining;
+        lot.cost *= remaining;
+    }
+    lots.retain(|lot| lot.quantity > QUANTITY_EPSILON);
+    cost
+}
+
+fn consume_in_order(lots: &mut VecDeque<OpenLot>, quantity: f64, first: Option<Uuid>) -> f64 {
+    if let Some(index) = first.and_then(|id| lots.iter().position(|lot| lot.trade_id == id)) {
+        if let Some(lot) = lots.remove(index) {
+            lots.push_front(lot);
+        }
+    }
+    let mut remaining = quantity;
+    let mut cost = 0.0;
+    while remaining > QUANTITY_EPSILON {
+        let Some(lot) = lots.front_mut() else {
+            break;
+        };
+        let taken = remaining.min(lot.quantity);
+        let taken_cost = lot.cost * taken / lot.quantity;
+        lot.quantity -= taken;
+        lot.cost -= taken_cost;
+        cost += taken_cost;
+        remaining -= taken;
+        if lot.quantity <= QUANTITY_EPSILON {
+            lots.pop_front();
+        }
+    }
+    cost
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compute_position, CostBasisMethod, TradeInput, TradeSide};
+    use chrono::{Duration, TimeZone, Utc};
+    use uuid::Uuid;
+
+    fn trade(side: TradeSide, day: i64, quantity: f64, price: f64, fees: f64) -> TradeInput {
+        TradeInput {
+            id: Uuid::new_v4(),
+            side,
+            quantity,
+            price,
+            fees,
+            traded_at: Utc.with_ymd_and_hms(2024, 1, 1, 15, 0, 0).unwrap() + Duration::days(day),
+            lot_id: None,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    fn sample() -> Vec<TradeInput> {
+        vec![
+            trade(TradeSide::Buy, 0, 10.0, 100.0, 0.0),
+            trade(TradeSide::Buy, 10, 10.0, 120.0, 0.0),
+            trade(TradeSide::Sell, 20, 15.0, 130.0, 0.0),
+        ]
+    }
+
+    #[test]
+    fn fifo_sells_oldest_lots_first() {
+        let position = compute_position(&sample(), CostBasisMethod::Fifo).unwrap();
+        assert_close(position.quantity, 5.0);
+        assert_close(position.cost_basis, 600.0);
+        assert_close(position.realized_gain, 15.0 * 130.0 - (1000.0 + 600.0));
+        assert_close(position.unrealized_gain(130.0), 50.0);
+    }
+
+    #[test]
+    fn average_cost_spreads_basis_across_lots() {
+        let position = compute_position(&sample(), CostBasisMethod::Average).unwrap();
+        assert_close(position.quantity, 5.0);
+        assert_close(position.average_cost().unwrap(), 110.0);
+        assert_close(position.realized_gain, 15.0 * (130.0 - 110.0));
+    }
+
+    #[test]
+    fn specific_lot_sells_referenced_lot_first() {
+        let mut trades = sample();
+        trades[2].lot_id = Some(trades[1].id);
+        let position = compute_position(&trades, CostBasisMethod::SpecificLot).unwrap();
+        assert_close(position.cost_basis, 500.0);
+        assert_close(position.realized_gain, 15.0 * 130.0 - (1200.0 + 500.0));
+        assert_eq!(position.open_lots[0].trade_id, trades[0].id);
+    }
+
+    #[test]
+    fn fees_adjust_cost_and_proceeds() {
+        let trades = vec![
+            trade(TradeSide::Buy, 0, 10.0, 10.0, 5.0),
+            trade(TradeSide::Sell, 1, 10.0, 12.0, 5.0),
+        ];
+        let position = compute_position(&trades, CostBasisMethod::Fifo).unwrap();
+        assert_close(position.quantity, 0.0);
+        assert_close(position.realized_gain, 120.0 - 5.0 - 105.0);
+        assert!(position.average_cost().is_none());
+    }
+
+    #[test]
+    fn rejects_selling_more_than_held() {
+        let trades = vec![
+            trade(TradeSide::Buy, 0, 1.0, 10.0, 0.0),
+            trade(TradeSide::Sell, 1, 2.0, 12.0, 0.0),
+        ];
+        assert!(compute_position(&trades, CostBasisMethod::Fifo).is_err());
+    }
+}
@@ -0,0 +1,237 @@
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveTime, Utc};
+use sqlx::{postgres::PgPool, Row};
+use uuid::Uuid;
+
+use crate::services::balances::OPENING_BALANCE_CATEGORY;
+
+pub const ACCOUNT_TYPES: [&str; 6] = [
+    "checking",
+    "savings",
+    "credit_card",
+    "brokerage",
+    "cash",
+    "loan",
+];
+
+pub const CREDIT_CARD: &str = "credit_card";
+
+const DEFAULT_REMINDER_DAYS: u64 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatementCycle {
+    pub period_start: NaiveDate,
+    pub closing_date: NaiveDate,
+    pub due_date: NaiveDate,
+    pub next_closing_date: NaiveDate,
+}
+
+impl StatementCycle {
+    pub fn next_due_date(&self, payment_due_day: u32, today: NaiveDate) -> NaiveDate {
+        if self.due_date >= today {
+            self.due_date
+        } else {
+            due_date_after(self.next_closing_date, payment_due_day)
+        }
+    }
+}
+
+fn clamped_date(year: i32, month: u32, day: u32) -> NaiveDate {
+    let first = NaiveDate::from_ymd_opt(year, month, 1).expect("valid month");
+    let last_day = (first + Months::new(1) - Days::new(1)).day();
+    first + Days::new((day.clamp(1, last_day) - 1) as u64)
+}
+
+fn closing_on_or_before(closing_day: u32, date: NaiveDate) -> NaiveDate {
+    let candidate = clamped_date(date.year(), date.month(), closing_day);
+    if candidate <= date {
+        return candidate;
+    }
+    let previous = date - Months::new(1);
+    clamped_date(previous.year(), previous.month(), closing_day)
+}
+
+fn due_date_after(closing_date: NaiveDate, payment_due_day: u32) -> NaiveDate {
+    let candidate = clamped_date(closing_date.year(), closing_date.month(), payment_due_day);
+    if candidate > closing_date {
+        return candidate;
+    }
+    let next = closing_date + Months::new(1);
+    clamped_date(next.year(), next.month(), payment_due_day)
+}
+
+pub fn statement_cycle(closing_day: u32, payment_due_day: u32, today: NaiveDate) -> StatementCycle {
+    let closing_date = closing_on_or_before(closing_day, today);
+    let previous_close = closing_on_or_before(closing_day, closing_date - Days::new(1));
+    let following = closing_date + Months::new(1);
+    StatementCycle {
+        period_start: previous_close + Days::new(1),
+        closing_date,
+        due_date: due_date_after(closing_date, payment_due_day),
+        next_closing_date: clamped_date(following.year(), following.month(), closing_day),
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CardBalances {
+    pub statement_balance: f64,
+    pub payments_since_statement: f64,
+    pub current_balance: f64,
+}
+
+impl CardBalances {
+    pub fn remaining_statement_balance(&self) -> f64 {
+        (self.statement_balance - self.payments_since_statement).max(0.0)
+    }
+}
+
+pub async fn card_balances(
+    pool: &PgPool,
+    account_id: Uuid,
+    closing_date: NaiveDate,
+) -> Result<CardBalances, sqlx::Error> {
+    let statement_end = (closing_date + Days::new(1))
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+    let row = sqlx::query(
+        r#"
+        SELECT COALESCE(SUM(CASE WHEN occurred_at < $2 THEN
+                   CASE WHEN transaction_type = 'income' THEN -amount ELSE amount END
+               END), 0.0) as statement_balance,
+               COALESCE(SUM(CASE WHEN occurred_at >= $2 AND transaction_type = 'income'
+                   AND category <> $3
+                   THEN amount END), 0.0) as payments_since_statement,
+               COALESCE(SUM(
+                   CASE WHEN transaction_type = 'income' THEN -amount ELSE amount END
+               ), 0.0) as current_balance
+        FROM transactions
+        WHERE account_id = $1
+        "#,
+    )
+    .bind(account_id)
+    .bind(statement_end)
+    .bind(OPENING_BALANCE_CATEGORY)
+    .fetch_one(pool)
+    .await?;
+    Ok(CardBalances {
+        statement_balance: row.try_get("statement_balance")?,
+        payments_since_statement: row.try_get("payments_since_statement")?,
+        current_balance: row.try_get("current_balance")?,
+    })
+}
+
+fn reminder_days() -> u64 {
+    std::env::var("BILL_REMINDER_DAYS")
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_REMINDER_DAYS)
+}
+
+pub async fn record_bill_reminders(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, currency_code, statement_closing_day, payment_due_day
+        FROM accounts
+        WHERE account_type = 'credit_card'
+          AND statement_closing_day IS NOT NULL
+          AND payment_due_day IS NOT NULL
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let today = Utc::now().date_naive();
+    let horizon = today + Days::new(reminder_days());
+    let mut created = 0usize;
+    for row in rows {
+        let account_id: Uuid = row.try_get("id")?;
+        let currency_code: String = row.try_get("currency_code")?;
+        let closing_day: i32 = row.try_get("statement_closing_day")?;
+        let due_day: i32 = row.try_get("payment_due_day")?;
+        let cycle = statement_cycle(closing_day as u32, due_day as u32, today);
+        if cycle.due_date < today || cycle.due_date > horizon {
+            continue;
+        }
+        let balances = card_balances(pool, account_id, cycle.closing_date).await?;
+        let remaining = balances.remaining_statement_balance();
+        if remaining <= 0.0 {
+            continue;
+        }
+        created += sqlx::query(
+            r#"
+            INSERT INTO bill_reminders (id, account_id, due_date, statement_balance, currency_code)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (account_id, due_date) DO NOTHING
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(account_id)
+        .bind(cycle.due_date)
+        .bind(remaining)
+        .bind(currency_code)
+        .execute(pool)
+        .await?
+        .rows_affected() as usize;
+    }
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{statement_cycle, CardBalances};
+    use chrono::NaiveDate;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn due_date_falls_in_the_month_after_closing() {
+        let cycle = statement_cycle(25, 20, date(2024, 3, 10));
+        assert_eq!(cycle.closing_date, date(2024, 2, 25));
+        assert_eq!(cycle.period_start, date(2024, 1, 26));
+        assert_eq!(cycle.due_date, date(2024, 3, 20));
+        assert_eq!(cycle.next_closing_date, date(2024, 3, 25));
+        assert_eq!(
+            cycle.next_due_date(20, date(2024, 3, 10)),
+            date(2024, 3, 20)
+        );
+    }
+
+    #[test]
+    fn next_due_date_moves_on_once_paid_date_passes() {
+        let cycle = statement_cycle(25, 20, date(2024, 3, 22));
+        assert_eq!(cycle.closing_date, date(2024, 2, 25));
+        assert_eq!(
+            cycle.next_due_date(20, date(2024, 3, 22)),
+            date(2024, 4, 20)
+        );
+
+        let cycle = statement_cycle(5, 28, date(2024, 3, 5));
+        assert_eq!(cycle.closing_date, date(2024, 3, 5));
+        assert_eq!(cycle.due_date, date(2024, 3, 28));
+    }
+
+    #[test]
+    fn clamps_days_to_short_months() {
+        let cycle = statement_cycle(31, 30, date(2024, 3, 15));
+        assert_eq!(cycle.closing_date, date(2024, 2, 29));
+        assert_eq!(cycle.period_start, date(2024, 2, 1));
+        assert_eq!(cycle.due_date, date(2024, 3, 30));
+        assert_eq!(cycle.next_closing_date, date(2024, 3, 31));
+    }
+
+    #[test]
+    fn payments_reduce_remaining_statement_balance() {
+        let balances = CardBalances {
+            statement_balance: 800.0,
+            payments_since_statement: 300.0,
+            current_balance: 650.0,
+        };
+        assert_eq!(balances.remaining_statement_balance(), 500.0);
+        let paid = CardBalances {
+            payments_since_statement: 900.0,
+            ..balances
+        };
+        assert_eq!(paid.remaining_statement_balance(), 0.0);
+    }
+}
@@ -0,0 +1,188 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use crate::services::http::get_text;
+use crate::services::market_data::{PriceProvider, ProviderFuture};
+use crate::services::pricing::{Candle, QuoteBatch, SymbolFailure};
+
+const COINBASE_PROVIDER: &str = "coinbase";
+
+const CANDLES_PER_PAGE: i64 = 300;
+
+const MAX_CANDLE_PAGES: i64 = 10;
+
+const USD_STABLECOINS: [&str; 3] = ["USDT", "USDC", "DAI"];
+
+pub fn crypto_pair(symbol: &str, default_quote: &str) -> Option<(String, String)> {
+    let symbol = symbol.trim().to_uppercase();
+    let (base, quote) = match symbol.split_once(['-', '/']) {
+        Some((base, quote)) => (base.to_string(), quote.to_string()),
+        None => (symbol, default_quote.trim().to_uppercase()),
+    };
+    let valid = |value: &str| !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric());
+    (valid(&base) && valid(&quote)).then_some((base, quote))
+}
+
+pub fn crypto_symbol(symbol: &str, default_quote: &str) -> Option<String> {
+    crypto_pair(symbol, default_quote).map(|(base, quote)| format!("{base}-{quote}"))
+}
+
+pub fn settlement_currency(quote: &str) -> &str {
+    if USD_STABLECOINS.contains(&quote) {
+        "USD"
+    } else {
+        quote
+    }
+}
+
+#[derive(Deserialize)]
+struct SpotResponse {
+    data: SpotPrice,
+}
+
+#[derive(Deserialize)]
+struct SpotPrice {
+    amount: String,
+    currency: String,
+}
+
+fn parse_coinbase_spot(body: &str) -> Result<(f64, String), String> {
+    let response: SpotResponse =
+        serde_json::from_str(body).map_err(|err| format!("unexpected coinbase response: {err}"))?;
+    let price = response
+        .data
+        .amount
+        .parse::<f64>()
+        .map_err(|_| format!("invalid coinbase price: {}", response.data.amount))?;
+    Ok((price, response.data.currency.to_uppercase()))
+}
+
+fn parse_coinbase_candles(body: &str) -> Result<Vec<Candle>, String> {
+    let rows: Vec<Vec<f64>> =
+        serde_json::from_str(body).map_err(|err| format!("unexpected coinbase candles: {err}"))?;
+    Ok(rows
+        .into_iter()
+        .filter(|row| row.len() >= 5)
+        .filter_map(|row| {
+            let date = DateTime::<Utc>::from_timestamp(row[0] as i64, 0)?;
+            Some(Candle {
+                date: date.format("%Y-%m-%d").to_string(),
+                open: row[3],
+                high: row[2],
+                low: row[1],
+                close: row[4],
+            })
+        })
+        .collect())
+}
+
+pub struct CoinbaseProvider;
+
+impl CoinbaseProvider {
+    async fn spot(&self, symbol: &str) -> Result<(f64, String), String> {
+        let url = format!("https://api.coinbase.com/v2/prices/{symbol}/spot");
+        let body = get_text(COINBASE_PROVIDER, &url)
+            .await
+            .map_err(|err| err.to_string())?;
+        parse_coinbase_spot(&body)
+    }
+
+    async fn candles(&self, symbol: &str) -> Result<Vec<Candle>, String> {
+        let mut candles = Vec::new();
+        let mut end = Utc::now();
+        for _ in 0..MAX_CANDLE_PAGES {
+            let start = end - Duration::days(CANDLES_PER_PAGE);
+            let url = format!(
+                "https://api.exchange.coinbase.com/products/{symbol}/candles?granularity=86400&start={}&end={}",
+                start.format("%Y-%m-%dT%H:%M:%SZ"),
+                end.format("%Y-%m-%dT%H:%M:%SZ"),
+            );
+            let body = get_text(COINBASE_PROVIDER, &url)
+                .await
+                .map_err(|err| err.to_string())?;
+            let page = parse_coinbase_candles(&body)?;
+            if page.is_empty() {
+                break;
+            }
+            candles.extend(page);
+            end = start;
+        }
+        candles.sort_by(|a, b| a.date.cmp(&b.date));
+        candles.dedup_by(|a, b| a.date == b.date);
+        Ok(candles)
+    }
+}
+
+impl PriceProvider for CoinbaseProvider {
+    fn name(&self) -> &'static str {
+        COINBASE_PROVIDER
+    }
+
+    fn quotes<'a>(&'a self, symbols: &'a [String]) -> ProviderFuture<'a, QuoteBatch> {
+        Box::pin(async move {
+            let mut batch = QuoteBatch::default();
+            for symbol in symbols {
+                match self.spot(symbol).await {
+                    Ok(quote) => {
+                        batch.prices.insert(symbol.clone(), quote);
+                    }
+                    Err(reason) => batch.failures.push(SymbolFailure {
+                        symbol: symbol.clone(),
+                        reason,
+                    }),
+                }
+            }
+            batch
+        })
+    }
+
+    fn daily_candles<'a>(
+        &'a self,
+        symbol: &'a str,
+    ) -> ProviderFuture<'a, Result<Vec<Candle>, String>> {
+        Box::pin(self.candles(symbol))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        crypto_pair, crypto_symbol, parse_coinbase_candles, parse_coinbase_spot,
+        settlement_currency,
+    };
+
+    #[test]
+    fn crypto_pair_uses_default_quote() {
+        assert_eq!(
+            crypto_pair(" btc ", "usd"),
+            Some(("BTC".to_string(), "USD".to_string()))
+        );
+        assert_eq!(crypto_symbol("eth/eur", "USD").as_deref(), Some("ETH-EUR"));
+        assert_eq!(
+            crypto_symbol("SOL-USDT", "EUR").as_deref(),
+            Some("SOL-USDT")
+        );
+        assert_eq!(crypto_symbol("BTC-", "USD"), None);
+        assert_eq!(crypto_symbol("../BTC", "USD"), None);
+    }
+
+    #[test]
+    fn stablecoins_settle_in_usd() {
+        assert_eq!(settlement_currency("USDT"), "USD");
+        assert_eq!(settlement_currency("EUR"), "EUR");
+    }
+
+    #[test]
+    fn parses_spot_and_candles() {
+        let spot = r#"{"data":{"amount":"64123.45","base":"BTC","currency":"USD"}}"#;
+        assert_eq!(parse_coinbase_spot(spot), Ok((64123.45, "USD".to_string())));
+        assert!(parse_coinbase_spot(r#"{"errors":[]}"#).is_err());
+
+        let candles =
+            parse_coinbase_candles("[[1704153600,42000.5,43500,42200,43100.25,1200.5]]").unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].date, "2024-01-02");
+        assert_eq!(candles[0].low, 42000.5);
+        assert_eq!(candles[0].close, 43100.25);
+    }
+}
@@ -1,16 +1,47 @@
 use chrono::{NaiveDate, Utc};
-use reqwest::{header, Client, Url};
+use reqwest::Url;
 use serde::Deserialize;
 use sqlx::postgres::PgPool;
 use std::collections::HashMap;
-use std::io::{Error as IoError, ErrorKind};
+use std::io::Error as IoError;
 use tracing::debug;
 
-#[allow(dead_code)]
+use crate::services::http::get_text;
+
+pub async fn load_usd_rates(pool: &PgPool) -> Result<HashMap<String, f64>, sqlx::Error> {
+    let mut rates: HashMap<String, f64> = sqlx::query_as::<_, (String, f64)>(
+        r#"
+        SELECT DISTINCT ON (base_currency) base_currency, rate
+        FROM fx_rates
+        WHERE quote_currency = 'USD'
+        ORDER BY base_currency, recorded_on DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+    rates.insert("USD".to_string(), 1.0);
+    Ok(rates)
+}
+
+pub fn convert_amount(
+    amount: f64,
+    from: &str,
+    to: &str,
+    rates: &HashMap<String, f64>,
+) -> Option<f64> {
+    if from == to {
+        return Some(amount);
+    }
+    let from_rate = rates.get(from)?;
+    let to_rate = rates.get(to).filter(|rate| **rate > 0.0)?;
+    Some(amount * from_rate / to_rate)
+}
+
 const SUPPORTED_CURRENCIES: [&str; 5] = ["USD", "EUR", "GBP", "JPY", "HKD"];
 
 #[derive(Deserialize)]
-#[allow(dead_code)]
 struct FxResponse {
     base: Option<String>,
     date: Option<String>,
@@ -20,20 +51,17 @@ struct FxResponse {
 }
 
 #[derive(Deserialize)]
-#[allow(dead_code)]
 struct FxError {
     info: Option<String>,
     error: Option<String>,
 }
 
-#[allow(dead_code)]
 pub async fn refresh_fx_rates(pool: &PgPool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let access_key = std::env::var("FX_ACCESS_KEY").ok();
     if access_key.is_none() {
         debug!("FX_ACCESS_KEY not set; skipping FX refresh");
         return Ok(());
     }
-    let client = Client::new();
     let symbols = SUPPORTED_CURRENCIES.join(",");
     let access_key = access_key.expect("checked above");
     let url = Url::parse_with_params(
@@ -44,40 +72,28 @@ pub async fn refresh_fx_rates(pool: &PgPool) -> Result<(), Box<dyn std::error::E
             ("access_key", access_key.as_str()),
         ],
     )?;
-    let response = client
-        .get(url)
-        .header(header::USER_AGENT, "firecash-api")
-        .send()
-        .await?;
-    if !response.status().is_success() {
-        return Err(Box::new(IoError::new(
-            ErrorKind::Other,
-            format!("fx rate request failed: {}", response.status()),
-        )));
-    }
-    let body = response.text().await?;
+    let body = get_text("exchangerate_host", url.as_str())
+        .await
+        .map_err(|err| IoError::other(format!("fx rate request failed: {err}")))?;
     let payload: FxResponse = serde_json::from_str(&body).map_err(|err| {
-        IoError::new(
-            ErrorKind::Other,
-            format!("failed to decode fx response: {err}. body: {body}"),
-        )
+        IoError::other(format!("failed to decode fx response: {err}. body: {body}"))
     })?;
     if payload.success == Some(false) {
         let message = payload
             .error
             .and_then(|error| error.info.or(error.error))
             .unwrap_or_else(|| "fx API returned an error".to_string());
-        return Err(Box::new(IoError::new(ErrorKind::Other, message)));
+        return Err(Box::new(IoError::other(message)));
     }
     let base = payload
         .base
-        .ok_or_else(|| IoError::new(ErrorKind::Other, "fx response missing base"))?;
+        .ok_or_else(|| IoError::other("fx response missing base"))?;
     let date = payload
         .date
-        .ok_or_else(|| IoError::new(ErrorKind::Other, "fx response missing date"))?;
+        .ok_or_else(|| IoError::other("fx response missing date"))?;
     let rates = payload
         .rates
-        .ok_or_else(|| IoError::new(ErrorKind::Other, "fx response missing rates"))?;
+        .ok_or_else(|| IoError::other("fx response missing rates"))?;
     let recorded_on = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
         .unwrap_or_else(|_| Utc::now().date_naive());
 
@@ -104,3 +120,23 @@ pub async fn refresh_fx_rates(pool: &PgPool) -> Result<(), Box<dyn std::error::E
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::convert_amount;
+    use std::collections::HashMap;
+
+    #[test]
+    fn converts_through_usd() {
+        let rates = HashMap::from([
+            ("USD".to_string(), 1.0),
+            ("EUR".to_string(), 1.1),
+            ("GBP".to_string(), 1.25),
+        ]);
+        assert_eq!(convert_amount(10.0, "EUR", "EUR", &rates), Some(10.0));
+        assert_eq!(convert_amount(10.0, "EUR", "USD", &rates), Some(11.0));
+        let pounds = convert_amount(11.0, "EUR", "GBP", &rates).unwrap();
+        assert!((pounds - 9.68).abs() < 1e-12);
+        assert_eq!(convert_amount(10.0, "JPY", "USD", &rates), None);
+    }
+}
@@ -0,0 +1,196 @@
+use rand_core::{OsRng, RngCore};
+use reqwest::{header, Client, StatusCode};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::warn;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub fn market_client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .user_agent("firecash-api")
+            .build()
+            .expect("failed to build http client")
+    })
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub const DEFAULT: Self = Self {
+        max_attempts: 3,
+        base_delay: Duration::from_millis(500),
+        max_delay: Duration::from_secs(8),
+    };
+
+    pub fn delay_for(&self, retry: u32, jitter: f64) -> Duration {
+        let exponent = retry.saturating_sub(1).min(16);
+        let backoff = self
+            .base_delay
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_delay);
+        backoff.mul_f64(0.5 + 0.5 * jitter.clamp(0.0, 1.0))
+    }
+}
+
+pub struct RateLimiter {
+    min_interval: Duration,
+    next_slot: tokio::sync::Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: f64) -> Self {
+        let min_interval = if requests_per_second > 0.0 {
+            Duration::from_secs_f64(1.0 / requests_per_second)
+        } else {
+            Duration::ZERO
+        };
+        Self {
+            min_interval,
+            next_slot: tokio::sync::Mutex::new(Instant::now()),
+        }
+    }
+
+    pub async fn acquire(&self) {
+        let mut next_slot = self.next_slot.lock().await;
+        let now = Instant::now();
+        if *next_slot > now {
+            tokio::time::sleep_until(*next_slot).await;
+        }
+        *next_slot = Instant::now() + self.min_interval;
+    }
+}
+
+pub fn provider_limiter(provider: &str) -> Arc<RateLimiter> {
+    static LIMITERS: OnceLock<Mutex<HashMap<String, Arc<RateLimiter>>>> = OnceLock::new();
+    let limiters = LIMITERS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut limiters = limiters.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    limiters
+        .entry(provider.to_string())
+        .or_insert_with(|| {
+            let requests_per_second = std::env::var(format!(
+                "MARKET_RATE_LIMIT_{}",
+                provider.to_uppercase()
+            ))
+            .ok()
+            .and_then(|value| value.parse::<f64>().ok())
+            .unwrap_or(2.0);
+            Arc::new(RateLimiter::new(requests_per_second))
+        })
+        .clone()
+}
+
+#[derive(Debug)]
+pub enum FetchError {
+    Status(StatusCode),
+    Network(String),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status(status) => write!(f, "request failed with status {status}"),
+            Self::Network(message) => write!(f, "request failed: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+pub async fn get_text(provider: &str, url: &str) -> Result<String, FetchError> {
+    get_text_with_policy(provider, url, RetryPolicy::DEFAULT).await
+}
+
+pub async fn get_text_with_policy(
+    provider: &str,
+    url: &str,
+    policy: RetryPolicy,
+) -> Result<String, FetchError> {
+    let limiter = provider_limiter(provider);
+    let mut attempt = 1;
+    loop {
+        limiter.acquire().await;
+        let result = market_client()
+            .get(url)
+            .header(header::USER_AGENT, "firecash-api")
+            .send()
+            .await;
+        let error = match result {
+            Ok(response) if response.status().is_success() => {
+                return response
+                    .text()
+                    .await
+                    .map_err(|err| FetchError::Network(err.to_string()));
+            }
+            Ok(response) => {
+                let status = response.status();
+                if !is_retryable_status(status) {
+                    return Err(FetchError::Status(status));
+                }
+                FetchError::Status(status)
+            }
+            Err(err) => FetchError::Network(err.to_string()),
+        };
+        if attempt >= policy.max_attempts {
+            return Err(error);
+        }
+        let jitter = OsRng.next_u32() as f64 / u32::MAX as f64;
+        let delay = policy.delay_for(attempt, jitter);
+        warn!(provider, attempt, ?delay, %error, "retrying market data request");
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_retryable_status, RetryPolicy};
+    use reqwest::StatusCode;
+    use std::time::Duration;
+
+    #[test]
+    fn backoff_grows_exponentially_and_caps() {
+        let policy = RetryPolicy {
+            max_attempts: 6,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(3),
+        };
+        assert_eq!(policy.delay_for(1, 1.0), Duration::from_millis(500));
+        assert_eq!(policy.delay_for(2, 1.0), Duration::from_secs(1));
+        assert_eq!(policy.delay_for(3, 1.0), Duration::from_secs(2));
+        assert_eq!(policy.delay_for(4, 1.0), Duration::from_secs(3));
+        assert_eq!(policy.delay_for(10, 1.0), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn jitter_scales_between_half_and_full_delay() {
+        let policy = RetryPolicy::DEFAULT;
+        assert_eq!(policy.delay_for(2, 0.0), Duration::from_millis(500));
+        assert_eq!(policy.delay_for(2, 0.5), Duration::from_millis(750));
+        assert_eq!(policy.delay_for(2, 7.0), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn retries_only_transient_statuses() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+    }
+}
@@ -0,0 +1,153 @@
+use chrono::{Duration, Months, NaiveDate};
+use serde::Serialize;
+
+pub const INTEREST_CATEGORY: &str = "Loan Interest";
+pub const PRINCIPAL_CATEGORY: &str = "Loan Principal";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaymentFrequency {
+    Weekly,
+    Biweekly,
+    Monthly,
+    Quarterly,
+}
+
+impl PaymentFrequency {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "weekly" => Some(Self::Weekly),
+            "biweekly" => Some(Self::Biweekly),
+            "monthly" => Some(Self::Monthly),
+            "quarterly" => Some(Self::Quarterly),
+            _ => None,
+        }
+    }
+
+    pub fn periods_per_year(&self) -> u32 {
+        match self {
+            Self::Weekly => 52,
+            Self::Biweekly => 26,
+            Self::Monthly => 12,
+            Self::Quarterly => 4,
+        }
+    }
+
+    pub fn due_date(&self, start: NaiveDate, period: u32) -> NaiveDate {
+        match self {
+            Self::Weekly => start + Duration::weeks(period as i64),
+            Self::Biweekly => start + Duration::weeks(2 * period as i64),
+            Self::Monthly => start + Months::new(period),
+            Self::Quarterly => start + Months::new(3 * period),
+        }
+    }
+
+    pub fn payment_count(&self, term_months: u32) -> u32 {
+        let periods = (term_months as f64 * self.periods_per_year() as f64 / 12.0).round();
+        (periods as u32).max(1)
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ScheduledPayment {
+    pub period: u32,
+    pub due_date: NaiveDate,
+    pub payment: f64,
+    pub interest: f64,
+    pub principal: f64,
+    pub balance: f64,
+}
+
+pub fn level_payment(principal: f64, period_rate: f64, periods: u32) -> f64 {
+    if periods == 0 {
+        return principal;
+    }
+    if period_rate.abs() < f64::EPSILON {
+        return principal / periods as f64;
+    }
+    principal * period_rate / (1.0 - (1.0 + period_rate).powi(-(periods as i32)))
+}
+
+pub fn amortization_schedule(
+    principal: f64,
+    annual_rate: f64,
+    term_months: u32,
+    frequency: PaymentFrequency,
+    start: NaiveDate,
+) -> Vec<ScheduledPayment> {
+    let periods = frequency.payment_count(term_months);
+    let period_rate = annual_rate / frequency.periods_per_year() as f64;
+    let payment = round_cents(level_payment(principal, period_rate, periods));
+
+    let mut balance = principal;
+    let mut schedule = Vec::with_capacity(periods as usize);
+    for period in 1..=periods {
+        let interest = round_cents(balance * period_rate);
+        let principal_part = if period == periods {
+            balance
+        } else {
+            round_cents(payment - interest).min(balance)
+        };
+        balance = round_cents(balance - principal_part);
+        schedule.push(ScheduledPayment {
+            period,
+            due_date: frequency.due_date(start, period),
+            payment: round_cents(interest + principal_part),
+            interest,
+            principal: round_cents(principal_part),
+            balance,
+        });
+    }
+    schedule
+}
+
+fn round_cents(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{amortization_schedule, level_payment, PaymentFrequency};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn payment_dates_follow_frequency() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        assert_eq!(
+            PaymentFrequency::Monthly.due_date(start, 1),
+            NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()
+        );
+        assert_eq!(
+            PaymentFrequency::Biweekly.due_date(start, 2),
+            NaiveDate::from_ymd_opt(2024, 2, 28).unwrap()
+        );
+        assert_eq!(PaymentFrequency::Monthly.payment_count(360), 360);
+        assert_eq!(PaymentFrequency::Biweekly.payment_count(12), 26);
+        assert_eq!(PaymentFrequency::parse("daily"), None);
+    }
+
+    #[test]
+    fn amortizes_a_mortgage() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let schedule =
+            amortization_schedule(200_000.0, 0.06, 360, PaymentFrequency::Monthly, start);
+        assert_eq!(schedule.len(), 360);
+        assert!((schedule[0].payment - 1199.10).abs() < 0.01);
+        assert!((schedule[0].interest - 1000.0).abs() < 0.01);
+        assert!((schedule[0].principal - 199.10).abs() < 0.01);
+        assert_eq!(schedule.last().unwrap().balance, 0.0);
+        let repaid: f64 = schedule.iter().map(|payment| payment.principal).sum();
+        assert!((repaid - 200_000.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn zero_rate_loans_split_principal_evenly() {
+        assert_eq!(level_payment(1200.0, 0.0, 12), 100.0);
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let schedule = amortization_schedule(1000.0, 0.0, 3, PaymentFrequency::Monthly, start);
+        assert_eq!(schedule.len(), 3);
+        assert!(schedule.iter().all(|payment| payment.interest == 0.0));
+        assert_eq!(schedule[0].principal, 333.33);
+        assert_eq!(schedule[2].principal, 333.34);
+        assert_eq!(schedule[2].balance, 0.0);
+    }
+}
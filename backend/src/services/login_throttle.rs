@@ -0,0 +1,175 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::postgres::PgPool;
+
+const FAILURE_WINDOW_MINUTES: i64 = 15;
+const BASE_LOCKOUT_SECONDS: i64 = 60;
+const MAX_LOCKOUT_SECONDS: i64 = 60 * 60;
+const BASE_DELAY_MILLIS: u64 = 250;
+const MAX_DELAY_MILLIS: u64 = 4_000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThrottleScope {
+    Account,
+    Ip,
+}
+
+impl ThrottleScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Account => "account",
+            Self::Ip => "ip",
+        }
+    }
+
+    pub fn lockout_threshold(&self) -> i32 {
+        match self {
+            Self::Account => 5,
+            Self::Ip => 20,
+        }
+    }
+}
+
+pub fn lockout_duration(failures: i32, threshold: i32) -> Option<Duration> {
+    if failures < threshold {
+        return None;
+    }
+    let doublings = (failures - threshold).min(16) as u32;
+    let seconds = (BASE_LOCKOUT_SECONDS << doublings).min(MAX_LOCKOUT_SECONDS);
+    Some(Duration::seconds(seconds))
+}
+
+pub fn failure_delay(failures: i32) -> std::time::Duration {
+    if failures <= 1 {
+        return std::time::Duration::ZERO;
+    }
+    let doublings = (failures - 1).min(16) as u32;
+    std::time::Duration::from_millis((BASE_DELAY_MILLIS << doublings).min(MAX_DELAY_MILLIS))
+}
+
+pub async fn active_lockout(
+    pool: &PgPool,
+    scope: ThrottleScope,
+    key: &str,
+) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT locked_until
+        FROM login_failures
+        WHERE scope = $1
+          AND key = $2
+          AND locked_until > NOW()
+        "#,
+    )
+    .bind(scope.as_str())
+    .bind(key)
+    .fetch_optional(pool)
+    .await
+    .map(Option::flatten)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RecordedFailure {
+    pub failures: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+pub async fn record_failure(
+    pool: &PgPool,
+    scope: ThrottleScope,
+    key: &str,
+) -> Result<RecordedFailure, sqlx::Error> {
+    let failures: i32 = sqlx::query_scalar(
+        r#"
+        INSERT INTO login_failures (scope, key, failure_count, last_failed_at)
+        VALUES ($1, $2, 1, NOW())
+        ON CONFLICT (scope, key) DO UPDATE
+        SET failure_count = CASE
+                WHEN login_failures.last_failed_at < NOW() - make_interval(mins => $3) THEN 1
+                ELSE login_failures.failure_count + 1
+            END,
+            last_failed_at = NOW()
+        RETURNING failure_count
+        "#,
+    )
+    .bind(scope.as_str())
+    .bind(key)
+    .bind(FAILURE_WINDOW_MINUTES as i32)
+    .fetch_one(pool)
+    .await?;
+
+    let locked_until = match lockout_duration(failures, scope.lockout_threshold()) {
+        Some(duration) => {
+            let locked_until = Utc::now() + duration;
+            sqlx::query(
+                r#"
+                UPDATE login_failures
+                SET locked_until = $3
+                WHERE scope = $1 AND key = $2
+                "#,
+            )
+            .bind(scope.as_str())
+            .bind(key)
+            .bind(locked_until)
+            .execute(pool)
+            .await?;
+            Some(locked_until)
+        }
+        None => None,
+    };
+
+    Ok(RecordedFailure {
+        failures,
+        locked_until,
+    })
+}
+
+pub async fn clear_failures(
+    pool: &PgPool,
+    scope: ThrottleScope,
+    key: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        DELETE FROM login_failures
+        WHERE scope = $1 AND key = $2
+        "#,
+    )
+    .bind(scope.as_str())
+    .bind(key)
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{failure_delay, lockout_duration, ThrottleScope};
+    use chrono::Duration;
+
+    #[test]
+    fn lockouts_start_at_the_threshold_and_double() {
+        let threshold = ThrottleScope::Account.lockout_threshold();
+        assert_eq!(lockout_duration(threshold - 1, threshold), None);
+        assert_eq!(
+            lockout_duration(threshold, threshold),
+            Some(Duration::minutes(1))
+        );
+        assert_eq!(
+            lockout_duration(threshold + 2, threshold),
+            Some(Duration::minutes(4))
+        );
+        assert_eq!(
+            lockout_duration(threshold + 40, threshold),
+            Some(Duration::hours(1))
+        );
+        assert!(ThrottleScope::Ip.lockout_threshold() > threshold);
+    }
+
+    #[test]
+    fn delays_grow_with_repeated_failures() {
+        assert_eq!(failure_delay(1), std::time::Duration::ZERO);
+        assert_eq!(failure_delay(2), std::time::Duration::from_millis(500));
+        assert_eq!(failure_delay(3), std::time::Duration::from_secs(1));
+        assert_eq!(failure_delay(50), std::time::Duration::from_secs(4));
+    }
+}
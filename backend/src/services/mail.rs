@@ -0,0 +1,253 @@
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use chrono::Utc;
+use lettre::{message::Mailbox, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+pub type MailFuture<'a> = Pin<Box<dyn Future<Output = Result<(), String>> + Send + 'a>>;
+
+const DEFAULT_FROM: &str = "Firecash <no-reply@firecash.local>";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+pub trait MailSender: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn send<'a>(&'a self, message: &'a MailMessage) -> MailFuture<'a>;
+}
+
+pub struct SmtpSender {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpSender {
+    pub fn new(url: &str, from: &str) -> Result<Self, String> {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::from_url(url)
+            .map_err(|err| err.to_string())?
+            .build();
+        let from = from
+            .parse()
+            .map_err(|err: lettre::address::AddressError| err.to_string())?;
+        Ok(Self { transport, from })
+    }
+}
+
+impl MailSender for SmtpSender {
+    fn name(&self) -> &'static str {
+        "smtp"
+    }
+
+    fn send<'a>(&'a self, message: &'a MailMessage) -> MailFuture<'a> {
+        Box::pin(async move {
+            let to: Mailbox = message
+                .to
+                .parse()
+                .map_err(|err: lettre::address::AddressError| err.to_string())?;
+            let email = Message::builder()
+                .from(self.from.clone())
+                .to(to)
+                .subject(&message.subject)
+                .body(message.body.clone())
+                .map_err(|err| err.to_string())?;
+            self.transport
+                .send(email)
+                .await
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        })
+    }
+}
+
+pub struct FileSender {
+    dir: PathBuf,
+}
+
+impl FileSender {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl MailSender for FileSender {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn send<'a>(&'a self, message: &'a MailMessage) -> MailFuture<'a> {
+        Box::pin(async move {
+            tokio::fs::create_dir_all(&self.dir)
+                .await
+                .map_err(|err| err.to_string())?;
+            let path = self.dir.join(format!(
+                "{}-{}.eml",
+                Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+                uuid::Uuid::new_v4()
+            ));
+            let contents = format!(
+                "To: {}\r\nSubject: {}\r\n\r\n{}\r\n",
+                message.to, message.subject, message.body
+            );
+            tokio::fs::write(&path, contents)
+                .await
+                .map_err(|err| err.to_string())
+        })
+    }
+}
+
+pub struct LogSender;
+
+impl MailSender for LogSender {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    fn send<'a>(&'a self, message: &'a MailMessage) -> MailFuture<'a> {
+        Box::pin(async move {
+            tracing::info!(
+                to = %message.to,
+                subject = %message.subject,
+                body = %message.body,
+                "mail not sent; MAIL_TRANSPORT=log"
+            );
+            Ok(())
+        })
+    }
+}
+
+pub fn mail_sender_from_env() -> Arc<dyn MailSender> {
+    let transport = std::env::var("MAIL_TRANSPORT").unwrap_or_default();
+    match transport.trim() {
+        "smtp" => {
+            let url = std::env::var("SMTP_URL").unwrap_or_default();
+            let from = std::env::var("MAIL_FROM").unwrap_or_else(|_| DEFAULT_FROM.into());
+            match SmtpSender::new(url.trim(), from.trim()) {
+                Ok(sender) => Arc::new(sender),
+                Err(err) => {
+                    tracing::error!(error = %err, "invalid SMTP configuration; logging mail instead");
+                    Arc::new(LogSender)
+                }
+            }
+        }
+        "file" => Arc::new(FileSender::new(
+            std::env::var("MAIL_DIR").unwrap_or_else(|_| "mail".into()),
+        )),
+        _ => Arc::new(LogSender),
+    }
+}
+
+pub fn password_reset_message(to: &str, link: &str, valid_minutes: i64) -> MailMessage {
+    MailMessage {
+        to: to.to_string(),
+        subject: "Reset your Firecash password".into(),
+        body: format!(
+            "Someone asked to reset the password for this Firecash account.\n\n\
+             Open this link within {valid_minutes} minutes to choose a new password:\n{link}\n\n\
+             If this was not you, ignore this email; your password has not changed."
+        ),
+    }
+}
+
+pub fn email_verification_message(to: &str, link: &str, valid_hours: i64) -> MailMessage {
+    MailMessage {
+        to: to.to_string(),
+        subject: "Verify your Firecash email".into(),
+        body: format!(
+            "Confirm this address for your Firecash account by opening this link within \
+             {valid_hours} hours:\n{link}"
+        ),
+    }
+}
+
+pub fn group_invitation_message(
+    to: &str,
+    inviter: &str,
+    group_name: &str,
+    role: &str,
+    link: &str,
+    valid_days: i64,
+) -> MailMessage {
+    MailMessage {
+        to: to.to_string(),
+        subject: format!("{inviter} invited you to {group_name} on Firecash"),
+        body: format!(
+            "{inviter} invited you to share the account group \"{group_name}\" on Firecash \
+             with {role} access.\n\n\
+             Open this link within {valid_days} days to accept or decline:\n{link}\n\n\
+             If you do not have a Firecash account yet, create one with this email \
+             address first."
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        group_invitation_message, password_reset_message, FileSender, MailSender, SmtpSender,
+    };
+
+    #[test]
+    fn reset_message_carries_the_link() {
+        let message = password_reset_message(
+            "ada@example.com",
+            "https://app.example.com/reset-password?token=abc",
+            60,
+        );
+        assert_eq!(message.to, "ada@example.com");
+        assert!(message
+            .body
+            .contains("https://app.example.com/reset-password?token=abc"));
+        assert!(message.body.contains("60 minutes"));
+    }
+
+    #[test]
+    fn invitation_message_names_the_group_and_role() {
+        let message = group_invitation_message(
+            "grace@example.com",
+            "Ada",
+            "Household",
+            "edit",
+            "https://app.example.com/invitations?token=abc",
+            7,
+        );
+        assert_eq!(message.subject, "Ada invited you to Household on Firecash");
+        assert!(message.body.contains("\"Household\""));
+        assert!(message.body.contains("with edit access"));
+        assert!(message
+            .body
+            .contains("https://app.example.com/invitations?token=abc"));
+        assert!(message.body.contains("7 days"));
+    }
+
+    #[test]
+    fn smtp_sender_validates_configuration() {
+        assert!(
+            SmtpSender::new("smtp://localhost:1025", "Firecash <no-reply@example.com>").is_ok()
+        );
+        assert!(SmtpSender::new("smtp://localhost:1025", "not an address").is_err());
+        assert!(SmtpSender::new("ftp://localhost", "no-reply@example.com").is_err());
+    }
+
+    #[tokio::test]
+    async fn file_sender_writes_messages() {
+        let dir = std::env::temp_dir().join(format!("firecash-mail-{}", uuid::Uuid::new_v4()));
+        let sender = FileSender::new(&dir);
+        let message = password_reset_message("ada@example.com", "https://example.com/r", 30);
+        sender.send(&message).await.unwrap();
+
+        let mut entries = std::fs::read_dir(&dir).unwrap();
+        let path = entries.next().unwrap().unwrap().path();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            contents.starts_with("To: ada@example.com\r\nSubject: Reset your Firecash password")
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
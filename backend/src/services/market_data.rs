@@ -0,0 +1,368 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::services::crypto::{crypto_symbol, CoinbaseProvider};
+use crate::services::pricing::{
+    currency_from_symbol, fetch_stooq_candles, fetch_stooq_prices, parse_candles_csv, Candle,
+    QuoteBatch, SymbolFailure,
+};
+
+pub type ProviderFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+pub const DATA_SOURCES: [&str; 4] = ["stooq", "coinbase", "file", "manual"];
+
+pub const MANUAL_SOURCE: &str = "manual";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AssetClass {
+    Equity,
+    Crypto,
+}
+
+impl AssetClass {
+    pub fn from_asset_type(asset_type: &str) -> Self {
+        match asset_type.trim().to_lowercase().as_str() {
+            "crypto" | "cryptocurrency" => Self::Crypto,
+            _ => Self::Equity,
+        }
+    }
+}
+
+pub fn resolve_symbol(class: AssetClass, symbol: &str, currency_code: &str) -> Option<String> {
+    match class {
+        AssetClass::Equity => {
+            let symbol = symbol.trim().to_uppercase();
+            (!symbol.is_empty()).then_some(symbol)
+        }
+        AssetClass::Crypto => crypto_symbol(symbol, currency_code),
+    }
+}
+
+pub trait PriceProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn quotes<'a>(&'a self, symbols: &'a [String]) -> ProviderFuture<'a, QuoteBatch>;
+
+    fn daily_candles<'a>(
+        &'a self,
+        symbol: &'a str,
+    ) -> ProviderFuture<'a, Result<Vec<Candle>, String>>;
+}
+
+pub struct StooqProvider;
+
+impl PriceProvider for StooqProvider {
+    fn name(&self) -> &'static str {
+        "stooq"
+    }
+
+    fn quotes<'a>(&'a self, symbols: &'a [String]) -> ProviderFuture<'a, QuoteBatch> {
+        Box::pin(fetch_stooq_prices(symbols))
+    }
+
+    fn daily_candles<'a>(
+        &'a self,
+        symbol: &'a str,
+    ) -> ProviderFuture<'a, Result<Vec<Candle>, String>> {
+        Box::pin(async move { fetch_stooq_candles(symbol).await.map_err(|err| err.to_string()) })
+    }
+}
+
+pub struct FileProvider {
+    root: PathBuf,
+}
+
+impl FileProvider {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn from_env() -> Option<Self> {
+        std::env::var("MARKET_DATA_DIR")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .map(Self::new)
+    }
+
+    async fn load(&self, symbol: &str) -> Result<Vec<Candle>, String> {
+        let Some(stem) = normalize_symbol(symbol) else {
+            return Err(format!("unsupported symbol for file provider: {symbol}"));
+        };
+        let csv_path = self.root.join(format!("{stem}.csv"));
+        let json_path = self.root.join(format!("{stem}.json"));
+        let mut candles = if let Some(body) = read_optional(&csv_path).await? {
+            parse_candles_csv(&body)
+        } else if let Some(body) = read_optional(&json_path).await? {
+            serde_json::from_str::<Vec<Candle>>(&body)
+                .map_err(|err| format!("invalid candle file {}: {err}", json_path.display()))?
+        } else {
+            return Err(format!("no price file for {symbol}"));
+        };
+        candles.sort_by(|a, b| a.date.cmp(&b.date));
+        Ok(candles)
+    }
+}
+
+impl PriceProvider for FileProvider {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn quotes<'a>(&'a self, symbols: &'a [String]) -> ProviderFuture<'a, QuoteBatch> {
+        Box::pin(async move {
+            let mut batch = QuoteBatch::default();
+            for symbol in symbols {
+                match self.load(symbol).await {
+                    Ok(candles) => match candles.last() {
+                        Some(candle) => {
+                            batch
+                                .prices
+                                .insert(symbol.clone(), (candle.close, currency_from_symbol(symbol)));
+                        }
+                        None => batch.failures.push(SymbolFailure {
+                            symbol: symbol.clone(),
+                            reason: "price file has no rows".to_string(),
+                        }),
+                    },
+                    Err(reason) => batch.failures.push(SymbolFailure {
+                        symbol: symbol.clone(),
+                        reason,
+                    }),
+                }
+            }
+            batch
+        })
+    }
+
+    fn daily_candles<'a>(
+        &'a self,
+        symbol: &'a str,
+    ) -> ProviderFuture<'a, Result<Vec<Candle>, String>> {
+        Box::pin(self.load(symbol))
+    }
+}
+
+async fn read_optional(path: &Path) -> Result<Option<String>, String> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(body) => Ok(Some(body)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(format!("failed to read {}: {err}", path.display())),
+    }
+}
+
+pub fn normalize_symbol(symbol: &str) -> Option<String> {
+    let symbol = symbol.trim().to_uppercase();
+    let valid = !symbol.is_empty()
+        && !symbol.starts_with('.')
+        && symbol
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '^' | '='));
+    valid.then_some(symbol)
+}
+
+pub fn is_known_source(source: &str) -> bool {
+    DATA_SOURCES.contains(&source)
+}
+
+pub fn provider_chain(preferred: Option<&str>, class: AssetClass) -> Vec<Arc<dyn PriceProvider>> {
+    if preferred == Some(MANUAL_SOURCE) {
+        return Vec::new();
+    }
+    let mut chain: Vec<Arc<dyn PriceProvider>> = match class {
+        AssetClass::Equity => vec![Arc::new(StooqProvider)],
+        AssetClass::Crypto => vec![Arc::new(CoinbaseProvider)],
+    };
+    if let Some(provider) = FileProvider::from_env() {
+        chain.push(Arc::new(provider));
+    }
+    prefer(chain, preferred)
+}
+
+fn prefer(
+    mut chain: Vec<Arc<dyn PriceProvider>>,
+    preferred: Option<&str>,
+) -> Vec<Arc<dyn PriceProvider>> {
+    if let Some(index) = preferred.and_then(|name| {
+        chain
+            .iter()
+            .position(|provider| provider.name() == name)
+    }) {
+        let provider = chain.remove(index);
+        chain.insert(0, provider);
+    }
+    chain
+}
+
+pub async fn fetch_quotes(chain: &[Arc<dyn PriceProvider>], symbols: &[String]) -> QuoteBatch {
+    let mut result = QuoteBatch::default();
+    let mut pending: Vec<String> = symbols.to_vec();
+    let mut reasons: HashMap<String, Vec<String>> = HashMap::new();
+    for provider in chain {
+        if pending.is_empty() {
+            break;
+        }
+        let batch = provider.quotes(&pending).await;
+        for failure in batch.failures {
+            reasons
+                .entry(failure.symbol)
+                .or_default()
+                .push(format!("{}: {}", provider.name(), failure.reason));
+        }
+        pending.retain(|symbol| !batch.prices.contains_key(symbol));
+        result.prices.extend(batch.prices);
+    }
+    for symbol in pending {
+        let reason = reasons
+            .remove(&symbol)
+            .map(|reasons| reasons.join("; "))
+            .unwrap_or_else(|| "no price provider returned a quote".to_string());
+        result.failures.push(SymbolFailure { symbol, reason });
+    }
+    result
+}
+
+pub async fn fetch_candles(
+    chain: &[Arc<dyn PriceProvider>],
+    symbol: &str,
+) -> Result<Vec<Candle>, String> {
+    let mut reasons = Vec::new();
+    for provider in chain {
+        match provider.daily_candles(symbol).await {
+            Ok(candles) if !candles.is_empty() => return Ok(candles),
+            Ok(_) => reasons.push(format!("{}: no candles", provider.name())),
+            Err(reason) => reasons.push(format!("{}: {reason}", provider.name())),
+        }
+    }
+    if reasons.is_empty() {
+        Err(format!("no price provider configured for {symbol}"))
+    } else {
+        Err(reasons.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        fetch_candles, fetch_quotes, is_known_source, normalize_symbol, prefer, provider_chain,
+        resolve_symbol, AssetClass, FileProvider, PriceProvider,
+    };
+    use std::sync::Arc;
+
+    fn data_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("firecash-{name}-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn normalize_symbol_rejects_path_traversal() {
+        assert_eq!(normalize_symbol(" spy ").as_deref(), Some("SPY"));
+        assert_eq!(normalize_symbol("0700.HK").as_deref(), Some("0700.HK"));
+        assert_eq!(normalize_symbol("../etc/passwd"), None);
+        assert_eq!(normalize_symbol("a/b"), None);
+        assert_eq!(normalize_symbol(""), None);
+    }
+
+    #[test]
+    fn resolves_symbols_by_asset_class() {
+        assert_eq!(AssetClass::from_asset_type("Crypto"), AssetClass::Crypto);
+        assert_eq!(AssetClass::from_asset_type("Stock"), AssetClass::Equity);
+        assert_eq!(
+            resolve_symbol(AssetClass::Equity, " btc ", "USD").as_deref(),
+            Some("BTC")
+        );
+        assert_eq!(
+            resolve_symbol(AssetClass::Crypto, "btc", "EUR").as_deref(),
+            Some("BTC-EUR")
+        );
+        assert_eq!(resolve_symbol(AssetClass::Equity, " ", "USD"), None);
+    }
+
+    #[test]
+    fn crypto_assets_skip_stooq() {
+        let names: Vec<_> = provider_chain(None, AssetClass::Crypto)
+            .iter()
+            .map(|provider| provider.name())
+            .collect();
+        assert_eq!(names.first(), Some(&"coinbase"));
+        assert!(!names.contains(&"stooq"));
+        assert!(provider_chain(Some("manual"), AssetClass::Crypto).is_empty());
+    }
+
+    #[test]
+    fn preferred_provider_moves_to_front() {
+        let dir = data_dir("prefer");
+        let chain: Vec<Arc<dyn PriceProvider>> = vec![
+            Arc::new(super::StooqProvider),
+            Arc::new(FileProvider::new(&dir)),
+        ];
+        let names: Vec<_> = prefer(chain, Some("file"))
+            .iter()
+            .map(|provider| provider.name())
+            .collect();
+        assert_eq!(names, ["file", "stooq"]);
+    }
+
+    #[test]
+    fn only_sources_with_a_provider_are_known() {
+        for source in ["stooq", "coinbase", "file", "manual"] {
+            assert!(is_known_source(source), "{source}");
+        }
+        assert!(!is_known_source("broker"));
+        assert!(!is_known_source("custom"));
+    }
+
+    #[tokio::test]
+    async fn file_provider_reads_csv_and_json() {
+        let dir = data_dir("files");
+        std::fs::write(
+            dir.join("SPY.csv"),
+            "Date,Open,High,Low,Close,Volume\n2024-01-03,470,472,468,471.5,100\n2024-01-02,468,471,467,470.2,100\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("VOD.L.json"),
+            r#"[{"date":"2024-01-02","open":70.1,"high":71.0,"low":69.8,"close":70.5}]"#,
+        )
+        .unwrap();
+        let provider = FileProvider::new(&dir);
+
+        let candles = provider.daily_candles("spy").await.unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0].date, "2024-01-02");
+
+        let batch = provider
+            .quotes(&["SPY".to_string(), "VOD.L".to_string(), "QQQ".to_string()])
+            .await;
+        assert_eq!(batch.prices["SPY"], (471.5, "USD".to_string()));
+        assert_eq!(batch.prices["VOD.L"], (70.5, "GBP".to_string()));
+        assert_eq!(batch.failures.len(), 1);
+        assert_eq!(batch.failures[0].symbol, "QQQ");
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_next_provider() {
+        let empty = data_dir("empty");
+        let filled = data_dir("filled");
+        std::fs::write(
+            filled.join("SPY.csv"),
+            "Date,Open,High,Low,Close\n2024-01-02,468,471,467,470.2\n",
+        )
+        .unwrap();
+        let chain: Vec<Arc<dyn PriceProvider>> = vec![
+            Arc::new(FileProvider::new(&empty)),
+            Arc::new(FileProvider::new(&filled)),
+        ];
+
+        let batch = fetch_quotes(&chain, &["SPY".to_string(), "QQQ".to_string()]).await;
+        assert_eq!(batch.prices["SPY"].0, 470.2);
+        assert_eq!(batch.failures.len(), 1);
+        assert!(batch.failures[0].reason.contains("no price file for QQQ"));
+
+        assert_eq!(fetch_candles(&chain, "SPY").await.unwrap().len(), 1);
+        assert!(fetch_candles(&[], "SPY").await.is_err());
+    }
+}
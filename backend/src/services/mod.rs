@@ -1,3 +1,24 @@
 pub mod pricing;
 pub mod forex;
+pub mod http;
+pub mod allocation;
+pub mod backfill;
+pub mod balances;
+pub mod benchmark;
+pub mod change_history;
+pub mod corporate_actions;
+pub mod cost_basis;
+pub mod credit_cards;
+pub mod crypto;
 pub mod integrations;
+pub mod liabilities;
+pub mod login_throttle;
+pub mod mail;
+pub mod market_data;
+pub mod oidc;
+pub mod recurring_detection;
+pub mod returns;
+pub mod scheduler;
+pub mod token_scopes;
+pub mod totp;
+pub mod valuation;
@@ -1,12 +1,19 @@
-use reqwest::{header, Client};
-use serde::Serialize;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::{postgres::PgPool, QueryBuilder, Row};
 use std::collections::HashMap;
-use std::io::{Error as IoError, ErrorKind};
-use tracing::warn;
+use std::io::Error as IoError;
+use tracing::{info, warn};
 use uuid::Uuid;
 
-#[derive(Serialize)]
+use crate::services::crypto::settlement_currency;
+use crate::services::forex::{convert_amount, load_usd_rates};
+use crate::services::http::get_text;
+use crate::services::market_data::{fetch_quotes, provider_chain, resolve_symbol, AssetClass};
+
+const STOOQ_PROVIDER: &str = "stooq";
+
+#[derive(Serialize, Deserialize)]
 pub struct Candle {
     pub date: String,
     pub open: f64,
@@ -15,70 +22,259 @@ pub struct Candle {
     pub close: f64,
 }
 
+#[derive(Clone, Debug, Serialize)]
+pub struct SymbolFailure {
+    pub symbol: String,
+    pub reason: String,
+}
+
+#[derive(Default, Serialize)]
+pub struct RefreshSummary {
+    pub updated: usize,
+    pub failures: Vec<SymbolFailure>,
+}
+
+#[derive(Default)]
+pub struct QuoteBatch {
+    pub prices: HashMap<String, (f64, String)>,
+    pub failures: Vec<SymbolFailure>,
+}
+
 pub async fn refresh_asset_prices(
     pool: &PgPool,
     user_id: Option<Uuid>,
-) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<RefreshSummary, Box<dyn std::error::Error + Send + Sync>> {
     let mut query = QueryBuilder::new(
         r#"
-        SELECT a.id, a.symbol, a.currency_code
+        SELECT a.id,
+               a.symbol,
+               a.asset_type,
+               a.currency_code,
+               COALESCE(a.data_source, up.value #>> '{}') as data_source
         FROM assets a
         INNER JOIN accounts acc ON a.account_id = acc.id
+        LEFT JOIN user_preferences up
+          ON up.user_id = acc.user_id AND up.key = 'asset_data_source'
+        WHERE a.valuation_mode = 'market'
         "#,
     );
 
     if let Some(user_id) = user_id {
-        query.push(" WHERE acc.user_id = ");
+        query.push(" AND acc.user_id = ");
         query.push_bind(user_id);
     }
 
     let rows = query.build().fetch_all(pool).await?;
     if rows.is_empty() {
-        return Ok(0);
+        return Ok(RefreshSummary::default());
     }
 
-    let mut symbol_map: HashMap<String, Vec<Uuid>> = HashMap::new();
+    let mut sources: HashMap<(Option<String>, AssetClass), HashMap<String, Vec<Uuid>>> =
+        HashMap::new();
     let mut currency_map: HashMap<Uuid, String> = HashMap::new();
+    let mut summary = RefreshSummary::default();
 
     for row in rows {
         let asset_id: Uuid = row.try_get("id")?;
         let symbol: String = row.try_get("symbol")?;
+        let asset_type: String = row.try_get("asset_type")?;
         let currency_code: String = row.try_get("currency_code")?;
-        let normalized_symbol = symbol.trim().to_uppercase();
-        symbol_map.entry(normalized_symbol).or_default().push(asset_id);
+        let data_source: Option<String> = row.try_get("data_source")?;
+        let class = AssetClass::from_asset_type(&asset_type);
+        let Some(lookup) = resolve_symbol(class, &symbol, &currency_code) else {
+            summary.failures.push(SymbolFailure {
+                symbol,
+                reason: "symbol cannot be priced".to_string(),
+            });
+            continue;
+        };
+        sources
+            .entry((data_source, class))
+            .or_default()
+            .entry(lookup)
+            .or_default()
+            .push(asset_id);
         currency_map.insert(asset_id, currency_code);
     }
 
-    let symbols: Vec<String> = symbol_map.keys().cloned().collect();
-    let client = Client::new();
-    let mut updated = 0usize;
-
-    for chunk in symbols.chunks(50) {
-        let prices = fetch_stooq_prices(&client, chunk).await?;
-        if prices.is_empty() {
-            warn!("stooq returned no prices for {}", chunk.join(","));
+    let rates = load_usd_rates(pool).await?;
+    for ((data_source, class), symbol_map) in sources {
+        let chain = provider_chain(data_source.as_deref(), class);
+        if chain.is_empty() {
             continue;
         }
-        updated += apply_prices(pool, &symbol_map, &currency_map, prices).await?;
+        let symbols: Vec<String> = symbol_map.keys().cloned().collect();
+        for chunk in symbols.chunks(50) {
+            let mut batch = fetch_quotes(&chain, chunk).await;
+            skip_unconvertible_quotes(&mut batch, &symbol_map, &currency_map, &rates);
+            for failure in &batch.failures {
+                warn!(symbol = %failure.symbol, reason = %failure.reason, "price fetch failed");
+            }
+            record_fetch_status(pool, &symbol_map, &batch).await?;
+            summary.updated +=
+                apply_prices(pool, &symbol_map, &currency_map, &rates, batch.prices).await?;
+            summary.failures.extend(batch.failures);
+        }
+    }
+
+    Ok(summary)
+}
+
+async fn record_fetch_status(
+    pool: &PgPool,
+    symbol_map: &HashMap<String, Vec<Uuid>>,
+    batch: &QuoteBatch,
+) -> Result<(), sqlx::Error> {
+    for symbol in batch.prices.keys() {
+        for asset_id in symbol_map.get(symbol).into_iter().flatten() {
+            sqlx::query(
+                r#"
+                INSERT INTO asset_price_fetch_status (
+                    asset_id, symbol, last_attempt_at, last_success_at, last_error,
+                    consecutive_failures
+                )
+                VALUES ($1, $2, NOW(), NOW(), NULL, 0)
+                ON CONFLICT (asset_id)
+                DO UPDATE SET symbol = EXCLUDED.symbol,
+                              last_attempt_at = NOW(),
+                              last_success_at = NOW(),
+                              last_error = NULL,
+                              consecutive_failures = 0
+                "#,
+            )
+            .bind(asset_id)
+            .bind(symbol)
+            .execute(pool)
+            .await?;
+        }
     }
+    for failure in &batch.failures {
+        for asset_id in symbol_map.get(&failure.symbol).into_iter().flatten() {
+            sqlx::query(
+                r#"
+                INSERT INTO asset_price_fetch_status (
+                    asset_id, symbol, last_attempt_at, last_error, consecutive_failures
+                )
+                VALUES ($1, $2, NOW(), $3, 1)
+                ON CONFLICT (asset_id)
+                DO UPDATE SET symbol = EXCLUDED.symbol,
+                              last_attempt_at = NOW(),
+                              last_error = EXCLUDED.last_error,
+                              consecutive_failures =
+                                  asset_price_fetch_status.consecutive_failures + 1
+                "#,
+            )
+            .bind(asset_id)
+            .bind(&failure.symbol)
+            .bind(&failure.reason)
+            .execute(pool)
+            .await?;
+        }
+    }
+    Ok(())
+}
 
+pub async fn refresh_due_asset_prices(
+    pool: &PgPool,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT acc.user_id,
+               up.value #>> '{}' as cadence,
+               MAX(ph.recorded_at) as last_refreshed_at
+        FROM assets a
+        INNER JOIN accounts acc ON a.account_id = acc.id
+        LEFT JOIN user_preferences up
+          ON up.user_id = acc.user_id AND up.key = 'asset_refresh_cadence'
+        LEFT JOIN price_history ph ON ph.asset_id = a.id
+        WHERE a.valuation_mode = 'market'
+        GROUP BY acc.user_id, up.value
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let now = Utc::now();
+    let mut updated = 0usize;
+    for row in rows {
+        let user_id: Uuid = row.try_get("user_id")?;
+        let cadence: Option<String> = row.try_get("cadence")?;
+        let last_refreshed_at: Option<DateTime<Utc>> = row.try_get("last_refreshed_at")?;
+        if !cadence_is_due(cadence.as_deref().unwrap_or("daily"), last_refreshed_at, now) {
+            continue;
+        }
+        info!(%user_id, "refreshing asset prices");
+        let summary = refresh_asset_prices(pool, Some(user_id)).await?;
+        updated += summary.updated;
+    }
     Ok(updated)
 }
 
+fn cadence_is_due(
+    cadence: &str,
+    last_refreshed_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> bool {
+    let period = match cadence {
+        "manual" => return false,
+        "hourly" => Duration::hours(1),
+        "weekly" => Duration::weeks(1),
+        _ => Duration::days(1),
+    };
+    match last_refreshed_at {
+        Some(last_refreshed_at) => now - last_refreshed_at >= period - Duration::minutes(5),
+        None => true,
+    }
+}
+
+fn skip_unconvertible_quotes(
+    batch: &mut QuoteBatch,
+    symbol_map: &HashMap<String, Vec<Uuid>>,
+    currency_map: &HashMap<Uuid, String>,
+    rates: &HashMap<String, f64>,
+) {
+    let mut skipped = Vec::new();
+    for (symbol, (price, currency)) in &batch.prices {
+        let quote_currency = settlement_currency(currency);
+        let missing = symbol_map
+            .get(symbol)
+            .into_iter()
+            .flatten()
+            .filter_map(|asset_id| currency_map.get(asset_id))
+            .find(|code| convert_amount(*price, quote_currency, code, rates).is_none());
+        if let Some(code) = missing {
+            skipped.push(SymbolFailure {
+                symbol: symbol.clone(),
+                reason: format!("no FX rate from {quote_currency} to {code}"),
+            });
+        }
+    }
+    for failure in skipped {
+        batch.prices.remove(&failure.symbol);
+        batch.failures.push(failure);
+    }
+}
+
 async fn apply_prices(
     pool: &PgPool,
     symbol_map: &HashMap<String, Vec<Uuid>>,
     currency_map: &HashMap<Uuid, String>,
+    rates: &HashMap<String, f64>,
     prices: HashMap<String, (f64, String)>,
 ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
     let mut updated = 0usize;
     for (symbol, (price, currency)) in prices {
         if let Some(asset_ids) = symbol_map.get(&symbol) {
+            let quote_currency = settlement_currency(&currency);
             for asset_id in asset_ids {
                 let currency_code = currency_map
                     .get(asset_id)
                     .cloned()
-                    .unwrap_or_else(|| currency.clone());
+                    .unwrap_or_else(|| quote_currency.to_string());
+                let Some(price) = convert_amount(price, quote_currency, &currency_code, rates)
+                else {
+                    continue;
+                };
                 sqlx::query(
                     r#"
                     INSERT INTO price_history (id, asset_id, price, currency_code, recorded_at)
@@ -98,41 +294,46 @@ async fn apply_prices(
     Ok(updated)
 }
 
-async fn fetch_stooq_prices(
-    client: &Client,
-    symbols: &[String],
-) -> Result<HashMap<String, (f64, String)>, Box<dyn std::error::Error + Send + Sync>> {
-    let mut prices = HashMap::new();
+pub(crate) async fn fetch_stooq_prices(symbols: &[String]) -> QuoteBatch {
+    let mut batch = QuoteBatch::default();
     for symbol in symbols {
         let lookup = stooq_symbol(symbol);
-        let url = format!(
-            "https://stooq.com/q/l/?s={lookup}&f=sd2t2ohlcv&h&e=csv"
-        );
-        let response = client
-            .get(url)
-            .header(header::USER_AGENT, "firecash-api")
-            .send()
-            .await?;
-        if !response.status().is_success() {
-            continue;
-        }
-        let body = response.text().await?;
-        let mut lines = body.lines();
-        let _header = lines.next();
-        let Some(row) = lines.next() else {
-            continue;
+        let url = format!("https://stooq.com/q/l/?s={lookup}&f=sd2t2ohlcv&h&e=csv");
+        let result = match get_text(STOOQ_PROVIDER, &url).await {
+            Ok(body) => parse_stooq_quote(&body),
+            Err(err) => Err(err.to_string()),
         };
-        let parts: Vec<&str> = row.split(',').collect();
-        if parts.len() < 8 {
-            continue;
-        }
-        let close = parts[6].trim().parse::<f64>();
-        if let Ok(price) = close {
-            let currency = currency_from_symbol(symbol);
-            prices.insert(symbol.clone(), (price, currency));
+        match result {
+            Ok(price) => {
+                let currency = currency_from_symbol(symbol);
+                batch.prices.insert(symbol.clone(), (price, currency));
+            }
+            Err(reason) => batch.failures.push(SymbolFailure {
+                symbol: symbol.clone(),
+                reason,
+            }),
         }
     }
-    Ok(prices)
+    batch
+}
+
+fn parse_stooq_quote(body: &str) -> Result<f64, String> {
+    let mut lines = body.lines();
+    let _header = lines.next();
+    let row = lines
+        .next()
+        .ok_or_else(|| "stooq returned an empty response".to_string())?;
+    let parts: Vec<&str> = row.split(',').collect();
+    if parts.len() < 8 {
+        return Err(format!("unexpected stooq response: {row}"));
+    }
+    let close = parts[6].trim();
+    if close == "N/D" {
+        return Err("stooq has no data for this symbol".to_string());
+    }
+    close
+        .parse::<f64>()
+        .map_err(|_| format!("invalid stooq close price: {close}"))
 }
 
 pub async fn fetch_stooq_candles(
@@ -140,19 +341,13 @@ pub async fn fetch_stooq_candles(
 ) -> Result<Vec<Candle>, Box<dyn std::error::Error + Send + Sync>> {
     let lookup = stooq_symbol(symbol);
     let url = format!("https://stooq.com/q/d/l/?s={lookup}&i=d");
-    let client = Client::new();
-    let response = client
-        .get(url)
-        .header(header::USER_AGENT, "firecash-api")
-        .send()
-        .await?;
-    if !response.status().is_success() {
-        return Err(Box::new(IoError::new(
-            ErrorKind::Other,
-            format!("stooq candle request failed: {}", response.status()),
-        )));
-    }
-    let body = response.text().await?;
+    let body = get_text(STOOQ_PROVIDER, &url).await.map_err(|err| {
+        IoError::other(format!("stooq candle request failed: {err}"))
+    })?;
+    Ok(parse_candles_csv(&body))
+}
+
+pub(crate) fn parse_candles_csv(body: &str) -> Vec<Candle> {
     let mut lines = body.lines();
     let _header = lines.next();
     let mut candles = Vec::new();
@@ -176,7 +371,7 @@ pub async fn fetch_stooq_candles(
             });
         }
     }
-    Ok(candles)
+    candles
 }
 
 fn stooq_symbol(symbol: &str) -> String {
@@ -187,7 +382,7 @@ fn stooq_symbol(symbol: &str) -> String {
     }
 }
 
-fn currency_from_symbol(symbol: &str) -> String {
+pub(crate) fn currency_from_symbol(symbol: &str) -> String {
     let symbol = symbol.to_uppercase();
     if symbol.ends_with(".HK") {
         "HKD".to_string()
@@ -208,7 +403,38 @@ fn currency_from_symbol(symbol: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{currency_from_symbol, stooq_symbol};
+    use super::{
+        cadence_is_due, currency_from_symbol, parse_stooq_quote, skip_unconvertible_quotes,
+        stooq_symbol, QuoteBatch,
+    };
+    use chrono::{Duration, TimeZone, Utc};
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    #[test]
+    fn quotes_without_an_fx_rate_become_failures() {
+        let eur_asset = Uuid::new_v4();
+        let usd_asset = Uuid::new_v4();
+        let symbol_map = HashMap::from([
+            ("btc-usdt".to_string(), vec![eur_asset]),
+            ("aapl.us".to_string(), vec![usd_asset]),
+        ]);
+        let currency_map =
+            HashMap::from([(eur_asset, "EUR".to_string()), (usd_asset, "USD".to_string())]);
+        let mut batch = QuoteBatch {
+            prices: HashMap::from([
+                ("btc-usdt".to_string(), (60_000.0, "USDT".to_string())),
+                ("aapl.us".to_string(), (190.0, "USD".to_string())),
+            ]),
+            failures: Vec::new(),
+        };
+        skip_unconvertible_quotes(&mut batch, &symbol_map, &currency_map, &HashMap::new());
+        assert!(batch.prices.contains_key("aapl.us"));
+        assert!(!batch.prices.contains_key("btc-usdt"));
+        assert_eq!(batch.failures.len(), 1);
+        assert_eq!(batch.failures[0].symbol, "btc-usdt");
+        assert_eq!(batch.failures[0].reason, "no FX rate from USD to EUR");
+    }
 
     #[test]
     fn stooq_symbol_defaults_to_us() {
@@ -230,4 +456,30 @@ mod tests {
         assert_eq!(currency_from_symbol("BMW.DE"), "EUR");
         assert_eq!(currency_from_symbol("AAPL"), "USD");
     }
+
+    #[test]
+    fn parse_stooq_quote_reports_missing_data() {
+        let header = "Symbol,Date,Time,Open,High,Low,Close,Volume";
+        assert_eq!(
+            parse_stooq_quote(&format!(
+                "{header}\nAAPL.US,2024-05-10,22:00:00,184.9,185.09,182.13,183.05,50759496"
+            )),
+            Ok(183.05)
+        );
+        assert!(parse_stooq_quote(&format!("{header}\nXXXX.US,N/D,N/D,N/D,N/D,N/D,N/D,N/D"))
+            .unwrap_err()
+            .contains("no data"));
+        assert!(parse_stooq_quote("").is_err());
+    }
+
+    #[test]
+    fn cadence_controls_refresh_due() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+        assert!(cadence_is_due("daily", None, now));
+        assert!(!cadence_is_due("manual", None, now));
+        assert!(cadence_is_due("hourly", Some(now - Duration::minutes(58)), now));
+        assert!(!cadence_is_due("daily", Some(now - Duration::hours(6)), now));
+        assert!(cadence_is_due("daily", Some(now - Duration::hours(24)), now));
+        assert!(!cadence_is_due("weekly", Some(now - Duration::days(3)), now));
+    }
 }
@@ -0,0 +1,374 @@
+use chrono::{DateTime, Duration, Utc};
+use hex::encode as hex_encode;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+pub const AMOUNT_TOLERANCE: f64 = 0.1;
+pub const DEFAULT_MIN_OCCURRENCES: usize = 3;
+const MIN_CONFIDENCE: f64 = 0.5;
+
+pub struct DetectionTransaction {
+    pub account_id: Uuid,
+    pub amount: f64,
+    pub currency_code: String,
+    pub transaction_type: String,
+    pub merchant: Option<String>,
+    pub description: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RecurringSuggestion {
+    pub suggestion_id: String,
+    pub account_id: Uuid,
+    pub label: String,
+    pub amount: f64,
+    pub currency_code: String,
+    pub transaction_type: String,
+    pub cadence: String,
+    pub interval_days: i32,
+    pub occurrences: usize,
+    pub first_occurred_at: DateTime<Utc>,
+    pub last_occurred_at: DateTime<Utc>,
+    pub next_occurs_at: DateTime<Utc>,
+    pub confidence: f64,
+}
+
+struct Cadence {
+    name: &'static str,
+    interval_days: i32,
+    min_days: f64,
+    max_days: f64,
+}
+
+const CADENCES: [Cadence; 5] = [
+    Cadence {
+        name: "weekly",
+        interval_days: 7,
+        min_days: 6.0,
+        max_days: 8.0,
+    },
+    Cadence {
+        name: "biweekly",
+        interval_days: 14,
+        min_days: 12.0,
+        max_days: 16.0,
+    },
+    Cadence {
+        name: "monthly",
+        interval_days: 30,
+        min_days: 27.0,
+        max_days: 33.0,
+    },
+    Cadence {
+        name: "quarterly",
+        interval_days: 91,
+        min_days: 84.0,
+        max_days: 98.0,
+    },
+    Cadence {
+        name: "yearly",
+        interval_days: 365,
+        min_days: 355.0,
+        max_days: 375.0,
+    },
+];
+
+pub fn detect_recurring(
+    transactions: &[DetectionTransaction],
+    min_occurrences: usize,
+) -> Vec<RecurringSuggestion> {
+    let min_occurrences = min_occurrences.max(2);
+    let mut groups: HashMap<(Uuid, String, String, String), Vec<&DetectionTransaction>> =
+        HashMap::new();
+    for transaction in transactions {
+        let Some(label) = normalize_label(
+            transaction
+                .merchant
+                .as_deref()
+                .or(transaction.description.as_deref()),
+        ) else {
+            continue;
+        };
+        groups
+            .entry((
+                transaction.account_id,
+                transaction.transaction_type.clone(),
+                transaction.currency_code.clone(),
+                label,
+            ))
+            .or_default()
+            .push(transaction);
+    }
+
+    let mut suggestions = Vec::new();
+    for ((account_id, transaction_type, currency_code, label), mut group) in groups {
+        group.sort_by(|a, b| a.amount.total_cmp(&b.amount));
+        for cluster in split_by_amount(&group) {
+            if cluster.len() < min_occurrences {
+                continue;
+            }
+            if let Some(suggestion) = analyze_cluster(
+                account_id,
+                &transaction_type,
+                &currency_code,
+                &label,
+                cluster,
+            ) {
+                suggestions.push(suggestion);
+            }
+        }
+    }
+
+    suggestions.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    let mut seen = HashSet::new();
+    suggestions.retain(|suggestion| seen.insert(suggestion.suggestion_id.clone()));
+    suggestions
+}
+
+fn analyze_cluster(
+    account_id: Uuid,
+    transaction_type: &str,
+    currency_code: &str,
+    label: &str,
+    mut cluster: Vec<&DetectionTransaction>,
+) -> Option<RecurringSuggestion> {
+    cluster.sort_by_key(|transaction| transaction.occurred_at);
+    let intervals: Vec<f64> = cluster
+        .windows(2)
+        .map(|pair| (pair[1].occurred_at - pair[0].occurred_at).num_hours() as f64 / 24.0)
+        .collect();
+    let median_interval = median(&intervals)?;
+    let cadence = CADENCES.iter().find(|cadence| {
+        median_interval >= cadence.min_days && median_interval <= cadence.max_days
+    })?;
+
+    let regular = intervals
+        .iter()
+        .filter(|interval| **interval >= cadence.min_days && **interval <= cadence.max_days)
+        .count();
+    let regularity = regular as f64 / intervals.len() as f64;
+
+    let amounts: Vec<f64> = cluster.iter().map(|transaction| transaction.amount).collect();
+    let mean_amount = amounts.iter().sum::<f64>() / amounts.len() as f64;
+    let variance = amounts
+        .iter()
+        .map(|amount| (amount - mean_amount).powi(2))
+        .sum::<f64>()
+        / amounts.len() as f64;
+    let amount_consistency = if mean_amount.abs() > f64::EPSILON {
+        (1.0 - variance.sqrt() / mean_amount.abs()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let occurrence_factor = (cluster.len() as f64 / 6.0).min(1.0);
+    let confidence = regularity * 0.6 + amount_consistency * 0.25 + occurrence_factor * 0.15;
+    if confidence < MIN_CONFIDENCE {
+        return None;
+    }
+
+    let anchor_amount = amounts.iter().copied().fold(f64::INFINITY, f64::min);
+    let first = cluster.first()?;
+    let last = cluster.last()?;
+    let amount = (mean_amount * 100.0).round() / 100.0;
+    Some(RecurringSuggestion {
+        suggestion_id: suggestion_id(
+            account_id,
+            transaction_type,
+            currency_code,
+            label,
+            cadence.interval_days,
+            anchor_amount,
+        ),
+        account_id,
+        label: last
+            .merchant
+            .clone()
+            .or_else(|| last.description.clone())
+            .unwrap_or_else(|| label.to_string()),
+        amount,
+        currency_code: currency_code.to_string(),
+        transaction_type: transaction_type.to_string(),
+        cadence: cadence.name.to_string(),
+        interval_days: cadence.interval_days,
+        occurrences: cluster.len(),
+        first_occurred_at: first.occurred_at,
+        last_occurred_at: last.occurred_at,
+        next_occurs_at: last.occurred_at + Duration::days(cadence.interval_days as i64),
+        confidence: (confidence * 100.0).round() / 100.0,
+    })
+}
+
+fn split_by_amount<'a>(
+    sorted: &[&'a DetectionTransaction],
+) -> Vec<Vec<&'a DetectionTransaction>> {
+    let mut clusters: Vec<Vec<&DetectionTransaction>> = Vec::new();
+    for transaction in sorted {
+        let starts_new_cluster = match clusters.last().and_then(|cluster| cluster.first()) {
+            Some(anchor) => {
+                let base = anchor.amount.abs().max(f64::EPSILON);
+                (transaction.amount - anchor.amount).abs() / base > AMOUNT_TOLERANCE
+            }
+            None => true,
+        };
+        if starts_new_cluster {
+            clusters.push(vec![*transaction]);
+        } else if let Some(cluster) = clusters.last_mut() {
+            cluster.push(*transaction);
+        }
+    }
+    clusters
+}
+
+fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        Some((sorted[mid - 1] + sorted[mid]) / 2.0)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+fn normalize_label(value: Option<&str>) -> Option<String> {
+    let words: Vec<String> = value?
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !word.chars().all(|c| c.is_ascii_digit()))
+        .map(|word| word.to_lowercase())
+        .collect();
+    if words.is_empty() {
+        None
+    } else {
+        Some(words.join(" "))
+    }
+}
+
+fn suggestion_id(
+    account_id: Uuid,
+    transaction_type: &str,
+    currency_code: &str,
+    label: &str,
+    interval_days: i32,
+    anchor_amount: f64,
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(
+        format!(
+            "{account_id}|{transaction_type}|{currency_code}|{label}|{interval_days}|{anchor_amount:.2}"
+        )
+        .as_bytes(),
+    );
+    hex_encode(hasher.finalize())[..16].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect_recurring, normalize_label, DetectionTransaction};
+    use chrono::{Duration, TimeZone, Utc};
+    use uuid::Uuid;
+
+    fn transaction(account_id: Uuid, merchant: &str, amount: f64, day: i64) -> DetectionTransaction {
+        DetectionTransaction {
+            account_id,
+            amount,
+            currency_code: "USD".to_string(),
+            transaction_type: "expense".to_string(),
+            merchant: Some(merchant.to_string()),
+            description: None,
+            occurred_at: Utc.with_ymd_and_hms(2024, 1, 5, 12, 0, 0).unwrap() + Duration::days(day),
+        }
+    }
+
+    #[test]
+    fn normalize_label_strips_reference_numbers() {
+        assert_eq!(normalize_label(Some("NETFLIX.COM #88213")).as_deref(), Some("netflix com"));
+        assert_eq!(normalize_label(Some("  12345 ")), None);
+    }
+
+    #[test]
+    fn detects_monthly_subscription() {
+        let account_id = Uuid::new_v4();
+        let transactions: Vec<_> = [0, 31, 60, 91, 121]
+            .iter()
+            .map(|day| transaction(account_id, "Netflix", 15.49, *day))
+            .collect();
+        let suggestions = detect_recurring(&transactions, 3);
+        assert_eq!(suggestions.len(), 1);
+        let suggestion = &suggestions[0];
+        assert_eq!(suggestion.cadence, "monthly");
+        assert_eq!(suggestion.interval_days, 30);
+        assert_eq!(suggestion.occurrences, 5);
+        assert!(suggestion.confidence > 0.8);
+    }
+
+    #[test]
+    fn separates_distinct_amounts_for_same_merchant() {
+        let account_id = Uuid::new_v4();
+        let mut transactions: Vec<_> = [0, 7, 14, 21]
+            .iter()
+            .map(|day| transaction(account_id, "Gym", 10.0, *day))
+            .collect();
+        transactions.push(transaction(account_id, "Gym", 120.0, 3));
+        let suggestions = detect_recurring(&transactions, 3);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].cadence, "weekly");
+        assert_eq!(suggestions[0].amount, 10.0);
+    }
+
+    #[test]
+    fn keeps_same_cadence_subscriptions_at_different_prices() {
+        let account_id = Uuid::new_v4();
+        let transactions: Vec<_> = [0, 31, 60, 91]
+            .iter()
+            .flat_map(|day| {
+                [
+                    transaction(account_id, "Apple", 2.99, *day),
+                    transaction(account_id, "Apple", 9.99, *day + 2),
+                ]
+            })
+            .collect();
+        let suggestions = detect_recurring(&transactions, 3);
+        assert_eq!(suggestions.len(), 2);
+        assert_ne!(suggestions[0].suggestion_id, suggestions[1].suggestion_id);
+        let mut amounts: Vec<f64> = suggestions.iter().map(|suggestion| suggestion.amount).collect();
+        amounts.sort_by(|a, b| a.total_cmp(b));
+        assert_eq!(amounts, [2.99, 9.99]);
+    }
+
+    #[test]
+    fn ignores_irregular_spending() {
+        let account_id = Uuid::new_v4();
+        let transactions: Vec<_> = [0, 3, 50, 52, 140]
+            .iter()
+            .map(|day| transaction(account_id, "Coffee", 4.5, *day))
+            .collect();
+        assert!(detect_recurring(&transactions, 3).is_empty());
+    }
+
+    #[test]
+    fn suggestion_ids_are_stable() {
+        let account_id = Uuid::new_v4();
+        let transactions: Vec<_> = [0, 30, 61]
+            .iter()
+            .map(|day| transaction(account_id, "Spotify", 9.99, *day))
+            .collect();
+        let first = detect_recurring(&transactions, 3);
+        let second = detect_recurring(&transactions, 3);
+        assert_eq!(first[0].suggestion_id, second[0].suggestion_id);
+
+        let mut with_price_rise: Vec<_> = [0, 30, 61]
+            .iter()
+            .map(|day| transaction(account_id, "Spotify", 9.99, *day))
+            .collect();
+        with_price_rise.push(transaction(account_id, "Spotify", 10.49, 91));
+        let third = detect_recurring(&with_price_rise, 3);
+        assert_ne!(third[0].amount, first[0].amount);
+        assert_eq!(third[0].suggestion_id, first[0].suggestion_id);
+    }
+}
@@ -0,0 +1,290 @@
+use chrono::{Duration, NaiveDate};
+
+const XIRR_TOLERANCE: f64 = 1e-10;
+const XIRR_MAX_ITERATIONS: usize = 100;
+
+pub struct HoldingInput {
+    pub base_quantity: f64,
+    pub trades: Vec<HoldingTrade>,
+    pub prices: Vec<(NaiveDate, f64)>,
+    pub fx_rate: f64,
+}
+
+pub struct HoldingTrade {
+    pub date: NaiveDate,
+    pub quantity: f64,
+    pub cash: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DailyPoint {
+    pub date: NaiveDate,
+    pub value: f64,
+    pub flow: f64,
+}
+
+pub fn daily_valuations(
+    holdings: &[HoldingInput],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Vec<DailyPoint> {
+    let mut points = Vec::new();
+    let mut day = start;
+    while day <= end {
+        let mut value = 0.0;
+        let mut flow = 0.0;
+        for holding in holdings {
+            let quantity = if holding.trades.is_empty() {
+                holding.base_quantity
+            } else {
+                holding
+                    .trades
+                    .iter()
+                    .filter(|trade| trade.date <= day)
+                    .map(|trade| trade.quantity)
+                    .sum()
+            };
+            if let Some(price) = price_on(&holding.prices, day) {
+                value += quantity * price * holding.fx_rate;
+            }
+            flow += holding
+                .trades
+                .iter()
+                .filter(|trade| trade.date == day)
+                .map(|trade| trade.cash * holding.fx_rate)
+                .sum::<f64>();
+        }
+        points.push(DailyPoint {
+            date: day,
+            value,
+            flow,
+        });
+        day += Duration::days(1);
+    }
+    points
+}
+
+fn price_on(prices: &[(NaiveDate, f64)], day: NaiveDate) -> Option<f64> {
+    let index = prices.partition_point(|(date, _)| *date <= day);
+    (index > 0).then(|| prices[index - 1].1)
+}
+
+pub fn time_weighted_return(points: &[DailyPoint]) -> Option<f64> {
+    let (first, rest) = points.split_first()?;
+    let mut growth = 1.0;
+    let mut previous = first.value;
+    let mut linked = false;
+    for point in rest {
+        if previous.abs() > f64::EPSILON {
+            growth *= (point.value - point.flow) / previous;
+            linked = true;
+        } else if point.flow.abs() > f64::EPSILON {
+            growth *= point.value / point.flow;
+            linked = true;
+        }
+        previous = point.value;
+    }
+    linked.then_some(growth - 1.0)
+}
+
+pub fn investor_cashflows(points: &[DailyPoint]) -> Vec<(NaiveDate, f64)> {
+    let Some((first, rest)) = points.split_first() else {
+        return Vec::new();
+    };
+    let mut cashflows = vec![(first.date, -first.value)];
+    cashflows.extend(
+        rest.iter()
+            .filter(|point| point.flow.abs() > f64::EPSILON)
+            .map(|point| (point.date, -point.flow)),
+    );
+    if let Some(last) = points.last() {
+        cashflows.push((last.date, last.value));
+    }
+    cashflows
+}
+
+pub fn xirr(cashflows: &[(NaiveDate, f64)]) -> Option<f64> {
+    let has_inflow = cashflows.iter().any(|(_, amount)| *amount > 0.0);
+    let has_outflow = cashflows.iter().any(|(_, amount)| *amount < 0.0);
+    if !has_inflow || !has_outflow {
+        return None;
+    }
+    let origin = cashflows.iter().map(|(date, _)| *date).min()?;
+    let years: Vec<(f64, f64)> = cashflows
+        .iter()
+        .map(|(date, amount)| ((*date - origin).num_days() as f64 / 365.0, *amount))
+        .collect();
+    let npv = |rate: f64| -> f64 {
+        years
+            .iter()
+            .map(|(t, amount)| amount / (1.0 + rate).powf(*t))
+            .sum()
+    };
+    let derivative = |rate: f64| -> f64 {
+        years
+            .iter()
+            .map(|(t, amount)| -t * amount / (1.0 + rate).powf(t + 1.0))
+            .sum()
+    };
+
+    let mut rate = 0.1;
+    for _ in 0..XIRR_MAX_ITERATIONS {
+        let value = npv(rate);
+        if value.abs() < XIRR_TOLERANCE {
+            return Some(rate);
+        }
+        let slope = derivative(rate);
+        if slope.abs() < f64::EPSILON {
+            break;
+        }
+        let next = rate - value / slope;
+        if !next.is_finite() || next <= -1.0 {
+            break;
+        }
+        if (next - rate).abs() < XIRR_TOLERANCE {
+            return Some(next);
+        }
+        rate = next;
+    }
+
+    let (mut low, mut high) = (-0.9999, 10.0);
+    if npv(low).signum() == npv(high).signum() {
+        return None;
+    }
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        let value = npv(mid);
+        if value.abs() < XIRR_TOLERANCE || (high - low) / 2.0 < XIRR_TOLERANCE {
+            return Some(mid);
+        }
+        if value.signum() == npv(low).signum() {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Some((low + high) / 2.0)
+}
+
+pub fn annualize(cumulative: f64, days: i64) -> Option<f64> {
+    (days > 0 && cumulative > -1.0).then(|| (1.0 + cumulative).powf(365.0 / days as f64) - 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        annualize, daily_valuations, investor_cashflows, time_weighted_return, xirr, DailyPoint,
+        HoldingInput, HoldingTrade,
+    };
+    use chrono::NaiveDate;
+
+    fn day(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn point(date: &str, value: f64, flow: f64) -> DailyPoint {
+        DailyPoint {
+            date: day(date),
+            value,
+            flow,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn twr_ignores_contribution_size() {
+        // +10% on 100, another +10%, then 1000 is added at the close.
+        let points = vec![
+            point("2024-01-01", 100.0, 0.0),
+            point("2024-01-02", 110.0, 0.0),
+            point("2024-01-03", 1121.0, 1000.0),
+            point("2024-01-04", 1121.0, 0.0),
+        ];
+        assert_close(time_weighted_return(&points).unwrap(), 0.21, 1e-12);
+    }
+
+    #[test]
+    fn twr_needs_a_valued_period() {
+        assert_eq!(time_weighted_return(&[point("2024-01-01", 0.0, 0.0)]), None);
+        assert_eq!(
+            time_weighted_return(&[point("2024-01-01", 0.0, 0.0), point("2024-01-02", 0.0, 0.0)]),
+            None
+        );
+    }
+
+    #[test]
+    fn xirr_matches_simple_annual_growth() {
+        let cashflows = vec![(day("2023-01-01"), -1000.0), (day("2024-01-01"), 1100.0)];
+        assert_close(xirr(&cashflows).unwrap(), 0.1, 1e-8);
+    }
+
+    #[test]
+    fn xirr_matches_spreadsheet_example() {
+        // The reference example from the spreadsheet XIRR documentation.
+        let cashflows = vec![
+            (day("2008-01-01"), -10000.0),
+            (day("2008-03-01"), 2750.0),
+            (day("2008-10-30"), 4250.0),
+            (day("2009-02-15"), 3250.0),
+            (day("2009-04-01"), 2750.0),
+        ];
+        assert_close(xirr(&cashflows).unwrap(), 0.373362535, 1e-6);
+    }
+
+    #[test]
+    fn xirr_requires_both_directions() {
+        assert_eq!(xirr(&[(day("2024-01-01"), -100.0)]), None);
+        assert_eq!(xirr(&[]), None);
+    }
+
+    #[test]
+    fn valuations_follow_trades_and_prices() {
+        let holdings = vec![HoldingInput {
+            base_quantity: 0.0,
+            trades: vec![
+                HoldingTrade {
+                    date: day("2024-01-02"),
+                    quantity: 10.0,
+                    cash: 1000.0,
+                },
+                HoldingTrade {
+                    date: day("2024-01-04"),
+                    quantity: -5.0,
+                    cash: -550.0,
+                },
+            ],
+            prices: vec![
+                (day("2024-01-02"), 100.0),
+                (day("2024-01-03"), 105.0),
+                (day("2024-01-04"), 110.0),
+            ],
+            fx_rate: 2.0,
+        }];
+        let points = daily_valuations(&holdings, day("2024-01-01"), day("2024-01-04"));
+        assert_eq!(
+            points,
+            vec![
+                point("2024-01-01", 0.0, 0.0),
+                point("2024-01-02", 2000.0, 2000.0),
+                point("2024-01-03", 2100.0, 0.0),
+                point("2024-01-04", 1100.0, -1100.0),
+            ]
+        );
+        assert_close(time_weighted_return(&points).unwrap(), 0.1, 1e-12);
+        let cashflows = investor_cashflows(&points);
+        assert_eq!(cashflows.first(), Some(&(day("2024-01-01"), -0.0)));
+        assert_eq!(cashflows.last(), Some(&(day("2024-01-04"), 1100.0)));
+    }
+
+    #[test]
+    fn annualize_scales_by_period() {
+        assert_close(annualize(0.21, 730).unwrap(), 0.1, 1e-12);
+        assert_eq!(annualize(0.1, 0), None);
+    }
+}
@@ -0,0 +1,533 @@
+use chrono::{DateTime, Datelike, Duration, DurationRound, TimeZone, Timelike, Utc};
+use rand_core::{OsRng, RngCore};
+use sqlx::postgres::PgPool;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+pub type JobError = Box<dyn std::error::Error + Send + Sync>;
+pub type JobFuture = Pin<Box<dyn Future<Output = Result<(), JobError>> + Send>>;
+pub type JobHandler = Arc<dyn Fn(PgPool) -> JobFuture + Send + Sync>;
+
+pub struct JobSpec {
+    pub name: &'static str,
+    pub schedule: &'static str,
+    pub jitter_secs: u64,
+}
+
+pub const WORKER_JOBS: [JobSpec; 7] = [
+    JobSpec {
+        name: "recurring_transactions",
+        schedule: "@every 15m",
+        jitter_secs: 30,
+    },
+    JobSpec {
+        name: "fx_rates",
+        schedule: "0 6 * * *",
+        jitter_secs: 300,
+    },
+    JobSpec {
+        name: "asset_prices",
+        schedule: "@every 1h",
+        jitter_secs: 120,
+    },
+    JobSpec {
+        name: "price_backfill",
+        schedule: "@every 10m",
+        jitter_secs: 60,
+    },
+    JobSpec {
+        name: "benchmark_prices",
+        schedule: "30 22 * * 1-5",
+        jitter_secs: 300,
+    },
+    JobSpec {
+        name: "manual_valuations",
+        schedule: "15 0 * * *",
+        jitter_secs: 300,
+    },
+    JobSpec {
+        name: "bill_reminders",
+        schedule: "0 7 * * *",
+        jitter_secs: 300,
+    },
+];
+
+pub fn configured_schedule(spec: &JobSpec) -> String {
+    std::env::var(format!("JOB_SCHEDULE_{}", spec.name.to_uppercase()))
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| spec.schedule.to_string())
+}
+
+#[derive(Clone, Debug)]
+pub enum Schedule {
+    Every(Duration),
+    Cron(CronExpr),
+}
+
+impl Schedule {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        match value {
+            "@hourly" => return Ok(Self::Every(Duration::hours(1))),
+            "@daily" => return Ok(Self::Every(Duration::days(1))),
+            "@weekly" => return Ok(Self::Every(Duration::weeks(1))),
+            _ => {}
+        }
+        if let Some(interval) = value.strip_prefix("@every") {
+            return parse_interval(interval.trim()).map(Self::Every);
+        }
+        CronExpr::parse(value).map(Self::Cron)
+    }
+
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Every(interval) => Some(after + *interval),
+            Self::Cron(expr) => expr.next_after(after),
+        }
+    }
+}
+
+fn parse_interval(value: &str) -> Result<Duration, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("missing unit in interval '{value}'"))?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("invalid interval '{value}'"))?;
+    if amount <= 0 {
+        return Err(format!("interval must be positive: '{value}'"));
+    }
+    match unit {
+        "s" => Ok(Duration::seconds(amount)),
+        "m" => Ok(Duration::minutes(amount)),
+        "h" => Ok(Duration::hours(amount)),
+        "d" => Ok(Duration::days(amount)),
+        _ => Err(format!("unknown interval unit '{unit}'")),
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u32,
+    days_of_month: u32,
+    months: u16,
+    days_of_week: u8,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+impl CronExpr {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = value.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("cron expression needs 5 fields: '{value}'"));
+        }
+        let mut days_of_week = parse_cron_field(fields[4], 0, 7)?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+        Ok(Self {
+            minutes: parse_cron_field(fields[0], 0, 59)?,
+            hours: parse_cron_field(fields[1], 0, 23)? as u32,
+            days_of_month: parse_cron_field(fields[2], 1, 31)? as u32,
+            months: parse_cron_field(fields[3], 1, 12)? as u16,
+            days_of_week: (days_of_week & 0x7f) as u8,
+            day_of_month_restricted: fields[2] != "*",
+            day_of_week_restricted: fields[4] != "*",
+        })
+    }
+
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut candidate = after.duration_trunc(Duration::minutes(1)).ok()? + Duration::minutes(1);
+        let limit = after + Duration::days(366 * 5);
+        while candidate <= limit {
+            if self.months & (1 << candidate.month()) == 0 {
+                let (year, month) = if candidate.month() == 12 {
+                    (candidate.year() + 1, 1)
+                } else {
+                    (candidate.year(), candidate.month() + 1)
+                };
+                candidate = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+                continue;
+            }
+            if !self.matches_day(candidate) {
+                candidate = (candidate + Duration::days(1))
+                    .duration_trunc(Duration::days(1))
+                    .ok()?;
+                continue;
+            }
+            if self.hours & (1 << candidate.hour()) == 0 {
+                candidate = (candidate + Duration::hours(1))
+                    .duration_trunc(Duration::hours(1))
+                    .ok()?;
+                continue;
+            }
+            if self.minutes & (1 << candidate.minute()) == 0 {
+                candidate += Duration::minutes(1);
+                continue;
+            }
+            return Some(candidate);
+        }
+        None
+    }
+
+    fn matches_day(&self, candidate: DateTime<Utc>) -> bool {
+        let day_of_month = self.days_of_month & (1 << candidate.day()) != 0;
+        let day_of_week =
+            self.days_of_week & (1 << candidate.weekday().num_days_from_sunday()) != 0;
+        match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => day_of_month || day_of_week,
+            (true, false) => day_of_month,
+            (false, true) => day_of_week,
+            (false, false) => true,
+        }
+    }
+}
+
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("invalid cron step '{part}'"))?;
+                if step == 0 {
+                    return Err(format!("invalid cron step '{part}'"));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_cron_value(start, min, max)?,
+                parse_cron_value(end, min, max)?,
+            )
+        } else {
+            let value = parse_cron_value(range, min, max)?;
+            (value, if step > 1 { max } else { value })
+        };
+        if start > end {
+            return Err(format!("invalid cron range '{part}'"));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+fn parse_cron_value(value: &str, min: u32, max: u32) -> Result<u32, String> {
+    let parsed: u32 = value
+        .parse()
+        .map_err(|_| format!("invalid cron value '{value}'"))?;
+    if parsed < min || parsed > max {
+        return Err(format!("cron value {parsed} out of range {min}-{max}"));
+    }
+    Ok(parsed)
+}
+
+pub struct Job {
+    pub name: &'static str,
+    pub expression: String,
+    pub schedule: Schedule,
+    pub jitter_secs: u64,
+    pub handler: JobHandler,
+}
+
+pub struct Scheduler {
+    pool: PgPool,
+    jobs: Vec<Job>,
+}
+
+impl Scheduler {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            jobs: Vec::new(),
+        }
+    }
+
+    pub fn register(&mut self, spec: &JobSpec, handler: JobHandler) -> Result<(), String> {
+        let expression = configured_schedule(spec);
+        let schedule = Schedule::parse(&expression)
+            .map_err(|error| format!("invalid schedule for job {}: {error}", spec.name))?;
+        self.jobs.push(Job {
+            name: spec.name,
+            expression,
+            schedule,
+            jitter_secs: spec.jitter_secs,
+            handler,
+        });
+        Ok(())
+    }
+
+    pub async fn run(self) {
+        if let Err(err) = record_schedules(&self.pool, &self.jobs).await {
+            warn!(error = %err, "failed to record job schedules");
+        }
+        if let Err(err) = fail_abandoned_runs(&self.pool).await {
+            error!(error = %err, "failed to reconcile abandoned job runs");
+        }
+        let now = Utc::now();
+        let mut next_runs = Vec::with_capacity(self.jobs.len());
+        for job in &self.jobs {
+            let last_started_at = last_started_at(&self.pool, job.name).await.unwrap_or(None);
+            let next_run = last_started_at
+                .and_then(|started_at| job.schedule.next_after(started_at))
+                .unwrap_or(now)
+                .max(now);
+            info!(job = job.name, %next_run, "scheduled job");
+            next_runs.push(next_run);
+        }
+
+        loop {
+            let Some(next_due) = next_runs.iter().min().copied() else {
+                return;
+            };
+            let wait = (next_due - Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+
+            let now = Utc::now();
+            for (job, next_run) in self.jobs.iter().zip(next_runs.iter_mut()) {
+                if *next_run > now {
+                    continue;
+                }
+                tokio::spawn(run_job(self.pool.clone(), job.name, job.handler.clone()));
+                *next_run = match job.schedule.next_after(now) {
+                    Some(next) => next + jitter(job.jitter_secs),
+                    None => now + Duration::days(365 * 100),
+                };
+            }
+        }
+    }
+}
+
+async fn run_job(pool: PgPool, name: &'static str, handler: JobHandler) {
+    // The lock is transaction-scoped, so it is released however this task ends.
+    let mut lock_tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(err) => {
+            error!(job = name, error = %err, "failed to open transaction for job lock");
+            return;
+        }
+    };
+    let locked: bool = match sqlx::query_scalar("SELECT pg_try_advisory_xact_lock(hashtext($1))")
+        .bind(lock_key(name))
+        .fetch_one(&mut *lock_tx)
+        .await
+    {
+        Ok(locked) => locked,
+        Err(err) => {
+            error!(job = name, error = %err, "failed to take job lock");
+            return;
+        }
+    };
+    if !locked {
+        debug!(job = name, "job already running on another worker");
+        return;
+    }
+
+    let run_id = Uuid::new_v4();
+    if let Err(err) = sqlx::query(
+        r#"
+        INSERT INTO job_runs (id, job_name, started_at, status)
+        VALUES ($1, $2, NOW(), 'running')
+        "#,
+    )
+    .bind(run_id)
+    .bind(name)
+    .execute(&pool)
+    .await
+    {
+        warn!(job = name, error = %err, "failed to record job start");
+    }
+
+    info!(job = name, "running job");
+    let result = tokio::spawn(handler(pool.clone())).await;
+    let (status, error_message) = match result {
+        Ok(Ok(())) => ("succeeded", None),
+        Ok(Err(err)) => {
+            error!(job = name, error = %err, "job failed");
+            ("failed", Some(err.to_string()))
+        }
+        Err(err) => {
+            error!(job = name, error = %err, "job panicked");
+            ("failed", Some(format!("job panicked: {err}")))
+        }
+    };
+
+    if let Err(err) = sqlx::query(
+        r#"
+        UPDATE job_runs
+        SET finished_at = NOW(),
+            status = $1,
+            error = $2
+        WHERE id = $3
+        "#,
+    )
+    .bind(status)
+    .bind(error_message)
+    .bind(run_id)
+    .execute(&pool)
+    .await
+    {
+        warn!(job = name, %status, error = %err, "failed to record job result");
+    }
+
+    if let Err(err) = lock_tx.commit().await {
+        warn!(job = name, error = %err, "failed to release job lock");
+    }
+}
+
+async fn record_schedules(pool: &PgPool, jobs: &[Job]) -> Result<(), sqlx::Error> {
+    for job in jobs {
+        sqlx::query(
+            r#"
+            INSERT INTO job_schedules (job_name, schedule, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (job_name)
+            DO UPDATE SET schedule = EXCLUDED.schedule, updated_at = NOW()
+            "#,
+        )
+        .bind(job.name)
+        .bind(&job.expression)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn fail_abandoned_runs(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let names: Vec<String> =
+        sqlx::query_scalar("SELECT DISTINCT job_name FROM job_runs WHERE status = 'running'")
+            .fetch_all(pool)
+            .await?;
+    for name in names {
+        let mut tx = pool.begin().await?;
+        let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock(hashtext($1))")
+            .bind(lock_key(&name))
+            .fetch_one(&mut *tx)
+            .await?;
+        if !locked {
+            continue;
+        }
+        let result = sqlx::query(
+            r#"
+            UPDATE job_runs
+            SET finished_at = NOW(),
+                status = 'failed',
+                error = 'worker stopped before the job finished'
+            WHERE job_name = $1 AND status = 'running'
+            "#,
+        )
+        .bind(&name)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        info!(
+            job = name,
+            runs = result.rows_affected(),
+            "marked abandoned job runs as failed"
+        );
+    }
+    Ok(())
+}
+
+async fn last_started_at(pool: &PgPool, name: &str) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT MAX(started_at)
+        FROM job_runs
+        WHERE job_name = $1
+        "#,
+    )
+    .bind(name)
+    .fetch_one(pool)
+    .await
+}
+
+fn lock_key(name: &str) -> String {
+    format!("firecash:job:{name}")
+}
+
+fn jitter(max_secs: u64) -> Duration {
+    if max_secs == 0 {
+        return Duration::zero();
+    }
+    Duration::seconds((OsRng.next_u64() % (max_secs + 1)) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{jitter, Schedule};
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[test]
+    fn parses_every_intervals() {
+        let schedule = Schedule::parse("@every 15m").unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 10, 0, 0).unwrap();
+        assert_eq!(
+            schedule.next_after(start),
+            Some(start + Duration::minutes(15))
+        );
+        assert!(Schedule::parse("@every 0m").is_err());
+        assert!(Schedule::parse("@every 5x").is_err());
+    }
+
+    #[test]
+    fn cron_daily_at_fixed_time() {
+        let schedule = Schedule::parse("0 6 * * *").unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 10, 0, 0).unwrap();
+        assert_eq!(
+            schedule.next_after(start),
+            Some(Utc.with_ymd_and_hms(2024, 3, 2, 6, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn cron_steps_and_lists() {
+        let schedule = Schedule::parse("*/20 9,17 * * *").unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 41, 10).unwrap();
+        assert_eq!(
+            schedule.next_after(start),
+            Some(Utc.with_ymd_and_hms(2024, 3, 1, 17, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn cron_day_of_week_and_month_rollover() {
+        // 2024-12-31 is a Tuesday; the next Monday is 2025-01-06.
+        let schedule = Schedule::parse("30 2 * * 1").unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 12, 31, 12, 0, 0).unwrap();
+        assert_eq!(
+            schedule.next_after(start),
+            Some(Utc.with_ymd_and_hms(2025, 1, 6, 2, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn cron_rejects_invalid_expressions() {
+        assert!(Schedule::parse("61 * * * *").is_err());
+        assert!(Schedule::parse("* * *").is_err());
+        assert!(Schedule::parse("*/0 * * * *").is_err());
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        for _ in 0..50 {
+            let value = jitter(10);
+            assert!(value >= Duration::zero() && value <= Duration::seconds(10));
+        }
+        assert_eq!(jitter(0), Duration::zero());
+    }
+}
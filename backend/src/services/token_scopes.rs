@@ -0,0 +1,161 @@
+use uuid::Uuid;
+
+pub const SCOPES: [&str; 9] = [
+    "accounts:read",
+    "accounts:write",
+    "transactions:read",
+    "transactions:write",
+    "assets:read",
+    "assets:write",
+    "reports:read",
+    "backup:export",
+    "backup:restore",
+];
+
+const FILTERABLE_PATHS: [&str; 7] = [
+    "/api/transactions",
+    "/api/transactions/daily-totals",
+    "/api/assets",
+    "/api/assets/returns",
+    "/api/allocation",
+    "/api/liabilities",
+    "/api/balance-assertions/check",
+];
+
+const BODY_CHECKED_PATHS: [&str; 4] = [
+    "/api/transactions",
+    "/api/recurring-transactions",
+    "/api/assets",
+    "/api/liabilities",
+];
+
+pub fn is_known_scope(scope: &str) -> bool {
+    SCOPES.contains(&scope)
+}
+
+pub fn required_scope(path: &str, is_write: bool) -> Option<&'static str> {
+    let mut segments = path.trim_start_matches("/api/").split('/');
+    let resource = segments.next().unwrap_or_default();
+    let (read, write) = match resource {
+        "accounts" | "account-groups" | "balance-assertions" | "bill-reminders" | "liabilities" => {
+            ("accounts:read", "accounts:write")
+        }
+        "transactions" | "recurring-transactions" => ("transactions:read", "transactions:write"),
+        "assets" | "allocation" => ("assets:read", "assets:write"),
+        "dashboard" | "totals" | "history" | "fx-rates" if !is_write => {
+            return Some("reports:read")
+        }
+        "backup" => {
+            return match segments.next() {
+                Some("export") => Some("backup:export"),
+                Some("restore") => Some("backup:restore"),
+                _ => None,
+            }
+        }
+        _ => return None,
+    };
+    Some(if is_write { write } else { read })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathTarget {
+    Account(Uuid),
+    AccountGroup(Uuid),
+    Transaction(Uuid),
+    RecurringTransaction(Uuid),
+    Asset(Uuid),
+    Liability(Uuid),
+    BillReminder(Uuid),
+}
+
+pub fn path_target(path: &str) -> Option<PathTarget> {
+    let mut segments = path.trim_start_matches("/api/").split('/');
+    let resource = segments.next()?;
+    let id = Uuid::parse_str(segments.next()?).ok()?;
+    let target = match resource {
+        "accounts" => PathTarget::Account(id),
+        "account-groups" => PathTarget::AccountGroup(id),
+        "transactions" => PathTarget::Transaction(id),
+        "recurring-transactions" => PathTarget::RecurringTransaction(id),
+        "assets" => PathTarget::Asset(id),
+        "liabilities" => PathTarget::Liability(id),
+        "bill-reminders" => PathTarget::BillReminder(id),
+        _ => return None,
+    };
+    Some(target)
+}
+
+pub fn allows_without_target(path: &str, is_write: bool, has_account_filter: bool) -> bool {
+    if is_write {
+        BODY_CHECKED_PATHS.contains(&path)
+    } else {
+        has_account_filter && FILTERABLE_PATHS.contains(&path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{allows_without_target, path_target, required_scope, PathTarget};
+    use uuid::Uuid;
+
+    #[test]
+    fn maps_routes_to_scopes() {
+        assert_eq!(
+            required_scope("/api/transactions", false),
+            Some("transactions:read")
+        );
+        assert_eq!(
+            required_scope("/api/recurring-transactions/abc/skip", true),
+            Some("transactions:write")
+        );
+        assert_eq!(
+            required_scope("/api/assets/abc/trades", true),
+            Some("assets:write")
+        );
+        assert_eq!(
+            required_scope("/api/accounts/abc/statement", false),
+            Some("accounts:read")
+        );
+        assert_eq!(required_scope("/api/history", false), Some("reports:read"));
+        assert_eq!(required_scope("/api/fx-rates/refresh", true), None);
+        assert_eq!(
+            required_scope("/api/backup/export", false),
+            Some("backup:export")
+        );
+        assert_eq!(
+            required_scope("/api/backup/restore", true),
+            Some("backup:restore")
+        );
+        assert_eq!(required_scope("/api/tokens", false), None);
+        assert_eq!(required_scope("/api/me", false), None);
+    }
+
+    #[test]
+    fn parses_record_ids_from_paths() {
+        let id = Uuid::new_v4();
+        assert_eq!(
+            path_target(&format!("/api/accounts/{id}/opening-balance")),
+            Some(PathTarget::Account(id))
+        );
+        assert_eq!(
+            path_target(&format!("/api/assets/{id}")),
+            Some(PathTarget::Asset(id))
+        );
+        assert_eq!(path_target("/api/transactions/daily-totals"), None);
+        assert_eq!(path_target("/api/transactions"), None);
+    }
+
+    #[test]
+    fn restricted_tokens_need_a_filter_or_body_check() {
+        assert!(allows_without_target("/api/transactions", false, true));
+        assert!(!allows_without_target("/api/transactions", false, false));
+        assert!(!allows_without_target("/api/dashboard", false, true));
+        assert!(allows_without_target("/api/transactions", true, false));
+        assert!(!allows_without_target("/api/accounts", true, false));
+        assert!(!allows_without_target(
+            "/api/assets/refresh-prices",
+            true,
+            false
+        ));
+    }
+}
@@ -0,0 +1,179 @@
+use hmac::{Hmac, Mac};
+use rand_core::{OsRng, RngCore};
+use sha1::Sha1;
+
+pub const TIME_STEP: u64 = 30;
+pub const CODE_DIGITS: u32 = 6;
+const ALLOWED_DRIFT_STEPS: u64 = 1;
+const SECRET_BYTES: usize = 20;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+pub fn base32_encode(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer = 0u64;
+    let mut bits = 0u32;
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u64;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        output.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    output
+}
+
+pub fn base32_decode(value: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(value.len() * 5 / 8);
+    let mut buffer = 0u64;
+    let mut bits = 0u32;
+    for character in value.chars() {
+        if character == '=' || character.is_whitespace() {
+            continue;
+        }
+        let upper = character.to_ascii_uppercase() as u8;
+        let index = BASE32_ALPHABET.iter().position(|item| *item == upper)?;
+        buffer = (buffer << 5) | index as u64;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+    Some(output)
+}
+
+pub fn hotp(secret: &[u8], counter: u64, digits: u32) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    binary % 10u32.pow(digits)
+}
+
+pub fn totp_code(secret: &[u8], unix_time: u64, digits: u32) -> String {
+    let value = hotp(secret, unix_time / TIME_STEP, digits);
+    format!("{:0width$}", value, width = digits as usize)
+}
+
+pub fn verify_totp(
+    secret: &[u8],
+    code: &str,
+    unix_time: u64,
+    last_used_step: Option<u64>,
+) -> Option<u64> {
+    let code = code.trim();
+    if code.len() != CODE_DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let current = unix_time / TIME_STEP;
+    let first = current.saturating_sub(ALLOWED_DRIFT_STEPS);
+    (first..=current + ALLOWED_DRIFT_STEPS)
+        .filter(|step| last_used_step.is_none_or(|last| *step > last))
+        .find(|step| totp_code(secret, step * TIME_STEP, CODE_DIGITS) == code)
+}
+
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    base32_encode(&bytes)
+}
+
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    let issuer = url::form_urlencoded::byte_serialize(issuer.as_bytes()).collect::<String>();
+    let account = url::form_urlencoded::byte_serialize(account.as_bytes()).collect::<String>();
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={CODE_DIGITS}&period={TIME_STEP}"
+    )
+}
+
+pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|_| {
+            let mut bytes = [0u8; 7];
+            OsRng.fill_bytes(&mut bytes);
+            let encoded = base32_encode(&bytes);
+            format!("{}-{}", &encoded[..5], &encoded[5..10])
+        })
+        .collect()
+}
+
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        base32_decode, base32_encode, generate_recovery_codes, hotp, normalize_recovery_code,
+        totp_code, verify_totp,
+    };
+
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn base32_matches_rfc_4648() {
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32_encode(b"f"), "MY");
+        assert_eq!(base32_decode("mzxw6ytboi======").unwrap(), b"foobar");
+        assert_eq!(
+            base32_decode(&base32_encode(RFC_SECRET)).unwrap(),
+            RFC_SECRET
+        );
+        assert!(base32_decode("MZ1W").is_none());
+    }
+
+    #[test]
+    fn hotp_matches_rfc_4226() {
+        assert_eq!(hotp(RFC_SECRET, 0, 6), 755224);
+        assert_eq!(hotp(RFC_SECRET, 1, 6), 287082);
+        assert_eq!(hotp(RFC_SECRET, 9, 6), 520489);
+    }
+
+    #[test]
+    fn totp_matches_rfc_6238() {
+        let vectors = [
+            (59, "94287082"),
+            (1_111_111_109, "07081804"),
+            (1_111_111_111, "14050471"),
+            (1_234_567_890, "89005924"),
+            (2_000_000_000, "69279037"),
+            (20_000_000_000, "65353130"),
+        ];
+        for (time, code) in vectors {
+            assert_eq!(totp_code(RFC_SECRET, time, 8), code);
+        }
+    }
+
+    #[test]
+    fn verification_allows_drift_and_refuses_replays() {
+        let now = 1_111_111_111;
+        let previous = totp_code(RFC_SECRET, now - 30, 6);
+        let step = verify_totp(RFC_SECRET, &previous, now, None).unwrap();
+        assert_eq!(step, (now - 30) / 30);
+        assert_eq!(verify_totp(RFC_SECRET, &previous, now, Some(step)), None);
+        let stale = totp_code(RFC_SECRET, now - 90, 6);
+        assert_eq!(verify_totp(RFC_SECRET, &stale, now, None), None);
+        assert_eq!(verify_totp(RFC_SECRET, "12345", now, None), None);
+    }
+
+    #[test]
+    fn recovery_codes_normalize_for_lookup() {
+        let codes = generate_recovery_codes(3);
+        assert_eq!(codes.len(), 3);
+        assert!(codes.iter().all(|code| code.len() == 11));
+        assert_eq!(normalize_recovery_code(" abcde-fghij "), "ABCDEFGHIJ");
+    }
+}
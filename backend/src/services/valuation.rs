@@ -0,0 +1,176 @@
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgPool, QueryBuilder, Row};
+use uuid::Uuid;
+
+pub const VALUATION_MODES: [&str; 2] = ["market", "manual"];
+
+pub const MANUAL_VALUATION: &str = "manual";
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 86_400.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScheduleMethod {
+    Compound,
+    StraightLine,
+}
+
+impl ScheduleMethod {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "compound" => Some(Self::Compound),
+            "straight_line" => Some(Self::StraightLine),
+            _ => None,
+        }
+    }
+}
+
+pub fn scheduled_price(
+    anchor_price: f64,
+    anchor_at: DateTime<Utc>,
+    method: ScheduleMethod,
+    annual_rate: f64,
+    at: DateTime<Utc>,
+) -> f64 {
+    let years = (at - anchor_at).num_seconds() as f64 / SECONDS_PER_YEAR;
+    if years <= 0.0 {
+        return anchor_price;
+    }
+    let price = match method {
+        ScheduleMethod::Compound => anchor_price * (1.0 + annual_rate).powf(years),
+        ScheduleMethod::StraightLine => anchor_price * (1.0 + annual_rate * years),
+    };
+    price.max(0.0)
+}
+
+pub async fn apply_valuation_schedules(
+    pool: &PgPool,
+    asset_id: Option<Uuid>,
+) -> Result<usize, sqlx::Error> {
+    let mut query = QueryBuilder::new(
+        r#"
+        SELECT s.asset_id, s.method, s.annual_rate, v.price, v.currency_code, v.valued_at
+        FROM asset_valuation_schedules s
+        INNER JOIN assets a ON a.id = s.asset_id AND a.valuation_mode = 'manual'
+        INNER JOIN LATERAL (
+            SELECT price, currency_code, valued_at
+            FROM asset_valuations
+            WHERE asset_id = s.asset_id
+            ORDER BY valued_at DESC, created_at DESC
+            LIMIT 1
+        ) v ON TRUE
+        WHERE v.valued_at < NOW()
+          AND NOT EXISTS (
+              SELECT 1
+              FROM price_history ph
+              WHERE ph.asset_id = s.asset_id
+                AND ph.recorded_at >= date_trunc('day', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+          )
+        "#,
+    );
+    if let Some(asset_id) = asset_id {
+        query.push(" AND s.asset_id = ");
+        query.push_bind(asset_id);
+    }
+    let rows = query.build().fetch_all(pool).await?;
+
+    let now = Utc::now();
+    let mut written = 0usize;
+    for row in rows {
+        let asset_id: Uuid = row.try_get("asset_id")?;
+        let method: String = row.try_get("method")?;
+        let Some(method) = ScheduleMethod::parse(&method) else {
+            continue;
+        };
+        let annual_rate: f64 = row.try_get("annual_rate")?;
+        let anchor_price: f64 = row.try_get("price")?;
+        let currency_code: String = row.try_get("currency_code")?;
+        let valued_at: DateTime<Utc> = row.try_get("valued_at")?;
+        let price = scheduled_price(anchor_price, valued_at, method, annual_rate, now);
+        sqlx::query(
+            r#"
+            INSERT INTO price_history (id, asset_id, price, currency_code, recorded_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(asset_id)
+        .bind(price)
+        .bind(currency_code)
+        .bind(now)
+        .execute(pool)
+        .await?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scheduled_price, ScheduleMethod};
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[test]
+    fn parses_schedule_methods() {
+        assert_eq!(
+            ScheduleMethod::parse("compound"),
+            Some(ScheduleMethod::Compound)
+        );
+        assert_eq!(
+            ScheduleMethod::parse("straight_line"),
+            Some(ScheduleMethod::StraightLine)
+        );
+        assert_eq!(ScheduleMethod::parse("linear"), None);
+    }
+
+    #[test]
+    fn compounds_appreciation_and_depreciation() {
+        let anchor_at = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let two_years = anchor_at + Duration::seconds((2.0 * super::SECONDS_PER_YEAR) as i64);
+        let house = scheduled_price(
+            500_000.0,
+            anchor_at,
+            ScheduleMethod::Compound,
+            0.03,
+            two_years,
+        );
+        assert!((house - 500_000.0 * 1.03_f64.powi(2)).abs() < 1e-6);
+        let car = scheduled_price(
+            30_000.0,
+            anchor_at,
+            ScheduleMethod::Compound,
+            -0.15,
+            two_years,
+        );
+        assert!((car - 30_000.0 * 0.85_f64.powi(2)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn straight_line_stops_at_zero() {
+        let anchor_at = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let year = anchor_at + Duration::seconds(super::SECONDS_PER_YEAR as i64);
+        let price = scheduled_price(
+            10_000.0,
+            anchor_at,
+            ScheduleMethod::StraightLine,
+            -0.2,
+            year,
+        );
+        assert!((price - 8_000.0).abs() < 1e-6);
+        let later = anchor_at + Duration::days(365 * 10);
+        assert_eq!(
+            scheduled_price(
+                10_000.0,
+                anchor_at,
+                ScheduleMethod::StraightLine,
+                -0.2,
+                later
+            ),
+            0.0
+        );
+        let before = anchor_at - Duration::days(30);
+        assert_eq!(
+            scheduled_price(10_000.0, anchor_at, ScheduleMethod::Compound, -0.2, before),
+            10_000.0
+        );
+    }
+}
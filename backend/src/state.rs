@@ -1,8 +1,17 @@
+use std::{net::IpAddr, sync::Arc};
+
 use sqlx::PgPool;
 
+use crate::services::{mail::MailSender, oidc::OidcConfig};
+
 #[derive(Clone)]
 pub struct AppState {
     pub pool: PgPool,
     pub jwt_secret: String,
     pub admin_emails: Vec<String>,
+    pub oidc: Option<OidcConfig>,
+    pub password_login_enabled: bool,
+    pub mailer: Arc<dyn MailSender>,
+    pub app_url: String,
+    pub trusted_proxies: Vec<IpAddr>,
 }
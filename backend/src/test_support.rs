@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use sqlx::{
+    postgres::{PgConnectOptions, PgPoolOptions},
+    ConnectOptions, Executor, PgPool,
+};
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+use crate::{auth::AuthenticatedUser, services::mail::LogSender, state::AppState};
+
+static DATABASE: OnceCell<Option<PgConnectOptions>> = OnceCell::const_new();
+
+pub async fn test_pool() -> Option<PgPool> {
+    let options = DATABASE
+        .get_or_init(|| async {
+            let options = fresh_database().await?;
+            let pool = connect(&options).await;
+            sqlx::migrate!("./migrations")
+                .run(&pool)
+                .await
+                .expect("failed to migrate test database");
+            pool.close().await;
+            Some(options)
+        })
+        .await
+        .clone()?;
+    Some(connect(&options).await)
+}
+
+pub async fn fresh_database() -> Option<PgConnectOptions> {
+    let url = std::env::var("TEST_DATABASE_URL").ok()?;
+    let options: PgConnectOptions = url.parse().expect("invalid TEST_DATABASE_URL");
+    let name = format!("firecash_test_{}", Uuid::new_v4().simple());
+    let mut admin = options
+        .connect()
+        .await
+        .expect("failed to connect to TEST_DATABASE_URL");
+    admin
+        .execute(format!("CREATE DATABASE {name}").as_str())
+        .await
+        .expect("failed to create test database");
+    Some(options.database(&name))
+}
+
+pub async fn connect(options: &PgConnectOptions) -> PgPool {
+    PgPoolOptions::new()
+        .max_connections(5)
+        .connect_with(options.clone())
+        .await
+        .expect("failed to connect to test database")
+}
+
+pub fn test_state(pool: PgPool) -> AppState {
+    AppState {
+        pool,
+        jwt_secret: "test-secret".into(),
+        admin_emails: Vec::new(),
+        oidc: None,
+        password_login_enabled: true,
+        mailer: Arc::new(LogSender),
+        app_url: "http://localhost:5173".into(),
+        trusted_proxies: Vec::new(),
+    }
+}
+
+pub async fn create_user(pool: &PgPool, name: &str) -> AuthenticatedUser {
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO users (id, name, email) VALUES ($1, $2, $3)")
+        .bind(id)
+        .bind(name)
+        .bind(format!("{name}-{id}@example.com"))
+        .execute(pool)
+        .await
+        .expect("failed to create user");
+    AuthenticatedUser {
+        id,
+        session_id: None,
+        allowed_accounts: None,
+    }
+}
+
+pub async fn create_account(pool: &PgPool, owner: &AuthenticatedUser) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO accounts (id, user_id, name, currency_code)
+        VALUES ($1, $2, 'Checking', 'USD')
+        "#,
+    )
+    .bind(id)
+    .bind(owner.id)
+    .execute(pool)
+    .await
+    .expect("failed to create account");
+    id
+}
+
+pub async fn share_account(
+    pool: &PgPool,
+    owner: &AuthenticatedUser,
+    account_id: Uuid,
+    member: &AuthenticatedUser,
+    role: &str,
+) -> Uuid {
+    let group_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO account_groups (id, user_id, name) VALUES ($1, $2, 'Shared')")
+        .bind(group_id)
+        .bind(owner.id)
+        .execute(pool)
+        .await
+        .expect("failed to create group");
+    sqlx::query("INSERT INTO account_group_members (group_id, account_id) VALUES ($1, $2)")
+        .bind(group_id)
+        .bind(account_id)
+        .execute(pool)
+        .await
+        .expect("failed to add account to group");
+    sqlx::query("INSERT INTO account_group_users (group_id, user_id, role) VALUES ($1, $2, $3)")
+        .bind(group_id)
+        .bind(member.id)
+        .bind(role)
+        .execute(pool)
+        .await
+        .expect("failed to add group member");
+    group_id
+}
@@ -4,13 +4,17 @@ import DashboardLayout from "./layouts/DashboardLayout";
 import AccountsPage from "./pages/AccountsPage";
 import DashboardPage from "./pages/DashboardPage";
 import LoginPage from "./pages/LoginPage";
+import OidcCallbackPage from "./pages/OidcCallbackPage";
 import RegisterPage from "./pages/RegisterPage";
 import ReportsPage from "./pages/ReportsPage";
+import ResetPasswordPage from "./pages/ResetPasswordPage";
 import IntegrationsPage from "./pages/IntegrationsPage";
+import InvitationPage from "./pages/InvitationPage";
 import SettingsPage from "./pages/SettingsPage";
 import StocksPage from "./pages/StocksPage";
 import StockMarketPage from "./pages/StockMarketPage";
 import TransactionsPage from "./pages/TransactionsPage";
+import VerifyEmailPage from "./pages/VerifyEmailPage";
 
 export default function App() {
   return (
@@ -18,6 +22,10 @@ export default function App() {
       <Route path="/" element={<Navigate to="/dashboard" replace />} />
       <Route path="/login" element={<LoginPage />} />
       <Route path="/register" element={<RegisterPage />} />
+      <Route path="/auth/callback" element={<OidcCallbackPage />} />
+      <Route path="/reset-password" element={<ResetPasswordPage />} />
+      <Route path="/verify-email" element={<VerifyEmailPage />} />
+      <Route path="/invitations" element={<InvitationPage />} />
       <Route
         element={
           <RequireAuth>
@@ -58,3 +58,46 @@ export async function updateAccountGroupUser(groupId: string, userId: string, ro
 export async function removeAccountGroupUser(groupId: string, userId: string) {
   return del(`/api/account-groups/${groupId}/members/${userId}`);
 }
+
+export type GroupInvitation = {
+  id: string;
+  group_id: string;
+  group_name: string;
+  email: string;
+  role: "view" | "edit" | "admin";
+  status: "pending" | "accepted" | "declined" | "revoked" | "expired" | "failed";
+  invited_by_name: string | null;
+  created_at: string;
+  expires_at: string;
+  responded_at: string | null;
+};
+
+export type InvitationReference = { token: string } | { invitation_id: string };
+
+export async function fetchGroupInvitations(groupId: string) {
+  return get<GroupInvitation[]>(`/api/account-groups/${groupId}/invitations`);
+}
+
+export async function inviteToAccountGroup(groupId: string, email: string, role: string) {
+  return post<GroupInvitation>(`/api/account-groups/${groupId}/invitations`, { email, role });
+}
+
+export async function revokeGroupInvitation(groupId: string, invitationId: string) {
+  return del(`/api/account-groups/${groupId}/invitations/${invitationId}`);
+}
+
+export async function fetchMyInvitations() {
+  return get<GroupInvitation[]>("/api/invitations");
+}
+
+export async function previewInvitation(token: string) {
+  return get<GroupInvitation>(`/api/invitations/preview?token=${encodeURIComponent(token)}`);
+}
+
+export async function acceptInvitation(reference: InvitationReference) {
+  return post("/api/invitations/accept", reference);
+}
+
+export async function declineInvitation(reference: InvitationReference) {
+  return post("/api/invitations/decline", reference);
+}
@@ -0,0 +1,22 @@
+import { get } from "../utils/apiClient";
+
+export type FieldChange = {
+  old: unknown;
+  new: unknown;
+};
+
+export type ActivityEntry = {
+  id: string;
+  entity_type: "account" | "transaction" | "asset";
+  entity_id: string;
+  action: "created" | "updated" | "deleted";
+  changes: Record<string, FieldChange>;
+  account_ids: string[];
+  user_id: string | null;
+  user_name: string | null;
+  created_at: string;
+};
+
+export async function fetchActivity(limit = 50, offset = 0) {
+  return get<ActivityEntry[]>(`/api/activity?limit=${limit}&offset=${offset}`);
+}
@@ -1,14 +1,30 @@
 import { get, post } from "../utils/apiClient";
 
+export const apiTokenScopes = [
+  "accounts:read",
+  "accounts:write",
+  "transactions:read",
+  "transactions:write",
+  "assets:read",
+  "assets:write",
+  "reports:read",
+  "backup:export",
+  "backup:restore",
+] as const;
+
 export type ApiTokenSummary = {
   id: string;
   name: string;
   token_prefix?: string | null;
   is_read_only: boolean;
+  scopes?: string[] | null;
+  account_ids: string[];
+  account_group_ids: string[];
   created_at: string;
   last_used_at?: string | null;
   revoked_at?: string | null;
   expires_at?: string | null;
+  replaced_by?: string | null;
 };
 
 export type ApiTokenCreated = {
@@ -16,6 +32,9 @@ export type ApiTokenCreated = {
   token: string;
   token_prefix: string;
   is_read_only: boolean;
+  scopes?: string[] | null;
+  account_ids: string[];
+  account_group_ids: string[];
   created_at: string;
   expires_at?: string | null;
 };
@@ -28,6 +47,9 @@ export async function createApiToken(payload: {
   name: string;
   is_read_only?: boolean;
   expires_at?: string | null;
+  scopes?: string[];
+  account_ids?: string[];
+  account_group_ids?: string[];
 }) {
   return post<ApiTokenCreated>("/api/tokens", payload);
 }
@@ -35,3 +57,9 @@ export async function createApiToken(payload: {
 export async function revokeApiToken(tokenId: string) {
   return post(`/api/tokens/${tokenId}/revoke`);
 }
+
+export async function rotateApiToken(tokenId: string, gracePeriodHours?: number) {
+  return post<ApiTokenCreated>(`/api/tokens/${tokenId}/rotate`, {
+    grace_period_hours: gracePeriodHours,
+  });
+}
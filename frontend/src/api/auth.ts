@@ -0,0 +1,82 @@
+import { get, post, put } from "../utils/apiClient";
+
+export type AuthConfig = {
+  password_login_enabled: boolean;
+  oidc_enabled: boolean;
+  oidc_provider_name?: string | null;
+};
+
+export type AuthTokens = {
+  token: string;
+  refresh_token: string;
+  expires_in: number;
+  session_id: string;
+  user_id: string;
+};
+
+export async function fetchAuthConfig() {
+  return get<AuthConfig>("/api/auth/config", undefined, { cacheTtlMs: 60000 });
+}
+
+export type TwoFactorChallenge = {
+  two_factor_required: true;
+  challenge_token: string;
+  expires_in: number;
+};
+
+const SSO_BINDING_KEY = "firecash.sso_binding";
+
+export async function startSingleSignOn() {
+  const response = await get<{ authorization_url: string; binding: string }>(
+    "/api/oidc/authorize",
+    undefined,
+    { cacheTtlMs: 0 },
+  );
+  sessionStorage.setItem(SSO_BINDING_KEY, response.binding);
+  return response;
+}
+
+export async function completeSingleSignOn(code: string, state: string) {
+  const binding = sessionStorage.getItem(SSO_BINDING_KEY) ?? "";
+  sessionStorage.removeItem(SSO_BINDING_KEY);
+  return post<AuthTokens | TwoFactorChallenge>(
+    "/api/oidc/callback",
+    { code, state, binding },
+    undefined,
+    { skipAuth: true },
+  );
+}
+
+export async function completeTwoFactorLogin(challengeToken: string, code: string) {
+  return post<AuthTokens>(
+    "/api/login/2fa",
+    { challenge_token: challengeToken, code },
+    undefined,
+    { skipAuth: true },
+  );
+}
+
+export async function requestPasswordReset(email: string) {
+  return post("/api/password/forgot", { email }, undefined, { skipAuth: true });
+}
+
+export async function resetPassword(token: string, newPassword: string) {
+  return post("/api/password/reset", { token, new_password: newPassword }, undefined, {
+    skipAuth: true,
+  });
+}
+
+export async function changePassword(currentPassword: string, newPassword: string) {
+  return put("/api/me/password", {
+    current_password: currentPassword,
+    new_password: newPassword,
+  });
+}
+
+export async function verifyEmail(token: string) {
+  return post("/api/email/verify", { token }, undefined, { skipAuth: true });
+}
+
+export async function resendEmailVerification() {
+  return post("/api/email/verification");
+}
@@ -8,6 +8,7 @@ type PreferencesResponse = {
   export_redaction?: string;
   asset_refresh_cadence?: string;
   asset_data_source?: string;
+  benchmark_symbol?: string;
 };
 
 export type Preferences = {
@@ -18,6 +19,7 @@ export type Preferences = {
   exportRedaction: string;
   assetRefreshCadence: string;
   assetDataSource: string;
+  benchmarkSymbol: string;
 };
 
 export const DEFAULT_CATEGORIES = ["General", "Housing", "Investing", "Lifestyle", "Bills"];
@@ -52,6 +54,7 @@ export async function fetchPreferences(): Promise<Preferences> {
     exportRedaction: response.export_redaction ?? "none",
     assetRefreshCadence: response.asset_refresh_cadence ?? "daily",
     assetDataSource: response.asset_data_source ?? "stooq",
+    benchmarkSymbol: response.benchmark_symbol ?? "SPY",
   };
 }
 
@@ -78,6 +81,9 @@ export async function updatePreferences(update: Partial<Preferences>): Promise<P
   if (update.assetDataSource) {
     payload.asset_data_source = update.assetDataSource;
   }
+  if (update.benchmarkSymbol) {
+    payload.benchmark_symbol = update.benchmarkSymbol;
+  }
   const response = await put<PreferencesResponse>("/api/preferences", payload);
   return {
     categories: normalizeList(response.categories, DEFAULT_CATEGORIES),
@@ -87,5 +93,6 @@ export async function updatePreferences(update: Partial<Preferences>): Promise<P
     exportRedaction: response.export_redaction ?? "none",
     assetRefreshCadence: response.asset_refresh_cadence ?? "daily",
     assetDataSource: response.asset_data_source ?? "stooq",
+    benchmarkSymbol: response.benchmark_symbol ?? "SPY",
   };
 }
@@ -12,6 +12,7 @@ export type Transaction = {
   amount: number;
   currency_code: string;
   transaction_type: string;
+  category: string;
   description: string | null;
   occurred_at: string;
 };
@@ -0,0 +1,19 @@
+import { get, post } from "../utils/apiClient";
+
+export type SessionSummary = {
+  id: string;
+  user_agent?: string | null;
+  ip_address?: string | null;
+  created_at: string;
+  last_used_at: string;
+  expires_at: string;
+  is_current: boolean;
+};
+
+export async function fetchSessions() {
+  return get<SessionSummary[]>("/api/sessions", undefined, { cacheTtlMs: 0 });
+}
+
+export async function revokeSession(sessionId: string) {
+  return post(`/api/sessions/${sessionId}/revoke`);
+}
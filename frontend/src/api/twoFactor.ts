@@ -0,0 +1,36 @@
+import { get, post } from "../utils/apiClient";
+
+export type TwoFactorStatus = {
+  enabled: boolean;
+  confirmed_at?: string | null;
+  recovery_codes_remaining: number;
+};
+
+export type TwoFactorEnrollment = {
+  secret: string;
+  otpauth_uri: string;
+};
+
+export type RecoveryCodes = {
+  recovery_codes: string[];
+};
+
+export async function fetchTwoFactorStatus() {
+  return get<TwoFactorStatus>("/api/2fa", undefined, { cacheTtlMs: 0 });
+}
+
+export async function enrollTwoFactor() {
+  return post<TwoFactorEnrollment>("/api/2fa/enroll");
+}
+
+export async function confirmTwoFactor(code: string) {
+  return post<RecoveryCodes>("/api/2fa/confirm", { code });
+}
+
+export async function disableTwoFactor(code: string) {
+  return post("/api/2fa/disable", { code });
+}
+
+export async function regenerateRecoveryCodes(code: string) {
+  return post<RecoveryCodes>("/api/2fa/recovery-codes", { code });
+}
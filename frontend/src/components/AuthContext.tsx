@@ -8,12 +8,12 @@ import {
   type ReactNode,
 } from "react";
 import { Navigate, useNavigate } from "react-router-dom";
-import { clearToken, getToken, setToken, setUnauthorizedHandler } from "../utils/apiClient";
+import { clearToken, getToken, post, setToken, setUnauthorizedHandler } from "../utils/apiClient";
 
 type AuthContextValue = {
   token: string | null;
   isAuthenticated: boolean;
-  login: (token: string) => void;
+  login: (token: string, refreshToken?: string) => void;
   logout: () => void;
 };
 
@@ -27,12 +27,14 @@ export function AuthProvider({ children }: AuthProviderProps) {
   const navigate = useNavigate();
   const [tokenState, setTokenState] = useState<string | null>(() => getToken());
 
-  const login = useCallback((token: string) => {
-    setToken(token);
+  const login = useCallback((token: string, refreshToken?: string) => {
+    setToken(token, refreshToken);
     setTokenState(token);
   }, []);
 
   const logout = useCallback(() => {
+    // Revoke the session server-side; the local tokens are dropped regardless.
+    post("/api/logout").catch(() => undefined);
     clearToken();
     setTokenState(null);
   }, []);
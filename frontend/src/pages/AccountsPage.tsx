@@ -22,8 +22,21 @@ type Account = {
   id: string;
   name: string;
   currency_code: string;
+  account_type?: string;
+  credit_limit?: number | null;
+  statement_closing_day?: number | null;
+  payment_due_day?: number | null;
 };
 
+const accountTypes = [
+  { value: "checking", label: "Checking" },
+  { value: "savings", label: "Savings" },
+  { value: "credit_card", label: "Credit card" },
+  { value: "brokerage", label: "Brokerage" },
+  { value: "cash", label: "Cash" },
+  { value: "loan", label: "Loan" },
+];
+
 type AccountGroup = {
   id: string;
   name: string;
@@ -44,6 +57,10 @@ export default function AccountsPage() {
   const [groupDeleteTarget, setGroupDeleteTarget] = useState<AccountGroup | null>(null);
   const [accountName, setAccountName] = useState("");
   const [accountCurrency, setAccountCurrency] = useState("USD");
+  const [accountType, setAccountType] = useState("checking");
+  const [creditLimit, setCreditLimit] = useState("");
+  const [closingDay, setClosingDay] = useState("");
+  const [dueDay, setDueDay] = useState("");
   const [membershipGroup, setMembershipGroup] = useState("");
   const [membershipAccount, setMembershipAccount] = useState("");
   const [accounts, setAccounts] = useState<Account[]>([]);
@@ -307,19 +324,34 @@ export default function AccountsPage() {
       return;
     }
     const trimmed = accountName.trim();
+    const isCard = accountType === "credit_card";
+    const cardFields = isCard
+      ? {
+          credit_limit: creditLimit ? Number(creditLimit) : null,
+          statement_closing_day: closingDay ? Number(closingDay) : null,
+          payment_due_day: dueDay ? Number(dueDay) : null,
+        }
+      : {};
     const tempId = `temp-${Date.now()}`;
     const optimisticAccount: Account = {
       id: tempId,
       name: trimmed,
       currency_code: accountCurrency,
+      account_type: accountType,
+      ...cardFields,
     };
     setAccounts((prev) => [optimisticAccount, ...prev]);
     setIsAccountOpen(false);
     setAccountName("");
+    setCreditLimit("");
+    setClosingDay("");
+    setDueDay("");
     try {
       const created = await post<Account>("/api/accounts", {
         name: trimmed,
         currency_code: accountCurrency,
+        account_type: accountType,
+        ...cardFields,
       });
       setAccounts((prev) =>
         prev.map((account) => (account.id === tempId ? created : account)),
@@ -565,6 +597,53 @@ export default function AccountsPage() {
               ))}
             </select>
           </label>
+          <label>
+            Account type
+            <select value={accountType} onChange={(event) => setAccountType(event.target.value)}>
+              {accountTypes.map((type) => (
+                <option key={type.value} value={type.value}>
+                  {type.label}
+                </option>
+              ))}
+            </select>
+          </label>
+          {accountType === "credit_card" ? (
+            <>
+              <label>
+                Credit limit
+                <input
+                  type="number"
+                  min="0"
+                  step="any"
+                  placeholder="5000"
+                  value={creditLimit}
+                  onChange={(event) => setCreditLimit(event.target.value)}
+                />
+              </label>
+              <label>
+                Statement closing day
+                <input
+                  type="number"
+                  min="1"
+                  max="31"
+                  placeholder="25"
+                  value={closingDay}
+                  onChange={(event) => setClosingDay(event.target.value)}
+                />
+              </label>
+              <label>
+                Payment due day
+                <input
+                  type="number"
+                  min="1"
+                  max="31"
+                  placeholder="20"
+                  value={dueDay}
+                  onChange={(event) => setDueDay(event.target.value)}
+                />
+              </label>
+            </>
+          ) : null}
         </div>
       </Modal>
       <Modal
@@ -0,0 +1,92 @@
+import { useEffect, useState } from "react";
+import { Link, useSearchParams } from "react-router-dom";
+import {
+  acceptInvitation,
+  declineInvitation,
+  previewInvitation,
+  type GroupInvitation,
+} from "../api/accountGroups";
+import { useAuth } from "../components/AuthContext";
+import { getFriendlyErrorMessage } from "../utils/errorMessages";
+import { pageTitles } from "../utils/pageTitles";
+import { usePageTitle } from "../utils/pageMeta";
+
+export default function InvitationPage() {
+  usePageTitle(pageTitles.invitation);
+  const { isAuthenticated } = useAuth();
+  const [searchParams] = useSearchParams();
+  const token = searchParams.get("token");
+  const [invitation, setInvitation] = useState<GroupInvitation | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [notice, setNotice] = useState<string | null>(null);
+  const [isSubmitting, setIsSubmitting] = useState(false);
+
+  useEffect(() => {
+    if (!token) {
+      setError("The invitation link is missing its token.");
+      return;
+    }
+    previewInvitation(token)
+      .then(setInvitation)
+      .catch((err) => setError(getFriendlyErrorMessage(err, "Unable to load this invitation.")));
+  }, [token]);
+
+  const respond = async (accept: boolean) => {
+    if (!token) {
+      return;
+    }
+    setError(null);
+    setIsSubmitting(true);
+    try {
+      if (accept) {
+        await acceptInvitation({ token });
+        setNotice(`You joined ${invitation?.group_name ?? "the group"}.`);
+      } else {
+        await declineInvitation({ token });
+        setNotice("Invitation declined.");
+      }
+    } catch (err) {
+      setError(getFriendlyErrorMessage(err, "Unable to answer this invitation."));
+    } finally {
+      setIsSubmitting(false);
+    }
+  };
+
+  return (
+    <div className="auth-page">
+      <div className="auth-card">
+        <h1>{pageTitles.invitation}</h1>
+        {invitation && (
+          <p className="muted">
+            {invitation.invited_by_name ?? "A Firecash user"} invited {invitation.email} to the
+            account group <strong>{invitation.group_name}</strong> with {invitation.role} access.
+          </p>
+        )}
+        {error && <p className="form-error">{error}</p>}
+        {notice ? (
+          <p className="muted">
+            {notice} <Link to="/dashboard">Continue to Firecash</Link>
+          </p>
+        ) : invitation && invitation.status !== "pending" ? (
+          <p className="muted">This invitation is {invitation.status}.</p>
+        ) : invitation && isAuthenticated ? (
+          <div className="action-grid">
+            <button className="pill primary" disabled={isSubmitting} onClick={() => respond(true)}>
+              Accept
+            </button>
+            <button className="pill" disabled={isSubmitting} onClick={() => respond(false)}>
+              Decline
+            </button>
+          </div>
+        ) : invitation ? (
+          <p className="muted">
+            <Link to="/login">Sign in</Link> or <Link to="/register">create an account</Link> with
+            {" "}
+            {invitation.email}, then open this link again. Once your email is verified, the
+            invitation also appears under Settings.
+          </p>
+        ) : null}
+      </div>
+    </div>
+  );
+}
@@ -1,11 +1,18 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
 import { Link, useNavigate } from "react-router-dom";
+import { fetchAuthConfig, startSingleSignOn, type AuthConfig } from "../api/auth";
 import { useAuth } from "../components/AuthContext";
 import { ApiError, post } from "../utils/apiClient";
 import { getFriendlyErrorMessage } from "../utils/errorMessages";
 import { pageTitles } from "../utils/pageTitles";
 import { usePageTitle } from "../utils/pageMeta";
 
+type AuthResponse = { token: string; refresh_token: string };
+
+type LoginResponse =
+  | AuthResponse
+  | { two_factor_required: true; challenge_token: string; expires_in: number };
+
 export default function LoginPage() {
   usePageTitle(pageTitles.login);
   const navigate = useNavigate();
@@ -15,97 +22,184 @@ export default function LoginPage() {
   const [error, setError] = useState<string | null>(null);
   const [isSubmitting, setIsSubmitting] = useState(false);
   const [isDemoLoading, setIsDemoLoading] = useState(false);
+  const [challengeToken, setChallengeToken] = useState<string | null>(null);
+  const [code, setCode] = useState("");
+  const [authConfig, setAuthConfig] = useState<AuthConfig | null>(null);
+  const [isSsoLoading, setIsSsoLoading] = useState(false);
+  const passwordLoginEnabled = authConfig?.password_login_enabled ?? true;
+
+  useEffect(() => {
+    fetchAuthConfig()
+      .then(setAuthConfig)
+      .catch(() => setAuthConfig(null));
+  }, []);
 
   return (
     <div className="auth-page">
       <div className="auth-card">
         <h1>{pageTitles.login}</h1>
         <p className="muted">Sign in to track your assets.</p>
-        <form
-          className="auth-form"
-          onSubmit={async (event) => {
-            event.preventDefault();
-            setError(null);
-            setIsSubmitting(true);
-            try {
-              const response = await post<{ token: string }>(
-                "/api/login",
-                { email, password },
-                undefined,
-                { skipAuth: true },
-              );
-              login(response.token);
-              navigate("/dashboard");
-            } catch (err) {
-              if (err instanceof ApiError && err.status === 401) {
-                setError("Incorrect email or password.");
-              } else {
-                setError(getFriendlyErrorMessage(err, "Unable to sign in. Please try again."));
+        {authConfig?.oidc_enabled && (
+          <button
+            className="pill primary"
+            type="button"
+            disabled={isSsoLoading}
+            onClick={async () => {
+              setError(null);
+              setIsSsoLoading(true);
+              try {
+                const { authorization_url } = await startSingleSignOn();
+                window.location.assign(authorization_url);
+              } catch (err) {
+                setError(getFriendlyErrorMessage(err, "Unable to start single sign-on."));
+                setIsSsoLoading(false);
               }
-            } finally {
-              setIsSubmitting(false);
-            }
-          }}
-        >
-          <label>
-            Email
-            <input
-              type="email"
-              placeholder="you@example.com"
-              value={email}
-              onChange={(event) => setEmail(event.target.value)}
-              aria-describedby={error ? "login-error" : undefined}
-              required
-            />
-          </label>
-          <label>
-            Password
-            <input
-              type="password"
-              placeholder="••••••••"
-              value={password}
-              onChange={(event) => setPassword(event.target.value)}
-              aria-describedby={error ? "login-error" : undefined}
-              required
-            />
-          </label>
-          {error && (
-            <p className="form-error" id="login-error">
-              {error}
-            </p>
-          )}
-          <button className="pill primary" type="submit">
-            {isSubmitting ? "Signing in..." : "Sign in"}
+            }}
+          >
+            {isSsoLoading
+              ? "Redirecting..."
+              : `Sign in with ${authConfig.oidc_provider_name ?? "SSO"}`}
           </button>
-        </form>
-        <button
-          className="pill"
-          type="button"
-          disabled={isDemoLoading}
-          onClick={async () => {
-            setError(null);
-            setIsDemoLoading(true);
-            try {
-              const response = await post<{ token: string }>(
-                "/api/demo-login",
-                undefined,
-                undefined,
-                { skipAuth: true },
-              );
-              login(response.token);
-              navigate("/dashboard");
-            } catch (err) {
-              setError(getFriendlyErrorMessage(err, "Unable to load the demo account."));
-            } finally {
-              setIsDemoLoading(false);
-            }
-          }}
-        >
-          {isDemoLoading ? "Loading demo..." : "Use demo account"}
-        </button>
-        <p className="muted">
-          Don’t have an account? <Link to="/register">Create one</Link>
-        </p>
+        )}
+        {!passwordLoginEnabled && error && (
+          <p className="form-error" id="login-error">
+            {error}
+          </p>
+        )}
+        {passwordLoginEnabled && (
+          <>
+            <form
+              className="auth-form"
+              onSubmit={async (event) => {
+                event.preventDefault();
+                setError(null);
+                setIsSubmitting(true);
+                try {
+                  if (challengeToken) {
+                    const response = await post<AuthResponse>(
+                      "/api/login/2fa",
+                      { challenge_token: challengeToken, code },
+                      undefined,
+                      { skipAuth: true },
+                    );
+                    login(response.token, response.refresh_token);
+                    navigate("/dashboard");
+                    return;
+                  }
+                  const response = await post<LoginResponse>(
+                    "/api/login",
+                    { email, password },
+                    undefined,
+                    { skipAuth: true },
+                  );
+                  if ("two_factor_required" in response) {
+                    setChallengeToken(response.challenge_token);
+                    setCode("");
+                    return;
+                  }
+                  login(response.token, response.refresh_token);
+                  navigate("/dashboard");
+                } catch (err) {
+                  if (challengeToken && err instanceof ApiError && err.status === 401) {
+                    if (err.message.includes("expired")) {
+                      setChallengeToken(null);
+                      setError("That sign-in attempt expired. Enter your password again.");
+                    } else {
+                      setError("Incorrect code.");
+                    }
+                  } else if (err instanceof ApiError && err.status === 401) {
+                    setError("Incorrect email or password.");
+                  } else {
+                    setError(getFriendlyErrorMessage(err, "Unable to sign in. Please try again."));
+                  }
+                } finally {
+                  setIsSubmitting(false);
+                }
+              }}
+            >
+              {challengeToken ? (
+                <label>
+                  Authentication code
+                  <input
+                    type="text"
+                    inputMode="numeric"
+                    autoComplete="one-time-code"
+                    placeholder="123456 or recovery code"
+                    value={code}
+                    onChange={(event) => setCode(event.target.value)}
+                    aria-describedby={error ? "login-error" : undefined}
+                    autoFocus
+                    required
+                  />
+                </label>
+              ) : (
+                <>
+                  <label>
+                    Email
+                    <input
+                      type="email"
+                      placeholder="you@example.com"
+                      value={email}
+                      onChange={(event) => setEmail(event.target.value)}
+                      aria-describedby={error ? "login-error" : undefined}
+                      required
+                    />
+                  </label>
+                  <label>
+                    Password
+                    <input
+                      type="password"
+                      placeholder="••••••••"
+                      value={password}
+                      onChange={(event) => setPassword(event.target.value)}
+                      aria-describedby={error ? "login-error" : undefined}
+                      required
+                    />
+                  </label>
+                </>
+              )}
+              {error && (
+                <p className="form-error" id="login-error">
+                  {error}
+                </p>
+              )}
+              <button className="pill primary" type="submit">
+                {isSubmitting ? "Signing in..." : challengeToken ? "Verify" : "Sign in"}
+              </button>
+            </form>
+            <button
+              className="pill"
+              type="button"
+              disabled={isDemoLoading}
+              onClick={async () => {
+                setError(null);
+                setIsDemoLoading(true);
+                try {
+                  const response = await post<{ token: string; refresh_token: string }>(
+                    "/api/demo-login",
+                    undefined,
+                    undefined,
+                    { skipAuth: true },
+                  );
+                  login(response.token, response.refresh_token);
+                  navigate("/dashboard");
+                } catch (err) {
+                  setError(getFriendlyErrorMessage(err, "Unable to load the demo account."));
+                } finally {
+                  setIsDemoLoading(false);
+                }
+              }}
+            >
+              {isDemoLoading ? "Loading demo..." : "Use demo account"}
+            </button>
+            <p className="muted">
+              <Link to="/reset-password">Forgot your password?</Link>
+            </p>
+            <p className="muted">
+              Don’t have an account? <Link to="/register">Create one</Link>
+            </p>
+          </>
+        )}
       </div>
     </div>
   );
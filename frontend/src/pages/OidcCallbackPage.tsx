@@ -0,0 +1,109 @@
+import { useEffect, useRef, useState } from "react";
+import { Link, useNavigate, useSearchParams } from "react-router-dom";
+import { completeSingleSignOn, completeTwoFactorLogin } from "../api/auth";
+import { useAuth } from "../components/AuthContext";
+import { ApiError } from "../utils/apiClient";
+import { getFriendlyErrorMessage } from "../utils/errorMessages";
+import { pageTitles } from "../utils/pageTitles";
+import { usePageTitle } from "../utils/pageMeta";
+
+export default function OidcCallbackPage() {
+  usePageTitle(pageTitles.singleSignOn);
+  const navigate = useNavigate();
+  const { login } = useAuth();
+  const [searchParams] = useSearchParams();
+  const [error, setError] = useState<string | null>(null);
+  const [challengeToken, setChallengeToken] = useState<string | null>(null);
+  const [code, setCode] = useState("");
+  const [isSubmitting, setIsSubmitting] = useState(false);
+  const hasStarted = useRef(false);
+
+  useEffect(() => {
+    // The authorization code is single-use, so only redeem it once.
+    if (hasStarted.current) {
+      return;
+    }
+    hasStarted.current = true;
+    const code = searchParams.get("code");
+    const state = searchParams.get("state");
+    const providerError = searchParams.get("error_description") ?? searchParams.get("error");
+    if (providerError || !code || !state) {
+      setError(providerError ?? "The sign-in response was incomplete.");
+      return;
+    }
+    completeSingleSignOn(code, state)
+      .then((response) => {
+        if ("two_factor_required" in response) {
+          setChallengeToken(response.challenge_token);
+          return;
+        }
+        login(response.token, response.refresh_token);
+        navigate("/dashboard", { replace: true });
+      })
+      .catch((err) => {
+        setError(getFriendlyErrorMessage(err, "Unable to complete single sign-on."));
+      });
+  }, [login, navigate, searchParams]);
+
+  return (
+    <div className="auth-page">
+      <div className="auth-card">
+        <h1>{pageTitles.singleSignOn}</h1>
+        {challengeToken ? (
+          <form
+            className="auth-form"
+            onSubmit={async (event) => {
+              event.preventDefault();
+              setError(null);
+              setIsSubmitting(true);
+              try {
+                const response = await completeTwoFactorLogin(challengeToken, code);
+                login(response.token, response.refresh_token);
+                navigate("/dashboard", { replace: true });
+              } catch (err) {
+                if (err instanceof ApiError && err.status === 401) {
+                  if (err.message.includes("expired")) {
+                    setChallengeToken(null);
+                    setError("That sign-in attempt expired. Start single sign-on again.");
+                  } else {
+                    setError("Incorrect code.");
+                  }
+                } else {
+                  setError(getFriendlyErrorMessage(err, "Unable to sign in. Please try again."));
+                }
+              } finally {
+                setIsSubmitting(false);
+              }
+            }}
+          >
+            <label>
+              Authentication code
+              <input
+                type="text"
+                inputMode="numeric"
+                autoComplete="one-time-code"
+                placeholder="123456 or recovery code"
+                value={code}
+                onChange={(event) => setCode(event.target.value)}
+                required
+              />
+            </label>
+            {error && <p className="form-error">{error}</p>}
+            <button className="pill primary" type="submit" disabled={isSubmitting}>
+              {isSubmitting ? "Signing in..." : "Verify"}
+            </button>
+          </form>
+        ) : error ? (
+          <>
+            <p className="form-error">{error}</p>
+            <p className="muted">
+              <Link to="/login">Back to sign in</Link>
+            </p>
+          </>
+        ) : (
+          <p className="muted">Completing sign-in with your identity provider...</p>
+        )}
+      </div>
+    </div>
+  );
+}
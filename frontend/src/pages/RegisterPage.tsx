@@ -41,13 +41,13 @@ export default function RegisterPage() {
             setError(null);
             setIsSubmitting(true);
             try {
-              const response = await post<{ token: string }>(
+              const response = await post<{ token: string; refresh_token: string }>(
                 "/api/register",
                 { name, email, password },
                 undefined,
                 { skipAuth: true },
               );
-              login(response.token);
+              login(response.token, response.refresh_token);
               navigate("/dashboard");
             } catch (err) {
               if (err instanceof ApiError && err.status === 409) {
@@ -25,6 +25,8 @@ import { formatApiErrorDetail } from "../utils/errorMessages";
 import { pageTitles } from "../utils/pageTitles";
 import { usePageMeta } from "../utils/pageMeta";
 
+const OPENING_BALANCE_CATEGORY = "Opening Balance";
+
 export default function ReportsPage() {
   usePageMeta({ title: pageTitles.reports });
   const navigate = useNavigate();
@@ -89,7 +91,11 @@ export default function ReportsPage() {
   const recentTransactions = useMemo(() => {
     const cutoff = new Date();
     cutoff.setDate(cutoff.getDate() - 30);
-    return transactions.filter((transaction) => new Date(transaction.occurred_at) >= cutoff);
+    return transactions.filter(
+      (transaction) =>
+        new Date(transaction.occurred_at) >= cutoff &&
+        transaction.category !== OPENING_BALANCE_CATEGORY,
+    );
   }, [transactions]);
 
   const { income, expenses } = useMemo(() => {
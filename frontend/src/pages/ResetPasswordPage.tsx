@@ -0,0 +1,103 @@
+import { useState } from "react";
+import { Link, useSearchParams } from "react-router-dom";
+import { requestPasswordReset, resetPassword } from "../api/auth";
+import { getFriendlyErrorMessage } from "../utils/errorMessages";
+import { pageTitles } from "../utils/pageTitles";
+import { usePageTitle } from "../utils/pageMeta";
+
+export default function ResetPasswordPage() {
+  usePageTitle(pageTitles.resetPassword);
+  const [searchParams] = useSearchParams();
+  const token = searchParams.get("token");
+  const [email, setEmail] = useState("");
+  const [password, setPassword] = useState("");
+  const [confirmPassword, setConfirmPassword] = useState("");
+  const [error, setError] = useState<string | null>(null);
+  const [notice, setNotice] = useState<string | null>(null);
+  const [isSubmitting, setIsSubmitting] = useState(false);
+
+  return (
+    <div className="auth-page">
+      <div className="auth-card">
+        <h1>{pageTitles.resetPassword}</h1>
+        <p className="muted">
+          {token
+            ? "Choose a new password. You will be signed out everywhere."
+            : "Enter your email and we will send you a reset link."}
+        </p>
+        {notice ? (
+          <p className="muted">{notice}</p>
+        ) : (
+          <form
+            className="auth-form"
+            onSubmit={async (event) => {
+              event.preventDefault();
+              setError(null);
+              if (token && password !== confirmPassword) {
+                setError("Passwords do not match.");
+                return;
+              }
+              setIsSubmitting(true);
+              try {
+                if (token) {
+                  await resetPassword(token, password);
+                  setNotice("Your password has been reset. Sign in with the new password.");
+                } else {
+                  await requestPasswordReset(email);
+                  setNotice("If that email has an account, a reset link is on its way.");
+                }
+              } catch (err) {
+                setError(getFriendlyErrorMessage(err, "Unable to reset your password."));
+              } finally {
+                setIsSubmitting(false);
+              }
+            }}
+          >
+            {token ? (
+              <>
+                <label>
+                  New password
+                  <input
+                    type="password"
+                    value={password}
+                    onChange={(event) => setPassword(event.target.value)}
+                    minLength={8}
+                    required
+                  />
+                </label>
+                <label>
+                  Confirm password
+                  <input
+                    type="password"
+                    value={confirmPassword}
+                    onChange={(event) => setConfirmPassword(event.target.value)}
+                    minLength={8}
+                    required
+                  />
+                </label>
+              </>
+            ) : (
+              <label>
+                Email
+                <input
+                  type="email"
+                  placeholder="you@example.com"
+                  value={email}
+                  onChange={(event) => setEmail(event.target.value)}
+                  required
+                />
+              </label>
+            )}
+            {error && <p className="form-error">{error}</p>}
+            <button className="pill primary" type="submit" disabled={isSubmitting}>
+              {isSubmitting ? "Sending..." : token ? "Reset password" : "Send reset link"}
+            </button>
+          </form>
+        )}
+        <p className="muted">
+          <Link to="/login">Back to sign in</Link>
+        </p>
+      </div>
+    </div>
+  );
+}
@@ -6,23 +6,43 @@ import Modal from "../components/Modal";
 import { useAuth } from "../components/AuthContext";
 import { exportBackupCsv, exportBackupJson, restoreBackup, type BackupPayload } from "../api/backup";
 import { fetchAuditLogs, type AuditLogEntry } from "../api/audit";
+import { fetchActivity, type ActivityEntry } from "../api/activity";
 import {
-  addAccountGroupUser,
+  acceptInvitation,
+  declineInvitation,
   fetchAccountGroups,
   fetchAccountGroupUsers,
+  fetchGroupInvitations,
+  fetchMyInvitations,
+  inviteToAccountGroup,
   removeAccountGroupUser,
+  revokeGroupInvitation,
   updateAccountGroupUser,
   type AccountGroup,
   type AccountGroupUser,
+  type GroupInvitation,
 } from "../api/accountGroups";
 import { fetchPreferences, updatePreferences } from "../api/preferences";
 import {
+  apiTokenScopes,
   createApiToken,
   fetchApiTokens,
   revokeApiToken,
+  rotateApiToken,
   type ApiTokenCreated,
   type ApiTokenSummary,
 } from "../api/apiTokens";
+import { changePassword, resendEmailVerification } from "../api/auth";
+import { fetchSessions, revokeSession, type SessionSummary } from "../api/sessions";
+import {
+  confirmTwoFactor,
+  disableTwoFactor,
+  enrollTwoFactor,
+  fetchTwoFactorStatus,
+  regenerateRecoveryCodes,
+  type TwoFactorEnrollment,
+  type TwoFactorStatus,
+} from "../api/twoFactor";
 import { get, post, put } from "../utils/apiClient";
 import { formatDateDisplay } from "../utils/date";
 import { formatApiErrorDetail, getFriendlyErrorMessage } from "../utils/errorMessages";
@@ -35,6 +55,7 @@ type UserProfile = {
   id: string;
   name: string;
   email: string;
+  email_verified: boolean;
 };
 
 type FxRate = {
@@ -68,16 +89,20 @@ export default function SettingsPage() {
   const [restoreError, setRestoreError] = useState<string | null>(null);
   const [isRestoreOpen, setIsRestoreOpen] = useState(false);
   const [auditLogs, setAuditLogs] = useState<AuditLogEntry[]>([]);
+  const [activity, setActivity] = useState<ActivityEntry[]>([]);
   const [auditError, setAuditError] = useState<string | null>(null);
   const [auditErrorDetails, setAuditErrorDetails] = useState<string[]>([]);
   const [accountGroups, setAccountGroups] = useState<AccountGroup[]>([]);
   const [selectedGroupId, setSelectedGroupId] = useState<string | null>(null);
   const [groupMembers, setGroupMembers] = useState<AccountGroupUser[]>([]);
+  const [groupInvitations, setGroupInvitations] = useState<GroupInvitation[]>([]);
+  const [myInvitations, setMyInvitations] = useState<GroupInvitation[]>([]);
   const [memberEmail, setMemberEmail] = useState("");
   const [memberRole, setMemberRole] = useState<"view" | "edit" | "admin">("view");
   const [isMembershipSaving, setIsMembershipSaving] = useState(false);
   const [assetRefreshCadence, setAssetRefreshCadence] = useState("daily");
   const [assetDataSource, setAssetDataSource] = useState("stooq");
+  const [benchmarkSymbol, setBenchmarkSymbol] = useState("SPY");
   const [themePreference, setThemePreferenceState] = useState<ThemePreference>(() =>
     getThemePreference(),
   );
@@ -85,11 +110,24 @@ export default function SettingsPage() {
   const [apiTokens, setApiTokens] = useState<ApiTokenSummary[]>([]);
   const [isTokenLoading, setIsTokenLoading] = useState(true);
   const [tokenName, setTokenName] = useState("");
-  const [tokenReadOnly, setTokenReadOnly] = useState(true);
+  const [tokenAccess, setTokenAccess] = useState<"read" | "full" | "scoped">("read");
+  const [tokenScopes, setTokenScopes] = useState<string[]>(["transactions:read"]);
+  const [tokenGroupId, setTokenGroupId] = useState("");
   const [tokenExpiresAt, setTokenExpiresAt] = useState("");
   const [isTokenSaving, setIsTokenSaving] = useState(false);
   const [tokenReveal, setTokenReveal] = useState<ApiTokenCreated | null>(null);
   const [isTokenModalOpen, setIsTokenModalOpen] = useState(false);
+  const [sessions, setSessions] = useState<SessionSummary[]>([]);
+  const [isSessionLoading, setIsSessionLoading] = useState(true);
+  const [twoFactor, setTwoFactor] = useState<TwoFactorStatus | null>(null);
+  const [twoFactorEnrollment, setTwoFactorEnrollment] = useState<TwoFactorEnrollment | null>(null);
+  const [twoFactorCode, setTwoFactorCode] = useState("");
+  const [recoveryCodes, setRecoveryCodes] = useState<string[]>([]);
+  const [isTwoFactorSaving, setIsTwoFactorSaving] = useState(false);
+  const [currentPassword, setCurrentPassword] = useState("");
+  const [newPassword, setNewPassword] = useState("");
+  const [isPasswordSaving, setIsPasswordSaving] = useState(false);
+  const [isVerificationSending, setIsVerificationSending] = useState(false);
 
   const showToast = (title: string, description?: string) => {
     setToast({ title, description });
@@ -145,6 +183,7 @@ export default function SettingsPage() {
       setExportRedaction(response.exportRedaction);
       setAssetRefreshCadence(response.assetRefreshCadence);
       setAssetDataSource(response.assetDataSource);
+      setBenchmarkSymbol(response.benchmarkSymbol);
     } catch (error) {
       setPreferencesError("Unable to load preferences right now.");
       const detail = formatApiErrorDetail(error);
@@ -188,6 +227,12 @@ export default function SettingsPage() {
     loadAuditLogs();
   }, []);
 
+  useEffect(() => {
+    fetchActivity()
+      .then(setActivity)
+      .catch(() => setActivity([]));
+  }, []);
+
   const loadAccountGroups = async () => {
     try {
       const groups = await fetchAccountGroups();
@@ -219,9 +264,50 @@ export default function SettingsPage() {
     loadApiTokens();
   }, []);
 
+  const loadSessions = async () => {
+    setIsSessionLoading(true);
+    try {
+      setSessions(await fetchSessions());
+    } catch (error) {
+      setSessions([]);
+    } finally {
+      setIsSessionLoading(false);
+    }
+  };
+
+  useEffect(() => {
+    loadSessions();
+  }, []);
+
+  const loadTwoFactor = async () => {
+    try {
+      setTwoFactor(await fetchTwoFactorStatus());
+    } catch (error) {
+      setTwoFactor(null);
+    }
+  };
+
+  useEffect(() => {
+    loadTwoFactor();
+  }, []);
+
+  const runTwoFactorAction = async (action: () => Promise<void>, failure: string) => {
+    setIsTwoFactorSaving(true);
+    try {
+      await action();
+      setTwoFactorCode("");
+      await loadTwoFactor();
+    } catch (error) {
+      showToast("Two-factor update failed", getFriendlyErrorMessage(error, failure));
+    } finally {
+      setIsTwoFactorSaving(false);
+    }
+  };
+
   const loadGroupMembers = async (groupId: string | null) => {
     if (!groupId) {
       setGroupMembers([]);
+      setGroupInvitations([]);
       return;
     }
     try {
@@ -230,8 +316,27 @@ export default function SettingsPage() {
     } catch (error) {
       setGroupMembers([]);
     }
+    try {
+      // Only group admins may list invitations.
+      const invitations = await fetchGroupInvitations(groupId);
+      setGroupInvitations(invitations.filter((invitation) => invitation.status === "pending"));
+    } catch (error) {
+      setGroupInvitations([]);
+    }
+  };
+
+  const loadMyInvitations = async () => {
+    try {
+      setMyInvitations(await fetchMyInvitations());
+    } catch (error) {
+      setMyInvitations([]);
+    }
   };
 
+  useEffect(() => {
+    loadMyInvitations();
+  }, []);
+
   useEffect(() => {
     loadGroupMembers(selectedGroupId);
   }, [selectedGroupId]);
@@ -299,6 +404,35 @@ export default function SettingsPage() {
                   onChange={(event) => setEmail(event.target.value)}
                 />
               </label>
+              <p className="muted">
+                {profile.email_verified ? "Email verified." : "Email not verified yet."}
+                {!profile.email_verified && (
+                  <>
+                    {" "}
+                    <button
+                      className="pill"
+                      type="button"
+                      disabled={isVerificationSending}
+                      onClick={async () => {
+                        setIsVerificationSending(true);
+                        try {
+                          await resendEmailVerification();
+                          showToast("Verification sent", `Check ${profile.email} for the link.`);
+                        } catch (error) {
+                          showToast(
+                            "Send failed",
+                            getFriendlyErrorMessage(error, "Unable to send a verification email."),
+                          );
+                        } finally {
+                          setIsVerificationSending(false);
+                        }
+                      }}
+                    >
+                      {isVerificationSending ? "Sending..." : "Resend verification"}
+                    </button>
+                  </>
+                )}
+              </p>
             </div>
           ) : (
             <p className="muted">Profile details are unavailable.</p>
@@ -334,6 +468,53 @@ export default function SettingsPage() {
             {isSaving ? "Saving..." : "Save changes"}
           </button>
         </div>
+        <div className="card" id="password">
+          <h3>Password</h3>
+          <p className="muted">
+            Changing your password signs out every other session.
+          </p>
+          <div className="form-grid">
+            <label>
+              Current password
+              <input
+                type="password"
+                value={currentPassword}
+                onChange={(event) => setCurrentPassword(event.target.value)}
+              />
+            </label>
+            <label>
+              New password
+              <input
+                type="password"
+                value={newPassword}
+                minLength={8}
+                onChange={(event) => setNewPassword(event.target.value)}
+              />
+            </label>
+          </div>
+          <button
+            className="pill primary"
+            disabled={isPasswordSaving || !currentPassword || newPassword.length < 8}
+            onClick={async () => {
+              setIsPasswordSaving(true);
+              try {
+                await changePassword(currentPassword, newPassword);
+                setCurrentPassword("");
+                setNewPassword("");
+                showToast("Password changed", "Other sessions have been signed out.");
+              } catch (error) {
+                showToast(
+                  "Change failed",
+                  getFriendlyErrorMessage(error, "Unable to change your password."),
+                );
+              } finally {
+                setIsPasswordSaving(false);
+              }
+            }}
+          >
+            {isPasswordSaving ? "Saving..." : "Change password"}
+          </button>
+        </div>
         <div className="card" id="api-tokens">
           <h3>API tokens</h3>
           <p className="muted">
@@ -360,11 +541,28 @@ export default function SettingsPage() {
             <label>
               Access level
               <select
-                value={tokenReadOnly ? "read" : "full"}
-                onChange={(event) => setTokenReadOnly(event.target.value === "read")}
+                value={tokenAccess}
+                onChange={(event) =>
+                  setTokenAccess(event.target.value as "read" | "full" | "scoped")
+                }
               >
                 <option value="read">Read-only</option>
                 <option value="full">Full access</option>
+                <option value="scoped">Specific scopes</option>
+              </select>
+            </label>
+            <label>
+              Limit to account group (optional)
+              <select
+                value={tokenGroupId}
+                onChange={(event) => setTokenGroupId(event.target.value)}
+              >
+                <option value="">All accounts</option>
+                {accountGroups.map((group) => (
+                  <option key={group.id} value={group.id}>
+                    {group.name}
+                  </option>
+                ))}
               </select>
             </label>
             <label>
@@ -376,6 +574,26 @@ export default function SettingsPage() {
               />
             </label>
           </div>
+          {tokenAccess === "scoped" && (
+            <div className="form-grid">
+              {apiTokenScopes.map((scope) => (
+                <label key={scope}>
+                  <input
+                    type="checkbox"
+                    checked={tokenScopes.includes(scope)}
+                    onChange={(event) =>
+                      setTokenScopes((current) =>
+                        event.target.checked
+                          ? [...current, scope]
+                          : current.filter((value) => value !== scope),
+                      )
+                    }
+                  />
+                  {scope}
+                </label>
+              ))}
+            </div>
+          )}
           <button
             className="pill primary"
             disabled={isTokenSaving}
@@ -391,8 +609,10 @@ export default function SettingsPage() {
                   : null;
                 const created = await createApiToken({
                   name: tokenName.trim(),
-                  is_read_only: tokenReadOnly,
+                  is_read_only: tokenAccess === "read",
                   expires_at: expiresAtIso,
+                  scopes: tokenAccess === "scoped" ? tokenScopes : undefined,
+                  account_group_ids: tokenGroupId ? [tokenGroupId] : [],
                 });
                 setTokenName("");
                 setTokenExpiresAt("");
@@ -431,8 +651,35 @@ export default function SettingsPage() {
                 <div className="table-row columns-4" key={token.id}>
                   <span>{token.name}</span>
                   <span>{token.token_prefix ?? "Hidden"}</span>
-                  <span>{token.is_read_only ? "Read-only" : "Full"}</span>
                   <span>
+                    {token.scopes
+                      ? token.scopes.join(", ")
+                      : token.is_read_only
+                        ? "Read-only"
+                        : "Full"}
+                    {token.account_ids.length + token.account_group_ids.length > 0 &&
+                      " (restricted accounts)"}
+                  </span>
+                  <span>
+                    <button
+                      className="pill"
+                      disabled={Boolean(token.revoked_at || token.replaced_by)}
+                      onClick={async () => {
+                        try {
+                          const created = await rotateApiToken(token.id);
+                          setTokenReveal(created);
+                          setIsTokenModalOpen(true);
+                          await loadApiTokens();
+                        } catch (error) {
+                          showToast(
+                            "Rotate failed",
+                            getFriendlyErrorMessage(error, "Unable to rotate this token."),
+                          );
+                        }
+                      }}
+                    >
+                      {token.replaced_by ? "Rotated" : "Rotate"}
+                    </button>
                     <button
                       className="pill"
                       disabled={Boolean(token.revoked_at)}
@@ -457,6 +704,162 @@ export default function SettingsPage() {
             </div>
           )}
         </div>
+        <div className="card">
+          <h3>Two-factor authentication</h3>
+          <p className="muted">
+            {twoFactor?.enabled
+              ? `Enabled. ${twoFactor.recovery_codes_remaining} recovery codes remaining.`
+              : "Require a code from an authenticator app when signing in."}
+          </p>
+          {twoFactorEnrollment && !twoFactor?.enabled && (
+            <div className="form-grid">
+              <label>
+                Setup key
+                <input type="text" value={twoFactorEnrollment.secret} readOnly />
+              </label>
+              <label>
+                Authenticator link
+                <input type="text" value={twoFactorEnrollment.otpauth_uri} readOnly />
+              </label>
+            </div>
+          )}
+          {(twoFactor?.enabled || twoFactorEnrollment) && (
+            <label>
+              Authentication code
+              <input
+                type="text"
+                inputMode="numeric"
+                autoComplete="one-time-code"
+                value={twoFactorCode}
+                onChange={(event) => setTwoFactorCode(event.target.value)}
+                placeholder="123456"
+              />
+            </label>
+          )}
+          {recoveryCodes.length > 0 && (
+            <div className="alert-card">
+              <strong>Save your recovery codes</strong>
+              <p className="muted">Each code signs you in once if you lose your device.</p>
+              <div className="chip-grid">
+                {recoveryCodes.map((code) => (
+                  <span key={code} className="chip">
+                    {code}
+                  </span>
+                ))}
+              </div>
+            </div>
+          )}
+          <div className="toolbar">
+            {twoFactor?.enabled ? (
+              <>
+                <button
+                  className="pill"
+                  disabled={isTwoFactorSaving || !twoFactorCode.trim()}
+                  onClick={() =>
+                    runTwoFactorAction(async () => {
+                      const response = await regenerateRecoveryCodes(twoFactorCode.trim());
+                      setRecoveryCodes(response.recovery_codes);
+                    }, "Unable to create new recovery codes.")
+                  }
+                >
+                  New recovery codes
+                </button>
+                <button
+                  className="pill"
+                  disabled={isTwoFactorSaving || !twoFactorCode.trim()}
+                  onClick={() =>
+                    runTwoFactorAction(async () => {
+                      await disableTwoFactor(twoFactorCode.trim());
+                      setRecoveryCodes([]);
+                      showToast("Two-factor disabled", "Sign-in now only needs your password.");
+                    }, "Unable to disable two-factor authentication.")
+                  }
+                >
+                  Disable
+                </button>
+              </>
+            ) : twoFactorEnrollment ? (
+              <button
+                className="pill primary"
+                disabled={isTwoFactorSaving || !twoFactorCode.trim()}
+                onClick={() =>
+                  runTwoFactorAction(async () => {
+                    const response = await confirmTwoFactor(twoFactorCode.trim());
+                    setTwoFactorEnrollment(null);
+                    setRecoveryCodes(response.recovery_codes);
+                    showToast("Two-factor enabled", "Codes are now required at sign-in.");
+                  }, "That code did not match. Try the next one.")
+                }
+              >
+                Confirm code
+              </button>
+            ) : (
+              <button
+                className="pill primary"
+                disabled={isTwoFactorSaving}
+                onClick={() =>
+                  runTwoFactorAction(async () => {
+                    setTwoFactorEnrollment(await enrollTwoFactor());
+                  }, "Unable to start two-factor setup.")
+                }
+              >
+                Set up
+              </button>
+            )}
+          </div>
+        </div>
+        <div className="card">
+          <h3>Active sessions</h3>
+          <p className="muted">Devices signed in to your account. Revoke any you do not recognize.</p>
+          {isSessionLoading ? (
+            <LoadingState
+              title="Loading sessions"
+              description="Fetching your signed-in devices."
+              className="loading-state-inline"
+            />
+          ) : sessions.length === 0 ? (
+            <p className="muted">No active sessions.</p>
+          ) : (
+            <div className="table compact">
+              <div className="table-row table-header columns-4">
+                <span>Device</span>
+                <span>IP address</span>
+                <span>Last active</span>
+                <span>Actions</span>
+              </div>
+              {sessions.map((session) => (
+                <div className="table-row columns-4" key={session.id}>
+                  <span>{session.user_agent ?? "Unknown device"}</span>
+                  <span>{session.ip_address ?? "Unknown"}</span>
+                  <span>{formatDateDisplay(session.last_used_at)}</span>
+                  <span>
+                    {session.is_current ? (
+                      <span className="muted">This device</span>
+                    ) : (
+                      <button
+                        className="pill"
+                        onClick={async () => {
+                          try {
+                            await revokeSession(session.id);
+                            await loadSessions();
+                            showToast("Session revoked", "That device has been signed out.");
+                          } catch (error) {
+                            showToast(
+                              "Revoke failed",
+                              getFriendlyErrorMessage(error, "Unable to revoke this session."),
+                            );
+                          }
+                        }}
+                      >
+                        Revoke
+                      </button>
+                    )}
+                  </span>
+                </div>
+              ))}
+            </div>
+          )}
+        </div>
         <div className="card">
           <h3>Currency</h3>
           <p className="muted">Base display currency for dashboards.</p>
@@ -705,11 +1108,28 @@ export default function SettingsPage() {
                 }}
               >
                 <option value="stooq">Stooq pricing</option>
+                <option value="coinbase">Coinbase (crypto)</option>
+                <option value="file">Local price files</option>
                 <option value="manual">Manual uploads</option>
-                <option value="broker">Broker APIs</option>
-                <option value="custom">Custom feed</option>
               </select>
             </label>
+            <label>
+              Benchmark symbol
+              <input
+                value={benchmarkSymbol}
+                onChange={(event) => setBenchmarkSymbol(event.target.value.toUpperCase())}
+                onBlur={async () => {
+                  const next = benchmarkSymbol.trim() || "SPY";
+                  setBenchmarkSymbol(next);
+                  try {
+                    await updatePreferences({ benchmarkSymbol: next });
+                    showToast("Benchmark saved", `Performance now compares against ${next}.`);
+                  } catch (error) {
+                    showToast("Save failed", "Unable to update benchmark symbol.");
+                  }
+                }}
+              />
+            </label>
           </div>
           <p className="muted small">
             These settings apply to asset refresh scheduling and labels shown in performance views.
@@ -763,12 +1183,18 @@ export default function SettingsPage() {
                     }
                     setIsMembershipSaving(true);
                     try {
-                      await addAccountGroupUser(selectedGroupId, memberEmail.trim(), memberRole);
+                      await inviteToAccountGroup(selectedGroupId, memberEmail.trim(), memberRole);
                       await loadGroupMembers(selectedGroupId);
                       setMemberEmail("");
-                      showToast("Member invited", `Added to ${selectedGroupName}.`);
+                      showToast(
+                        "Invitation sent",
+                        `${memberEmail.trim()} was invited to ${selectedGroupName}.`,
+                      );
                     } catch (error) {
-                      showToast("Invite failed", "Unable to add this member.");
+                      showToast(
+                        "Invite failed",
+                        getFriendlyErrorMessage(error, "Unable to invite this member."),
+                      );
                     } finally {
                       setIsMembershipSaving(false);
                     }
@@ -832,9 +1258,143 @@ export default function SettingsPage() {
                   </div>
                 ))}
               </div>
+              {groupInvitations.length > 0 && (
+                <div className="table compact">
+                  <div className="table-row table-header columns-3">
+                    <span>Pending invitation</span>
+                    <span>Role</span>
+                    <span>Actions</span>
+                  </div>
+                  {groupInvitations.map((invitation) => (
+                    <div className="table-row columns-3" key={invitation.id}>
+                      <span>
+                        {invitation.email}{" "}
+                        <span className="muted">
+                          (expires {new Date(invitation.expires_at).toLocaleDateString()})
+                        </span>
+                      </span>
+                      <span>{invitation.role}</span>
+                      <span>
+                        <button
+                          className="pill"
+                          onClick={async () => {
+                            try {
+                              await revokeGroupInvitation(invitation.group_id, invitation.id);
+                              await loadGroupMembers(invitation.group_id);
+                              showToast(
+                                "Invitation revoked",
+                                `${invitation.email} can no longer join.`,
+                              );
+                            } catch (error) {
+                              showToast("Revoke failed", "Unable to revoke this invitation.");
+                            }
+                          }}
+                        >
+                          Revoke
+                        </button>
+                      </span>
+                    </div>
+                  ))}
+                </div>
+              )}
+            </>
+          )}
+          {myInvitations.length > 0 && (
+            <>
+              <h4>Invitations for you</h4>
+              <div className="table compact">
+                {myInvitations.map((invitation) => (
+                  <div className="table-row columns-3" key={invitation.id}>
+                    <span>
+                      {invitation.group_name}{" "}
+                      <span className="muted">
+                        from {invitation.invited_by_name ?? "a Firecash user"}
+                      </span>
+                    </span>
+                    <span>{invitation.role}</span>
+                    <span>
+                      <button
+                        className="pill primary"
+                        onClick={async () => {
+                          try {
+                            await acceptInvitation({ invitation_id: invitation.id });
+                            await loadMyInvitations();
+                            await loadAccountGroups();
+                            showToast(
+                              "Invitation accepted",
+                              `You joined ${invitation.group_name}.`,
+                            );
+                          } catch (error) {
+                            showToast(
+                              "Accept failed",
+                              getFriendlyErrorMessage(error, "Unable to accept this invitation."),
+                            );
+                          }
+                        }}
+                      >
+                        Accept
+                      </button>
+                      <button
+                        className="pill"
+                        onClick={async () => {
+                          try {
+                            await declineInvitation({ invitation_id: invitation.id });
+                            await loadMyInvitations();
+                            showToast(
+                              "Invitation declined",
+                              `${invitation.group_name} was declined.`,
+                            );
+                          } catch (error) {
+                            showToast("Decline failed", "Unable to decline this invitation.");
+                          }
+                        }}
+                      >
+                        Decline
+                      </button>
+                    </span>
+                  </div>
+                ))}
+              </div>
             </>
           )}
         </div>
+        <div className="card">
+          <h3>Recent changes</h3>
+          <p className="muted">
+            Edits to accounts, transactions and assets you can access, including shared groups.
+          </p>
+          {activity.length === 0 ? (
+            <p className="muted">No changes recorded yet.</p>
+          ) : (
+            <div className="table compact">
+              <div className="table-row table-header columns-3">
+                <span>Change</span>
+                <span>By</span>
+                <span>Time</span>
+              </div>
+              {activity.map((entry) => (
+                <div className="table-row columns-3" key={entry.id}>
+                  <span>
+                    {entry.entity_type} {entry.action}
+                    {entry.action === "updated" && (
+                      <span className="muted">
+                        {" "}
+                        {Object.entries(entry.changes)
+                          .map(
+                            ([field, change]) =>
+                              `${field}: ${String(change.old ?? "—")} → ${String(change.new ?? "—")}`,
+                          )
+                          .join(", ")}
+                      </span>
+                    )}
+                  </span>
+                  <span>{entry.user_name ?? "Former member"}</span>
+                  <span>{formatDateDisplay(entry.created_at)}</span>
+                </div>
+              ))}
+            </div>
+          )}
+        </div>
         <div className="card">
           <h3>Audit log</h3>
           <p className="muted">Security activity visible to admins.</p>
@@ -1089,7 +1649,14 @@ export default function SettingsPage() {
           <div className="stack">
             <div className="alert-card">
               <strong>{tokenReveal.token_prefix}</strong>
-              <p className="muted">Access level: {tokenReveal.is_read_only ? "Read-only" : "Full"}</p>
+              <p className="muted">
+                Access level:{" "}
+                {tokenReveal.scopes
+                  ? tokenReveal.scopes.join(", ")
+                  : tokenReveal.is_read_only
+                    ? "Read-only"
+                    : "Full"}
+              </p>
               <p className="muted">{tokenReveal.token}</p>
             </div>
             <p className="muted small">
@@ -80,6 +80,10 @@ type AssetPerformance = {
   return_pct: number | null;
   benchmark_label: string;
   benchmark_return: number | null;
+  relative_return: number | null;
+  portfolio_return: number | null;
+  portfolio_benchmark_return: number | null;
+  portfolio_relative_return: number | null;
 };
 
 type Candle = {
@@ -164,6 +168,14 @@ function currencyFromSymbol(symbol: string) {
   return "USD";
 }
 
+function currencyForHolding(symbol: string, assetType: string) {
+  if (assetType === "Crypto") {
+    const [, quote] = symbol.toUpperCase().split(/[-/]/);
+    return quote === "USDT" || quote === "USDC" || !quote ? "USD" : quote;
+  }
+  return currencyFromSymbol(symbol);
+}
+
 export default function StocksPage() {
   usePageMeta({ title: pageTitles.stocks });
   const { currency: displayCurrency } = useCurrency();
@@ -172,6 +184,7 @@ export default function StocksPage() {
   const [range, setRange] = useState<DateRange>(() => getDefaultRange(90));
   const [isHoldingOpen, setIsHoldingOpen] = useState(false);
   const [holdingTicker, setHoldingTicker] = useState("");
+  const [holdingAssetType, setHoldingAssetType] = useState("Stock");
   const [holdingShares, setHoldingShares] = useState("");
   const [holdingPrice, setHoldingPrice] = useState("");
   const [holdingDate, setHoldingDate] = useState(getTodayDate);
@@ -837,6 +850,7 @@ export default function StocksPage() {
     : "Not yet";
   const dataSourceLabelMap: Record<string, string> = {
     stooq: "Stooq pricing",
+    file: "Local price files",
     manual: "Manual uploads",
     broker: "Broker APIs",
     custom: "Custom feed",
@@ -850,7 +864,7 @@ export default function StocksPage() {
     return `${percent >= 0 ? "+" : ""}${percent.toFixed(1)}%`;
   };
   const benchmarkLabel = performanceMetrics[0]?.benchmark_label ?? "Benchmark";
-  const benchmarkReturn = performanceMetrics[0]?.benchmark_return ?? null;
+  const portfolioPerformance = performanceMetrics[0] ?? null;
 
   return (
     <section className="page">
@@ -908,12 +922,12 @@ export default function StocksPage() {
                   showToast("Account required", "Select an account to continue.");
                   return;
                 }
-                const currencyCode = currencyFromSymbol(normalizedTicker);
+                const currencyCode = currencyForHolding(normalizedTicker, holdingAssetType);
                 try {
                   const createdAsset = await post<Asset>("/api/assets", {
                     account_id: accountId,
                     symbol: normalizedTicker,
-                    asset_type: "Stock",
+                    asset_type: holdingAssetType,
                     quantity: shares,
                     currency_code: currencyCode,
                   });
@@ -1007,6 +1021,16 @@ export default function StocksPage() {
               }
             />
           </label>
+          <label>
+            Asset type
+            <select
+              value={holdingAssetType}
+              onChange={(event) => setHoldingAssetType(event.target.value)}
+            >
+              <option value="Stock">Stock / ETF</option>
+              <option value="Crypto">Crypto (e.g. BTC or BTC-EUR)</option>
+            </select>
+          </label>
           <datalist id="stock-symbols">
             {symbolSuggestions.map((symbol) => (
               <option key={symbol} value={symbol} />
@@ -1016,6 +1040,7 @@ export default function StocksPage() {
             Shares
             <input
               type="number"
+              step="any"
               placeholder="0"
               value={holdingShares}
               onChange={(event) => setHoldingShares(event.target.value)}
@@ -1178,28 +1203,40 @@ export default function StocksPage() {
       <div className="card">
         <h3>Performance vs benchmark</h3>
         <p className="muted">
-          Returns compare the first and latest price snapshots for each holding.
+          Each holding is compared with {benchmarkLabel} over the same holding period.
         </p>
         {performanceMetrics.length === 0 ? (
           <p className="muted">No performance data yet.</p>
         ) : (
           <div className="table compact">
-            <div className="table-row table-header columns-3">
+            <div className="table-row table-header columns-4">
               <span>Asset</span>
               <span>Return</span>
               <span>{benchmarkLabel}</span>
+              <span>Relative</span>
             </div>
             {performanceMetrics.map((metric) => (
-              <div className="table-row columns-3" key={metric.asset_id}>
+              <div className="table-row columns-4" key={metric.asset_id}>
                 <span>{metric.symbol}</span>
                 <span>{formatPercent(metric.return_pct)}</span>
-                <span>{formatPercent(benchmarkReturn)}</span>
+                <span>{formatPercent(metric.benchmark_return)}</span>
+                <span>{formatPercent(metric.relative_return)}</span>
               </div>
             ))}
+            <div className="table-row columns-4">
+              <span>Portfolio</span>
+              <span>{formatPercent(portfolioPerformance?.portfolio_return ?? null)}</span>
+              <span>
+                {formatPercent(portfolioPerformance?.portfolio_benchmark_return ?? null)}
+              </span>
+              <span>
+                {formatPercent(portfolioPerformance?.portfolio_relative_return ?? null)}
+              </span>
+            </div>
           </div>
         )}
         <p className="muted small">
-          Benchmark data is a composite based on your holdings’ start and latest pricing.
+          Change the benchmark symbol in Settings. Portfolio returns weight holdings by invested capital.
         </p>
       </div>
       <div className="card">
@@ -1408,6 +1445,7 @@ export default function StocksPage() {
                   {isEditMode ? (
                     <input
                       type="number"
+                      step="any"
                       value={pendingHolding?.shares ?? row.shares}
                       onChange={(event) =>
                         setPendingHoldings((prev) => ({
@@ -0,0 +1,48 @@
+import { useEffect, useRef, useState } from "react";
+import { Link, useSearchParams } from "react-router-dom";
+import { verifyEmail } from "../api/auth";
+import { getFriendlyErrorMessage } from "../utils/errorMessages";
+import { pageTitles } from "../utils/pageTitles";
+import { usePageTitle } from "../utils/pageMeta";
+
+export default function VerifyEmailPage() {
+  usePageTitle(pageTitles.verifyEmail);
+  const [searchParams] = useSearchParams();
+  const [status, setStatus] = useState<"pending" | "verified" | "failed">("pending");
+  const [error, setError] = useState<string | null>(null);
+  const hasStarted = useRef(false);
+
+  useEffect(() => {
+    // Verification tokens are single-use, so only submit once.
+    if (hasStarted.current) {
+      return;
+    }
+    hasStarted.current = true;
+    const token = searchParams.get("token");
+    if (!token) {
+      setStatus("failed");
+      setError("The verification link is missing its token.");
+      return;
+    }
+    verifyEmail(token)
+      .then(() => setStatus("verified"))
+      .catch((err) => {
+        setStatus("failed");
+        setError(getFriendlyErrorMessage(err, "Unable to verify this email."));
+      });
+  }, [searchParams]);
+
+  return (
+    <div className="auth-page">
+      <div className="auth-card">
+        <h1>{pageTitles.verifyEmail}</h1>
+        {status === "pending" && <p className="muted">Confirming your email...</p>}
+        {status === "verified" && <p className="muted">Your email address is verified.</p>}
+        {status === "failed" && <p className="form-error">{error}</p>}
+        <p className="muted">
+          <Link to="/dashboard">Continue to Firecash</Link>
+        </p>
+      </div>
+    </div>
+  );
+}
@@ -1,4 +1,5 @@
 const TOKEN_KEY = "firecash.jwt";
+const REFRESH_TOKEN_KEY = "firecash.refresh";
 const CACHE_PREFIX = "firecash.cache:";
 const DEFAULT_CACHE_TTL_MS = 2000;
 
@@ -13,6 +14,7 @@ type UnauthorizedHandler = (() => void) | null;
 
 let unauthorizedHandler: UnauthorizedHandler = null;
 const inflightRequests = new Map<string, Promise<unknown>>();
+let refreshPromise: Promise<boolean> | null = null;
 const rateLimitUntilByPath = new Map<string, number>();
 
 export class ApiError extends Error {
@@ -54,12 +56,54 @@ export function getToken() {
   return localStorage.getItem(TOKEN_KEY);
 }
 
-export function setToken(token: string) {
+export function setToken(token: string, refreshToken?: string) {
   localStorage.setItem(TOKEN_KEY, token);
+  if (refreshToken) {
+    localStorage.setItem(REFRESH_TOKEN_KEY, refreshToken);
+  }
+}
+
+export function getRefreshToken() {
+  return localStorage.getItem(REFRESH_TOKEN_KEY);
 }
 
 export function clearToken() {
   localStorage.removeItem(TOKEN_KEY);
+  localStorage.removeItem(REFRESH_TOKEN_KEY);
+}
+
+async function requestTokenRefresh(refreshToken: string) {
+  try {
+    const response = await fetch(buildUrl("/api/refresh"), {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ refresh_token: refreshToken }),
+    });
+    if (response.ok) {
+      const data = (await response.json()) as { token: string; refresh_token: string };
+      setToken(data.token, data.refresh_token);
+      return true;
+    }
+  } catch (error) {
+    return false;
+  }
+  // Another tab may have rotated the refresh token first; use its tokens.
+  const latest = getRefreshToken();
+  return Boolean(latest && latest !== refreshToken);
+}
+
+// Concurrent 401s share one refresh so the rotated token is only spent once.
+export function refreshAccessToken() {
+  const refreshToken = getRefreshToken();
+  if (!refreshToken) {
+    return Promise.resolve(false);
+  }
+  if (!refreshPromise) {
+    refreshPromise = requestTokenRefresh(refreshToken).finally(() => {
+      refreshPromise = null;
+    });
+  }
+  return refreshPromise;
 }
 
 async function parseResponseBody(response: Response) {
@@ -177,7 +221,7 @@ export async function apiRequest<T>(
   await waitForRateLimit(path);
 
   let response: Response;
-  const runRequest = async () => {
+  const runRequest = async (isRetry = false): Promise<T> => {
     try {
       response = await fetch(buildUrl(path), {
         ...options,
@@ -211,6 +255,10 @@ export async function apiRequest<T>(
       const message =
         (typeof data === "object" && data && "message" in data && (data as { message: string }).message) ||
         (typeof data === "string" ? data : "Request failed");
+      if (response.status === 401 && !config.skipAuth && !isRetry && (await refreshAccessToken())) {
+        headers.set("Authorization", `Bearer ${getToken()}`);
+        return runRequest(true);
+      }
       if (response.status === 401) {
         clearToken();
         unauthorizedHandler?.();
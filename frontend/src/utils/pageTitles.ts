@@ -9,6 +9,10 @@ export const pageTitles = {
   stockMarket: "Market",
   login: "Welcome back",
   register: "Create your account",
+  singleSignOn: "Signing you in",
+  resetPassword: "Reset your password",
+  verifyEmail: "Verify your email",
+  invitation: "Account group invitation",
 };
 
 export type PageTitleKey = keyof typeof pageTitles;